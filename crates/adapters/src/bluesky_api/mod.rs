@@ -0,0 +1,168 @@
+//! Bluesky (AT Protocol) XRPC adapters
+
+mod read;
+mod session;
+mod write;
+
+pub use read::BlueskyPostSource;
+pub use session::BlueskySession;
+pub use write::{BlueskyPublishMode, BlueskyPublisher};
+
+use news_tagger_domain::{PostSourceError, PublishError};
+use reqwest::{RequestBuilder, StatusCode};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use time::OffsetDateTime;
+
+/// Errors shared by the Bluesky read and write adapters
+#[derive(Debug)]
+enum XrpcError {
+    Network(String),
+    Auth(String),
+    RateLimited(Option<Duration>),
+    NotFound(String),
+    Api(String),
+}
+
+impl From<XrpcError> for PostSourceError {
+    fn from(error: XrpcError) -> Self {
+        match error {
+            XrpcError::Network(e) => PostSourceError::Network(e),
+            XrpcError::Auth(e) => PostSourceError::Auth(e),
+            XrpcError::RateLimited(wait) => PostSourceError::RateLimited(wait),
+            XrpcError::NotFound(e) => PostSourceError::AccountUnavailable(e),
+            XrpcError::Api(e) => PostSourceError::Api(e),
+        }
+    }
+}
+
+impl From<XrpcError> for PublishError {
+    fn from(error: XrpcError) -> Self {
+        match error {
+            XrpcError::Network(e) | XrpcError::NotFound(e) | XrpcError::Api(e) => {
+                PublishError::Api(e)
+            }
+            XrpcError::Auth(e) => PublishError::Auth(e),
+            XrpcError::RateLimited(wait) => PublishError::RateLimited(wait),
+        }
+    }
+}
+
+/// A fully read XRPC response, so callers can inspect error bodies and retry
+struct XrpcResponse {
+    status: StatusCode,
+    body: String,
+}
+
+impl XrpcResponse {
+    fn json<T: DeserializeOwned>(&self) -> Result<T, XrpcError> {
+        serde_json::from_str(&self.body).map_err(|e| XrpcError::Api(e.to_string()))
+    }
+
+    /// The XRPC `error` name of a failed call, e.g. `ExpiredToken`
+    fn error_name(&self) -> Option<String> {
+        #[derive(Deserialize)]
+        struct ErrorBody {
+            error: Option<String>,
+        }
+        serde_json::from_str::<ErrorBody>(&self.body)
+            .ok()
+            .and_then(|body| body.error)
+    }
+
+    /// Fail on anything but success, keeping the XRPC error name in the message
+    fn success(self, context: &str) -> Result<Self, XrpcError> {
+        if self.status.is_success() {
+            Ok(self)
+        } else {
+            Err(XrpcError::Api(format!("{}: {}", context, self.body)))
+        }
+    }
+}
+
+/// Send a request and read its body, mapping rate limiting
+async fn execute(request: RequestBuilder) -> Result<XrpcResponse, XrpcError> {
+    let response = request
+        .send()
+        .await
+        .map_err(|e| XrpcError::Network(e.to_string()))?;
+
+    let status = response.status();
+    if status == StatusCode::TOO_MANY_REQUESTS {
+        return Err(XrpcError::RateLimited(retry_after(&response)));
+    }
+
+    let body = response
+        .text()
+        .await
+        .map_err(|e| XrpcError::Network(e.to_string()))?;
+    Ok(XrpcResponse { status, body })
+}
+
+/// Parse the `ratelimit-reset` header (unix seconds) into a wait duration
+fn retry_after(response: &reqwest::Response) -> Option<Duration> {
+    let reset: i64 = response
+        .headers()
+        .get("ratelimit-reset")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|s| s.trim().parse().ok())?;
+    let wait = reset - OffsetDateTime::now_utc().unix_timestamp();
+    Some(Duration::from_secs(wait.max(0) as u64))
+}
+
+/// Web URL for a post, given its author (handle or DID) and AT-URI
+fn post_url(author: &str, uri: &str) -> String {
+    let rkey = uri.rsplit('/').next().unwrap_or_default();
+    format!("https://bsky.app/profile/{}/post/{}", author, rkey)
+}
+
+/// Reference to a specific version of a record
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct StrongRef {
+    uri: String,
+    cid: String,
+}
+
+/// `app.bsky.feed.defs#postView`
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PostView {
+    uri: String,
+    cid: String,
+    author: Profile,
+    record: PostRecord,
+    #[serde(default)]
+    embed: Option<serde_json::Value>,
+    indexed_at: String,
+}
+
+impl PostView {
+    fn strong_ref(&self) -> StrongRef {
+        StrongRef {
+            uri: self.uri.clone(),
+            cid: self.cid.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct Profile {
+    handle: String,
+}
+
+/// The `app.bsky.feed.post` record inside a post view
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PostRecord {
+    #[serde(default)]
+    text: String,
+    created_at: Option<String>,
+    reply: Option<ReplyRecord>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ReplyRecord {
+    root: StrongRef,
+    parent: StrongRef,
+}
@@ -0,0 +1,433 @@
+//! Bluesky read adapter for fetching author feeds
+
+use async_trait::async_trait;
+use news_tagger_domain::{
+    ContextRelation, PostContext, PostSource, PostSourceError, SourcePost, compare_post_ids,
+};
+use reqwest::{Client, RequestBuilder};
+use serde::Deserialize;
+use std::cmp::Ordering;
+use std::sync::Arc;
+use std::time::Duration;
+use time::OffsetDateTime;
+
+use super::{BlueskySession, PostView, XrpcError, XrpcResponse, execute, post_url};
+
+/// Feed items requested per page (the API maximum)
+const PAGE_LIMIT: &str = "100";
+
+/// Pages walked back per poll before giving up on reaching `since_id`
+const MAX_PAGES: usize = 5;
+
+/// Bluesky post source reading `app.bsky.feed.getAuthorFeed` for handles or DIDs
+///
+/// Post IDs are AT-URIs. Reposts use the repost record's URI when the AppView
+/// reports it, so they order by when they were reposted.
+pub struct BlueskyPostSource {
+    client: Client,
+    base_url: String,
+    session: Option<Arc<BlueskySession>>,
+}
+
+impl BlueskyPostSource {
+    /// Create an unauthenticated source against a public AppView
+    pub fn new(base_url: String) -> Self {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(30))
+            .build()
+            .expect("Failed to build HTTP client");
+
+        Self {
+            client,
+            base_url: base_url.trim_end_matches('/').to_string(),
+            session: None,
+        }
+    }
+
+    /// Read through an authenticated session (proxied by its PDS) instead
+    pub fn with_session(mut self, session: Arc<BlueskySession>) -> Self {
+        self.session = Some(session);
+        self
+    }
+
+    async fn get_author_feed(
+        &self,
+        actor: &str,
+        cursor: Option<&str>,
+    ) -> Result<AuthorFeed, XrpcError> {
+        let build = |client: &Client, base_url: &str| -> RequestBuilder {
+            let mut query = vec![
+                ("actor", actor),
+                ("limit", PAGE_LIMIT),
+                ("filter", "posts_with_replies"),
+            ];
+            if let Some(cursor) = cursor {
+                query.push(("cursor", cursor));
+            }
+            client
+                .get(format!("{}/xrpc/app.bsky.feed.getAuthorFeed", base_url))
+                .query(&query)
+        };
+
+        let response: XrpcResponse = match &self.session {
+            Some(session) => {
+                session
+                    .send(|client| build(client, session.base_url()))
+                    .await?
+            }
+            None => execute(build(&self.client, &self.base_url)).await?,
+        };
+
+        // Unknown actors come back as a generic InvalidRequest
+        if matches!(
+            response.error_name().as_deref(),
+            Some("AccountTakedown" | "AccountDeactivated" | "BlockedActor")
+        ) || response.body.contains("Profile not found")
+        {
+            return Err(XrpcError::NotFound(format!(
+                "Bluesky account not found: {}",
+                actor
+            )));
+        }
+        response.success("Failed to get author feed")?.json()
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct AuthorFeed {
+    #[serde(default)]
+    feed: Vec<FeedViewPost>,
+    cursor: Option<String>,
+}
+
+/// `app.bsky.feed.defs#feedViewPost`
+#[derive(Debug, Deserialize)]
+struct FeedViewPost {
+    post: PostView,
+    reply: Option<ReplyView>,
+    reason: Option<Reason>,
+}
+
+/// Thread context; either side may be a not-found or blocked stub
+#[derive(Debug, Deserialize)]
+struct ReplyView {
+    root: serde_json::Value,
+    parent: serde_json::Value,
+}
+
+#[derive(Debug, Deserialize)]
+struct Reason {
+    #[serde(rename = "$type")]
+    kind: String,
+    uri: Option<String>,
+}
+
+impl Reason {
+    fn is_repost(&self) -> bool {
+        self.kind == "app.bsky.feed.defs#reasonRepost"
+    }
+}
+
+#[async_trait]
+impl PostSource for BlueskyPostSource {
+    async fn fetch_posts(
+        &self,
+        account: &str,
+        since_id: Option<&str>,
+    ) -> Result<Vec<SourcePost>, PostSourceError> {
+        let mut posts = Vec::new();
+        let mut cursor: Option<String> = None;
+
+        for _ in 0..MAX_PAGES {
+            let page = self.get_author_feed(account, cursor.as_deref()).await?;
+
+            let mut reached_since = false;
+            for item in page.feed {
+                let post = feed_item_to_post(item);
+                if let Some(since_id) = since_id {
+                    if compare_post_ids(&post.id, since_id) != Ordering::Greater {
+                        // Reposts of old posts can trail newer items; only originals end the walk
+                        reached_since |= !post.is_repost;
+                        continue;
+                    }
+                }
+                posts.push(post);
+            }
+
+            // The first poll for an account only looks at the latest page
+            if reached_since || since_id.is_none() {
+                break;
+            }
+            match page.cursor {
+                Some(next) => cursor = Some(next),
+                None => break,
+            }
+        }
+
+        posts.sort_by(|a, b| compare_post_ids(&a.id, &b.id));
+        posts.dedup_by(|a, b| a.id == b.id);
+        Ok(posts)
+    }
+}
+
+fn feed_item_to_post(item: FeedViewPost) -> SourcePost {
+    let post = item.post;
+    let is_repost = item.reason.as_ref().is_some_and(Reason::is_repost);
+    let id = item
+        .reason
+        .filter(Reason::is_repost)
+        .and_then(|reason| reason.uri)
+        .unwrap_or_else(|| post.uri.clone());
+
+    let created_at = post
+        .record
+        .created_at
+        .as_deref()
+        .and_then(|s| OffsetDateTime::parse(s, &time::format_description::well_known::Rfc3339).ok())
+        .or_else(|| {
+            OffsetDateTime::parse(
+                &post.indexed_at,
+                &time::format_description::well_known::Rfc3339,
+            )
+            .ok()
+        })
+        .unwrap_or_else(OffsetDateTime::now_utc);
+
+    let mut context = Vec::new();
+    if let Some(quoted) = post.embed.as_ref().and_then(quoted_record) {
+        context.push(quoted);
+    }
+    let reply_to_id = post
+        .record
+        .reply
+        .as_ref()
+        .map(|reply| reply.parent.uri.clone());
+    if let Some(reply) = item.reply {
+        let parent = serde_json::from_value::<PostView>(reply.parent).ok();
+        let root = serde_json::from_value::<PostView>(reply.root).ok();
+        if let Some(parent) = &parent {
+            context.push(view_context(ContextRelation::RepliedTo, parent));
+        }
+        if let Some(root) = root.filter(|root| Some(&root.uri) != parent.as_ref().map(|p| &p.uri)) {
+            context.push(view_context(ContextRelation::ConversationRoot, &root));
+        }
+    }
+
+    SourcePost {
+        url: post_url(&post.author.handle, &post.uri),
+        id,
+        text: post.record.text,
+        author: post.author.handle,
+        created_at,
+        is_repost,
+        is_reply: reply_to_id.is_some(),
+        reply_to_id,
+        context,
+    }
+}
+
+fn view_context(relation: ContextRelation, view: &PostView) -> PostContext {
+    PostContext {
+        relation,
+        id: view.uri.clone(),
+        author: Some(view.author.handle.clone()),
+        text: view.record.text.clone(),
+    }
+}
+
+/// Quoted post from an `app.bsky.embed.record` (or `recordWithMedia`) view
+fn quoted_record(embed: &serde_json::Value) -> Option<PostContext> {
+    let record = match embed.get("$type")?.as_str()? {
+        "app.bsky.embed.record#view" => embed.get("record")?,
+        "app.bsky.embed.recordWithMedia#view" => embed.get("record")?.get("record")?,
+        _ => return None,
+    };
+
+    Some(PostContext {
+        relation: ContextRelation::Quoted,
+        id: record.get("uri")?.as_str()?.to_string(),
+        author: record
+            .get("author")
+            .and_then(|author| author.get("handle"))
+            .and_then(|handle| handle.as_str())
+            .map(str::to_string),
+        text: record.get("value")?.get("text")?.as_str()?.to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn post_json(did: &str, rkey: &str, text: &str) -> serde_json::Value {
+        serde_json::json!({
+            "uri": format!("at://{}/app.bsky.feed.post/{}", did, rkey),
+            "cid": format!("cid-{}", rkey),
+            "author": {"did": did, "handle": "alice.bsky.social"},
+            "record": {
+                "$type": "app.bsky.feed.post",
+                "text": text,
+                "createdAt": "2024-01-01T12:00:00.000Z"
+            },
+            "indexedAt": "2024-01-01T12:00:01.000Z"
+        })
+    }
+
+    #[tokio::test]
+    async fn test_fetch_author_feed_pages_back_to_since_id() {
+        let mock_server = MockServer::start().await;
+        let since = "at://did:plc:alice/app.bsky.feed.post/3kaaaaaaaaa22";
+
+        Mock::given(method("GET"))
+            .and(path("/xrpc/app.bsky.feed.getAuthorFeed"))
+            .and(query_param("actor", "alice.bsky.social"))
+            .and(query_param("cursor", "page-2"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "feed": [
+                    {"post": post_json("did:plc:alice", "3kbbbbbbbbb22", "older new post")},
+                    {"post": post_json("did:plc:alice", "3kaaaaaaaaa22", "already seen")}
+                ],
+                "cursor": "page-3"
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let mut quote = post_json("did:plc:alice", "3kddddddddd22", "look at this");
+        quote["embed"] = serde_json::json!({
+            "$type": "app.bsky.embed.record#view",
+            "record": {
+                "uri": "at://did:plc:bob/app.bsky.feed.post/3kccccccccc22",
+                "author": {"did": "did:plc:bob", "handle": "bob.bsky.social"},
+                "value": {"text": "quoted text"}
+            }
+        });
+        Mock::given(method("GET"))
+            .and(path("/xrpc/app.bsky.feed.getAuthorFeed"))
+            .and(query_param("actor", "alice.bsky.social"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "feed": [{"post": quote}],
+                "cursor": "page-2"
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let source = BlueskyPostSource::new(mock_server.uri());
+        let posts = source
+            .fetch_posts("alice.bsky.social", Some(since))
+            .await
+            .unwrap();
+
+        assert_eq!(posts.len(), 2);
+        assert_eq!(posts[0].text, "older new post");
+        assert_eq!(
+            posts[1].url,
+            "https://bsky.app/profile/alice.bsky.social/post/3kddddddddd22"
+        );
+        assert_eq!(posts[1].context.len(), 1);
+        assert_eq!(posts[1].context[0].relation, ContextRelation::Quoted);
+        assert_eq!(posts[1].context[0].text, "quoted text");
+    }
+
+    #[tokio::test]
+    async fn test_reposts_and_replies() {
+        let mock_server = MockServer::start().await;
+
+        let mut reply = post_json("did:plc:alice", "3keeeeeeeee22", "I disagree");
+        reply["record"]["reply"] = serde_json::json!({
+            "root": {"uri": "at://did:plc:bob/app.bsky.feed.post/3kroot000022", "cid": "c1"},
+            "parent": {"uri": "at://did:plc:bob/app.bsky.feed.post/3kparent0022", "cid": "c2"}
+        });
+        let parent = post_json("did:plc:bob", "3kparent0022", "parent text");
+
+        Mock::given(method("GET"))
+            .and(path("/xrpc/app.bsky.feed.getAuthorFeed"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "feed": [
+                    {
+                        "post": reply,
+                        "reply": {
+                            "root": {"$type": "app.bsky.feed.defs#notFoundPost", "uri": "at://did:plc:bob/app.bsky.feed.post/3kroot000022", "notFound": true},
+                            "parent": parent
+                        }
+                    },
+                    {
+                        "post": post_json("did:plc:carol", "3k000000000022", "boosted"),
+                        "reason": {
+                            "$type": "app.bsky.feed.defs#reasonRepost",
+                            "by": {"did": "did:plc:alice", "handle": "alice.bsky.social"},
+                            "uri": "at://did:plc:alice/app.bsky.feed.repost/3kfffffffff22",
+                            "indexedAt": "2024-01-01T12:00:00.000Z"
+                        }
+                    }
+                ]
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let source = BlueskyPostSource::new(mock_server.uri());
+        let posts = source.fetch_posts("alice.bsky.social", None).await.unwrap();
+
+        assert_eq!(posts.len(), 2);
+        assert!(posts[0].is_reply);
+        assert_eq!(
+            posts[0].reply_to_id.as_deref(),
+            Some("at://did:plc:bob/app.bsky.feed.post/3kparent0022")
+        );
+        assert_eq!(posts[0].context.len(), 1);
+        assert_eq!(posts[0].context[0].text, "parent text");
+
+        assert!(posts[1].is_repost);
+        assert_eq!(
+            posts[1].id,
+            "at://did:plc:alice/app.bsky.feed.repost/3kfffffffff22"
+        );
+        assert_eq!(posts[1].text, "boosted");
+    }
+
+    #[tokio::test]
+    async fn test_unknown_actor_is_unavailable() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/xrpc/app.bsky.feed.getAuthorFeed"))
+            .respond_with(ResponseTemplate::new(400).set_body_json(serde_json::json!({
+                "error": "InvalidRequest",
+                "message": "Profile not found"
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let source = BlueskyPostSource::new(mock_server.uri());
+        let result = source.fetch_posts("ghost.bsky.social", None).await;
+
+        assert!(matches!(
+            result,
+            Err(PostSourceError::AccountUnavailable(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_rate_limited_uses_reset_header() {
+        let mock_server = MockServer::start().await;
+
+        let reset = OffsetDateTime::now_utc().unix_timestamp() + 300;
+        Mock::given(method("GET"))
+            .and(path("/xrpc/app.bsky.feed.getAuthorFeed"))
+            .respond_with(
+                ResponseTemplate::new(429).insert_header("ratelimit-reset", reset.to_string()),
+            )
+            .mount(&mock_server)
+            .await;
+
+        let source = BlueskyPostSource::new(mock_server.uri());
+        let result = source.fetch_posts("alice.bsky.social", None).await;
+
+        assert!(matches!(
+            result,
+            Err(PostSourceError::RateLimited(Some(wait))) if wait > Duration::from_secs(240)
+        ));
+    }
+}
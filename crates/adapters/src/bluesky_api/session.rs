@@ -0,0 +1,260 @@
+//! AT Protocol session handling (login and token refresh)
+
+use reqwest::{Client, RequestBuilder, StatusCode};
+use secrecy::{ExposeSecret, SecretString};
+use serde::Deserialize;
+use std::time::Duration;
+use tokio::sync::Mutex;
+
+use super::{XrpcError, XrpcResponse, execute};
+
+/// Authenticated session against a PDS, shared by the Bluesky source and publisher
+///
+/// Logs in lazily with an app password, refreshes the access token when the
+/// server reports it expired, and logs in again if the refresh token is gone too.
+pub struct BlueskySession {
+    client: Client,
+    base_url: String,
+    identifier: String,
+    password: SecretString,
+    tokens: Mutex<Option<Tokens>>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Tokens {
+    access_jwt: String,
+    refresh_jwt: String,
+    did: String,
+    handle: String,
+}
+
+impl BlueskySession {
+    /// Create a session for `identifier` (handle, DID or email) on the PDS at `base_url`
+    pub fn new(base_url: String, identifier: String, password: SecretString) -> Self {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(30))
+            .build()
+            .expect("Failed to build HTTP client");
+
+        Self {
+            client,
+            base_url: base_url.trim_end_matches('/').to_string(),
+            identifier,
+            password,
+            tokens: Mutex::new(None),
+        }
+    }
+
+    pub(super) fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    /// DID and handle of the logged-in account
+    pub(super) async fn account(&self) -> Result<(String, String), XrpcError> {
+        let tokens = self.tokens().await?;
+        Ok((tokens.did, tokens.handle))
+    }
+
+    /// Send an authenticated request, renewing the session once if the token expired
+    pub(super) async fn send(
+        &self,
+        build: impl Fn(&Client) -> RequestBuilder,
+    ) -> Result<XrpcResponse, XrpcError> {
+        let tokens = self.tokens().await?;
+        let response = execute(build(&self.client).bearer_auth(&tokens.access_jwt)).await?;
+        if !is_token_error(&response) {
+            return check_auth(response);
+        }
+
+        let access_jwt = self.renew(&tokens.access_jwt).await?;
+        check_auth(execute(build(&self.client).bearer_auth(access_jwt)).await?)
+    }
+
+    async fn tokens(&self) -> Result<Tokens, XrpcError> {
+        let mut tokens = self.tokens.lock().await;
+        if let Some(tokens) = tokens.as_ref() {
+            return Ok(tokens.clone());
+        }
+
+        let fresh = self.create_session().await?;
+        *tokens = Some(fresh.clone());
+        Ok(fresh)
+    }
+
+    /// Replace an expired access token, unless another caller already did
+    async fn renew(&self, stale_access_jwt: &str) -> Result<String, XrpcError> {
+        let mut tokens = self.tokens.lock().await;
+        if let Some(current) = tokens.as_ref() {
+            if current.access_jwt != stale_access_jwt {
+                return Ok(current.access_jwt.clone());
+            }
+        }
+
+        let refreshed = match tokens.as_ref() {
+            Some(current) => self.refresh_session(&current.refresh_jwt).await,
+            None => Err(XrpcError::Auth("No session".to_string())),
+        };
+        let fresh = match refreshed {
+            Ok(fresh) => fresh,
+            Err(XrpcError::Auth(_)) => self.create_session().await?,
+            Err(error) => return Err(error),
+        };
+
+        let access_jwt = fresh.access_jwt.clone();
+        *tokens = Some(fresh);
+        Ok(access_jwt)
+    }
+
+    async fn create_session(&self) -> Result<Tokens, XrpcError> {
+        tracing::debug!(identifier = %self.identifier, "Creating Bluesky session");
+        let response = execute(
+            self.client
+                .post(format!(
+                    "{}/xrpc/com.atproto.server.createSession",
+                    self.base_url
+                ))
+                .json(&serde_json::json!({
+                    "identifier": self.identifier,
+                    "password": self.password.expose_secret(),
+                })),
+        )
+        .await?;
+
+        check_auth(response)?
+            .success("Failed to create session")?
+            .json()
+    }
+
+    async fn refresh_session(&self, refresh_jwt: &str) -> Result<Tokens, XrpcError> {
+        tracing::debug!("Refreshing Bluesky session");
+        let response = execute(
+            self.client
+                .post(format!(
+                    "{}/xrpc/com.atproto.server.refreshSession",
+                    self.base_url
+                ))
+                .bearer_auth(refresh_jwt),
+        )
+        .await?;
+
+        if is_token_error(&response) {
+            return Err(XrpcError::Auth("Refresh token expired".to_string()));
+        }
+        check_auth(response)?
+            .success("Failed to refresh session")?
+            .json()
+    }
+}
+
+fn is_token_error(response: &XrpcResponse) -> bool {
+    matches!(
+        response.status,
+        StatusCode::BAD_REQUEST | StatusCode::UNAUTHORIZED
+    ) && matches!(
+        response.error_name().as_deref(),
+        Some("ExpiredToken" | "InvalidToken")
+    )
+}
+
+fn check_auth(response: XrpcResponse) -> Result<XrpcResponse, XrpcError> {
+    if response.status == StatusCode::UNAUTHORIZED {
+        return Err(XrpcError::Auth(format!(
+            "Bluesky authentication failed: {}",
+            response.body
+        )));
+    }
+    Ok(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn session_json(access: &str, refresh: &str) -> serde_json::Value {
+        serde_json::json!({
+            "accessJwt": access,
+            "refreshJwt": refresh,
+            "did": "did:plc:bot",
+            "handle": "bot.bsky.social"
+        })
+    }
+
+    #[tokio::test]
+    async fn test_refreshes_expired_access_token_once() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .and(path("/xrpc/com.atproto.server.createSession"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(session_json("access-1", "refresh-1")),
+            )
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        Mock::given(method("POST"))
+            .and(path("/xrpc/com.atproto.server.refreshSession"))
+            .and(header("authorization", "Bearer refresh-1"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(session_json("access-2", "refresh-2")),
+            )
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        Mock::given(method("GET"))
+            .and(path("/xrpc/app.bsky.actor.getProfile"))
+            .and(header("authorization", "Bearer access-1"))
+            .respond_with(ResponseTemplate::new(400).set_body_json(serde_json::json!({
+                "error": "ExpiredToken",
+                "message": "Token has expired"
+            })))
+            .mount(&mock_server)
+            .await;
+
+        Mock::given(method("GET"))
+            .and(path("/xrpc/app.bsky.actor.getProfile"))
+            .and(header("authorization", "Bearer access-2"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({})))
+            .expect(2)
+            .mount(&mock_server)
+            .await;
+
+        let session = BlueskySession::new(
+            mock_server.uri(),
+            "bot.bsky.social".to_string(),
+            SecretString::new("app-password".into()),
+        );
+        let url = format!("{}/xrpc/app.bsky.actor.getProfile", mock_server.uri());
+
+        for _ in 0..2 {
+            let response = session.send(|client| client.get(&url)).await.unwrap();
+            assert!(response.status.is_success());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_bad_credentials_are_auth_errors() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .and(path("/xrpc/com.atproto.server.createSession"))
+            .respond_with(ResponseTemplate::new(401).set_body_json(serde_json::json!({
+                "error": "AuthenticationRequired",
+                "message": "Invalid identifier or password"
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let session = BlueskySession::new(
+            mock_server.uri(),
+            "bot.bsky.social".to_string(),
+            SecretString::new("wrong".into()),
+        );
+
+        assert!(matches!(session.account().await, Err(XrpcError::Auth(_))));
+    }
+}
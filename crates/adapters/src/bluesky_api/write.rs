@@ -0,0 +1,483 @@
+//! Bluesky write adapter for publishing posts
+
+use async_trait::async_trait;
+use news_tagger_domain::{PublishError, PublishResult, Publisher, RenderedPost};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, LazyLock};
+use time::OffsetDateTime;
+
+use super::{BlueskySession, PostView, StrongRef, post_url};
+
+const POST_COLLECTION: &str = "app.bsky.feed.post";
+
+static LINK: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"https?://[^\s<>]+").expect("Valid regex"));
+static HASHTAG: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?:^|\s)(#[^\s#]+)").expect("Valid regex"));
+
+/// How the classification post relates to the source post
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BlueskyPublishMode {
+    /// Reply in the source post's thread
+    #[default]
+    Reply,
+    /// Quote post embedding the source post
+    Quote,
+}
+
+/// Bluesky publisher creating `app.bsky.feed.post` records
+pub struct BlueskyPublisher {
+    session: Option<Arc<BlueskySession>>,
+    mode: BlueskyPublishMode,
+    max_chars: usize,
+}
+
+impl BlueskyPublisher {
+    pub fn new(session: Arc<BlueskySession>, mode: BlueskyPublishMode, max_chars: usize) -> Self {
+        Self {
+            session: Some(session),
+            mode,
+            max_chars,
+        }
+    }
+
+    /// Create a disabled publisher (for testing/dry-run)
+    pub fn disabled() -> Self {
+        Self {
+            session: None,
+            mode: BlueskyPublishMode::default(),
+            max_chars: 300,
+        }
+    }
+
+    /// Look up the source post's current CID and thread root
+    async fn source_post(
+        &self,
+        session: &BlueskySession,
+        uri: &str,
+    ) -> Result<PostView, PublishError> {
+        let url = format!("{}/xrpc/app.bsky.feed.getPosts", session.base_url());
+        let response = session
+            .send(|client| client.get(&url).query(&[("uris", uri)]))
+            .await?
+            .success("Failed to get source post")?;
+
+        let posts: GetPostsResponse = response.json()?;
+        posts
+            .posts
+            .into_iter()
+            .next()
+            .ok_or_else(|| PublishError::Api(format!("Source post not found on Bluesky: {}", uri)))
+    }
+}
+
+#[derive(Deserialize)]
+struct GetPostsResponse {
+    posts: Vec<PostView>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PostRecordOut {
+    #[serde(rename = "$type")]
+    kind: &'static str,
+    text: String,
+    created_at: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    facets: Vec<Facet>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reply: Option<ReplyRefOut>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    embed: Option<RecordEmbed>,
+}
+
+#[derive(Serialize)]
+struct ReplyRefOut {
+    root: StrongRef,
+    parent: StrongRef,
+}
+
+#[derive(Serialize)]
+struct RecordEmbed {
+    #[serde(rename = "$type")]
+    kind: &'static str,
+    record: StrongRef,
+}
+
+/// `app.bsky.richtext.facet`, indexed by UTF-8 byte offsets
+#[derive(Debug, PartialEq, Serialize)]
+struct Facet {
+    index: ByteSlice,
+    features: Vec<FacetFeature>,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ByteSlice {
+    byte_start: usize,
+    byte_end: usize,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(tag = "$type")]
+enum FacetFeature {
+    #[serde(rename = "app.bsky.richtext.facet#link")]
+    Link { uri: String },
+    #[serde(rename = "app.bsky.richtext.facet#tag")]
+    Tag { tag: String },
+}
+
+#[derive(Deserialize)]
+struct CreateRecordResponse {
+    uri: String,
+}
+
+/// Detect links and hashtags; Bluesky renders neither without a facet
+fn detect_facets(text: &str) -> Vec<Facet> {
+    let trim = |s: &str| {
+        s.trim_end_matches(['.', ',', ';', ':', '!', '?', ')', '"', '\''])
+            .len()
+    };
+
+    let links = LINK.find_iter(text).map(|m| {
+        let end = m.start() + trim(m.as_str());
+        Facet {
+            index: ByteSlice {
+                byte_start: m.start(),
+                byte_end: end,
+            },
+            features: vec![FacetFeature::Link {
+                uri: text[m.start()..end].to_string(),
+            }],
+        }
+    });
+
+    let tags = HASHTAG.captures_iter(text).filter_map(|caps| {
+        let m = caps.get(1)?;
+        let end = m.start() + trim(m.as_str());
+        let tag = &text[m.start() + 1..end];
+        // "#1" and the like are not tags
+        if tag.is_empty() || tag.chars().all(|c| c.is_ascii_digit()) {
+            return None;
+        }
+        Some(Facet {
+            index: ByteSlice {
+                byte_start: m.start(),
+                byte_end: end,
+            },
+            features: vec![FacetFeature::Tag {
+                tag: tag.to_string(),
+            }],
+        })
+    });
+
+    links.chain(tags).collect()
+}
+
+/// AT-URI of the source post; reposts are addressed through the original's web URL
+fn source_post_uri(post: &RenderedPost) -> Option<String> {
+    if post.source_post_id.starts_with("at://")
+        && post
+            .source_post_id
+            .contains(&format!("/{}/", POST_COLLECTION))
+    {
+        return Some(post.source_post_id.clone());
+    }
+
+    let path = post
+        .source_post_url
+        .strip_prefix("https://bsky.app/profile/")?;
+    let (actor, rkey) = path.split_once("/post/")?;
+    Some(format!("at://{}/{}/{}", actor, POST_COLLECTION, rkey))
+}
+
+#[async_trait]
+impl Publisher for BlueskyPublisher {
+    async fn publish(&self, post: &RenderedPost) -> Result<PublishResult, PublishError> {
+        let Some(session) = self.session.as_deref() else {
+            return Err(PublishError::Api("Publisher disabled".to_string()));
+        };
+
+        let len = post.text.chars().count();
+        if len > self.max_chars {
+            return Err(PublishError::ContentTooLong {
+                len,
+                max: self.max_chars,
+            });
+        }
+
+        let uri = source_post_uri(post).ok_or_else(|| {
+            PublishError::Api(format!(
+                "Source post is not a Bluesky post: {}",
+                post.source_post_url
+            ))
+        })?;
+        let source = self.source_post(session, &uri).await?;
+        let parent = source.strong_ref();
+
+        let (reply, embed) = match self.mode {
+            BlueskyPublishMode::Reply => {
+                let root = source
+                    .record
+                    .reply
+                    .map(|reply| reply.root)
+                    .unwrap_or_else(|| parent.clone());
+                (Some(ReplyRefOut { root, parent }), None)
+            }
+            BlueskyPublishMode::Quote => (
+                None,
+                Some(RecordEmbed {
+                    kind: "app.bsky.embed.record",
+                    record: parent,
+                }),
+            ),
+        };
+
+        let record = PostRecordOut {
+            kind: POST_COLLECTION,
+            text: post.text.clone(),
+            created_at: OffsetDateTime::now_utc()
+                .format(&time::format_description::well_known::Rfc3339)
+                .map_err(|e| PublishError::Api(e.to_string()))?,
+            facets: detect_facets(&post.text),
+            reply,
+            embed,
+        };
+
+        let (did, handle) = session.account().await?;
+        let url = format!("{}/xrpc/com.atproto.repo.createRecord", session.base_url());
+        let body = serde_json::json!({
+            "repo": did,
+            "collection": POST_COLLECTION,
+            "record": record,
+        });
+        let created: CreateRecordResponse = session
+            .send(|client| client.post(&url).json(&body))
+            .await?
+            .success("Failed to create post")?
+            .json()?;
+
+        Ok(PublishResult {
+            url: Some(post_url(&handle, &created.uri)),
+            id: created.uri,
+        })
+    }
+
+    fn is_enabled(&self) -> bool {
+        self.session.is_some()
+    }
+
+    fn platform(&self) -> &'static str {
+        "bluesky"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use secrecy::SecretString;
+    use wiremock::matchers::{body_partial_json, header, method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    const SOURCE_URI: &str = "at://did:plc:alice/app.bsky.feed.post/3kaaaaaaaaa22";
+
+    async fn mock_session(mock_server: &MockServer) -> Arc<BlueskySession> {
+        Mock::given(method("POST"))
+            .and(path("/xrpc/com.atproto.server.createSession"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "accessJwt": "access",
+                "refreshJwt": "refresh",
+                "did": "did:plc:bot",
+                "handle": "bot.bsky.social"
+            })))
+            .mount(mock_server)
+            .await;
+
+        Arc::new(BlueskySession::new(
+            mock_server.uri(),
+            "bot.bsky.social".to_string(),
+            SecretString::new("app-password".into()),
+        ))
+    }
+
+    async fn mock_source_post(mock_server: &MockServer, reply: Option<serde_json::Value>) {
+        let mut record = serde_json::json!({
+            "$type": "app.bsky.feed.post",
+            "text": "source",
+            "createdAt": "2024-01-01T12:00:00.000Z"
+        });
+        if let Some(reply) = reply {
+            record["reply"] = reply;
+        }
+
+        Mock::given(method("GET"))
+            .and(path("/xrpc/app.bsky.feed.getPosts"))
+            .and(query_param("uris", SOURCE_URI))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "posts": [{
+                    "uri": SOURCE_URI,
+                    "cid": "cid-source",
+                    "author": {"did": "did:plc:alice", "handle": "alice.bsky.social"},
+                    "record": record,
+                    "indexedAt": "2024-01-01T12:00:01.000Z"
+                }]
+            })))
+            .mount(mock_server)
+            .await;
+    }
+
+    fn rendered(text: &str) -> RenderedPost {
+        RenderedPost {
+            text: text.to_string(),
+            source_post_id: SOURCE_URI.to_string(),
+            source_post_url: "https://bsky.app/profile/alice.bsky.social/post/3kaaaaaaaaa22"
+                .to_string(),
+            source_author: "alice.bsky.social".to_string(),
+            labels: vec![],
+            article: None,
+            analysis: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_reply_keeps_thread_root() {
+        let mock_server = MockServer::start().await;
+        let session = mock_session(&mock_server).await;
+        let root = serde_json::json!({
+            "uri": "at://did:plc:bob/app.bsky.feed.post/3kroot",
+            "cid": "cid-root"
+        });
+        mock_source_post(
+            &mock_server,
+            Some(serde_json::json!({"root": root, "parent": root})),
+        )
+        .await;
+
+        Mock::given(method("POST"))
+            .and(path("/xrpc/com.atproto.repo.createRecord"))
+            .and(header("authorization", "Bearer access"))
+            .and(body_partial_json(serde_json::json!({
+                "repo": "did:plc:bot",
+                "collection": "app.bsky.feed.post",
+                "record": {
+                    "text": "Tags: tag_one (0.85)",
+                    "reply": {
+                        "root": root,
+                        "parent": {"uri": SOURCE_URI, "cid": "cid-source"}
+                    }
+                }
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "uri": "at://did:plc:bot/app.bsky.feed.post/3kbbbbbbbbb22",
+                "cid": "cid-new"
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let publisher = BlueskyPublisher::new(session, BlueskyPublishMode::Reply, 300);
+        let result = publisher
+            .publish(&rendered("Tags: tag_one (0.85)"))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            result.id,
+            "at://did:plc:bot/app.bsky.feed.post/3kbbbbbbbbb22"
+        );
+        assert_eq!(
+            result.url.as_deref(),
+            Some("https://bsky.app/profile/bot.bsky.social/post/3kbbbbbbbbb22")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_quote_embeds_source_with_facets() {
+        let mock_server = MockServer::start().await;
+        let session = mock_session(&mock_server).await;
+        mock_source_post(&mock_server, None).await;
+
+        Mock::given(method("POST"))
+            .and(path("/xrpc/com.atproto.repo.createRecord"))
+            .and(body_partial_json(serde_json::json!({
+                "record": {
+                    "embed": {
+                        "$type": "app.bsky.embed.record",
+                        "record": {"uri": SOURCE_URI, "cid": "cid-source"}
+                    },
+                    "facets": [{
+                        "index": {"byteStart": 6, "byteEnd": 14},
+                        "features": [{"$type": "app.bsky.richtext.facet#tag", "tag": "tag_one"}]
+                    }]
+                }
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "uri": "at://did:plc:bot/app.bsky.feed.post/3kccccccccc22",
+                "cid": "cid-new"
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let publisher = BlueskyPublisher::new(session, BlueskyPublishMode::Quote, 300);
+        publisher
+            .publish(&rendered("Tags: #tag_one"))
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_rejects_non_bluesky_source() {
+        let mock_server = MockServer::start().await;
+        let session = mock_session(&mock_server).await;
+        let publisher = BlueskyPublisher::new(session, BlueskyPublishMode::Reply, 300);
+
+        let post = RenderedPost {
+            text: "Tags".to_string(),
+            source_post_id: "1234567890".to_string(),
+            source_post_url: "https://x.com/user/status/1234567890".to_string(),
+            source_author: "alice.bsky.social".to_string(),
+            labels: vec![],
+            article: None,
+            analysis: None,
+        };
+
+        assert!(matches!(
+            publisher.publish(&post).await,
+            Err(PublishError::Api(_))
+        ));
+    }
+
+    #[test]
+    fn test_detect_facets_uses_byte_offsets() {
+        let text = "Café https://example.com/a. #news #1";
+        let facets = detect_facets(text);
+
+        assert_eq!(facets.len(), 2);
+        assert_eq!(
+            facets[0],
+            Facet {
+                index: ByteSlice {
+                    byte_start: 6,
+                    byte_end: 27
+                },
+                features: vec![FacetFeature::Link {
+                    uri: "https://example.com/a".to_string()
+                }],
+            }
+        );
+        assert_eq!(
+            &text[facets[1].index.byte_start..facets[1].index.byte_end],
+            "#news"
+        );
+    }
+
+    #[test]
+    fn test_disabled_publisher() {
+        let publisher = BlueskyPublisher::disabled();
+        assert!(!publisher.is_enabled());
+        assert_eq!(publisher.platform(), "bluesky");
+    }
+}
@@ -56,6 +56,7 @@ impl FsDefinitionsRepo {
     }
 
     /// Simple YAML-like frontmatter parser
+    #[allow(clippy::collapsible_match)]
     fn parse_simple_yaml(&self, yaml: &str) -> Frontmatter {
         let mut fm = Frontmatter::default();
 
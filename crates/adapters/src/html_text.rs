@@ -0,0 +1,70 @@
+//! Minimal HTML to plain text conversion for post bodies
+
+use regex::Regex;
+use std::sync::LazyLock;
+
+static BLOCK_BREAK: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)<\s*(br\s*/?|/p|/div|/li|/blockquote|/h[1-6])\s*>").expect("Valid regex")
+});
+static COMMENT: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?s)<!--.*?-->").expect("Valid regex"));
+static SCRIPT_OR_STYLE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?is)<(script|style)\b.*?</(script|style)\s*>").expect("Valid regex")
+});
+static TAG: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?s)<[^>]*>").expect("Valid regex"));
+static ENTITY: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"&(#[0-9]+|#[xX][0-9a-fA-F]+|[a-zA-Z]+);").expect("Valid regex"));
+static WHITESPACE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\s+").expect("Valid regex"));
+static BLANK_LINES: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\n{3,}").expect("Valid regex"));
+
+/// Strip tags, turn block boundaries into line breaks and decode entities
+pub(crate) fn html_to_text(html: &str) -> String {
+    let text = COMMENT.replace_all(html, "");
+    let text = SCRIPT_OR_STYLE.replace_all(&text, "");
+    // Source newlines are insignificant in HTML; only block boundaries break lines
+    let text = WHITESPACE.replace_all(&text, " ");
+    let text = BLOCK_BREAK.replace_all(&text, "\n");
+    let text = TAG.replace_all(&text, "");
+    let text = ENTITY.replace_all(&text, |caps: &regex::Captures<'_>| decode_entity(&caps[1]));
+
+    let lines: Vec<String> = text.lines().map(|line| line.trim().to_string()).collect();
+    BLANK_LINES
+        .replace_all(lines.join("\n").trim(), "\n\n")
+        .into_owned()
+}
+
+fn decode_entity(entity: &str) -> String {
+    let decoded = match entity {
+        "amp" => Some('&'),
+        "lt" => Some('<'),
+        "gt" => Some('>'),
+        "quot" => Some('"'),
+        "apos" => Some('\''),
+        "nbsp" => Some(' '),
+        _ => entity
+            .strip_prefix("#x")
+            .or_else(|| entity.strip_prefix("#X"))
+            .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+            .or_else(|| entity.strip_prefix('#').and_then(|dec| dec.parse().ok()))
+            .and_then(char::from_u32),
+    };
+    decoded.map_or_else(|| format!("&{};", entity), String::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strips_tags_and_decodes_entities() {
+        let html =
+            "<p>Fish &amp; chips</p><p>Cost: &lt;&#36;5&gt; <a href=\"x\">link</a>&#x21;</p>";
+        assert_eq!(html_to_text(html), "Fish & chips\nCost: <$5> link!");
+    }
+
+    #[test]
+    fn collapses_whitespace_and_breaks() {
+        let html = "Line one<br/>   Line\n   two<br><br><br><br>Line three";
+        assert_eq!(html_to_text(html), "Line one\nLine two\n\nLine three");
+    }
+}
@@ -72,6 +72,7 @@ mod tests {
             is_repost: false,
             is_reply: false,
             reply_to_id: None,
+            context: vec![],
         }
     }
 
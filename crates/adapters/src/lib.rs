@@ -2,19 +2,38 @@
 //!
 //! This crate contains infrastructure adapters implementing the domain ports:
 //! - `definitions`: Filesystem-based definitions loader
-//! - `state`: SQLite and in-memory state stores
+//! - `state`: SQLite, PostgreSQL and in-memory state stores
 //! - `llm`: LLM provider adapters (OpenAI, Anthropic, etc.)
 //! - `x`: X (Twitter) API adapters
-//! - `nostr`: Nostr publishing adapter
+//! - `nostr`: Nostr publishing adapter and relay post source
+//! - `mastodon`: Mastodon API adapters
+//! - `bluesky`: Bluesky (AT Protocol) adapters
 //! - `jsonl_source`: JSONL file-based post source
+//! - `feed`: RSS/Atom feed post source
+//! - `routed`: Prefix-based routing across post sources
+//! - `webhook`: Signed JSON webhook publisher
+//! - `site`: Static HTML/JSON site export of stored classifications
+//! - `metrics`: Prometheus metrics for the run loop
 
 mod definitions_fs;
+mod feed_source;
+mod html_text;
 mod jsonl_source;
+mod metrics_prometheus;
 pub mod outbox;
+mod routed_source;
+mod site_export;
+mod state_maintenance;
 mod state_memory;
+mod state_migrations;
+mod state_postgres;
+mod state_rows;
 mod state_sqlite;
+mod webhook_publisher;
 
+pub mod bluesky_api;
 pub mod llm;
+pub mod mastodon_api;
 pub mod nostr;
 pub mod x_api;
 
@@ -25,16 +44,58 @@ pub mod definitions {
 
 /// Re-exports for state adapters
 pub mod state {
+    pub use crate::state_maintenance::{PruneReport, StateStats, TableStats, TagCount};
     pub use crate::state_memory::InMemoryStateStore;
+    pub use crate::state_migrations::{MIGRATIONS, Migration};
+    pub use crate::state_postgres::PostgresStateStore;
     pub use crate::state_sqlite::SqliteStateStore;
 }
 
+/// Re-exports for the static site export
+pub mod site {
+    pub use crate::site_export::{SiteBuilder, SiteError, SiteReport};
+}
+
+/// Re-exports for run loop metrics
+pub mod metrics {
+    pub use crate::metrics_prometheus::PrometheusMetrics;
+}
+
+/// Re-exports for webhook adapters
+pub mod webhook {
+    pub use crate::webhook_publisher::{
+        EVENT_ID_HEADER, SIGNATURE_HEADER, TIMESTAMP_HEADER, WebhookPublisher,
+    };
+}
+
 /// Re-exports for X API adapters
 pub mod x {
     pub use crate::x_api::{StubPostSource, StubXPublisher, XPostSource, XPublisher};
 }
 
+/// Re-exports for Mastodon API adapters
+pub mod mastodon {
+    pub use crate::mastodon_api::{MastodonPostSource, MastodonPublishMode, MastodonPublisher};
+}
+
+/// Re-exports for Bluesky adapters
+pub mod bluesky {
+    pub use crate::bluesky_api::{
+        BlueskyPostSource, BlueskyPublishMode, BlueskyPublisher, BlueskySession,
+    };
+}
+
 /// Re-exports for file-based post source
 pub mod jsonl {
     pub use crate::jsonl_source::JsonlPostSource;
 }
+
+/// Re-exports for RSS/Atom feed post source
+pub mod feed {
+    pub use crate::feed_source::{FeedPostSource, FeedSubscription};
+}
+
+/// Re-exports for prefix-routed post source
+pub mod routed {
+    pub use crate::routed_source::RoutedPostSource;
+}
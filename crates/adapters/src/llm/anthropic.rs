@@ -1,13 +1,16 @@
 //! Anthropic Claude API adapter
 
 use async_trait::async_trait;
-use news_tagger_domain::{Classifier, ClassifyError, ClassifyInput, ClassifyOutput};
+use news_tagger_domain::{Classifier, ClassifyError, ClassifyInput, ClassifyOutput, TokenUsage};
 use reqwest::Client;
 use secrecy::{ExposeSecret, SecretString};
 use serde::{Deserialize, Serialize};
 use std::time::Duration;
 
-use super::{LlmConfig, build_classification_prompt, parse_classification_response};
+use super::{
+    LlmConfig, build_classification_prompt, parse_classification_response, record_usage,
+    retry_after,
+};
 
 /// Anthropic classifier
 pub struct AnthropicClassifier {
@@ -30,7 +33,17 @@ impl AnthropicClassifier {
         }
     }
 
-    async fn call_api(&self, prompt: &str) -> Result<String, ClassifyError> {
+    #[tracing::instrument(
+        name = "llm_request",
+        skip_all,
+        fields(
+            provider = "anthropic",
+            model = %self.config.model,
+            input_tokens = tracing::field::Empty,
+            output_tokens = tracing::field::Empty,
+        )
+    )]
+    async fn call_api(&self, prompt: &str) -> Result<(String, Option<TokenUsage>), ClassifyError> {
         let request = AnthropicRequest {
             model: self.config.model.clone(),
             max_tokens: self.config.max_output_tokens,
@@ -62,7 +75,7 @@ impl AnthropicClassifier {
             })?;
 
         if response.status() == 429 {
-            return Err(ClassifyError::RateLimited);
+            return Err(ClassifyError::RateLimited(retry_after(&response)));
         }
 
         if !response.status().is_success() {
@@ -78,6 +91,10 @@ impl AnthropicClassifier {
             .json()
             .await
             .map_err(|e| ClassifyError::InvalidFormat(e.to_string()))?;
+        let usage = api_response.usage.map(|u| TokenUsage {
+            input_tokens: u.input_tokens,
+            output_tokens: u.output_tokens,
+        });
 
         let text = api_response
             .content
@@ -96,7 +113,8 @@ impl AnthropicClassifier {
             return Err(ClassifyError::InvalidFormat("Empty response".to_string()));
         }
 
-        Ok(text)
+        record_usage(usage);
+        Ok((text, usage))
     }
 }
 
@@ -120,6 +138,14 @@ struct Message {
 #[derive(Deserialize)]
 struct AnthropicResponse {
     content: Vec<ContentBlock>,
+    #[serde(default)]
+    usage: Option<AnthropicUsage>,
+}
+
+#[derive(Deserialize)]
+struct AnthropicUsage {
+    input_tokens: u64,
+    output_tokens: u64,
 }
 
 #[derive(Deserialize)]
@@ -135,6 +161,7 @@ impl Classifier for AnthropicClassifier {
         let prompt = build_classification_prompt(
             &input.post.text,
             &input.post.author,
+            &input.post.context,
             &input.definitions,
             input.policy_text.as_deref(),
         );
@@ -147,15 +174,15 @@ impl Classifier for AnthropicClassifier {
             }
 
             match self.call_api(&prompt).await {
-                Ok(response_text) => match parse_classification_response(&response_text) {
-                    Ok(output) => return Ok(output),
+                Ok((response_text, usage)) => match parse_classification_response(&response_text) {
+                    Ok(output) => return Ok(ClassifyOutput { usage, ..output }),
                     Err(e) => {
                         tracing::warn!(error = %e, "Failed to parse response");
                         last_error = Some(ClassifyError::InvalidFormat(e));
                     }
                 },
-                Err(ClassifyError::RateLimited) => {
-                    return Err(ClassifyError::RateLimited);
+                Err(error @ ClassifyError::RateLimited(_)) => {
+                    return Err(error);
                 }
                 Err(e) => {
                     last_error = Some(e);
@@ -165,4 +192,8 @@ impl Classifier for AnthropicClassifier {
 
         Err(last_error.unwrap_or_else(|| ClassifyError::Api("Unknown error".to_string())))
     }
+
+    fn provider(&self) -> &str {
+        "anthropic"
+    }
 }
@@ -28,4 +28,8 @@ impl Classifier for ClaudeCodeClassifier {
     async fn classify(&self, input: ClassifyInput) -> Result<ClassifyOutput, ClassifyError> {
         self.inner.classify(input).await
     }
+
+    fn provider(&self) -> &str {
+        "claude_code"
+    }
 }
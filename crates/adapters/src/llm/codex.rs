@@ -32,6 +32,10 @@ impl Classifier for CodexClassifier {
     async fn classify(&self, input: ClassifyInput) -> Result<ClassifyOutput, ClassifyError> {
         self.inner.classify(input).await
     }
+
+    fn provider(&self) -> &str {
+        "codex"
+    }
 }
 
 #[cfg(test)]
@@ -51,6 +55,7 @@ mod tests {
                 is_repost: false,
                 is_reply: false,
                 reply_to_id: None,
+                context: vec![],
             },
             definitions: vec![TagDefinition {
                 id: "test_tag".to_string(),
@@ -1,13 +1,16 @@
 //! Google Gemini API adapter
 
 use async_trait::async_trait;
-use news_tagger_domain::{Classifier, ClassifyError, ClassifyInput, ClassifyOutput};
+use news_tagger_domain::{Classifier, ClassifyError, ClassifyInput, ClassifyOutput, TokenUsage};
 use reqwest::Client;
 use secrecy::{ExposeSecret, SecretString};
 use serde::{Deserialize, Serialize};
 use std::time::Duration;
 
-use super::{LlmConfig, build_classification_prompt, parse_classification_response};
+use super::{
+    LlmConfig, build_classification_prompt, parse_classification_response, record_usage,
+    retry_after,
+};
 
 /// Gemini classifier
 pub struct GeminiClassifier {
@@ -30,7 +33,17 @@ impl GeminiClassifier {
         }
     }
 
-    async fn call_api(&self, prompt: &str) -> Result<String, ClassifyError> {
+    #[tracing::instrument(
+        name = "llm_request",
+        skip_all,
+        fields(
+            provider = "gemini",
+            model = %self.config.model,
+            input_tokens = tracing::field::Empty,
+            output_tokens = tracing::field::Empty,
+        )
+    )]
+    async fn call_api(&self, prompt: &str) -> Result<(String, Option<TokenUsage>), ClassifyError> {
         let request = GeminiRequest {
             contents: vec![Content {
                 parts: vec![Part {
@@ -71,7 +84,7 @@ impl GeminiClassifier {
             })?;
 
         if response.status() == 429 {
-            return Err(ClassifyError::RateLimited);
+            return Err(ClassifyError::RateLimited(retry_after(&response)));
         }
 
         if !response.status().is_success() {
@@ -87,6 +100,10 @@ impl GeminiClassifier {
             .json()
             .await
             .map_err(|e| ClassifyError::InvalidFormat(e.to_string()))?;
+        let usage = api_response.usage_metadata.map(|u| TokenUsage {
+            input_tokens: u.prompt_token_count,
+            output_tokens: u.candidates_token_count,
+        });
 
         let text = api_response
             .candidates
@@ -100,7 +117,8 @@ impl GeminiClassifier {
             return Err(ClassifyError::InvalidFormat("Empty response".to_string()));
         }
 
-        Ok(text)
+        record_usage(usage);
+        Ok((text, usage))
     }
 }
 
@@ -139,6 +157,16 @@ struct SystemInstruction {
 #[derive(Deserialize)]
 struct GeminiResponse {
     candidates: Vec<Candidate>,
+    #[serde(default, rename = "usageMetadata")]
+    usage_metadata: Option<UsageMetadata>,
+}
+
+#[derive(Deserialize)]
+struct UsageMetadata {
+    #[serde(default, rename = "promptTokenCount")]
+    prompt_token_count: u64,
+    #[serde(default, rename = "candidatesTokenCount")]
+    candidates_token_count: u64,
 }
 
 #[derive(Deserialize)]
@@ -157,6 +185,7 @@ impl Classifier for GeminiClassifier {
         let prompt = build_classification_prompt(
             &input.post.text,
             &input.post.author,
+            &input.post.context,
             &input.definitions,
             input.policy_text.as_deref(),
         );
@@ -169,15 +198,15 @@ impl Classifier for GeminiClassifier {
             }
 
             match self.call_api(&prompt).await {
-                Ok(response_text) => match parse_classification_response(&response_text) {
-                    Ok(output) => return Ok(output),
+                Ok((response_text, usage)) => match parse_classification_response(&response_text) {
+                    Ok(output) => return Ok(ClassifyOutput { usage, ..output }),
                     Err(e) => {
                         tracing::warn!(error = %e, "Failed to parse response");
                         last_error = Some(ClassifyError::InvalidFormat(e));
                     }
                 },
-                Err(ClassifyError::RateLimited) => {
-                    return Err(ClassifyError::RateLimited);
+                Err(error @ ClassifyError::RateLimited(_)) => {
+                    return Err(error);
                 }
                 Err(e) => {
                     last_error = Some(e);
@@ -187,4 +216,8 @@ impl Classifier for GeminiClassifier {
 
         Err(last_error.unwrap_or_else(|| ClassifyError::Api("Unknown error".to_string())))
     }
+
+    fn provider(&self) -> &str {
+        "gemini"
+    }
 }
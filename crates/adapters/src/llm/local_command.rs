@@ -108,6 +108,7 @@ impl Classifier for LocalCommandClassifier {
         let prompt = build_classification_prompt(
             &input.post.text,
             &input.post.author,
+            &input.post.context,
             &input.definitions,
             input.policy_text.as_deref(),
         );
@@ -135,6 +136,10 @@ impl Classifier for LocalCommandClassifier {
 
         Err(last_error.unwrap_or_else(|| ClassifyError::Api("Unknown error".to_string())))
     }
+
+    fn provider(&self) -> &str {
+        "local_command"
+    }
 }
 
 struct CommandContext<'a> {
@@ -222,6 +227,7 @@ mod tests {
                 is_repost: false,
                 is_reply: false,
                 reply_to_id: None,
+                context: vec![],
             },
             definitions: vec![TagDefinition {
                 id: "test_tag".to_string(),
@@ -22,7 +22,9 @@ pub use openai_compat::OpenAiCompatClassifier;
 pub use opencode::OpenCodeClassifier;
 pub use stub::StubClassifier;
 
+use news_tagger_domain::{ContextRelation, PostContext, TokenUsage};
 use serde::{Deserialize, Serialize};
+use std::time::Duration;
 
 /// Common LLM configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -51,10 +53,31 @@ impl Default for LlmConfig {
     }
 }
 
+/// Parse a `retry-after` header given in seconds
+pub(crate) fn retry_after(response: &reqwest::Response) -> Option<Duration> {
+    response
+        .headers()
+        .get(reqwest::header::RETRY_AFTER)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|s| s.trim().parse::<u64>().ok())
+        .map(Duration::from_secs)
+}
+
+/// Record the provider's token counts on the current `llm_request` span
+pub(crate) fn record_usage(usage: Option<TokenUsage>) {
+    if let Some(usage) = usage {
+        let span = tracing::Span::current();
+        // Signed, so OpenTelemetry exports them as numbers
+        span.record("input_tokens", usage.input_tokens as i64);
+        span.record("output_tokens", usage.output_tokens as i64);
+    }
+}
+
 /// Build the classification prompt
 pub fn build_classification_prompt(
     post_text: &str,
     author: &str,
+    context: &[PostContext],
     definitions: &[news_tagger_domain::TagDefinition],
     policy_text: Option<&str>,
 ) -> String {
@@ -66,6 +89,24 @@ pub fn build_classification_prompt(
     prompt.push_str(&format!("Author: {}\n", author));
     prompt.push_str(&format!("Content: {}\n\n", post_text));
 
+    if !context.is_empty() {
+        prompt.push_str("## Context (reference only)\n");
+        prompt.push_str("The post above refers to the following posts. Use them only to interpret the post; do not classify them and never quote them as evidence.\n");
+        for item in context {
+            let relation = match item.relation {
+                ContextRelation::Quoted => "Quoted post",
+                ContextRelation::RepliedTo => "Replied-to post",
+                ContextRelation::ConversationRoot => "Conversation root",
+            };
+            match &item.author {
+                Some(author) => prompt.push_str(&format!("### {} by {}\n", relation, author)),
+                None => prompt.push_str(&format!("### {}\n", relation)),
+            }
+            prompt.push_str(&format!("Content: {}\n", item.text));
+        }
+        prompt.push('\n');
+    }
+
     prompt.push_str("## Tag Definitions\n");
     for def in definitions {
         prompt.push_str(&format!("### {} (ID: {})\n", def.title, def.id));
@@ -99,7 +140,7 @@ Respond with ONLY a JSON object matching this exact schema:
 
 Rules:
 - Only include tags with confidence >= 0.5
-- Evidence must be direct quotes from the post
+- Evidence must be direct quotes from the post under "Post to Analyze", never from context
 - If no tags apply, return empty tags array
 - Be objective and neutral
 "#,
@@ -166,6 +207,31 @@ mod tests {
         assert_eq!(extract_json(input), r#"{"version": "1", "tags": []}"#);
     }
 
+    #[test]
+    fn test_prompt_presents_context_separately() {
+        let context = vec![PostContext {
+            relation: ContextRelation::Quoted,
+            id: "42".to_string(),
+            author: Some("origin".to_string()),
+            text: "The original claim".to_string(),
+        }];
+
+        let prompt =
+            build_classification_prompt("this is exactly it", "replier", &context, &[], None);
+
+        let post_pos = prompt.find("## Post to Analyze").unwrap();
+        let context_pos = prompt.find("## Context (reference only)").unwrap();
+        assert!(post_pos < context_pos);
+        assert!(prompt.contains("### Quoted post by origin\nContent: The original claim"));
+        assert!(prompt.contains("never from context"));
+    }
+
+    #[test]
+    fn test_prompt_omits_empty_context() {
+        let prompt = build_classification_prompt("text", "author", &[], &[], None);
+        assert!(!prompt.contains("## Context"));
+    }
+
     #[test]
     fn test_parse_valid_response() {
         let json = r#"{
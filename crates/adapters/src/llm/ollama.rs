@@ -1,12 +1,12 @@
 //! Ollama local LLM adapter
 
 use async_trait::async_trait;
-use news_tagger_domain::{Classifier, ClassifyError, ClassifyInput, ClassifyOutput};
+use news_tagger_domain::{Classifier, ClassifyError, ClassifyInput, ClassifyOutput, TokenUsage};
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use std::time::Duration;
 
-use super::{LlmConfig, build_classification_prompt, parse_classification_response};
+use super::{LlmConfig, build_classification_prompt, parse_classification_response, record_usage};
 
 /// Ollama classifier for local LLMs
 pub struct OllamaClassifier {
@@ -33,7 +33,17 @@ impl OllamaClassifier {
         }
     }
 
-    async fn call_api(&self, prompt: &str) -> Result<String, ClassifyError> {
+    #[tracing::instrument(
+        name = "llm_request",
+        skip_all,
+        fields(
+            provider = "ollama",
+            model = %self.config.model,
+            input_tokens = tracing::field::Empty,
+            output_tokens = tracing::field::Empty,
+        )
+    )]
+    async fn call_api(&self, prompt: &str) -> Result<(String, Option<TokenUsage>), ClassifyError> {
         let request = OllamaRequest {
             model: self.config.model.clone(),
             prompt: prompt.to_string(),
@@ -82,7 +92,15 @@ impl OllamaClassifier {
             return Err(ClassifyError::InvalidFormat("Empty response".to_string()));
         }
 
-        Ok(api_response.response)
+        let usage = match (api_response.prompt_eval_count, api_response.eval_count) {
+            (None, None) => None,
+            (input, output) => Some(TokenUsage {
+                input_tokens: input.unwrap_or_default(),
+                output_tokens: output.unwrap_or_default(),
+            }),
+        };
+        record_usage(usage);
+        Ok((api_response.response, usage))
     }
 }
 
@@ -108,6 +126,10 @@ struct OllamaOptions {
 #[derive(Deserialize)]
 struct OllamaResponse {
     response: String,
+    #[serde(default)]
+    prompt_eval_count: Option<u64>,
+    #[serde(default)]
+    eval_count: Option<u64>,
 }
 
 #[async_trait]
@@ -116,6 +138,7 @@ impl Classifier for OllamaClassifier {
         let prompt = build_classification_prompt(
             &input.post.text,
             &input.post.author,
+            &input.post.context,
             &input.definitions,
             input.policy_text.as_deref(),
         );
@@ -128,8 +151,8 @@ impl Classifier for OllamaClassifier {
             }
 
             match self.call_api(&prompt).await {
-                Ok(response_text) => match parse_classification_response(&response_text) {
-                    Ok(output) => return Ok(output),
+                Ok((response_text, usage)) => match parse_classification_response(&response_text) {
+                    Ok(output) => return Ok(ClassifyOutput { usage, ..output }),
                     Err(e) => {
                         tracing::warn!(error = %e, "Failed to parse response");
                         last_error = Some(ClassifyError::InvalidFormat(e));
@@ -143,4 +166,8 @@ impl Classifier for OllamaClassifier {
 
         Err(last_error.unwrap_or_else(|| ClassifyError::Api("Unknown error".to_string())))
     }
+
+    fn provider(&self) -> &str {
+        "ollama"
+    }
 }
@@ -1,13 +1,16 @@
 //! OpenAI Responses API adapter
 
 use async_trait::async_trait;
-use news_tagger_domain::{Classifier, ClassifyError, ClassifyInput, ClassifyOutput};
+use news_tagger_domain::{Classifier, ClassifyError, ClassifyInput, ClassifyOutput, TokenUsage};
 use reqwest::Client;
 use secrecy::{ExposeSecret, SecretString};
 use serde::{Deserialize, Serialize};
 use std::time::Duration;
 
-use super::{LlmConfig, build_classification_prompt, parse_classification_response};
+use super::{
+    LlmConfig, build_classification_prompt, parse_classification_response, record_usage,
+    retry_after,
+};
 
 /// OpenAI classifier using the Responses API
 pub struct OpenAiClassifier {
@@ -36,7 +39,17 @@ impl OpenAiClassifier {
         }
     }
 
-    async fn call_api(&self, prompt: &str) -> Result<String, ClassifyError> {
+    #[tracing::instrument(
+        name = "llm_request",
+        skip_all,
+        fields(
+            provider = "openai",
+            model = %self.config.model,
+            input_tokens = tracing::field::Empty,
+            output_tokens = tracing::field::Empty,
+        )
+    )]
+    async fn call_api(&self, prompt: &str) -> Result<(String, Option<TokenUsage>), ClassifyError> {
         let request = OpenAiRequest {
             model: self.config.model.clone(),
             input: prompt.to_string(),
@@ -69,7 +82,7 @@ impl OpenAiClassifier {
             })?;
 
         if response.status() == 429 {
-            return Err(ClassifyError::RateLimited);
+            return Err(ClassifyError::RateLimited(retry_after(&response)));
         }
 
         if !response.status().is_success() {
@@ -85,6 +98,10 @@ impl OpenAiClassifier {
             .json()
             .await
             .map_err(|e| ClassifyError::InvalidFormat(e.to_string()))?;
+        let usage = api_response.usage.map(|u| TokenUsage {
+            input_tokens: u.input_tokens,
+            output_tokens: u.output_tokens,
+        });
 
         // Extract text from response
         let text = api_response
@@ -110,7 +127,8 @@ impl OpenAiClassifier {
             return Err(ClassifyError::InvalidFormat("Empty response".to_string()));
         }
 
-        Ok(text)
+        record_usage(usage);
+        Ok((text, usage))
     }
 }
 
@@ -129,6 +147,14 @@ struct OpenAiRequest {
 #[derive(Deserialize)]
 struct OpenAiResponse {
     output: Vec<OutputItem>,
+    #[serde(default)]
+    usage: Option<OpenAiUsage>,
+}
+
+#[derive(Deserialize)]
+struct OpenAiUsage {
+    input_tokens: u64,
+    output_tokens: u64,
 }
 
 #[derive(Deserialize)]
@@ -151,6 +177,7 @@ impl Classifier for OpenAiClassifier {
         let prompt = build_classification_prompt(
             &input.post.text,
             &input.post.author,
+            &input.post.context,
             &input.definitions,
             input.policy_text.as_deref(),
         );
@@ -163,15 +190,15 @@ impl Classifier for OpenAiClassifier {
             }
 
             match self.call_api(&prompt).await {
-                Ok(response_text) => match parse_classification_response(&response_text) {
-                    Ok(output) => return Ok(output),
+                Ok((response_text, usage)) => match parse_classification_response(&response_text) {
+                    Ok(output) => return Ok(ClassifyOutput { usage, ..output }),
                     Err(e) => {
                         tracing::warn!(error = %e, "Failed to parse response, will retry");
                         last_error = Some(ClassifyError::InvalidFormat(e));
                     }
                 },
-                Err(ClassifyError::RateLimited) => {
-                    return Err(ClassifyError::RateLimited);
+                Err(error @ ClassifyError::RateLimited(_)) => {
+                    return Err(error);
                 }
                 Err(e) => {
                     last_error = Some(e);
@@ -181,6 +208,10 @@ impl Classifier for OpenAiClassifier {
 
         Err(last_error.unwrap_or_else(|| ClassifyError::Api("Unknown error".to_string())))
     }
+
+    fn provider(&self) -> &str {
+        "openai"
+    }
 }
 
 #[cfg(test)]
@@ -202,6 +233,7 @@ mod tests {
                 is_repost: false,
                 is_reply: false,
                 reply_to_id: None,
+                context: vec![],
             },
             definitions: vec![TagDefinition {
                 id: "climate_fear".to_string(),
@@ -228,7 +260,8 @@ mod tests {
                         }
                     ]
                 }
-            ]
+            ],
+            "usage": { "input_tokens": 412, "output_tokens": 57, "total_tokens": 469 }
         })
     }
 
@@ -253,6 +286,13 @@ mod tests {
 
         assert_eq!(result.tags.len(), 1);
         assert_eq!(result.tags[0].id, "climate_fear");
+        assert_eq!(
+            result.usage,
+            Some(TokenUsage {
+                input_tokens: 412,
+                output_tokens: 57,
+            })
+        );
     }
 
     #[tokio::test]
@@ -261,7 +301,7 @@ mod tests {
 
         Mock::given(method("POST"))
             .and(path("/responses"))
-            .respond_with(ResponseTemplate::new(429))
+            .respond_with(ResponseTemplate::new(429).insert_header("retry-after", "30"))
             .mount(&mock_server)
             .await;
 
@@ -276,7 +316,10 @@ mod tests {
 
         let result = classifier.classify(sample_input()).await;
 
-        assert!(matches!(result, Err(ClassifyError::RateLimited)));
+        assert!(matches!(
+            result,
+            Err(ClassifyError::RateLimited(Some(wait))) if wait == Duration::from_secs(30)
+        ));
     }
 
     #[tokio::test]
@@ -1,13 +1,16 @@
 //! OpenAI-compatible API adapter for generic providers
 
 use async_trait::async_trait;
-use news_tagger_domain::{Classifier, ClassifyError, ClassifyInput, ClassifyOutput};
+use news_tagger_domain::{Classifier, ClassifyError, ClassifyInput, ClassifyOutput, TokenUsage};
 use reqwest::Client;
 use secrecy::{ExposeSecret, SecretString};
 use serde::{Deserialize, Serialize};
 use std::time::Duration;
 
-use super::{LlmConfig, build_classification_prompt, parse_classification_response};
+use super::{
+    LlmConfig, build_classification_prompt, parse_classification_response, record_usage,
+    retry_after,
+};
 
 /// OpenAI-compatible classifier for third-party providers
 pub struct OpenAiCompatClassifier {
@@ -32,7 +35,17 @@ impl OpenAiCompatClassifier {
         }
     }
 
-    async fn call_api(&self, prompt: &str) -> Result<String, ClassifyError> {
+    #[tracing::instrument(
+        name = "llm_request",
+        skip_all,
+        fields(
+            provider = "openai_compat",
+            model = %self.config.model,
+            input_tokens = tracing::field::Empty,
+            output_tokens = tracing::field::Empty,
+        )
+    )]
+    async fn call_api(&self, prompt: &str) -> Result<(String, Option<TokenUsage>), ClassifyError> {
         let request = ChatCompletionRequest {
             model: self.config.model.clone(),
             messages: vec![
@@ -72,7 +85,7 @@ impl OpenAiCompatClassifier {
             })?;
 
         if response.status() == 429 {
-            return Err(ClassifyError::RateLimited);
+            return Err(ClassifyError::RateLimited(retry_after(&response)));
         }
 
         if !response.status().is_success() {
@@ -88,6 +101,10 @@ impl OpenAiCompatClassifier {
             .json()
             .await
             .map_err(|e| ClassifyError::InvalidFormat(e.to_string()))?;
+        let usage = api_response.usage.map(|u| TokenUsage {
+            input_tokens: u.prompt_tokens,
+            output_tokens: u.completion_tokens,
+        });
 
         let text = api_response
             .choices
@@ -100,7 +117,8 @@ impl OpenAiCompatClassifier {
             return Err(ClassifyError::InvalidFormat("Empty response".to_string()));
         }
 
-        Ok(text)
+        record_usage(usage);
+        Ok((text, usage))
     }
 }
 
@@ -123,6 +141,14 @@ struct ChatMessage {
 #[derive(Deserialize)]
 struct ChatCompletionResponse {
     choices: Vec<Choice>,
+    #[serde(default)]
+    usage: Option<ChatCompletionUsage>,
+}
+
+#[derive(Deserialize)]
+struct ChatCompletionUsage {
+    prompt_tokens: u64,
+    completion_tokens: u64,
 }
 
 #[derive(Deserialize)]
@@ -141,6 +167,7 @@ impl Classifier for OpenAiCompatClassifier {
         let prompt = build_classification_prompt(
             &input.post.text,
             &input.post.author,
+            &input.post.context,
             &input.definitions,
             input.policy_text.as_deref(),
         );
@@ -153,15 +180,15 @@ impl Classifier for OpenAiCompatClassifier {
             }
 
             match self.call_api(&prompt).await {
-                Ok(response_text) => match parse_classification_response(&response_text) {
-                    Ok(output) => return Ok(output),
+                Ok((response_text, usage)) => match parse_classification_response(&response_text) {
+                    Ok(output) => return Ok(ClassifyOutput { usage, ..output }),
                     Err(e) => {
                         tracing::warn!(error = %e, "Failed to parse response");
                         last_error = Some(ClassifyError::InvalidFormat(e));
                     }
                 },
-                Err(ClassifyError::RateLimited) => {
-                    return Err(ClassifyError::RateLimited);
+                Err(error @ ClassifyError::RateLimited(_)) => {
+                    return Err(error);
                 }
                 Err(e) => {
                     last_error = Some(e);
@@ -171,4 +198,8 @@ impl Classifier for OpenAiCompatClassifier {
 
         Err(last_error.unwrap_or_else(|| ClassifyError::Api("Unknown error".to_string())))
     }
+
+    fn provider(&self) -> &str {
+        "openai_compat"
+    }
 }
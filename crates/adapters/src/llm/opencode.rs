@@ -97,6 +97,11 @@ impl OpenCodeClassifier {
         Ok(created.id)
     }
 
+    #[tracing::instrument(
+        name = "llm_request",
+        skip_all,
+        fields(provider = "opencode", model = ?self.model_id, session_id = %session_id)
+    )]
     async fn prompt_session(
         &self,
         session_id: &str,
@@ -170,6 +175,7 @@ impl Classifier for OpenCodeClassifier {
         let prompt = build_classification_prompt(
             &input.post.text,
             &input.post.author,
+            &input.post.context,
             &input.definitions,
             input.policy_text.as_deref(),
         );
@@ -198,6 +204,10 @@ impl Classifier for OpenCodeClassifier {
 
         Err(last_error.unwrap_or_else(|| ClassifyError::Api("Unknown error".to_string())))
     }
+
+    fn provider(&self) -> &str {
+        "opencode"
+    }
 }
 
 #[derive(Serialize)]
@@ -276,6 +286,7 @@ mod tests {
                 is_repost: false,
                 is_reply: false,
                 reply_to_id: None,
+                context: vec![],
             },
             definitions: vec![TagDefinition {
                 id: "fear_narrative".to_string(),
@@ -60,7 +60,7 @@ impl Classifier for StubClassifier {
             return Err(match error {
                 ClassifyError::Api(msg) => ClassifyError::Api(msg.clone()),
                 ClassifyError::InvalidFormat(msg) => ClassifyError::InvalidFormat(msg.clone()),
-                ClassifyError::RateLimited => ClassifyError::RateLimited,
+                ClassifyError::RateLimited(wait) => ClassifyError::RateLimited(*wait),
                 ClassifyError::Timeout => ClassifyError::Timeout,
                 ClassifyError::Config(msg) => ClassifyError::Config(msg.clone()),
             });
@@ -102,6 +102,10 @@ impl Classifier for StubClassifier {
             tags,
         ))
     }
+
+    fn provider(&self) -> &str {
+        "stub"
+    }
 }
 
 #[cfg(test)]
@@ -121,6 +125,7 @@ mod tests {
                 is_repost: false,
                 is_reply: false,
                 reply_to_id: None,
+                context: vec![],
             },
             definitions: vec![
                 TagDefinition {
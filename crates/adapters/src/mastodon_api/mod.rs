@@ -0,0 +1,54 @@
+//! Mastodon REST API adapters
+
+mod read;
+mod write;
+
+pub use read::MastodonPostSource;
+pub use write::{MastodonPublishMode, MastodonPublisher};
+
+use serde::Deserialize;
+use std::time::Duration;
+use time::OffsetDateTime;
+
+/// Parse Mastodon's `x-ratelimit-reset` header (an ISO 8601 timestamp) into a wait duration
+fn retry_after(response: &reqwest::Response) -> Option<Duration> {
+    let reset = response
+        .headers()
+        .get("x-ratelimit-reset")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|s| {
+            OffsetDateTime::parse(s, &time::format_description::well_known::Rfc3339).ok()
+        })?;
+    let wait = reset - OffsetDateTime::now_utc();
+    Some(Duration::from_secs(wait.whole_seconds().max(0) as u64))
+}
+
+/// Host part of a URL, without scheme, port or path
+fn url_host(url: &str) -> Option<&str> {
+    let rest = url.split_once("://").map_or(url, |(_, rest)| rest);
+    rest.split(['/', ':', '?', '#'])
+        .next()
+        .filter(|h| !h.is_empty())
+}
+
+/// Status entity as returned by the Mastodon API
+#[derive(Debug, Deserialize)]
+struct Status {
+    id: String,
+    created_at: String,
+    #[serde(default)]
+    content: String,
+    #[serde(default)]
+    spoiler_text: String,
+    url: Option<String>,
+    uri: String,
+    account: Account,
+    in_reply_to_id: Option<String>,
+    reblog: Option<Box<Status>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Account {
+    id: String,
+    acct: String,
+}
@@ -0,0 +1,313 @@
+//! Mastodon read adapter for fetching account and hashtag timelines
+
+use async_trait::async_trait;
+use news_tagger_domain::{PostSource, PostSourceError, SourcePost, compare_post_ids};
+use reqwest::{Client, StatusCode};
+use secrecy::{ExposeSecret, SecretString};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+use time::OffsetDateTime;
+
+use super::{Account, Status, retry_after};
+use crate::html_text::html_to_text;
+
+/// Statuses requested per timeline page (the API maximum)
+const PAGE_LIMIT: &str = "40";
+
+/// Mastodon post source for account (`user` or `user@host`) and hashtag (`#tag`) timelines
+pub struct MastodonPostSource {
+    client: Client,
+    base_url: String,
+    access_token: Option<SecretString>,
+    account_ids: Mutex<HashMap<String, String>>,
+}
+
+impl MastodonPostSource {
+    /// Create a source for an instance; the token is only needed on instances
+    /// that restrict public timelines
+    pub fn new(base_url: String, access_token: Option<SecretString>) -> Self {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(30))
+            .build()
+            .expect("Failed to build HTTP client");
+
+        Self {
+            client,
+            base_url: base_url.trim_end_matches('/').to_string(),
+            access_token,
+            account_ids: Mutex::new(HashMap::new()),
+        }
+    }
+
+    async fn get(
+        &self,
+        path: &str,
+        query: &[(&str, &str)],
+    ) -> Result<reqwest::Response, PostSourceError> {
+        let mut request = self
+            .client
+            .get(format!("{}{}", self.base_url, path))
+            .query(query);
+        if let Some(token) = &self.access_token {
+            request = request.bearer_auth(token.expose_secret());
+        }
+
+        let response = request
+            .send()
+            .await
+            .map_err(|e| PostSourceError::Network(e.to_string()))?;
+
+        if response.status() == StatusCode::UNAUTHORIZED {
+            return Err(PostSourceError::Auth("Invalid access token".to_string()));
+        }
+
+        if response.status() == StatusCode::TOO_MANY_REQUESTS {
+            return Err(PostSourceError::RateLimited(retry_after(&response)));
+        }
+
+        Ok(response)
+    }
+
+    /// Resolve an `acct` handle to its account ID on this instance
+    async fn account_id(&self, acct: &str) -> Result<String, PostSourceError> {
+        let acct = acct.trim_start_matches('@').to_ascii_lowercase();
+        if let Some(id) = self
+            .account_ids
+            .lock()
+            .expect("account id lock poisoned")
+            .get(&acct)
+        {
+            return Ok(id.clone());
+        }
+
+        let response = self
+            .get("/api/v1/accounts/lookup", &[("acct", acct.as_str())])
+            .await?;
+
+        if response.status() == StatusCode::NOT_FOUND {
+            return Err(PostSourceError::AccountUnavailable(format!(
+                "Mastodon account not found: {}",
+                acct
+            )));
+        }
+
+        if !response.status().is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(PostSourceError::Api(format!(
+                "Failed to look up account: {}",
+                body
+            )));
+        }
+
+        let account: Account = response
+            .json()
+            .await
+            .map_err(|e| PostSourceError::Api(e.to_string()))?;
+
+        self.account_ids
+            .lock()
+            .expect("account id lock poisoned")
+            .insert(acct, account.id.clone());
+        Ok(account.id)
+    }
+}
+
+#[async_trait]
+impl PostSource for MastodonPostSource {
+    async fn fetch_posts(
+        &self,
+        account: &str,
+        since_id: Option<&str>,
+    ) -> Result<Vec<SourcePost>, PostSourceError> {
+        let path = match account.strip_prefix('#') {
+            Some(tag) => format!("/api/v1/timelines/tag/{}", tag),
+            None => format!(
+                "/api/v1/accounts/{}/statuses",
+                self.account_id(account).await?
+            ),
+        };
+
+        let mut query = vec![("limit", PAGE_LIMIT)];
+        if let Some(since_id) = since_id {
+            query.push(("since_id", since_id));
+        }
+
+        let response = self.get(&path, &query).await?;
+
+        if !response.status().is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(PostSourceError::Api(format!(
+                "Failed to get statuses: {}",
+                body
+            )));
+        }
+
+        let statuses: Vec<Status> = response
+            .json()
+            .await
+            .map_err(|e| PostSourceError::Api(e.to_string()))?;
+
+        let mut posts: Vec<SourcePost> = statuses.into_iter().map(status_to_post).collect();
+        posts.sort_by(|a, b| compare_post_ids(&a.id, &b.id));
+
+        Ok(posts)
+    }
+}
+
+/// Map a status to a post; boosts carry the boosted status' text and link
+fn status_to_post(status: Status) -> SourcePost {
+    let original = status.reblog.as_deref().unwrap_or(&status);
+
+    let body = html_to_text(&original.content);
+    let text = if original.spoiler_text.is_empty() {
+        body
+    } else {
+        format!("CW: {}\n\n{}", original.spoiler_text, body)
+    };
+    let url = original.url.clone().unwrap_or_else(|| original.uri.clone());
+
+    let created_at = OffsetDateTime::parse(
+        &status.created_at,
+        &time::format_description::well_known::Rfc3339,
+    )
+    .unwrap_or_else(|_| OffsetDateTime::now_utc());
+
+    SourcePost {
+        id: status.id.clone(),
+        text,
+        author: status.account.acct.clone(),
+        url,
+        created_at,
+        is_repost: status.reblog.is_some(),
+        is_reply: status.in_reply_to_id.is_some(),
+        reply_to_id: status.in_reply_to_id.clone(),
+        context: vec![],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn status_json(id: &str, content: &str) -> serde_json::Value {
+        serde_json::json!({
+            "id": id,
+            "created_at": "2024-01-01T12:00:00.000Z",
+            "content": content,
+            "spoiler_text": "",
+            "url": format!("https://mastodon.example/@alice/{}", id),
+            "uri": format!("https://mastodon.example/users/alice/statuses/{}", id),
+            "account": {"id": "42", "acct": "alice"},
+            "in_reply_to_id": null,
+            "reblog": null
+        })
+    }
+
+    #[tokio::test]
+    async fn test_fetch_account_statuses_converts_html() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/api/v1/accounts/lookup"))
+            .and(query_param("acct", "alice"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(serde_json::json!({"id": "42", "acct": "alice"})),
+            )
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        Mock::given(method("GET"))
+            .and(path("/api/v1/accounts/42/statuses"))
+            .and(query_param("since_id", "100"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([
+                status_json("110", "<p>Newer &amp; louder</p>"),
+                status_json("105", "<p>First line<br>second line</p>"),
+            ])))
+            .expect(2)
+            .mount(&mock_server)
+            .await;
+
+        let source = MastodonPostSource::new(mock_server.uri(), None);
+        let posts = source.fetch_posts("@Alice", Some("100")).await.unwrap();
+
+        assert_eq!(posts.len(), 2);
+        assert_eq!(posts[0].id, "105");
+        assert_eq!(posts[0].text, "First line\nsecond line");
+        assert_eq!(posts[1].text, "Newer & louder");
+        assert_eq!(posts[1].author, "alice");
+        assert_eq!(posts[1].url, "https://mastodon.example/@alice/110");
+
+        // Account ID lookups are cached
+        source.fetch_posts("alice", Some("100")).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_fetch_hashtag_timeline_marks_boosts() {
+        let mock_server = MockServer::start().await;
+
+        let mut boost = status_json("200", "");
+        boost["url"] = serde_json::Value::Null;
+        boost["reblog"] = status_json("150", "<p>Boosted text</p>");
+
+        Mock::given(method("GET"))
+            .and(path("/api/v1/timelines/tag/climate"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([boost])))
+            .mount(&mock_server)
+            .await;
+
+        let source = MastodonPostSource::new(mock_server.uri(), None);
+        let posts = source.fetch_posts("#climate", None).await.unwrap();
+
+        assert_eq!(posts.len(), 1);
+        assert_eq!(posts[0].id, "200");
+        assert!(posts[0].is_repost);
+        assert_eq!(posts[0].text, "Boosted text");
+        assert_eq!(posts[0].url, "https://mastodon.example/@alice/150");
+    }
+
+    #[tokio::test]
+    async fn test_unknown_account_is_unavailable() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/api/v1/accounts/lookup"))
+            .respond_with(ResponseTemplate::new(404))
+            .mount(&mock_server)
+            .await;
+
+        let source = MastodonPostSource::new(mock_server.uri(), None);
+        let result = source.fetch_posts("ghost", None).await;
+
+        assert!(matches!(
+            result,
+            Err(PostSourceError::AccountUnavailable(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_rate_limited_uses_reset_header() {
+        let mock_server = MockServer::start().await;
+
+        let reset = (OffsetDateTime::now_utc() + time::Duration::minutes(5))
+            .format(&time::format_description::well_known::Rfc3339)
+            .unwrap();
+        Mock::given(method("GET"))
+            .and(path("/api/v1/timelines/tag/news"))
+            .respond_with(ResponseTemplate::new(429).insert_header("x-ratelimit-reset", reset))
+            .mount(&mock_server)
+            .await;
+
+        let source = MastodonPostSource::new(mock_server.uri(), None);
+        let result = source.fetch_posts("#news", None).await;
+
+        assert!(matches!(
+            result,
+            Err(PostSourceError::RateLimited(Some(wait))) if wait > Duration::from_secs(240)
+        ));
+    }
+}
@@ -0,0 +1,369 @@
+//! Mastodon write adapter for publishing statuses
+
+use async_trait::async_trait;
+use news_tagger_domain::{PublishError, PublishResult, Publisher, RenderedPost};
+use reqwest::{Client, StatusCode};
+use secrecy::{ExposeSecret, SecretString};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+use super::{Status, retry_after, url_host};
+
+/// How the classification status relates to the source status
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MastodonPublishMode {
+    /// Reply in the source status' thread
+    #[default]
+    Reply,
+    /// Boost with comment (quote post)
+    Quote,
+}
+
+/// Mastodon publisher for replying to or quoting source statuses
+pub struct MastodonPublisher {
+    client: Client,
+    access_token: SecretString,
+    base_url: String,
+    mode: MastodonPublishMode,
+    max_chars: usize,
+    enabled: bool,
+}
+
+impl MastodonPublisher {
+    pub fn new(
+        base_url: String,
+        access_token: SecretString,
+        mode: MastodonPublishMode,
+        max_chars: usize,
+    ) -> Self {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(30))
+            .build()
+            .expect("Failed to build HTTP client");
+
+        Self {
+            client,
+            access_token,
+            base_url: base_url.trim_end_matches('/').to_string(),
+            mode,
+            max_chars,
+            enabled: true,
+        }
+    }
+
+    /// Create a disabled publisher (for testing/dry-run)
+    pub fn disabled() -> Self {
+        Self {
+            client: Client::new(),
+            access_token: SecretString::new("".into()),
+            base_url: String::new(),
+            mode: MastodonPublishMode::default(),
+            max_chars: 500,
+            enabled: false,
+        }
+    }
+
+    /// Find the source status' ID on this instance, federating it in if needed
+    async fn local_status_id(&self, post: &RenderedPost) -> Result<String, PublishError> {
+        let source_host = url_host(&post.source_post_url);
+        if source_host.is_some() && source_host == url_host(&self.base_url) {
+            return Ok(post.source_post_id.clone());
+        }
+
+        let response = self
+            .client
+            .get(format!("{}/api/v2/search", self.base_url))
+            .query(&[
+                ("q", post.source_post_url.as_str()),
+                ("type", "statuses"),
+                ("resolve", "true"),
+                ("limit", "1"),
+            ])
+            .bearer_auth(self.access_token.expose_secret())
+            .send()
+            .await
+            .map_err(|e| PublishError::Api(e.to_string()))?;
+
+        check_status(&response)?;
+        if !response.status().is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(PublishError::Api(format!(
+                "Failed to resolve source status: {}",
+                body
+            )));
+        }
+
+        let results: SearchResults = response
+            .json()
+            .await
+            .map_err(|e| PublishError::Api(e.to_string()))?;
+
+        results
+            .statuses
+            .into_iter()
+            .next()
+            .map(|status| status.id)
+            .ok_or_else(|| {
+                PublishError::Api(format!(
+                    "Source post is not reachable from Mastodon: {}",
+                    post.source_post_url
+                ))
+            })
+    }
+}
+
+#[derive(Serialize)]
+struct CreateStatusRequest {
+    status: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    in_reply_to_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    quoted_status_id: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct SearchResults {
+    #[serde(default)]
+    statuses: Vec<Status>,
+}
+
+#[derive(Deserialize)]
+struct CreatedStatus {
+    id: String,
+    url: Option<String>,
+}
+
+fn check_status(response: &reqwest::Response) -> Result<(), PublishError> {
+    match response.status() {
+        StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => {
+            Err(PublishError::Auth("Invalid access token".to_string()))
+        }
+        StatusCode::TOO_MANY_REQUESTS => Err(PublishError::RateLimited(retry_after(response))),
+        _ => Ok(()),
+    }
+}
+
+#[async_trait]
+impl Publisher for MastodonPublisher {
+    async fn publish(&self, post: &RenderedPost) -> Result<PublishResult, PublishError> {
+        if !self.enabled {
+            return Err(PublishError::Api("Publisher disabled".to_string()));
+        }
+
+        let len = post.text.chars().count();
+        if len > self.max_chars {
+            return Err(PublishError::ContentTooLong {
+                len,
+                max: self.max_chars,
+            });
+        }
+
+        let status_id = self.local_status_id(post).await?;
+        let request = match self.mode {
+            MastodonPublishMode::Reply => CreateStatusRequest {
+                status: post.text.clone(),
+                in_reply_to_id: Some(status_id),
+                quoted_status_id: None,
+            },
+            MastodonPublishMode::Quote => CreateStatusRequest {
+                status: post.text.clone(),
+                in_reply_to_id: None,
+                quoted_status_id: Some(status_id),
+            },
+        };
+
+        let response = self
+            .client
+            .post(format!("{}/api/v1/statuses", self.base_url))
+            .bearer_auth(self.access_token.expose_secret())
+            // Mastodon drops retried requests with the same key for an hour
+            .header(
+                "Idempotency-Key",
+                format!("news-tagger-{}", post.source_post_id),
+            )
+            .json(&request)
+            .send()
+            .await
+            .map_err(|e| PublishError::Api(e.to_string()))?;
+
+        check_status(&response)?;
+        if !response.status().is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(PublishError::Api(format!(
+                "Failed to create status: {}",
+                body
+            )));
+        }
+
+        let created: CreatedStatus = response
+            .json()
+            .await
+            .map_err(|e| PublishError::Api(e.to_string()))?;
+
+        Ok(PublishResult {
+            id: created.id,
+            url: created.url,
+        })
+    }
+
+    fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    fn platform(&self) -> &'static str {
+        "mastodon"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{body_json, header, method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn publisher(mock_server: &MockServer, mode: MastodonPublishMode) -> MastodonPublisher {
+        MastodonPublisher::new(
+            mock_server.uri(),
+            SecretString::new("test-token".into()),
+            mode,
+            500,
+        )
+    }
+
+    fn remote_post() -> RenderedPost {
+        RenderedPost {
+            text: "Tags: tag_one (0.85)".to_string(),
+            source_post_id: "110".to_string(),
+            source_post_url: "https://other.example/@alice/110".to_string(),
+            source_author: "user".to_string(),
+            labels: vec![],
+            article: None,
+            analysis: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_reply_resolves_remote_status() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/api/v2/search"))
+            .and(query_param("q", "https://other.example/@alice/110"))
+            .and(query_param("resolve", "true"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "statuses": [{
+                    "id": "987",
+                    "created_at": "2024-01-01T12:00:00.000Z",
+                    "content": "<p>hi</p>",
+                    "url": "https://other.example/@alice/110",
+                    "uri": "https://other.example/users/alice/statuses/110",
+                    "account": {"id": "7", "acct": "alice@other.example"},
+                    "in_reply_to_id": null,
+                    "reblog": null
+                }]
+            })))
+            .mount(&mock_server)
+            .await;
+
+        Mock::given(method("POST"))
+            .and(path("/api/v1/statuses"))
+            .and(header("authorization", "Bearer test-token"))
+            .and(header("idempotency-key", "news-tagger-110"))
+            .and(body_json(serde_json::json!({
+                "status": "Tags: tag_one (0.85)",
+                "in_reply_to_id": "987"
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "id": "555",
+                "url": "https://mastodon.example/@bot/555"
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let result = publisher(&mock_server, MastodonPublishMode::Reply)
+            .publish(&remote_post())
+            .await
+            .unwrap();
+
+        assert_eq!(result.id, "555");
+        assert_eq!(
+            result.url.as_deref(),
+            Some("https://mastodon.example/@bot/555")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_quote_uses_local_status_id_directly() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .and(path("/api/v1/statuses"))
+            .and(body_json(serde_json::json!({
+                "status": "Tags: tag_one (0.85)",
+                "quoted_status_id": "110"
+            })))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(serde_json::json!({"id": "556"})),
+            )
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let post = RenderedPost {
+            source_post_url: format!("{}/@alice/110", mock_server.uri()),
+            ..remote_post()
+        };
+        let result = publisher(&mock_server, MastodonPublishMode::Quote)
+            .publish(&post)
+            .await
+            .unwrap();
+
+        assert_eq!(result.id, "556");
+    }
+
+    #[tokio::test]
+    async fn test_rejects_content_over_limit() {
+        let mock_server = MockServer::start().await;
+        let post = RenderedPost {
+            text: "é".repeat(501),
+            ..remote_post()
+        };
+
+        let result = publisher(&mock_server, MastodonPublishMode::Reply)
+            .publish(&post)
+            .await;
+
+        assert!(matches!(
+            result,
+            Err(PublishError::ContentTooLong { len: 501, max: 500 })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_unreachable_source_is_an_error() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/api/v2/search"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(serde_json::json!({"statuses": []})),
+            )
+            .mount(&mock_server)
+            .await;
+
+        let result = publisher(&mock_server, MastodonPublishMode::Reply)
+            .publish(&remote_post())
+            .await;
+
+        assert!(matches!(result, Err(PublishError::Api(_))));
+    }
+
+    #[tokio::test]
+    async fn test_disabled_publisher() {
+        let publisher = MastodonPublisher::disabled();
+        assert!(!publisher.is_enabled());
+        assert_eq!(publisher.platform(), "mastodon");
+    }
+}
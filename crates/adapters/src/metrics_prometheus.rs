@@ -0,0 +1,287 @@
+//! Prometheus metrics for the run loop
+
+use news_tagger_domain::{ProcessResult, RunMetrics, TokenUsage};
+use prometheus::{
+    Counter, CounterVec, Encoder, HistogramOpts, HistogramVec, IntCounterVec, IntGaugeVec, Opts,
+    Registry, TextEncoder,
+};
+use std::time::Duration;
+
+/// Classification latency buckets, in seconds; local models can take minutes
+const LATENCY_BUCKETS: &[f64] = &[0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 20.0, 45.0, 90.0, 180.0];
+
+/// Run loop metrics kept in their own registry and rendered in the
+/// Prometheus text format
+pub struct PrometheusMetrics {
+    registry: Registry,
+    input_price_per_million: f64,
+    output_price_per_million: f64,
+    posts_fetched: IntCounterVec,
+    posts_filtered: IntCounterVec,
+    posts_classified: IntCounterVec,
+    posts_skipped: IntCounterVec,
+    posts_deferred: IntCounterVec,
+    posts_failed: IntCounterVec,
+    classification_seconds: HistogramVec,
+    tokens: IntCounterVec,
+    cost: CounterVec,
+    publishes: IntCounterVec,
+    rate_limit_wait_seconds: Counter,
+    retry_queue: IntGaugeVec,
+    taxonomy: IntGaugeVec,
+}
+
+impl PrometheusMetrics {
+    pub fn new() -> Self {
+        let registry = Registry::new();
+        let posts = |name: &str, help: &str| {
+            register(
+                &registry,
+                IntCounterVec::new(Opts::new(name, help), &["account"]),
+            )
+        };
+
+        Self {
+            posts_fetched: posts(
+                "news_tagger_posts_fetched_total",
+                "Posts returned by the post source",
+            ),
+            posts_filtered: posts(
+                "news_tagger_posts_filtered_total",
+                "Posts dropped by the reply, repost and ignore filters",
+            ),
+            posts_classified: posts(
+                "news_tagger_posts_classified_total",
+                "Posts classified and published (or logged in dry-run)",
+            ),
+            posts_skipped: posts(
+                "news_tagger_posts_skipped_total",
+                "Posts already processed with the current taxonomy",
+            ),
+            posts_deferred: posts(
+                "news_tagger_posts_deferred_total",
+                "Posts left for a later cycle by a rate limit or another worker's claim",
+            ),
+            posts_failed: posts(
+                "news_tagger_posts_failed_total",
+                "Posts whose classification failed",
+            ),
+            classification_seconds: register(
+                &registry,
+                HistogramVec::new(
+                    HistogramOpts::new(
+                        "news_tagger_classification_duration_seconds",
+                        "Time taken by classification calls",
+                    )
+                    .buckets(LATENCY_BUCKETS.to_vec()),
+                    &["provider"],
+                ),
+            ),
+            tokens: register(
+                &registry,
+                IntCounterVec::new(
+                    Opts::new(
+                        "news_tagger_llm_tokens_total",
+                        "Tokens reported by the LLM provider",
+                    ),
+                    &["provider", "direction"],
+                ),
+            ),
+            cost: register(
+                &registry,
+                CounterVec::new(
+                    Opts::new(
+                        "news_tagger_llm_cost_total",
+                        "LLM cost at the configured per-million-token prices",
+                    ),
+                    &["provider"],
+                ),
+            ),
+            publishes: register(
+                &registry,
+                IntCounterVec::new(
+                    Opts::new("news_tagger_publishes_total", "Publish attempts"),
+                    &["platform", "outcome"],
+                ),
+            ),
+            rate_limit_wait_seconds: register(
+                &registry,
+                Counter::new(
+                    "news_tagger_rate_limit_wait_seconds_total",
+                    "Time posts waited for the per-minute/per-hour limits",
+                ),
+            ),
+            retry_queue: register(
+                &registry,
+                IntGaugeVec::new(
+                    Opts::new("news_tagger_retry_queue_depth", "Queued publish retries"),
+                    &["status"],
+                ),
+            ),
+            taxonomy: register(
+                &registry,
+                IntGaugeVec::new(
+                    Opts::new(
+                        "news_tagger_taxonomy_info",
+                        "Hash of the taxonomy used by the last poll cycle",
+                    ),
+                    &["hash"],
+                ),
+            ),
+            registry,
+            input_price_per_million: 0.0,
+            output_price_per_million: 0.0,
+        }
+    }
+
+    /// Count LLM cost at these prices per million input and output tokens
+    pub fn with_token_prices(mut self, input_per_million: f64, output_per_million: f64) -> Self {
+        self.input_price_per_million = input_per_million;
+        self.output_price_per_million = output_per_million;
+        self
+    }
+
+    /// All metrics in the Prometheus text exposition format
+    pub fn render(&self) -> String {
+        let mut buffer = Vec::new();
+        TextEncoder::new()
+            .encode(&self.registry.gather(), &mut buffer)
+            .expect("text encoding into a Vec cannot fail");
+        String::from_utf8(buffer).expect("Prometheus text format is UTF-8")
+    }
+}
+
+impl Default for PrometheusMetrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Register a metric that is known to be valid and unique in `registry`
+fn register<M>(registry: &Registry, metric: prometheus::Result<M>) -> M
+where
+    M: prometheus::core::Collector + Clone + 'static,
+{
+    let metric = metric.expect("valid metric definition");
+    registry
+        .register(Box::new(metric.clone()))
+        .expect("metric registered once");
+    metric
+}
+
+impl RunMetrics for PrometheusMetrics {
+    fn posts_fetched(&self, account: &str, count: usize) {
+        self.posts_fetched
+            .with_label_values(&[account])
+            .inc_by(count as u64);
+    }
+
+    fn posts_filtered(&self, account: &str, count: usize) {
+        self.posts_filtered
+            .with_label_values(&[account])
+            .inc_by(count as u64);
+    }
+
+    fn post_processed(&self, account: &str, result: &ProcessResult) {
+        let counter = match result {
+            ProcessResult::Published { .. } => &self.posts_classified,
+            ProcessResult::Skipped { .. } => &self.posts_skipped,
+            ProcessResult::Deferred { .. } => &self.posts_deferred,
+            ProcessResult::Failed { .. } => &self.posts_failed,
+        };
+        counter.with_label_values(&[account]).inc();
+    }
+
+    fn classification(&self, provider: &str, elapsed: Duration, usage: Option<TokenUsage>) {
+        self.classification_seconds
+            .with_label_values(&[provider])
+            .observe(elapsed.as_secs_f64());
+
+        if let Some(usage) = usage {
+            self.tokens
+                .with_label_values(&[provider, "input"])
+                .inc_by(usage.input_tokens);
+            self.tokens
+                .with_label_values(&[provider, "output"])
+                .inc_by(usage.output_tokens);
+            let cost = (usage.input_tokens as f64 * self.input_price_per_million
+                + usage.output_tokens as f64 * self.output_price_per_million)
+                / 1_000_000.0;
+            self.cost.with_label_values(&[provider]).inc_by(cost);
+        }
+    }
+
+    fn publish(&self, platform: &str, success: bool) {
+        let outcome = if success { "success" } else { "failure" };
+        self.publishes.with_label_values(&[platform, outcome]).inc();
+    }
+
+    fn rate_limit_wait(&self, waited: Duration) {
+        self.rate_limit_wait_seconds.inc_by(waited.as_secs_f64());
+    }
+
+    fn retry_queue(&self, pending: usize, failed: usize) {
+        self.retry_queue
+            .with_label_values(&["pending"])
+            .set(pending as i64);
+        self.retry_queue
+            .with_label_values(&["failed"])
+            .set(failed as i64);
+    }
+
+    fn taxonomy(&self, hash: &str) {
+        // Only the current hash is reported; a changed taxonomy replaces it
+        self.taxonomy.reset();
+        self.taxonomy.with_label_values(&[hash]).set(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_recorded_metrics() {
+        let metrics = PrometheusMetrics::new().with_token_prices(2.0, 10.0);
+
+        metrics.posts_fetched("alice", 3);
+        metrics.posts_filtered("alice", 1);
+        metrics.post_processed(
+            "alice",
+            &ProcessResult::Failed {
+                error: "boom".to_string(),
+                retry: None,
+            },
+        );
+        metrics.classification(
+            "openai",
+            Duration::from_millis(1200),
+            Some(TokenUsage {
+                input_tokens: 500_000,
+                output_tokens: 100_000,
+            }),
+        );
+        metrics.publish("nostr", false);
+        metrics.retry_queue(2, 1);
+        metrics.taxonomy("old");
+        metrics.taxonomy("abc123");
+
+        let text = metrics.render();
+        assert!(text.contains(r#"news_tagger_posts_fetched_total{account="alice"} 3"#));
+        assert!(text.contains(r#"news_tagger_posts_filtered_total{account="alice"} 1"#));
+        assert!(text.contains(r#"news_tagger_posts_failed_total{account="alice"} 1"#));
+        assert!(text.contains(
+            r#"news_tagger_classification_duration_seconds_bucket{provider="openai",le="2.5"} 1"#
+        ));
+        assert!(text.contains(
+            r#"news_tagger_llm_tokens_total{direction="input",provider="openai"} 500000"#
+        ));
+        assert!(text.contains(r#"news_tagger_llm_cost_total{provider="openai"} 2"#));
+        assert!(
+            text.contains(r#"news_tagger_publishes_total{outcome="failure",platform="nostr"} 1"#)
+        );
+        assert!(text.contains(r#"news_tagger_retry_queue_depth{status="pending"} 2"#));
+        assert!(text.contains(r#"news_tagger_taxonomy_info{hash="abc123"} 1"#));
+        assert!(!text.contains(r#"hash="old""#));
+    }
+}
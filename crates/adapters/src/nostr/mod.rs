@@ -1,17 +1,34 @@
-//! Nostr publishing adapter
+//! Nostr publishing and relay subscription adapters
+
+mod relay;
+mod source;
+
+pub use source::NostrPostSource;
 
 use anyhow::{Result, anyhow, bail};
 use async_trait::async_trait;
-use bech32::Hrp;
-use k256::schnorr::SigningKey;
-use news_tagger_domain::{PublishError, PublishResult, Publisher, RenderedPost};
+use bech32::{Bech32, Hrp};
+use futures_util::future::join_all;
+use k256::schnorr::signature::hazmat::PrehashVerifier;
+use k256::schnorr::{Signature, SigningKey, VerifyingKey};
+use news_tagger_domain::{
+    PublishError, PublishResult, Publisher, RelayStatus, RenderedArticle, RenderedPost, StateStore,
+};
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::Duration;
 use time::OffsetDateTime;
 
 const NOSTR_TEXT_NOTE_KIND: u32 = 1;
+const NOSTR_LABEL_KIND: u32 = 1985;
+const NOSTR_RELAY_LIST_KIND: u32 = 10002;
+const NOSTR_AUTH_KIND: u32 = 22242;
+const NOSTR_LONG_FORM_KIND: u32 = 30023;
+/// Read relays of a referenced author that a reply is also sent to
+const MAX_DISCOVERED_RELAYS: usize = 4;
 const SCHNORR_ZERO_AUX_RANDOMNESS: [u8; 32] = [0; 32];
 
 /// Parse a Nostr secret key from either hex or `nsec` Bech32 format.
@@ -34,6 +51,74 @@ pub fn parse_secret_key(raw: &str) -> Result<SigningKey> {
     })
 }
 
+/// Parse a Nostr public key from either hex or `npub` Bech32 format into hex.
+pub fn parse_public_key(raw: &str) -> Result<String> {
+    let trimmed = raw.trim().to_ascii_lowercase();
+
+    let bytes = if trimmed.starts_with("npub1") {
+        let (hrp, data) =
+            bech32::decode(&trimmed).map_err(|e| anyhow!("Invalid npub Bech32 encoding: {}", e))?;
+        if hrp != Hrp::parse("npub")? || data.len() != 32 {
+            bail!("Invalid npub: expected a 32-byte 'npub' payload");
+        }
+        let mut bytes = [0u8; 32];
+        bytes.copy_from_slice(&data);
+        bytes
+    } else {
+        decode_hex_fixed::<32>(&trimmed).map_err(|e| anyhow!("Invalid hex public key: {}", e))?
+    };
+
+    VerifyingKey::from_bytes(&bytes).map_err(|_| anyhow!("Invalid Nostr public key point"))?;
+    Ok(hex_encode(&bytes))
+}
+
+/// NIP-19 `note1…` encoding of a hex event ID
+fn encode_note(event_id: &str) -> Option<String> {
+    encode_bech32("note", event_id)
+}
+
+/// NIP-19 `npub1…` encoding of a hex public key
+fn encode_npub(pubkey: &str) -> Option<String> {
+    encode_bech32("npub", pubkey)
+}
+
+/// NIP-19 `nevent1…` encoding of an event ID with optional relay and author hints
+fn encode_nevent(event_id: &str, relay: Option<&str>, author: Option<&str>) -> Option<String> {
+    // TLV entries: 0 = event ID, 1 = relay URL, 2 = author public key
+    let mut tlv = vec![0, 32];
+    tlv.extend_from_slice(&decode_hex_fixed::<32>(event_id).ok()?);
+    if let Some(relay) = relay.filter(|relay| relay.len() <= u8::MAX as usize) {
+        tlv.extend_from_slice(&[1, relay.len() as u8]);
+        tlv.extend_from_slice(relay.as_bytes());
+    }
+    if let Some(author) = author {
+        tlv.extend_from_slice(&[2, 32]);
+        tlv.extend_from_slice(&decode_hex_fixed::<32>(author).ok()?);
+    }
+    bech32::encode::<Bech32>(Hrp::parse("nevent").ok()?, &tlv).ok()
+}
+
+/// NIP-19 `naddr1…` encoding of a replaceable event coordinate with an optional relay hint
+fn encode_naddr(identifier: &str, relay: Option<&str>, author: &str, kind: u32) -> Option<String> {
+    // TLV entries: 0 = `d` identifier, 1 = relay URL, 2 = author public key, 3 = kind
+    let mut tlv = vec![0, u8::try_from(identifier.len()).ok()?];
+    tlv.extend_from_slice(identifier.as_bytes());
+    if let Some(relay) = relay.filter(|relay| relay.len() <= u8::MAX as usize) {
+        tlv.extend_from_slice(&[1, relay.len() as u8]);
+        tlv.extend_from_slice(relay.as_bytes());
+    }
+    tlv.extend_from_slice(&[2, 32]);
+    tlv.extend_from_slice(&decode_hex_fixed::<32>(author).ok()?);
+    tlv.extend_from_slice(&[3, 4]);
+    tlv.extend_from_slice(&kind.to_be_bytes());
+    bech32::encode::<Bech32>(Hrp::parse("naddr").ok()?, &tlv).ok()
+}
+
+fn encode_bech32(hrp: &str, hex: &str) -> Option<String> {
+    let bytes = decode_hex_fixed::<32>(hex).ok()?;
+    bech32::encode::<Bech32>(Hrp::parse(hrp).ok()?, &bytes).ok()
+}
+
 fn parse_nsec_key_bytes(raw: &str) -> Result<[u8; 32]> {
     let (hrp, data) =
         bech32::decode(raw).map_err(|e| anyhow!("Invalid nsec Bech32 encoding: {}", e))?;
@@ -93,7 +178,6 @@ fn hex_encode(bytes: &[u8]) -> String {
     output
 }
 
-#[cfg(test)]
 fn decode_hex_fixed<const N: usize>(raw: &str) -> Result<[u8; N]> {
     if raw.len() != N * 2 {
         bail!("Expected {} hex characters, got {}", N * 2, raw.len());
@@ -118,6 +202,12 @@ pub struct NostrPublisher {
     client: Client,
     signing_key: Option<SigningKey>,
     relays: Vec<String>,
+    label_namespace: Option<String>,
+    publish_relay_list: bool,
+    relay_list_published: AtomicBool,
+    discover_relays: bool,
+    state_store: Option<Arc<dyn StateStore>>,
+    timeout: Duration,
     enabled: bool,
 }
 
@@ -134,6 +224,12 @@ impl NostrPublisher {
             client,
             signing_key: Some(signing_key),
             relays,
+            label_namespace: None,
+            publish_relay_list: false,
+            relay_list_published: AtomicBool::new(false),
+            discover_relays: false,
+            state_store: None,
+            timeout: Duration::from_secs(10),
             enabled: true,
         })
     }
@@ -144,18 +240,217 @@ impl NostrPublisher {
             client: Client::new(),
             signing_key: None,
             relays: vec![],
+            label_namespace: None,
+            publish_relay_list: false,
+            relay_list_published: AtomicBool::new(false),
+            discover_relays: false,
+            state_store: None,
+            timeout: Duration::from_secs(10),
             enabled: false,
         }
     }
 
-    /// Generate a Nostr event (NIP-01)
+    /// Also publish a NIP-32 label event (kind 1985) per note, with tag IDs
+    /// as labels in `namespace`
+    pub fn with_label_events(mut self, namespace: impl Into<String>) -> Self {
+        self.label_namespace = Some(namespace.into());
+        self
+    }
+
+    /// Publish our NIP-65 relay list (kind 10002) naming the configured relays,
+    /// once per process before the first note
+    pub fn with_relay_list(mut self) -> Self {
+        self.publish_relay_list = true;
+        self
+    }
+
+    /// Also send replies to the read relays a referenced author lists in
+    /// their NIP-65 relay list, looked up on the configured relays
+    pub fn with_relay_discovery(mut self) -> Self {
+        self.discover_relays = true;
+        self
+    }
+
+    /// Record per-relay delivery results in the state store
+    pub fn with_state_store(mut self, state_store: Arc<dyn StateStore>) -> Self {
+        self.state_store = Some(state_store);
+        self
+    }
+
+    /// How long to wait for each relay to answer
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Generate an untagged Nostr text note (NIP-01)
+    #[cfg(test)]
     fn create_event(&self, content: &str, created_at: i64) -> Result<NostrEvent> {
+        self.sign_event(NOSTR_TEXT_NOTE_KIND, vec![], content, created_at)
+    }
+
+    /// Generate the text note for a rendered post
+    ///
+    /// The note carries an `r` tag for the original post and a `t` hashtag per
+    /// label. When the source is itself a Nostr note, the analysis is a NIP-10
+    /// reply to it, and the source note and author are mentioned in the content
+    /// as NIP-27 `nostr:` URIs instead of web links. A long-form `article`, if
+    /// given, is referenced with an `a` tag and linked as `nostr:naddr…`.
+    fn create_note(
+        &self,
+        post: &RenderedPost,
+        article: Option<&NostrEvent>,
+        created_at: i64,
+    ) -> Result<NostrEvent> {
+        let mut tags = vec![vec!["r".to_string(), post.source_post_url.clone()]];
+        for label in &post.labels {
+            tags.push(vec!["t".to_string(), label.id.to_lowercase()]);
+        }
+
+        let mut content = post.text.clone();
+        if let Some(article) = article {
+            let identifier = article
+                .tags
+                .iter()
+                .find(|tag| tag.first().map(String::as_str) == Some("d"))
+                .and_then(|tag| tag.get(1))
+                .ok_or_else(|| anyhow!("Article has no d tag"))?;
+            let relay_hint = self.relays.first().map(String::as_str);
+            let naddr = encode_naddr(identifier, relay_hint, &article.pubkey, article.kind)
+                .ok_or_else(|| anyhow!("Failed to encode article address"))?;
+            content = format!("{}\n\nFull analysis: nostr:{}", content, naddr);
+            tags.push(vec![
+                "a".to_string(),
+                format!("{}:{}:{}", article.kind, article.pubkey, identifier),
+                relay_hint.unwrap_or_default().to_string(),
+            ]);
+        }
+
+        if let Some((event_id, author)) = nostr_source(post) {
+            let relay_hint = self.relays.first().map(String::as_str);
+
+            // The source note anchors our thread; we don't know its own root
+            let mut e_tag = vec![
+                "e".to_string(),
+                event_id.to_string(),
+                relay_hint.unwrap_or_default().to_string(),
+                "root".to_string(),
+            ];
+            if let Some(author) = &author {
+                e_tag.push(author.clone());
+            }
+            tags.push(e_tag);
+
+            if let Some(nevent) = encode_nevent(event_id, relay_hint, author.as_deref()) {
+                let mention = format!("nostr:{}", nevent);
+                if content.contains(&post.source_post_url) {
+                    content = content.replace(&post.source_post_url, &mention);
+                } else {
+                    content = format!("{}\n\n{}", content, mention);
+                }
+            }
+
+            if let Some(author) = author {
+                if let Some(npub) = encode_npub(&author) {
+                    if post.source_author.starts_with("npub1") {
+                        content = content.replace(&post.source_author, &format!("nostr:{}", npub));
+                    }
+                }
+                tags.push(vec!["p".to_string(), author]);
+            }
+        }
+
+        self.sign_event(NOSTR_TEXT_NOTE_KIND, tags, &content, created_at)
+    }
+
+    /// Generate a NIP-23 long-form article (kind 30023)
+    ///
+    /// The `d` tag comes from the source post, so relays replace an earlier
+    /// analysis of the same post instead of keeping both.
+    fn create_article_event(
+        &self,
+        post: &RenderedPost,
+        article: &RenderedArticle,
+        created_at: i64,
+    ) -> Result<NostrEvent> {
+        let mut tags = vec![
+            vec!["d".to_string(), article.identifier.clone()],
+            vec!["title".to_string(), article.title.clone()],
+            vec!["summary".to_string(), article.summary.clone()],
+            vec!["published_at".to_string(), created_at.to_string()],
+            vec!["r".to_string(), post.source_post_url.clone()],
+        ];
+        for label in &post.labels {
+            tags.push(vec!["t".to_string(), label.id.to_lowercase()]);
+        }
+
+        self.sign_event(NOSTR_LONG_FORM_KIND, tags, &article.content, created_at)
+    }
+
+    /// Generate a NIP-32 label event pointing at the source post
+    ///
+    /// Each `l` tag carries the confidence as NIP-32 `quality` metadata.
+    fn create_label_event(
+        &self,
+        namespace: &str,
+        post: &RenderedPost,
+        created_at: i64,
+    ) -> Result<NostrEvent> {
+        let mut tags = vec![vec!["L".to_string(), namespace.to_string()]];
+        for label in &post.labels {
+            let metadata = serde_json::json!({ "quality": label.confidence });
+            tags.push(vec![
+                "l".to_string(),
+                label.id.clone(),
+                namespace.to_string(),
+                metadata.to_string(),
+            ]);
+        }
+
+        match source::event_id_from_post_id(&post.source_post_id) {
+            Some(event_id) => tags.push(vec!["e".to_string(), event_id.to_string()]),
+            None => tags.push(vec!["r".to_string(), post.source_post_url.clone()]),
+        }
+
+        self.sign_event(NOSTR_LABEL_KIND, tags, "", created_at)
+    }
+
+    /// Generate our NIP-65 relay list, marking every configured relay for read and write
+    fn create_relay_list_event(&self, created_at: i64) -> Result<NostrEvent> {
+        let tags = self
+            .relays
+            .iter()
+            .map(|relay| vec!["r".to_string(), relay.clone()])
+            .collect();
+        self.sign_event(NOSTR_RELAY_LIST_KIND, tags, "", created_at)
+    }
+
+    /// Generate a NIP-42 authentication event answering a relay's challenge
+    fn create_auth_event(
+        &self,
+        relay: &str,
+        challenge: &str,
+        created_at: i64,
+    ) -> Result<NostrEvent> {
+        let tags = vec![
+            vec!["relay".to_string(), relay.to_string()],
+            vec!["challenge".to_string(), challenge.to_string()],
+        ];
+        self.sign_event(NOSTR_AUTH_KIND, tags, "", created_at)
+    }
+
+    /// Canonicalize, hash and sign an event
+    fn sign_event(
+        &self,
+        kind: u32,
+        tags: Vec<Vec<String>>,
+        content: &str,
+        created_at: i64,
+    ) -> Result<NostrEvent> {
         let pubkey = self.derive_pubkey()?;
-        let tags: Vec<Vec<String>> = vec![];
 
-        let serialized =
-            serde_json::to_string(&(0, &pubkey, created_at, NOSTR_TEXT_NOTE_KIND, &tags, content))
-                .map_err(|e| anyhow!("Failed to canonicalize Nostr event: {}", e))?;
+        let serialized = serde_json::to_string(&(0, &pubkey, created_at, kind, &tags, content))
+            .map_err(|e| anyhow!("Failed to canonicalize Nostr event: {}", e))?;
 
         let id_bytes: [u8; 32] = Sha256::digest(serialized.as_bytes()).into();
         let id = hex_encode(&id_bytes);
@@ -173,7 +468,7 @@ impl NostrPublisher {
             id,
             pubkey,
             created_at,
-            kind: NOSTR_TEXT_NOTE_KIND,
+            kind,
             tags,
             content: content.to_string(),
             sig: hex_encode(&signature.to_bytes()),
@@ -190,18 +485,20 @@ impl NostrPublisher {
         Ok(hex_encode(&signing_key.verifying_key().to_bytes()))
     }
 
-    /// Publish event to a relay via HTTP (NIP-86 or relay-specific HTTP endpoint)
+    /// Publish event to a relay over WebSockets, or via HTTP for `http(s)://` endpoints
     async fn publish_to_relay(&self, relay: &str, event: &NostrEvent) -> Result<(), PublishError> {
-        // Most relays use WebSocket, but some support HTTP
-        // This is a simplified implementation
-        let url = if relay.starts_with("wss://") {
-            // Convert to HTTP endpoint if relay supports it
-            relay.replace("wss://", "https://")
-        } else if relay.starts_with("ws://") {
-            relay.replace("ws://", "http://")
-        } else {
-            relay.to_string()
-        };
+        if relay.starts_with("wss://") || relay.starts_with("ws://") {
+            let auth = |relay: &str, challenge: &str| {
+                let created_at = OffsetDateTime::now_utc().unix_timestamp();
+                self.create_auth_event(relay, challenge, created_at).ok()
+            };
+            return relay::publish(relay, event, &auth, self.timeout)
+                .await
+                .map_err(|e| PublishError::Api(format!("Relay {}: {}", relay, e)));
+        }
+
+        // Some relays also accept events over a relay-specific HTTP endpoint
+        let url = relay.to_string();
 
         let request = vec![
             "EVENT".to_string(),
@@ -226,6 +523,157 @@ impl NostrPublisher {
     }
 }
 
+impl NostrPublisher {
+    /// Send an event to every given relay; succeeds if at least one accepts it
+    async fn broadcast(&self, event: &NostrEvent, relays: &[String]) -> Result<(), PublishError> {
+        let mut last_error = None;
+        let mut success_count = 0;
+
+        for relay in relays {
+            let result = self.publish_to_relay(relay, event).await;
+            self.record_relay_result(relay, &result).await;
+            match result {
+                Ok(()) => {
+                    tracing::info!(
+                        relay = %relay,
+                        event_id = %event.id,
+                        kind = event.kind,
+                        "Published to Nostr relay"
+                    );
+                    success_count += 1;
+                }
+                Err(e) => {
+                    tracing::warn!(relay = %relay, error = %e, "Failed to publish to relay");
+                    last_error = Some(e);
+                }
+            }
+        }
+
+        if success_count == 0 {
+            return Err(last_error.unwrap_or_else(|| {
+                PublishError::Api("Failed to publish to any relay".to_string())
+            }));
+        }
+
+        Ok(())
+    }
+
+    /// Update the relay's delivery history, if a state store is attached
+    async fn record_relay_result(&self, relay: &str, result: &Result<(), PublishError>) {
+        let Some(state_store) = &self.state_store else {
+            return;
+        };
+
+        let now = OffsetDateTime::now_utc();
+        let mut status = match state_store.get_relay_status(relay).await {
+            Ok(Some(status)) => status,
+            Ok(None) => RelayStatus {
+                relay: relay.to_string(),
+                successes: 0,
+                failures: 0,
+                last_success_at: None,
+                last_error: None,
+                updated_at: now,
+            },
+            Err(e) => {
+                tracing::warn!(relay = %relay, error = %e, "Failed to load relay status");
+                return;
+            }
+        };
+
+        match result {
+            Ok(()) => {
+                status.successes += 1;
+                status.last_success_at = Some(now);
+                status.last_error = None;
+            }
+            Err(e) => {
+                status.failures += 1;
+                status.last_error = Some(e.to_string());
+            }
+        }
+        status.updated_at = now;
+
+        if let Err(e) = state_store.set_relay_status(&status).await {
+            tracing::warn!(relay = %relay, error = %e, "Failed to store relay status");
+        }
+    }
+
+    /// Publish the relay list if enabled and not yet accepted by any relay
+    async fn ensure_relay_list(&self) {
+        if !self.publish_relay_list || self.relay_list_published.load(Ordering::Relaxed) {
+            return;
+        }
+
+        let created_at = OffsetDateTime::now_utc().unix_timestamp();
+        let published = match self.create_relay_list_event(created_at) {
+            Ok(event) => self.broadcast(&event, &self.relays).await,
+            Err(e) => Err(PublishError::Api(e.to_string())),
+        };
+        match published {
+            Ok(()) => self.relay_list_published.store(true, Ordering::Relaxed),
+            Err(e) => tracing::warn!(error = %e, "Failed to publish Nostr relay list"),
+        }
+    }
+
+    /// Read relays from the author's latest NIP-65 relay list on the configured relays
+    async fn discover_read_relays(&self, author: &str) -> Vec<String> {
+        let filter = serde_json::json!({
+            "authors": [author],
+            "kinds": [NOSTR_RELAY_LIST_KIND],
+            "limit": 1,
+        });
+        let websocket_relays: Vec<&String> = self
+            .relays
+            .iter()
+            .filter(|relay| relay.starts_with("wss://") || relay.starts_with("ws://"))
+            .collect();
+        let results = join_all(
+            websocket_relays
+                .iter()
+                .map(|relay| relay::query(relay, &filter, self.timeout)),
+        )
+        .await;
+
+        let latest = results
+            .into_iter()
+            .filter_map(Result::ok)
+            .flatten()
+            .filter(|event| {
+                event.pubkey == author && event.kind == NOSTR_RELAY_LIST_KIND && event.verify()
+            })
+            .max_by_key(|event| event.created_at);
+        let Some(latest) = latest else {
+            return vec![];
+        };
+
+        let mut read_relays: Vec<String> = Vec::new();
+        for tag in &latest.tags {
+            let [name, url, marker @ ..] = tag.as_slice() else {
+                continue;
+            };
+            let is_read = marker.first().is_none_or(|marker| marker == "read");
+            let is_websocket = url.starts_with("wss://") || url.starts_with("ws://");
+            if name == "r"
+                && is_read
+                && is_websocket
+                && !self.relays.contains(url)
+                && !read_relays.contains(url)
+            {
+                read_relays.push(url.clone());
+            }
+        }
+        read_relays.truncate(MAX_DISCOVERED_RELAYS);
+        read_relays
+    }
+}
+
+/// Source event ID and hex author of a post whose source is a Nostr note
+fn nostr_source(post: &RenderedPost) -> Option<(&str, Option<String>)> {
+    let event_id = source::event_id_from_post_id(&post.source_post_id)?;
+    Some((event_id, parse_public_key(&post.source_author).ok()))
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct NostrEvent {
     id: String,
@@ -237,6 +685,40 @@ struct NostrEvent {
     sig: String,
 }
 
+impl NostrEvent {
+    /// Check the ID commits to the event fields and the signature is the author's
+    fn verify(&self) -> bool {
+        let Ok(serialized) = serde_json::to_string(&(
+            0,
+            &self.pubkey,
+            self.created_at,
+            self.kind,
+            &self.tags,
+            &self.content,
+        )) else {
+            return false;
+        };
+        let id_bytes: [u8; 32] = Sha256::digest(serialized.as_bytes()).into();
+        if hex_encode(&id_bytes) != self.id {
+            return false;
+        }
+
+        let (Ok(pubkey), Ok(sig)) = (
+            decode_hex_fixed::<32>(&self.pubkey),
+            decode_hex_fixed::<64>(&self.sig),
+        ) else {
+            return false;
+        };
+        let (Ok(verifying_key), Ok(signature)) = (
+            VerifyingKey::from_bytes(&pubkey),
+            Signature::try_from(sig.as_slice()),
+        ) else {
+            return false;
+        };
+        verifying_key.verify_prehash(&id_bytes, &signature).is_ok()
+    }
+}
+
 #[async_trait]
 impl Publisher for NostrPublisher {
     async fn publish(&self, post: &RenderedPost) -> Result<PublishResult, PublishError> {
@@ -248,37 +730,56 @@ impl Publisher for NostrPublisher {
             return Err(PublishError::Api("No relays configured".to_string()));
         }
 
-        let created_at = OffsetDateTime::now_utc().unix_timestamp();
-        let event = self
-            .create_event(&post.text, created_at)
-            .map_err(|e| PublishError::Api(format!("Failed to create Nostr event: {}", e)))?;
-        let event_id = event.id.clone();
+        self.ensure_relay_list().await;
 
-        // Try to publish to at least one relay
-        let mut last_error = None;
-        let mut success_count = 0;
-
-        for relay in &self.relays {
-            match self.publish_to_relay(relay, &event).await {
-                Ok(()) => {
-                    tracing::info!(relay = %relay, event_id = %event_id, "Published to Nostr relay");
-                    success_count += 1;
-                }
-                Err(e) => {
-                    tracing::warn!(relay = %relay, error = %e, "Failed to publish to relay");
-                    last_error = Some(e);
+        let mut targets = self.relays.clone();
+        if self.discover_relays {
+            if let Some((_, Some(author))) = nostr_source(post) {
+                let discovered = self.discover_read_relays(&author).await;
+                if !discovered.is_empty() {
+                    tracing::debug!(relays = ?discovered, "Also sending reply to author's read relays");
                 }
+                targets.extend(discovered);
             }
         }
 
-        if success_count == 0 {
-            return Err(last_error.unwrap_or_else(|| {
-                PublishError::Api("Failed to publish to any relay".to_string())
-            }));
+        let created_at = OffsetDateTime::now_utc().unix_timestamp();
+        let article = match &post.article {
+            Some(article) => {
+                let event = self
+                    .create_article_event(post, article, created_at)
+                    .map_err(|e| {
+                        PublishError::Api(format!("Failed to create Nostr article: {}", e))
+                    })?;
+                self.broadcast(&event, &targets).await?;
+                Some(event)
+            }
+            None => None,
+        };
+
+        let event = self
+            .create_note(post, article.as_ref(), created_at)
+            .map_err(|e| PublishError::Api(format!("Failed to create Nostr event: {}", e)))?;
+        self.broadcast(&event, &targets).await?;
+
+        if let Some(namespace) = self.label_namespace.as_deref() {
+            if !post.labels.is_empty() {
+                // Labels are supplementary; the note itself is already out
+                let labels = self
+                    .create_label_event(namespace, post, created_at)
+                    .map_err(|e| PublishError::Api(e.to_string()));
+                let published = match labels {
+                    Ok(labels) => self.broadcast(&labels, &self.relays).await,
+                    Err(e) => Err(e),
+                };
+                if let Err(e) = published {
+                    tracing::warn!(error = %e, "Failed to publish Nostr label event");
+                }
+            }
         }
 
         Ok(PublishResult {
-            id: event_id,
+            id: event.id,
             url: None, // Nostr doesn't have a canonical URL
         })
     }
@@ -295,8 +796,6 @@ impl Publisher for NostrPublisher {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use bech32::Bech32;
-    use k256::schnorr::{Signature, VerifyingKey};
     use wiremock::matchers::{method, path};
     use wiremock::{Mock, MockServer, ResponseTemplate};
 
@@ -305,6 +804,10 @@ mod tests {
             text: "Narrative analysis of @user\n\nTags: test_tag (0.85)\n\nOriginal: https://x.com/user/status/123".to_string(),
             source_post_id: "123".to_string(),
             source_post_url: "https://x.com/user/status/123".to_string(),
+            source_author: "user".to_string(),
+            labels: vec![],
+            article: None,
+            analysis: None,
         }
     }
 
@@ -499,12 +1002,378 @@ mod tests {
         let verifying_key = VerifyingKey::from_bytes(&pubkey_bytes).expect("valid pubkey");
         let signature = Signature::try_from(sig_bytes.as_slice()).expect("valid signature");
 
-        use k256::schnorr::signature::hazmat::PrehashVerifier;
         verifying_key
             .verify_prehash(&id_bytes, &signature)
             .expect("signature should verify against event id");
     }
 
+    #[test]
+    fn test_label_event_targets_source_url() {
+        let publisher = NostrPublisher::new(sample_secret(), vec![])
+            .expect("valid publisher")
+            .with_label_events("news-tagger");
+        let post = RenderedPost {
+            labels: vec![news_tagger_domain::TagLabel {
+                id: "test_tag".to_string(),
+                confidence: 0.85,
+            }],
+            ..sample_post()
+        };
+
+        let event = publisher
+            .create_label_event("news-tagger", &post, 1_700_000_000)
+            .expect("label event");
+
+        assert_eq!(event.kind, 1985);
+        assert!(event.verify());
+        assert_eq!(event.tags[0], vec!["L", "news-tagger"]);
+        assert_eq!(
+            event.tags[1],
+            vec!["l", "test_tag", "news-tagger", r#"{"quality":0.85}"#]
+        );
+        assert_eq!(event.tags[2], vec!["r", "https://x.com/user/status/123"]);
+    }
+
+    #[test]
+    fn test_label_event_targets_nostr_source_event() {
+        let publisher = NostrPublisher::new(sample_secret(), vec![]).expect("valid publisher");
+        let source_event_id = "ab".repeat(32);
+        let post = RenderedPost {
+            source_post_id: format!("1700000000-{}", source_event_id),
+            labels: vec![news_tagger_domain::TagLabel {
+                id: "test_tag".to_string(),
+                confidence: 0.6,
+            }],
+            ..sample_post()
+        };
+
+        let event = publisher
+            .create_label_event("news-tagger", &post, 1_700_000_001)
+            .expect("label event");
+
+        assert_eq!(
+            event.tags.last().unwrap(),
+            &vec!["e".to_string(), source_event_id]
+        );
+    }
+
+    #[test]
+    fn test_note_tags_original_url_and_hashtags() {
+        let publisher = NostrPublisher::new(sample_secret(), vec![]).expect("valid publisher");
+        let post = RenderedPost {
+            labels: vec![news_tagger_domain::TagLabel {
+                id: "Test_Tag".to_string(),
+                confidence: 0.85,
+            }],
+            ..sample_post()
+        };
+
+        let event = publisher
+            .create_note(&post, None, 1_700_000_000)
+            .expect("note");
+
+        assert!(event.verify());
+        assert_eq!(event.content, post.text);
+        assert_eq!(
+            event.tags,
+            vec![
+                vec!["r", "https://x.com/user/status/123"],
+                vec!["t", "test_tag"],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_note_replies_to_nostr_source_with_mentions() {
+        let relay = "wss://relay.example.com";
+        let publisher =
+            NostrPublisher::new(sample_secret(), vec![relay.to_string()]).expect("valid publisher");
+        let source = NostrPublisher::new("11".repeat(32), vec![]).expect("valid publisher");
+        let source_pubkey = source.derive_pubkey().unwrap();
+        let source_npub = encode_npub(&source_pubkey).unwrap();
+        let source_event_id = "ab".repeat(32);
+        let source_url = format!(
+            "https://njump.me/{}",
+            encode_note(&source_event_id).unwrap()
+        );
+        let post = RenderedPost {
+            text: format!(
+                "Narrative analysis of {}\n\nTags: test_tag (0.85)\n\nOriginal: {}",
+                source_npub, source_url
+            ),
+            source_post_id: format!("1700000000-{}", source_event_id),
+            source_post_url: source_url.clone(),
+            source_author: source_npub.clone(),
+            labels: vec![],
+            article: None,
+            analysis: None,
+        };
+
+        let event = publisher
+            .create_note(&post, None, 1_700_000_001)
+            .expect("note");
+
+        assert!(event.verify());
+        assert_eq!(
+            event.tags[1],
+            vec![
+                "e".to_string(),
+                source_event_id.clone(),
+                relay.to_string(),
+                "root".to_string(),
+                source_pubkey.clone(),
+            ]
+        );
+        assert_eq!(event.tags[2], vec!["p".to_string(), source_pubkey.clone()]);
+
+        let nevent = encode_nevent(&source_event_id, Some(relay), Some(&source_pubkey)).unwrap();
+        assert!(event.content.contains(&format!("nostr:{}", source_npub)));
+        assert!(
+            event
+                .content
+                .ends_with(&format!("Original: nostr:{}", nevent))
+        );
+        assert!(!event.content.contains(&source_url));
+    }
+
+    #[test]
+    fn test_nevent_encodes_tlv_entries() {
+        let event_id = "ab".repeat(32);
+        let author = "cd".repeat(32);
+        let nevent = encode_nevent(&event_id, Some("wss://r.example"), Some(&author)).unwrap();
+
+        let (hrp, data) = bech32::decode(&nevent).unwrap();
+        assert_eq!(hrp.as_str(), "nevent");
+        assert_eq!(&data[..2], &[0, 32]);
+        assert_eq!(hex_encode(&data[2..34]), event_id);
+        assert_eq!(&data[34..36], &[1, 15]);
+        assert_eq!(&data[36..51], b"wss://r.example");
+        assert_eq!(&data[51..53], &[2, 32]);
+        assert_eq!(hex_encode(&data[53..]), author);
+    }
+
+    #[tokio::test]
+    async fn test_publish_sends_label_event_after_note() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .and(path("/"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(2)
+            .mount(&mock_server)
+            .await;
+
+        let publisher = NostrPublisher::new(sample_secret(), vec![mock_server.uri()])
+            .expect("valid publisher")
+            .with_label_events("news-tagger");
+        let post = RenderedPost {
+            labels: vec![news_tagger_domain::TagLabel {
+                id: "test_tag".to_string(),
+                confidence: 0.85,
+            }],
+            ..sample_post()
+        };
+
+        publisher.publish(&post).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_publish_answers_auth_challenge_and_records_relays() {
+        let (auth_relay, received) = relay::test_relay::serve_with(vec![], true).await;
+        let dead_relay = "ws://127.0.0.1:1".to_string();
+        let store = Arc::new(crate::state::InMemoryStateStore::new());
+
+        let publisher = NostrPublisher::new(
+            sample_secret(),
+            vec![auth_relay.clone(), dead_relay.clone()],
+        )
+        .expect("valid publisher")
+        .with_state_store(store.clone())
+        .with_timeout(Duration::from_secs(5));
+        let result = publisher.publish(&sample_post()).await.unwrap();
+
+        let received = received.lock().unwrap().clone();
+        assert_eq!(received.len(), 1);
+        assert_eq!(received[0].id, result.id);
+
+        let accepted = store.get_relay_status(&auth_relay).await.unwrap().unwrap();
+        assert_eq!((accepted.successes, accepted.failures), (1, 0));
+        assert!(accepted.last_success_at.is_some());
+        let failed = store.get_relay_status(&dead_relay).await.unwrap().unwrap();
+        assert_eq!((failed.successes, failed.failures), (0, 1));
+        assert!(failed.last_error.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_relay_list_is_published_once() {
+        let (relay_url, received) = relay::test_relay::serve_with(vec![], false).await;
+
+        let publisher = NostrPublisher::new(sample_secret(), vec![relay_url.clone()])
+            .expect("valid publisher")
+            .with_relay_list();
+        publisher.publish(&sample_post()).await.unwrap();
+        publisher.publish(&sample_post()).await.unwrap();
+
+        let received = received.lock().unwrap().clone();
+        let kinds: Vec<u32> = received.iter().map(|event| event.kind).collect();
+        assert_eq!(kinds, vec![10002, 1, 1]);
+        assert_eq!(received[0].tags, vec![vec!["r".to_string(), relay_url]]);
+    }
+
+    #[tokio::test]
+    async fn test_reply_reaches_discovered_read_relays() {
+        let (read_relay, read_received) = relay::test_relay::serve_with(vec![], false).await;
+        let (write_relay, write_received) = relay::test_relay::serve_with(vec![], false).await;
+
+        let author = NostrPublisher::new("11".repeat(32), vec![]).expect("valid publisher");
+        let author_pubkey = author.derive_pubkey().unwrap();
+        let relay_list = author
+            .sign_event(
+                NOSTR_RELAY_LIST_KIND,
+                vec![
+                    vec!["r".to_string(), read_relay.clone(), "read".to_string()],
+                    vec!["r".to_string(), write_relay.clone(), "write".to_string()],
+                ],
+                "",
+                1_700_000_000,
+            )
+            .unwrap();
+        let (home_relay, home_received) =
+            relay::test_relay::serve_with(vec![relay_list], false).await;
+
+        let publisher = NostrPublisher::new(sample_secret(), vec![home_relay])
+            .expect("valid publisher")
+            .with_relay_discovery();
+        let post = RenderedPost {
+            source_post_id: format!("1700000000-{}", "ab".repeat(32)),
+            source_author: encode_npub(&author_pubkey).unwrap(),
+            ..sample_post()
+        };
+        publisher.publish(&post).await.unwrap();
+
+        assert_eq!(home_received.lock().unwrap().len(), 1);
+        assert_eq!(read_received.lock().unwrap().len(), 1);
+        assert!(write_received.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_long_form_article_is_published_before_linking_note() {
+        let (relay_url, received) = relay::test_relay::serve_with(vec![], false).await;
+        let publisher =
+            NostrPublisher::new(sample_secret(), vec![relay_url.clone()]).expect("valid publisher");
+        let post = RenderedPost {
+            article: Some(RenderedArticle {
+                identifier: "news-tagger-0123456789abcdef".to_string(),
+                title: "Narrative analysis of @user".to_string(),
+                summary: "Summary of the post".to_string(),
+                content: "## Summary\n\nSummary of the post\n".to_string(),
+            }),
+            ..sample_post()
+        };
+
+        let result = publisher.publish(&post).await.unwrap();
+
+        let received = received.lock().unwrap().clone();
+        assert_eq!(received.len(), 2);
+        let (article, note) = (&received[0], &received[1]);
+        assert_eq!(article.kind, 30023);
+        assert!(article.verify());
+        assert_eq!(article.content, "## Summary\n\nSummary of the post\n");
+        assert_eq!(article.tags[0], vec!["d", "news-tagger-0123456789abcdef"]);
+        assert_eq!(
+            article.tags[1],
+            vec!["title", "Narrative analysis of @user"]
+        );
+        assert_eq!(article.tags[2], vec!["summary", "Summary of the post"]);
+
+        assert_eq!(note.id, result.id);
+        assert_eq!(note.kind, 1);
+        let coordinate = format!("30023:{}:news-tagger-0123456789abcdef", article.pubkey);
+        assert!(
+            note.tags
+                .contains(&vec!["a".to_string(), coordinate, relay_url.clone()])
+        );
+        let naddr = encode_naddr(
+            "news-tagger-0123456789abcdef",
+            Some(&relay_url),
+            &article.pubkey,
+            30023,
+        )
+        .unwrap();
+        assert!(
+            note.content
+                .ends_with(&format!("Full analysis: nostr:{}", naddr))
+        );
+    }
+
+    #[test]
+    fn test_naddr_encodes_kind_big_endian() {
+        let author = "cd".repeat(32);
+        let naddr = encode_naddr("abc", None, &author, 30023).unwrap();
+
+        let (hrp, data) = bech32::decode(&naddr).unwrap();
+        assert_eq!(hrp.as_str(), "naddr");
+        assert_eq!(&data[..5], &[0, 3, b'a', b'b', b'c']);
+        assert_eq!(&data[5..7], &[2, 32]);
+        assert_eq!(&data[39..], &[3, 4, 0, 0, 0x75, 0x47]);
+    }
+
+    #[test]
+    fn test_auth_event_carries_relay_and_challenge() {
+        let publisher = NostrPublisher::new(sample_secret(), vec![]).expect("valid publisher");
+        let event = publisher
+            .create_auth_event("wss://relay.example.com", "abc", 1_700_000_000)
+            .unwrap();
+
+        assert_eq!(event.kind, 22242);
+        assert!(event.verify());
+        assert_eq!(
+            event.tags,
+            vec![
+                vec!["relay", "wss://relay.example.com"],
+                vec!["challenge", "abc"],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_verify_rejects_tampered_events() {
+        let publisher = NostrPublisher::new(sample_secret(), vec![]).expect("valid publisher");
+        let event = publisher
+            .create_event("original", 1_700_000_000)
+            .expect("event creation should succeed");
+        assert!(event.verify());
+
+        let mut tampered = event.clone();
+        tampered.content = "forged".to_string();
+        assert!(!tampered.verify());
+
+        let other = NostrPublisher::new(
+            "0000000000000000000000000000000000000000000000000000000000000003",
+            vec![],
+        )
+        .expect("valid publisher");
+        let mut wrong_author = event;
+        wrong_author.pubkey = other.derive_pubkey().expect("pubkey");
+        assert!(!wrong_author.verify());
+    }
+
+    #[test]
+    fn test_parse_public_key_npub_and_hex() {
+        let publisher = NostrPublisher::new(sample_secret(), vec![]).expect("valid publisher");
+        let hex = publisher.derive_pubkey().expect("pubkey");
+        let bytes = decode_hex_fixed::<32>(&hex).expect("valid hex");
+        let npub = bech32::encode::<Bech32>(Hrp::parse("npub").expect("valid hrp"), &bytes)
+            .expect("valid bech32");
+
+        assert_eq!(parse_public_key(&npub).expect("npub parses"), hex);
+        assert_eq!(
+            parse_public_key(&hex.to_uppercase()).expect("hex parses"),
+            hex
+        );
+        assert!(parse_public_key("npub1invalid").is_err());
+    }
+
     #[test]
     fn test_json_escaping_canonicalization() {
         let publisher = NostrPublisher::new(sample_secret(), vec![]).expect("valid publisher");
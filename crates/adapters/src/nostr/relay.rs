@@ -0,0 +1,295 @@
+//! Minimal NIP-01 relay client over WebSockets
+
+use futures_util::{SinkExt, StreamExt};
+use serde_json::Value;
+use std::time::Duration;
+use tokio_tungstenite::tungstenite::Message;
+
+use super::NostrEvent;
+
+/// Errors talking to a single relay
+#[derive(Debug, thiserror::Error)]
+pub(super) enum RelayError {
+    #[error("connection failed: {0}")]
+    Connect(String),
+    #[error("relay closed subscription: {0}")]
+    Closed(String),
+    #[error("relay timed out")]
+    Timeout,
+    #[error("relay rejected event: {0}")]
+    Rejected(String),
+}
+
+/// Signs a NIP-42 `AUTH` response for a relay URL and challenge
+pub(super) type AuthSigner<'a> = &'a (dyn Fn(&str, &str) -> Option<NostrEvent> + Sync);
+
+/// Run a one-shot `REQ` and collect stored events until `EOSE`
+///
+/// Events are returned as sent; callers must verify them before use.
+pub(super) async fn query(
+    relay: &str,
+    filter: &Value,
+    timeout: Duration,
+) -> Result<Vec<NostrEvent>, RelayError> {
+    tokio::time::timeout(timeout, query_inner(relay, filter))
+        .await
+        .map_err(|_| RelayError::Timeout)?
+}
+
+async fn query_inner(relay: &str, filter: &Value) -> Result<Vec<NostrEvent>, RelayError> {
+    let (mut socket, _) = tokio_tungstenite::connect_async(relay)
+        .await
+        .map_err(|e| RelayError::Connect(e.to_string()))?;
+
+    let subscription = "news-tagger";
+    let request = serde_json::json!(["REQ", subscription, filter]);
+    socket
+        .send(Message::text(request.to_string()))
+        .await
+        .map_err(|e| RelayError::Connect(e.to_string()))?;
+
+    let mut events = Vec::new();
+    while let Some(message) = socket.next().await {
+        let message = message.map_err(|e| RelayError::Connect(e.to_string()))?;
+        let Message::Text(text) = message else {
+            continue;
+        };
+        let Ok(Value::Array(frame)) = serde_json::from_str::<Value>(&text) else {
+            continue;
+        };
+
+        match frame.first().and_then(Value::as_str) {
+            Some("EVENT") if frame.get(1).and_then(Value::as_str) == Some(subscription) => {
+                match frame.get(2).cloned().map(serde_json::from_value) {
+                    Some(Ok(event)) => events.push(event),
+                    _ => tracing::debug!(relay = %relay, "Skipping malformed event"),
+                }
+            }
+            Some("EOSE") => break,
+            Some("CLOSED") => {
+                let reason = frame.get(2).and_then(Value::as_str).unwrap_or_default();
+                return Err(RelayError::Closed(reason.to_string()));
+            }
+            Some("NOTICE") => {
+                tracing::debug!(relay = %relay, notice = ?frame.get(1), "Relay notice");
+            }
+            _ => {}
+        }
+    }
+
+    let close = serde_json::json!(["CLOSE", subscription]);
+    let _ = socket.send(Message::text(close.to_string())).await;
+    let _ = socket.close(None).await;
+
+    Ok(events)
+}
+
+/// Send an event and wait for the relay's `OK`
+///
+/// Answers an `AUTH` challenge with an event from `auth`, and resends the event
+/// once if the relay turned it away as `auth-required` before we authenticated.
+pub(super) async fn publish(
+    relay: &str,
+    event: &NostrEvent,
+    auth: AuthSigner<'_>,
+    timeout: Duration,
+) -> Result<(), RelayError> {
+    tokio::time::timeout(timeout, publish_inner(relay, event, auth))
+        .await
+        .map_err(|_| RelayError::Timeout)?
+}
+
+async fn publish_inner(
+    relay: &str,
+    event: &NostrEvent,
+    auth: AuthSigner<'_>,
+) -> Result<(), RelayError> {
+    let (mut socket, _) = tokio_tungstenite::connect_async(relay)
+        .await
+        .map_err(|e| RelayError::Connect(e.to_string()))?;
+
+    let submit = Message::text(serde_json::json!(["EVENT", event]).to_string());
+    socket
+        .send(submit.clone())
+        .await
+        .map_err(|e| RelayError::Connect(e.to_string()))?;
+
+    let mut auth_event_id: Option<String> = None;
+    let mut authenticated = false;
+    let mut awaiting_auth = false;
+    let mut resent = false;
+
+    while let Some(message) = socket.next().await {
+        let message = message.map_err(|e| RelayError::Connect(e.to_string()))?;
+        let Message::Text(text) = message else {
+            continue;
+        };
+        let Ok(Value::Array(frame)) = serde_json::from_str::<Value>(&text) else {
+            continue;
+        };
+
+        match frame.first().and_then(Value::as_str) {
+            Some("AUTH") => {
+                let Some(challenge) = frame.get(1).and_then(Value::as_str) else {
+                    continue;
+                };
+                let Some(auth_event) = auth(relay, challenge) else {
+                    continue;
+                };
+                tracing::debug!(relay = %relay, "Answering relay AUTH challenge");
+                auth_event_id = Some(auth_event.id.clone());
+                let response = serde_json::json!(["AUTH", auth_event]);
+                socket
+                    .send(Message::text(response.to_string()))
+                    .await
+                    .map_err(|e| RelayError::Connect(e.to_string()))?;
+            }
+            Some("OK") => {
+                let id = frame.get(1).and_then(Value::as_str);
+                let accepted = frame.get(2).and_then(Value::as_bool).unwrap_or(false);
+                let reason = frame
+                    .get(3)
+                    .and_then(Value::as_str)
+                    .unwrap_or_default()
+                    .to_string();
+
+                if id.is_some() && id == auth_event_id.as_deref() {
+                    if !accepted {
+                        return Err(RelayError::Rejected(format!("AUTH failed: {}", reason)));
+                    }
+                    authenticated = true;
+                    if awaiting_auth && !resent {
+                        resent = true;
+                        socket
+                            .send(submit.clone())
+                            .await
+                            .map_err(|e| RelayError::Connect(e.to_string()))?;
+                    }
+                } else if id == Some(event.id.as_str()) {
+                    if accepted {
+                        let _ = socket.close(None).await;
+                        return Ok(());
+                    }
+                    if !reason.starts_with("auth-required:") || resent {
+                        return Err(RelayError::Rejected(reason));
+                    }
+                    if authenticated {
+                        resent = true;
+                        socket
+                            .send(submit.clone())
+                            .await
+                            .map_err(|e| RelayError::Connect(e.to_string()))?;
+                    } else {
+                        // Wait for the challenge (or our answer to it) and retry then
+                        awaiting_auth = true;
+                    }
+                }
+            }
+            Some("NOTICE") => {
+                tracing::debug!(relay = %relay, notice = ?frame.get(1), "Relay notice");
+            }
+            _ => {}
+        }
+    }
+
+    Err(RelayError::Closed(
+        "connection closed before OK".to_string(),
+    ))
+}
+
+/// Test relay serving canned events for any `REQ` and accepting any `EVENT`
+#[cfg(test)]
+pub(super) mod test_relay {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+    use tokio::net::TcpListener;
+
+    /// Events a test relay accepted, in order
+    pub type Received = Arc<Mutex<Vec<NostrEvent>>>;
+
+    /// Challenge sent by relays started with `require_auth`
+    pub const CHALLENGE: &str = "test-challenge";
+
+    /// Start a relay on a random port and return its `ws://` URL
+    pub async fn serve(events: Vec<NostrEvent>) -> String {
+        serve_with(events, false).await.0
+    }
+
+    /// Start a relay that optionally demands NIP-42 authentication before writes
+    pub async fn serve_with(events: Vec<NostrEvent>, require_auth: bool) -> (String, Received) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.expect("bind");
+        let addr = listener.local_addr().expect("local addr");
+        let url = format!("ws://{}", addr);
+        let received: Received = Arc::default();
+
+        let relay_url = url.clone();
+        let accepted = received.clone();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let events = events.clone();
+                let relay_url = relay_url.clone();
+                let accepted = accepted.clone();
+                tokio::spawn(async move {
+                    let Ok(mut socket) = tokio_tungstenite::accept_async(stream).await else {
+                        return;
+                    };
+                    let mut authenticated = !require_auth;
+                    if require_auth {
+                        let challenge = serde_json::json!(["AUTH", CHALLENGE]);
+                        let _ = socket.send(Message::text(challenge.to_string())).await;
+                    }
+
+                    while let Some(Ok(Message::Text(text))) = socket.next().await {
+                        let frame: Value = serde_json::from_str(&text).unwrap_or_default();
+                        let reply = match frame[0].as_str() {
+                            Some("REQ") => {
+                                let subscription = frame[1].clone();
+                                for event in &events {
+                                    let message = serde_json::json!(["EVENT", subscription, event]);
+                                    let _ = socket.send(Message::text(message.to_string())).await;
+                                }
+                                serde_json::json!(["EOSE", subscription])
+                            }
+                            Some("AUTH") => {
+                                let event: NostrEvent =
+                                    serde_json::from_value(frame[1].clone()).expect("auth event");
+                                let tag = |name: &str| {
+                                    event
+                                        .tags
+                                        .iter()
+                                        .find(|tag| tag[0] == name)
+                                        .map(|tag| tag[1].clone())
+                                };
+                                authenticated = event.verify()
+                                    && event.kind == 22242
+                                    && tag("challenge").as_deref() == Some(CHALLENGE)
+                                    && tag("relay").as_deref() == Some(relay_url.as_str());
+                                serde_json::json!(["OK", event.id, authenticated, ""])
+                            }
+                            Some("EVENT") => {
+                                let event: NostrEvent =
+                                    serde_json::from_value(frame[1].clone()).expect("event");
+                                if authenticated {
+                                    let id = event.id.clone();
+                                    accepted.lock().unwrap().push(event);
+                                    serde_json::json!(["OK", id, true, ""])
+                                } else {
+                                    serde_json::json!([
+                                        "OK",
+                                        event.id,
+                                        false,
+                                        "auth-required: sign in first"
+                                    ])
+                                }
+                            }
+                            _ => continue,
+                        };
+                        let _ = socket.send(Message::text(reply.to_string())).await;
+                    }
+                });
+            }
+        });
+
+        (url, received)
+    }
+}
@@ -0,0 +1,267 @@
+//! Nostr post source reading kind-1 notes from relays
+
+use async_trait::async_trait;
+use futures_util::future::join_all;
+use news_tagger_domain::{PostSource, PostSourceError, SourcePost, compare_post_ids};
+use std::collections::HashMap;
+use std::time::Duration;
+use time::OffsetDateTime;
+
+use super::relay::query;
+use super::{NOSTR_TEXT_NOTE_KIND, NostrEvent, encode_note, encode_npub, parse_public_key};
+
+/// Notes requested per relay and poll
+const QUERY_LIMIT: u32 = 100;
+
+/// Nostr post source subscribing to an author's notes on every configured relay
+///
+/// Accounts are `npub…` or hex public keys. Post IDs are `<created_at>-<event id>`
+/// so they order chronologically like other sources; the event ID is the suffix.
+/// The same event from several relays is kept once, and events whose ID or
+/// signature does not check out are dropped.
+pub struct NostrPostSource {
+    relays: Vec<String>,
+    timeout: Duration,
+}
+
+impl NostrPostSource {
+    pub fn new(relays: Vec<String>) -> Self {
+        Self {
+            relays,
+            timeout: Duration::from_secs(10),
+        }
+    }
+
+    /// How long to wait for each relay to reach end of stored events
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+}
+
+/// Sortable post ID for an event
+fn post_id(event: &NostrEvent) -> String {
+    format!("{:010}-{}", event.created_at.max(0), event.id)
+}
+
+/// Event ID of a post ID from [`post_id`], if it is one
+pub(super) fn event_id_from_post_id(post_id: &str) -> Option<&str> {
+    let (created_at, event_id) = post_id.split_once('-')?;
+    let is_event = created_at.len() == 10
+        && created_at.bytes().all(|b| b.is_ascii_digit())
+        && event_id.len() == 64
+        && event_id.bytes().all(|b| b.is_ascii_hexdigit());
+    is_event.then_some(event_id)
+}
+
+/// Unix timestamp encoded in a post ID from [`post_id`]
+fn since_timestamp(since_id: &str) -> Option<i64> {
+    since_id.split_once('-')?.0.parse().ok()
+}
+
+/// Event this note replies to, per NIP-10 markers or the deprecated positional form
+fn reply_target(event: &NostrEvent) -> Option<String> {
+    let e_tags: Vec<&Vec<String>> = event
+        .tags
+        .iter()
+        .filter(|tag| tag.first().map(String::as_str) == Some("e") && tag.len() >= 2)
+        .collect();
+
+    let marked = |marker: &str| {
+        e_tags
+            .iter()
+            .find(|tag| tag.get(3).map(String::as_str) == Some(marker))
+            .map(|tag| tag[1].clone())
+    };
+
+    if e_tags.iter().any(|tag| tag.len() >= 4) {
+        marked("reply").or_else(|| marked("root"))
+    } else {
+        e_tags.last().map(|tag| tag[1].clone())
+    }
+}
+
+fn event_to_post(event: NostrEvent) -> SourcePost {
+    let url = encode_note(&event.id)
+        .map(|note| format!("https://njump.me/{}", note))
+        .unwrap_or_else(|| format!("nostr:{}", event.id));
+    let author = encode_npub(&event.pubkey).unwrap_or_else(|| event.pubkey.clone());
+    let reply_to_id = reply_target(&event);
+
+    SourcePost {
+        id: post_id(&event),
+        created_at: OffsetDateTime::from_unix_timestamp(event.created_at)
+            .unwrap_or(OffsetDateTime::UNIX_EPOCH),
+        text: event.content,
+        author,
+        url,
+        is_repost: false,
+        is_reply: reply_to_id.is_some(),
+        reply_to_id,
+        context: vec![],
+    }
+}
+
+#[async_trait]
+impl PostSource for NostrPostSource {
+    async fn fetch_posts(
+        &self,
+        account: &str,
+        since_id: Option<&str>,
+    ) -> Result<Vec<SourcePost>, PostSourceError> {
+        if self.relays.is_empty() {
+            return Err(PostSourceError::Api("No relays configured".to_string()));
+        }
+
+        let pubkey = parse_public_key(account)
+            .map_err(|e| PostSourceError::AccountUnavailable(format!("{}: {}", account, e)))?;
+
+        let mut filter = serde_json::json!({
+            "authors": [pubkey],
+            "kinds": [NOSTR_TEXT_NOTE_KIND],
+            "limit": QUERY_LIMIT,
+        });
+        if let Some(since) = since_id.and_then(since_timestamp) {
+            filter["since"] = since.into();
+        }
+
+        let results = join_all(
+            self.relays
+                .iter()
+                .map(|relay| query(relay, &filter, self.timeout)),
+        )
+        .await;
+
+        let mut events: HashMap<String, NostrEvent> = HashMap::new();
+        let mut last_error = None;
+        let mut answered = 0;
+        for (relay, result) in self.relays.iter().zip(results) {
+            match result {
+                Ok(relay_events) => {
+                    answered += 1;
+                    for event in relay_events {
+                        if events.contains_key(&event.id) {
+                            continue;
+                        }
+                        if event.pubkey != pubkey
+                            || event.kind != NOSTR_TEXT_NOTE_KIND
+                            || !event.verify()
+                        {
+                            tracing::warn!(
+                                relay = %relay,
+                                event_id = %event.id,
+                                "Dropping Nostr event with invalid signature or unexpected author"
+                            );
+                            continue;
+                        }
+                        events.insert(event.id.clone(), event);
+                    }
+                }
+                Err(e) => {
+                    tracing::warn!(relay = %relay, error = %e, "Nostr relay query failed");
+                    last_error = Some(e);
+                }
+            }
+        }
+
+        if answered == 0 {
+            return Err(PostSourceError::Network(format!(
+                "No relay answered: {}",
+                last_error.map(|e| e.to_string()).unwrap_or_default()
+            )));
+        }
+
+        let mut posts: Vec<SourcePost> = events
+            .into_values()
+            .map(event_to_post)
+            .filter(|post| since_id.is_none_or(|since| compare_post_ids(&post.id, since).is_gt()))
+            .collect();
+        posts.sort_by(|a, b| compare_post_ids(&a.id, &b.id));
+
+        Ok(posts)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::NostrPublisher;
+    use super::super::relay::test_relay;
+    use super::*;
+
+    fn publisher() -> NostrPublisher {
+        NostrPublisher::new(
+            "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f",
+            vec![],
+        )
+        .expect("valid publisher")
+    }
+
+    #[tokio::test]
+    async fn test_dedupes_across_relays_and_drops_forgeries() {
+        let publisher = publisher();
+        let pubkey = publisher.derive_pubkey().unwrap();
+        let first = publisher.create_event("first note", 1_700_000_100).unwrap();
+        let second = publisher
+            .create_event("second note", 1_700_000_200)
+            .unwrap();
+        let mut forged = publisher.create_event("real text", 1_700_000_300).unwrap();
+        forged.content = "forged text".to_string();
+
+        let relay_a = test_relay::serve(vec![second.clone(), first.clone()]).await;
+        let relay_b = test_relay::serve(vec![first.clone(), forged]).await;
+
+        let source = NostrPostSource::new(vec![relay_a, relay_b]);
+        let posts = source.fetch_posts(&pubkey, None).await.unwrap();
+
+        assert_eq!(posts.len(), 2);
+        assert_eq!(posts[0].text, "first note");
+        assert_eq!(posts[0].id, format!("1700000100-{}", first.id));
+        assert!(posts[0].url.starts_with("https://njump.me/note1"));
+        assert!(posts[0].author.starts_with("npub1"));
+        assert_eq!(posts[1].text, "second note");
+    }
+
+    #[tokio::test]
+    async fn test_filters_out_posts_up_to_since_id() {
+        let publisher = publisher();
+        let pubkey = publisher.derive_pubkey().unwrap();
+        let old = publisher.create_event("old", 1_700_000_100).unwrap();
+        let new = publisher.create_event("new", 1_700_000_200).unwrap();
+
+        let relay = test_relay::serve(vec![old.clone(), new]).await;
+        let source = NostrPostSource::new(vec![relay]);
+        let since = post_id(&old);
+        let posts = source.fetch_posts(&pubkey, Some(&since)).await.unwrap();
+
+        assert_eq!(posts.len(), 1);
+        assert_eq!(posts[0].text, "new");
+    }
+
+    #[tokio::test]
+    async fn test_unreachable_relays_are_network_errors() {
+        let publisher = publisher();
+        let pubkey = publisher.derive_pubkey().unwrap();
+
+        let source = NostrPostSource::new(vec!["ws://127.0.0.1:1".to_string()])
+            .with_timeout(Duration::from_secs(2));
+        let result = source.fetch_posts(&pubkey, None).await;
+
+        assert!(matches!(result, Err(PostSourceError::Network(_))));
+    }
+
+    #[test]
+    fn test_reply_target_prefers_marked_reply() {
+        let mut event = publisher().create_event("reply", 1).unwrap();
+        event.tags = vec![
+            vec!["e".into(), "root-id".into(), "".into(), "root".into()],
+            vec!["e".into(), "parent-id".into(), "".into(), "reply".into()],
+        ];
+        assert_eq!(reply_target(&event).as_deref(), Some("parent-id"));
+
+        event.tags = vec![
+            vec!["e".into(), "root-id".into()],
+            vec!["e".into(), "parent-id".into()],
+        ];
+        assert_eq!(reply_target(&event).as_deref(), Some("parent-id"));
+    }
+}
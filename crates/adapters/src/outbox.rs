@@ -1,11 +1,12 @@
 //! Outbox publisher for require-approval mode.
 
 use async_trait::async_trait;
-use news_tagger_domain::model::RenderedPost;
+use news_tagger_domain::model::{PostAnalysis, RenderedArticle, RenderedPost, TagLabel};
 use news_tagger_domain::ports::{PublishError, PublishResult, Publisher};
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use time::OffsetDateTime;
 use tokio::fs::{self, OpenOptions};
 use tokio::io::AsyncWriteExt;
 use tokio::sync::Mutex;
@@ -49,7 +50,12 @@ impl OutboxWriter {
         &self.path
     }
 
-    async fn append(&self, entry: &OutboxEntry<'_>) -> Result<(), OutboxError> {
+    /// Mark an entry as approved and published
+    pub async fn record_approval(&self, approval: &OutboxApproval) -> Result<(), OutboxError> {
+        self.append(approval).await
+    }
+
+    pub(crate) async fn append<T: Serialize>(&self, entry: &T) -> Result<(), OutboxError> {
         let line = serde_json::to_string(entry)?;
         let mut file = self.file.lock().await;
         file.write_all(line.as_bytes()).await?;
@@ -71,22 +77,111 @@ impl OutboxPublisher {
     }
 }
 
-#[derive(Serialize)]
-struct OutboxEntry<'a> {
-    platform: &'a str,
-    source_post_id: &'a str,
-    source_post_url: &'a str,
-    text: &'a str,
+/// A rendered post waiting for approval
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutboxEntry {
+    /// Also the target ID recorded for the post until it is approved;
+    /// empty for entries written before entries had IDs
+    #[serde(default)]
+    pub id: String,
+    pub platform: String,
+    pub source_post_id: String,
+    pub source_post_url: String,
+    pub text: String,
+    #[serde(default)]
+    pub source_author: String,
+    #[serde(default)]
+    pub labels: Vec<TagLabel>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub article: Option<RenderedArticle>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub analysis: Option<PostAnalysis>,
+}
+
+impl OutboxEntry {
+    /// The post as it was rendered for the outbox
+    pub fn rendered(&self) -> RenderedPost {
+        RenderedPost {
+            text: self.text.clone(),
+            source_post_id: self.source_post_id.clone(),
+            source_post_url: self.source_post_url.clone(),
+            source_author: self.source_author.clone(),
+            labels: self.labels.clone(),
+            article: self.article.clone(),
+            analysis: self.analysis.clone().map(Arc::new),
+        }
+    }
+}
+
+/// Appended to the outbox once an entry was approved and published
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutboxApproval {
+    /// ID of the approved entry
+    pub approved: String,
+    /// ID the platform assigned to the published post
+    pub target_id: String,
+    pub url: Option<String>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub approved_at: OffsetDateTime,
+}
+
+/// An outbox entry and its approval, if it has one
+#[derive(Debug, Clone)]
+pub struct OutboxItem {
+    pub entry: OutboxEntry,
+    pub approval: Option<OutboxApproval>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum OutboxLine {
+    Approval(OutboxApproval),
+    Entry(Box<OutboxEntry>),
+}
+
+/// Read an outbox file, oldest entry first; a missing file is empty
+pub async fn read_outbox(path: &Path) -> Result<Vec<OutboxItem>, OutboxError> {
+    let contents = match fs::read_to_string(path).await {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(vec![]),
+        Err(e) => return Err(e.into()),
+    };
+
+    let mut items: Vec<OutboxItem> = Vec::new();
+    for line in contents.lines().filter(|line| !line.trim().is_empty()) {
+        match serde_json::from_str(line)? {
+            OutboxLine::Entry(entry) => items.push(OutboxItem {
+                entry: *entry,
+                approval: None,
+            }),
+            OutboxLine::Approval(approval) => {
+                if let Some(item) = items
+                    .iter_mut()
+                    .find(|item| item.entry.id == approval.approved)
+                {
+                    item.approval = Some(approval);
+                }
+            }
+        }
+    }
+
+    Ok(items)
 }
 
 #[async_trait]
 impl Publisher for OutboxPublisher {
     async fn publish(&self, post: &RenderedPost) -> Result<PublishResult, PublishError> {
+        let id = Uuid::new_v4().to_string();
         let entry = OutboxEntry {
-            platform: self.platform,
-            source_post_id: &post.source_post_id,
-            source_post_url: &post.source_post_url,
-            text: &post.text,
+            id: id.clone(),
+            platform: self.platform.to_string(),
+            source_post_id: post.source_post_id.clone(),
+            source_post_url: post.source_post_url.clone(),
+            text: post.text.clone(),
+            source_author: post.source_author.clone(),
+            labels: post.labels.clone(),
+            article: post.article.clone(),
+            analysis: post.analysis.as_deref().cloned(),
         };
 
         self.writer
@@ -94,10 +189,7 @@ impl Publisher for OutboxPublisher {
             .await
             .map_err(|error| PublishError::Api(format!("Outbox write failed: {}", error)))?;
 
-        Ok(PublishResult {
-            id: Uuid::new_v4().to_string(),
-            url: None,
-        })
+        Ok(PublishResult { id, url: None })
     }
 
     fn is_enabled(&self) -> bool {
@@ -127,6 +219,10 @@ mod tests {
             text: "Rendered content".to_string(),
             source_post_id: "123".to_string(),
             source_post_url: "https://x.com/example/status/123".to_string(),
+            source_author: "user".to_string(),
+            labels: vec![],
+            article: None,
+            analysis: None,
         };
 
         let result = publisher.publish(&post).await.expect("publish");
@@ -140,5 +236,44 @@ mod tests {
         assert_eq!(value["source_post_id"], "123");
         assert_eq!(value["source_post_url"], "https://x.com/example/status/123");
         assert_eq!(value["text"], "Rendered content");
+        assert_eq!(value["id"], result.id);
+    }
+
+    #[tokio::test]
+    async fn read_outbox_pairs_entries_with_approvals() {
+        let dir = TempDir::new().expect("temp dir");
+        let path = dir.path().join("outbox.jsonl");
+        assert!(read_outbox(&path).await.expect("missing file").is_empty());
+
+        let writer = OutboxWriter::new(path.clone()).await.expect("writer");
+        let publisher = OutboxPublisher::new(writer.clone(), "mastodon");
+        let mut ids = Vec::new();
+        for id in ["1", "2"] {
+            let post = RenderedPost {
+                text: format!("Analysis of {id}"),
+                source_post_id: id.to_string(),
+                source_author: "user".to_string(),
+                ..Default::default()
+            };
+            ids.push(publisher.publish(&post).await.expect("publish").id);
+        }
+        writer
+            .record_approval(&OutboxApproval {
+                approved: ids[1].clone(),
+                target_id: "109".to_string(),
+                url: Some("https://example.social/@bot/109".to_string()),
+                approved_at: OffsetDateTime::now_utc(),
+            })
+            .await
+            .expect("approve");
+
+        let items = read_outbox(&path).await.expect("read outbox");
+        assert_eq!(items.len(), 2);
+        assert!(items[0].approval.is_none());
+        assert_eq!(items[1].approval.as_ref().unwrap().target_id, "109");
+
+        let rendered = items[1].entry.rendered();
+        assert_eq!(rendered.text, "Analysis of 2");
+        assert_eq!(rendered.source_author, "user");
     }
 }
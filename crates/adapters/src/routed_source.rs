@@ -0,0 +1,140 @@
+//! Post source that dispatches accounts to other sources by prefix
+
+use async_trait::async_trait;
+use news_tagger_domain::{PostSource, PostSourceError, SourcePost};
+use std::sync::Arc;
+
+/// Routes `prefix:name` accounts to the source registered for `prefix`,
+/// and plain accounts to the default source
+///
+/// The full account string (prefix included) stays the run loop's key, so each
+/// routed account keeps its own cursor in the state store.
+#[derive(Default)]
+pub struct RoutedPostSource {
+    default: Option<Arc<dyn PostSource>>,
+    routes: Vec<(String, Arc<dyn PostSource>)>,
+}
+
+impl RoutedPostSource {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Source for accounts without a registered prefix
+    pub fn with_default(mut self, source: Arc<dyn PostSource>) -> Self {
+        self.default = Some(source);
+        self
+    }
+
+    /// Source for accounts written as `prefix:name`
+    pub fn with_route(mut self, prefix: impl Into<String>, source: Arc<dyn PostSource>) -> Self {
+        self.routes.push((prefix.into(), source));
+        self
+    }
+
+    /// Index of the route (None = default) and the account name it expects
+    fn route<'a>(&self, account: &'a str) -> (Option<usize>, &'a str) {
+        if let Some((prefix, name)) = account.split_once(':') {
+            if let Some(index) = self.routes.iter().position(|(p, _)| p == prefix) {
+                return (Some(index), name);
+            }
+        }
+        (None, account)
+    }
+
+    fn source(&self, route: Option<usize>) -> Option<&Arc<dyn PostSource>> {
+        match route {
+            Some(index) => self.routes.get(index).map(|(_, source)| source),
+            None => self.default.as_ref(),
+        }
+    }
+}
+
+#[async_trait]
+impl PostSource for RoutedPostSource {
+    async fn fetch_posts(
+        &self,
+        account: &str,
+        since_id: Option<&str>,
+    ) -> Result<Vec<SourcePost>, PostSourceError> {
+        let (route, name) = self.route(account);
+        let source = self.source(route).ok_or_else(|| {
+            PostSourceError::AccountUnavailable(format!("No source configured for {}", account))
+        })?;
+        source.fetch_posts(name, since_id).await
+    }
+
+    async fn prepare_accounts(&self, accounts: &[String]) -> Result<(), PostSourceError> {
+        let mut grouped: Vec<(Option<usize>, Vec<String>)> = Vec::new();
+        for account in accounts {
+            let (route, name) = self.route(account);
+            match grouped.iter_mut().find(|(r, _)| *r == route) {
+                Some((_, names)) => names.push(name.to_string()),
+                None => grouped.push((route, vec![name.to_string()])),
+            }
+        }
+
+        let mut first_error = None;
+        for (route, names) in grouped {
+            let Some(source) = self.source(route) else {
+                continue;
+            };
+            if let Err(e) = source.prepare_accounts(&names).await {
+                first_error.get_or_insert(e);
+            }
+        }
+
+        first_error.map_or(Ok(()), Err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::x_api::StubPostSource;
+    use time::OffsetDateTime;
+
+    fn post(id: &str, author: &str) -> SourcePost {
+        SourcePost {
+            id: id.to_string(),
+            text: "text".to_string(),
+            author: author.to_string(),
+            url: "https://example.com".to_string(),
+            created_at: OffsetDateTime::UNIX_EPOCH,
+            is_repost: false,
+            is_reply: false,
+            reply_to_id: None,
+            context: vec![],
+        }
+    }
+
+    #[tokio::test]
+    async fn test_routes_by_prefix_and_falls_back_to_default() {
+        let source = RoutedPostSource::new()
+            .with_default(Arc::new(StubPostSource::with_posts(vec![post("1", "x")])))
+            .with_route(
+                "feed",
+                Arc::new(StubPostSource::with_posts(vec![post("2", "feed")])),
+            );
+
+        let feed = source.fetch_posts("feed:world", None).await.unwrap();
+        assert_eq!(feed[0].author, "feed");
+
+        let x = source.fetch_posts("someone", None).await.unwrap();
+        assert_eq!(x[0].author, "x");
+    }
+
+    #[tokio::test]
+    async fn test_missing_default_is_unavailable() {
+        let source = RoutedPostSource::new().with_route(
+            "feed",
+            Arc::new(StubPostSource::with_posts(vec![post("2", "feed")])),
+        );
+
+        let result = source.fetch_posts("someone", None).await;
+        assert!(matches!(
+            result,
+            Err(PostSourceError::AccountUnavailable(_))
+        ));
+    }
+}
@@ -0,0 +1,777 @@
+//! Static HTML/JSON site rendered from stored classifications
+//!
+//! Layout of the output directory:
+//! - `index.html`: tags, accounts and every post, newest first
+//! - `posts/<post>.html`: post text with evidence highlighted, summary and tags
+//! - `tags/<tag>.html` and `tags/<tag>.atom`: posts per tag, monthly counts and a feed
+//! - `accounts/<account>.html`: posts per source account
+//! - `api/...`: the same data as JSON
+//!
+//! Files are only rewritten when their content changes and pages that are no
+//! longer generated are removed, so the site can be rebuilt in place.
+
+use atom_syndication::{Entry, Feed, FixedDateTime, Link, Person, Text};
+use news_tagger_domain::{ClassificationRecord, TagDefinition, TagMatch};
+use serde_json::{Value, json};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+use time::OffsetDateTime;
+use time::format_description::well_known::Rfc3339;
+
+/// Directories under the output directory owned by the site builder
+const SITE_DIRS: [&str; 4] = ["posts", "tags", "accounts", "api"];
+
+const STYLE: &str = "body{font-family:system-ui,sans-serif;max-width:48rem;margin:2rem auto;padding:0 1rem;line-height:1.5}\
+blockquote{white-space:pre-wrap;border-left:4px solid #ccc;margin:1rem 0;padding:.5rem 1rem}\
+mark{background:#fde68a}.meta{color:#555;font-size:.9rem}table{border-collapse:collapse}\
+td,th{padding:.2rem .8rem;text-align:left;border-bottom:1px solid #eee}";
+
+/// Errors while building the site
+#[derive(Debug, Error)]
+pub enum SiteError {
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("Feed error: {0}")]
+    Feed(String),
+}
+
+/// Files touched by a build
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct SiteReport {
+    /// Files created or rewritten
+    pub written: usize,
+    /// Files left alone because their content was already current
+    pub unchanged: usize,
+    /// Stale files removed
+    pub removed: usize,
+}
+
+/// Renders stored classifications into a static site
+pub struct SiteBuilder {
+    out_dir: PathBuf,
+    title: String,
+    base_url: Option<String>,
+    tag_titles: HashMap<String, String>,
+}
+
+/// A post as shown on the site: the latest classification of a source post
+struct SitePost<'a> {
+    record: &'a ClassificationRecord,
+    slug: String,
+}
+
+impl SiteBuilder {
+    pub fn new(out_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            out_dir: out_dir.into(),
+            title: "news-tagger".to_string(),
+            base_url: None,
+            tag_titles: HashMap::new(),
+        }
+    }
+
+    /// Site title shown in page headers and feed titles
+    pub fn with_title(mut self, title: impl Into<String>) -> Self {
+        self.title = title.into();
+        self
+    }
+
+    /// Public URL of the output directory, used for absolute links in feeds
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        let base_url = base_url.into();
+        self.base_url = Some(format!("{}/", base_url.trim_end_matches('/')));
+        self
+    }
+
+    /// Show tag titles from these definitions instead of tag IDs
+    pub fn with_definitions(mut self, definitions: &[TagDefinition]) -> Self {
+        self.tag_titles = definitions
+            .iter()
+            .map(|d| (d.id.clone(), d.title.clone()))
+            .collect();
+        self
+    }
+
+    /// Render the site from `records`, keeping the latest classification of each post
+    pub fn build(&self, records: &[ClassificationRecord]) -> Result<SiteReport, SiteError> {
+        let mut latest: HashMap<&str, &ClassificationRecord> = HashMap::new();
+        for record in records {
+            let entry = latest.entry(&record.source_post.id).or_insert(record);
+            if record.classified_at > entry.classified_at {
+                *entry = record;
+            }
+        }
+
+        let mut posts: Vec<SitePost<'_>> = latest
+            .into_values()
+            .map(|record| SitePost {
+                record,
+                slug: slug(&record.source_post.id),
+            })
+            .collect();
+        posts.sort_by(|a, b| {
+            b.record
+                .source_post
+                .created_at
+                .cmp(&a.record.source_post.created_at)
+                .then_with(|| a.record.source_post.id.cmp(&b.record.source_post.id))
+        });
+
+        let mut tags: BTreeMap<&str, Vec<(&SitePost<'_>, &TagMatch)>> = BTreeMap::new();
+        let mut accounts: BTreeMap<&str, Vec<&SitePost<'_>>> = BTreeMap::new();
+        for post in &posts {
+            for tag in &post.record.classification.tags {
+                tags.entry(&tag.id).or_default().push((post, tag));
+            }
+            accounts
+                .entry(&post.record.source_post.author)
+                .or_default()
+                .push(post);
+        }
+
+        let mut files: Vec<(String, Vec<u8>)> = Vec::new();
+
+        for post in &posts {
+            files.push((
+                format!("posts/{}.html", post.slug),
+                self.post_page(post).into_bytes(),
+            ));
+            files.push((
+                format!("api/posts/{}.json", post.slug),
+                serde_json::to_vec_pretty(post.record)?,
+            ));
+        }
+
+        for (tag_id, tagged) in &tags {
+            let tag_slug = slug(tag_id);
+            files.push((
+                format!("tags/{}.html", tag_slug),
+                self.tag_page(tag_id, tagged).into_bytes(),
+            ));
+            files.push((
+                format!("tags/{}.atom", tag_slug),
+                self.tag_feed(tag_id, tagged)?.into_bytes(),
+            ));
+            let api = json!({
+                "id": tag_id,
+                "title": self.tag_title(tag_id),
+                "count": tagged.len(),
+                "by_month": monthly_counts(tagged.iter().map(|(post, _)| *post))
+                    .into_iter()
+                    .map(|(month, count)| json!({ "month": month, "count": count }))
+                    .collect::<Vec<_>>(),
+                "posts": tagged
+                    .iter()
+                    .map(|(post, tag)| post_summary(post, Some(tag.confidence)))
+                    .collect::<Vec<_>>(),
+            });
+            files.push((
+                format!("api/tags/{}.json", tag_slug),
+                serde_json::to_vec_pretty(&api)?,
+            ));
+        }
+
+        for (account, account_posts) in &accounts {
+            let account_slug = slug(account);
+            files.push((
+                format!("accounts/{}.html", account_slug),
+                self.account_page(account, account_posts).into_bytes(),
+            ));
+            let api = json!({
+                "account": account,
+                "count": account_posts.len(),
+                "posts": account_posts
+                    .iter()
+                    .map(|post| post_summary(post, None))
+                    .collect::<Vec<_>>(),
+            });
+            files.push((
+                format!("api/accounts/{}.json", account_slug),
+                serde_json::to_vec_pretty(&api)?,
+            ));
+        }
+
+        let index = json!({
+            "title": self.title,
+            "tags": tags
+                .iter()
+                .map(|(id, tagged)| json!({
+                    "id": id,
+                    "title": self.tag_title(id),
+                    "count": tagged.len(),
+                    "slug": slug(id),
+                }))
+                .collect::<Vec<_>>(),
+            "accounts": accounts
+                .iter()
+                .map(|(account, account_posts)| json!({
+                    "account": account,
+                    "count": account_posts.len(),
+                    "slug": slug(account),
+                }))
+                .collect::<Vec<_>>(),
+            "posts": posts.iter().map(|post| post_summary(post, None)).collect::<Vec<_>>(),
+        });
+        files.push((
+            "api/index.json".to_string(),
+            serde_json::to_vec_pretty(&index)?,
+        ));
+        files.push((
+            "index.html".to_string(),
+            self.index_page(&posts, &tags, &accounts).into_bytes(),
+        ));
+
+        let mut report = SiteReport::default();
+        for (path, contents) in &files {
+            self.write(path, contents, &mut report)?;
+        }
+
+        let generated: HashSet<PathBuf> = files
+            .iter()
+            .map(|(path, _)| self.out_dir.join(path))
+            .collect();
+        for dir in SITE_DIRS {
+            remove_stale(&self.out_dir.join(dir), &generated, &mut report)?;
+        }
+
+        Ok(report)
+    }
+
+    fn write(&self, path: &str, contents: &[u8], report: &mut SiteReport) -> Result<(), SiteError> {
+        let path = self.out_dir.join(path);
+        if fs::read(&path).is_ok_and(|existing| existing == contents) {
+            report.unchanged += 1;
+            return Ok(());
+        }
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&path, contents)?;
+        report.written += 1;
+        Ok(())
+    }
+
+    fn tag_title<'a>(&'a self, tag_id: &'a str) -> &'a str {
+        self.tag_titles.get(tag_id).map_or(tag_id, String::as_str)
+    }
+
+    fn page(&self, title: &str, root: &str, head: &str, body: &str) -> String {
+        format!(
+            "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+             <meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n\
+             <title>{title} · {site}</title>\n<style>{STYLE}</style>\n{head}</head>\n<body>\n\
+             <nav><a href=\"{root}index.html\">{site}</a></nav>\n{body}</body>\n</html>\n",
+            title = escape(title),
+            site = escape(&self.title),
+        )
+    }
+
+    fn post_page(&self, post: &SitePost<'_>) -> String {
+        let source = &post.record.source_post;
+        let classification = &post.record.classification;
+        let evidence: Vec<&str> = classification
+            .tags
+            .iter()
+            .flat_map(|t| t.evidence.iter().map(String::as_str))
+            .collect();
+
+        let mut body = format!(
+            "<h1>Post by @{author}</h1>\n<p class=\"meta\"><a href=\"../accounts/{account}.html\">@{author}</a> · \
+             {date} · <a href=\"{url}\">Original post</a></p>\n<blockquote>{text}</blockquote>\n\
+             <h2>Summary</h2>\n<p>{summary}</p>\n<h2>Tags</h2>\n",
+            author = escape(&source.author),
+            account = slug(&source.author),
+            date = source.created_at.date(),
+            url = escape(&source.url),
+            text = highlight(&source.text, &evidence),
+            summary = escape(&classification.summary),
+        );
+        if classification.tags.is_empty() {
+            body.push_str("<p>No tags matched.</p>\n");
+        } else {
+            body.push_str("<ul>\n");
+            for tag in &classification.tags {
+                body.push_str(&format!(
+                    "<li><a href=\"../tags/{}.html\">{}</a> ({:.0}%)<p>{}</p>",
+                    slug(&tag.id),
+                    escape(self.tag_title(&tag.id)),
+                    tag.confidence * 100.0,
+                    escape(&tag.rationale),
+                ));
+                if !tag.evidence.is_empty() {
+                    body.push_str("<ul>");
+                    for quote in &tag.evidence {
+                        body.push_str(&format!("<li>“{}”</li>", escape(quote)));
+                    }
+                    body.push_str("</ul>");
+                }
+                body.push_str("</li>\n");
+            }
+            body.push_str("</ul>\n");
+        }
+        body.push_str(&format!(
+            "<p class=\"meta\"><a href=\"../api/posts/{}.json\">JSON</a></p>\n",
+            post.slug
+        ));
+
+        self.page(&format!("Post by @{}", source.author), "../", "", &body)
+    }
+
+    fn tag_page(&self, tag_id: &str, tagged: &[(&SitePost<'_>, &TagMatch)]) -> String {
+        let tag_slug = slug(tag_id);
+        let title = self.tag_title(tag_id);
+        let mut body = format!(
+            "<h1>{}</h1>\n<p class=\"meta\">{} posts · <a href=\"{tag_slug}.atom\">Atom feed</a> · \
+             <a href=\"../api/tags/{tag_slug}.json\">JSON</a></p>\n\
+             <h2>Over time</h2>\n<table>\n<tr><th>Month</th><th>Posts</th></tr>\n",
+            escape(title),
+            tagged.len(),
+        );
+        for (month, count) in monthly_counts(tagged.iter().map(|(post, _)| *post)) {
+            body.push_str(&format!("<tr><td>{}</td><td>{}</td></tr>\n", month, count));
+        }
+        body.push_str("</table>\n<h2>Posts</h2>\n<ul>\n");
+        for (post, tag) in tagged {
+            body.push_str(&format!(
+                "<li>{} ({:.0}%)</li>\n",
+                post_link(post, "../"),
+                tag.confidence * 100.0
+            ));
+        }
+        body.push_str("</ul>\n");
+
+        let head = format!(
+            "<link rel=\"alternate\" type=\"application/atom+xml\" href=\"{}.atom\">\n",
+            tag_slug
+        );
+        self.page(title, "../", &head, &body)
+    }
+
+    fn account_page(&self, account: &str, posts: &[&SitePost<'_>]) -> String {
+        let mut body = format!(
+            "<h1>@{}</h1>\n<p class=\"meta\">{} posts · <a href=\"../api/accounts/{}.json\">JSON</a></p>\n<ul>\n",
+            escape(account),
+            posts.len(),
+            slug(account),
+        );
+        for post in posts {
+            let tags: Vec<String> = post
+                .record
+                .classification
+                .tags
+                .iter()
+                .map(|t| {
+                    format!(
+                        "<a href=\"../tags/{}.html\">{}</a>",
+                        slug(&t.id),
+                        escape(self.tag_title(&t.id))
+                    )
+                })
+                .collect();
+            body.push_str(&format!(
+                "<li>{} {}</li>\n",
+                post_link(post, "../"),
+                tags.join(", ")
+            ));
+        }
+        body.push_str("</ul>\n");
+
+        self.page(&format!("@{}", account), "../", "", &body)
+    }
+
+    fn index_page(
+        &self,
+        posts: &[SitePost<'_>],
+        tags: &BTreeMap<&str, Vec<(&SitePost<'_>, &TagMatch)>>,
+        accounts: &BTreeMap<&str, Vec<&SitePost<'_>>>,
+    ) -> String {
+        let mut body = format!(
+            "<h1>{}</h1>\n<p class=\"meta\">{} classified posts · <a href=\"api/index.json\">JSON</a></p>\n\
+             <h2>Tags</h2>\n<table>\n<tr><th>Tag</th><th>Posts</th></tr>\n",
+            escape(&self.title),
+            posts.len(),
+        );
+        for (tag_id, tagged) in tags {
+            body.push_str(&format!(
+                "<tr><td><a href=\"tags/{}.html\">{}</a></td><td>{}</td></tr>\n",
+                slug(tag_id),
+                escape(self.tag_title(tag_id)),
+                tagged.len()
+            ));
+        }
+        body.push_str("</table>\n<h2>Accounts</h2>\n<ul>\n");
+        for (account, account_posts) in accounts {
+            body.push_str(&format!(
+                "<li><a href=\"accounts/{}.html\">@{}</a> ({})</li>\n",
+                slug(account),
+                escape(account),
+                account_posts.len()
+            ));
+        }
+        body.push_str("</ul>\n<h2>Posts</h2>\n<ul>\n");
+        for post in posts {
+            body.push_str(&format!("<li>{}</li>\n", post_link(post, "")));
+        }
+        body.push_str("</ul>\n");
+
+        self.page("Classified posts", "", "", &body)
+    }
+
+    fn tag_feed(
+        &self,
+        tag_id: &str,
+        tagged: &[(&SitePost<'_>, &TagMatch)],
+    ) -> Result<String, SiteError> {
+        let tag_slug = slug(tag_id);
+        let base = self.base_url.as_deref().unwrap_or("");
+        let updated = tagged
+            .iter()
+            .map(|(post, _)| post.record.classified_at)
+            .max()
+            .unwrap_or(OffsetDateTime::UNIX_EPOCH);
+
+        let mut feed = Feed {
+            title: Text::plain(format!("{}: {}", self.title, self.tag_title(tag_id))),
+            id: match &self.base_url {
+                Some(base) => format!("{}tags/{}.atom", base, tag_slug),
+                None => format!("urn:news-tagger:tag:{}", tag_id),
+            },
+            updated: feed_time(updated)?,
+            ..Default::default()
+        };
+        feed.links
+            .push(link(format!("{}tags/{}.html", base, tag_slug), "alternate"));
+        feed.links
+            .push(link(format!("{}tags/{}.atom", base, tag_slug), "self"));
+
+        for (post, tag) in tagged {
+            let source = &post.record.source_post;
+            let mut entry = Entry {
+                title: Text::plain(format!("@{}: {}", source.author, excerpt(&source.text, 80))),
+                id: format!("urn:news-tagger:post:{}", post.record.record_id),
+                updated: feed_time(post.record.classified_at)?,
+                published: Some(feed_time(source.created_at)?),
+                summary: Some(Text::plain(format!(
+                    "{} ({:.0}%): {}",
+                    self.tag_title(tag_id),
+                    tag.confidence * 100.0,
+                    tag.rationale
+                ))),
+                ..Default::default()
+            };
+            entry.authors.push(Person {
+                name: source.author.clone(),
+                ..Default::default()
+            });
+            entry.links.push(link(
+                format!("{}posts/{}.html", base, post.slug),
+                "alternate",
+            ));
+            entry.links.push(link(source.url.clone(), "related"));
+            feed.entries.push(entry);
+        }
+
+        let bytes = feed
+            .write_to(Vec::new())
+            .map_err(|e| SiteError::Feed(e.to_string()))?;
+        String::from_utf8(bytes).map_err(|e| SiteError::Feed(e.to_string()))
+    }
+}
+
+/// Remove files under `dir` that the current build did not generate
+fn remove_stale(
+    dir: &Path,
+    generated: &HashSet<PathBuf>,
+    report: &mut SiteReport,
+) -> Result<(), SiteError> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(e.into()),
+    };
+    for entry in entries {
+        let path = entry?.path();
+        if path.is_dir() {
+            remove_stale(&path, generated, report)?;
+        } else if !generated.contains(&path) {
+            fs::remove_file(&path)?;
+            report.removed += 1;
+        }
+    }
+    Ok(())
+}
+
+/// File-name-safe identifier: the value itself when already safe, otherwise
+/// a sanitized prefix plus a short hash so distinct values never collide
+fn slug(value: &str) -> String {
+    let safe: String = value
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    if safe == value && !value.is_empty() && value.len() <= 64 {
+        return safe;
+    }
+
+    let hash = format!("{:x}", Sha256::digest(value.as_bytes()));
+    let prefix: String = safe.trim_matches('-').chars().take(40).collect();
+    if prefix.is_empty() {
+        hash[..12].to_string()
+    } else {
+        format!("{}-{}", prefix, &hash[..12])
+    }
+}
+
+/// HTML-escape `text`, wrapping every occurrence of an evidence quote in `<mark>`
+fn highlight(text: &str, evidence: &[&str]) -> String {
+    let mut ranges: Vec<(usize, usize)> = evidence
+        .iter()
+        .filter(|quote| !quote.trim().is_empty())
+        .flat_map(|quote| {
+            text.match_indices(*quote)
+                .map(move |(start, _)| (start, start + quote.len()))
+        })
+        .collect();
+    ranges.sort_unstable();
+
+    let mut merged: Vec<(usize, usize)> = Vec::new();
+    for (start, end) in ranges {
+        match merged.last_mut() {
+            Some(last) if start <= last.1 => last.1 = last.1.max(end),
+            _ => merged.push((start, end)),
+        }
+    }
+
+    let mut out = String::with_capacity(text.len());
+    let mut cursor = 0;
+    for (start, end) in merged {
+        out.push_str(&escape(&text[cursor..start]));
+        out.push_str("<mark>");
+        out.push_str(&escape(&text[start..end]));
+        out.push_str("</mark>");
+        cursor = end;
+    }
+    out.push_str(&escape(&text[cursor..]));
+    out
+}
+
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+fn excerpt(text: &str, max_chars: usize) -> String {
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if text.chars().count() <= max_chars {
+        return text;
+    }
+    let cut: String = text.chars().take(max_chars - 1).collect();
+    format!("{}…", cut.trim_end())
+}
+
+/// Link to a post page with its author and date, relative to `root`
+fn post_link(post: &SitePost<'_>, root: &str) -> String {
+    let source = &post.record.source_post;
+    format!(
+        "<a href=\"{root}posts/{}.html\">{}</a> <span class=\"meta\">@{} · {}</span>",
+        post.slug,
+        escape(&excerpt(&source.text, 100)),
+        escape(&source.author),
+        source.created_at.date(),
+    )
+}
+
+fn post_summary(post: &SitePost<'_>, confidence: Option<f64>) -> Value {
+    let source = &post.record.source_post;
+    let mut summary = json!({
+        "slug": post.slug,
+        "id": source.id,
+        "author": source.author,
+        "url": source.url,
+        "created_at": source.created_at.format(&Rfc3339).unwrap_or_default(),
+        "tags": post.record.classification.tags.iter().map(|t| &t.id).collect::<Vec<_>>(),
+    });
+    if let Some(confidence) = confidence {
+        summary["confidence"] = json!(confidence);
+    }
+    summary
+}
+
+/// Post counts per `YYYY-MM` of the source post, oldest month first
+fn monthly_counts<'a, 'b: 'a>(
+    posts: impl Iterator<Item = &'a SitePost<'b>>,
+) -> Vec<(String, usize)> {
+    let mut counts: BTreeMap<String, usize> = BTreeMap::new();
+    for post in posts {
+        let created_at = post.record.source_post.created_at;
+        let month = format!(
+            "{:04}-{:02}",
+            created_at.year(),
+            u8::from(created_at.month())
+        );
+        *counts.entry(month).or_default() += 1;
+    }
+    counts.into_iter().collect()
+}
+
+fn link(href: String, rel: &str) -> Link {
+    Link {
+        href,
+        rel: rel.to_string(),
+        ..Default::default()
+    }
+}
+
+fn feed_time(value: OffsetDateTime) -> Result<FixedDateTime, SiteError> {
+    let formatted = value
+        .format(&Rfc3339)
+        .map_err(|e| SiteError::Feed(e.to_string()))?;
+    FixedDateTime::parse_from_rfc3339(&formatted).map_err(|e| SiteError::Feed(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use news_tagger_domain::{ClassifyOutput, SourcePost};
+    use tempfile::TempDir;
+    use uuid::Uuid;
+
+    fn record(id: &str, author: &str, text: &str, tags: Vec<TagMatch>) -> ClassificationRecord {
+        let created_at = OffsetDateTime::from_unix_timestamp(1_700_000_000).unwrap();
+        ClassificationRecord {
+            record_id: Uuid::new_v4(),
+            source_post: SourcePost {
+                id: id.to_string(),
+                text: text.to_string(),
+                author: author.to_string(),
+                url: format!("https://example.com/{}", id),
+                created_at,
+                is_repost: false,
+                is_reply: false,
+                reply_to_id: None,
+                context: vec![],
+            },
+            classification: ClassifyOutput::new("A summary".to_string(), tags),
+            taxonomy_hash: "hash".to_string(),
+            classified_at: created_at,
+        }
+    }
+
+    fn tag(id: &str, evidence: &str) -> TagMatch {
+        TagMatch {
+            id: id.to_string(),
+            confidence: 0.8,
+            rationale: "Because".to_string(),
+            evidence: vec![evidence.to_string()],
+        }
+    }
+
+    #[test]
+    fn test_highlight_escapes_and_merges_evidence() {
+        let text = "Prices <up> again & again";
+        let out = highlight(text, &["<up> again", "again & again", ""]);
+        assert_eq!(out, "Prices <mark>&lt;up&gt; again &amp; again</mark>");
+        assert_eq!(highlight("plain", &["missing"]), "plain");
+    }
+
+    #[test]
+    fn test_slug_keeps_safe_ids_and_hashes_others() {
+        assert_eq!(slug("12345"), "12345");
+        let uri = slug("at://did:plc:abc/app.bsky.feed.post/3k");
+        assert!(uri.starts_with("at---did-plc-abc-app-bsky-feed-post-3k-"));
+        assert_ne!(slug("a/b"), slug("a:b"));
+    }
+
+    #[test]
+    fn test_build_writes_pages_feeds_and_api() {
+        let dir = TempDir::new().unwrap();
+        let records = vec![
+            record(
+                "1",
+                "alice",
+                "The sky is falling",
+                vec![tag("doom", "sky is falling")],
+            ),
+            record("2", "bob", "All fine", vec![]),
+        ];
+        let definitions = vec![TagDefinition {
+            id: "doom".to_string(),
+            title: "Doom & Gloom".to_string(),
+            aliases: vec![],
+            short: None,
+            content: String::new(),
+            file_path: "doom.md".to_string(),
+        }];
+
+        let builder = SiteBuilder::new(dir.path())
+            .with_base_url("https://example.org/site")
+            .with_definitions(&definitions);
+        let report = builder.build(&records).unwrap();
+        assert_eq!(report.written, 13);
+
+        let post = fs::read_to_string(dir.path().join("posts/1.html")).unwrap();
+        assert!(post.contains("The <mark>sky is falling</mark>"));
+        assert!(post.contains("Doom &amp; Gloom"));
+
+        let tag_page = fs::read_to_string(dir.path().join("tags/doom.html")).unwrap();
+        assert!(tag_page.contains("<td>2023-11</td><td>1</td>"));
+
+        let feed = Feed::read_from(
+            fs::read(dir.path().join("tags/doom.atom"))
+                .unwrap()
+                .as_slice(),
+        )
+        .unwrap();
+        assert_eq!(feed.entries.len(), 1);
+        assert_eq!(
+            feed.entries[0].links[0].href,
+            "https://example.org/site/posts/1.html"
+        );
+
+        let api: Value =
+            serde_json::from_slice(&fs::read(dir.path().join("api/index.json")).unwrap()).unwrap();
+        assert_eq!(api["tags"][0]["count"], 1);
+        assert_eq!(api["accounts"].as_array().unwrap().len(), 2);
+        assert!(dir.path().join("accounts/bob.html").exists());
+    }
+
+    #[test]
+    fn test_rebuild_only_rewrites_changed_files_and_removes_stale_ones() {
+        let dir = TempDir::new().unwrap();
+        let builder = SiteBuilder::new(dir.path());
+        let first = record("1", "alice", "The sky is falling", vec![tag("doom", "sky")]);
+        builder.build(std::slice::from_ref(&first)).unwrap();
+
+        let again = builder.build(std::slice::from_ref(&first)).unwrap();
+        assert_eq!(again.written, 0);
+        assert_eq!(again.removed, 0);
+
+        // A later classification of the same post drops the tag
+        let mut reclassified = record("1", "alice", "The sky is falling", vec![]);
+        reclassified.classified_at = first.classified_at + time::Duration::hours(1);
+        let report = builder.build(&[first, reclassified]).unwrap();
+        assert!(report.written > 0);
+        assert_eq!(report.removed, 3);
+        assert!(!dir.path().join("tags/doom.html").exists());
+    }
+}
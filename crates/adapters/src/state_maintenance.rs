@@ -0,0 +1,177 @@
+//! Retention and housekeeping for the SQL state stores
+
+use news_tagger_domain::{ClassificationRecord, StateError};
+use std::collections::BTreeMap;
+use time::OffsetDateTime;
+
+/// Tables reported by `stats`, in schema order
+pub(crate) const TABLES: &[&str] = &[
+    "account_state",
+    "published_records",
+    "published_targets",
+    "classifications",
+    "publish_retries",
+    "resolved_accounts",
+    "rate_limit_deferrals",
+    "feed_cursors",
+    "relay_statuses",
+    "post_claims",
+    "account_leases",
+    "tag_counts",
+    "post_retries",
+    "schema_migrations",
+];
+
+/// Tables pruned with a published record and the column naming it,
+/// dependent rows first
+pub(crate) const PRUNED_TABLES: [(&str, &str); 4] = [
+    ("published_targets", "record_id"),
+    ("classifications", "record_id"),
+    ("publish_retries", "record_id"),
+    ("published_records", "id"),
+];
+
+/// Rows removed by a prune
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PruneReport {
+    pub published: u64,
+    pub targets: u64,
+    pub classifications: u64,
+    pub retries: u64,
+    /// Post retries last updated before the cutoff
+    pub post_retries: u64,
+    /// Expired post claims
+    pub claims: u64,
+    /// Rate-limit deferrals that have ended
+    pub deferrals: u64,
+}
+
+/// How often a tag was applied in a month, kept when its classifications
+/// are pruned
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TagCount {
+    pub tag: String,
+    /// `YYYY-MM` of the classifications
+    pub month: String,
+    pub count: u64,
+}
+
+/// Size of a state table
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TableStats {
+    pub table: String,
+    pub rows: u64,
+    /// Bytes on disk, including indexes, when the database reports them
+    pub bytes: Option<u64>,
+}
+
+/// Table sizes and the size of the whole database
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StateStats {
+    pub tables: Vec<TableStats>,
+    pub database_bytes: u64,
+}
+
+/// Tag applications per tag and month
+pub(crate) fn count_tags<'a>(
+    records: impl IntoIterator<Item = &'a ClassificationRecord>,
+) -> Vec<TagCount> {
+    let mut counts: BTreeMap<(String, String), u64> = BTreeMap::new();
+    for record in records {
+        let at = record.classified_at;
+        let month = format!("{:04}-{:02}", at.year(), u8::from(at.month()));
+        for tag in &record.classification.tags {
+            *counts.entry((tag.id.clone(), month.clone())).or_default() += 1;
+        }
+    }
+
+    counts
+        .into_iter()
+        .map(|((tag, month), count)| TagCount { tag, month, count })
+        .collect()
+}
+
+/// Keys of `(key, RFC 3339 time)` rows whose time is before `before`
+pub(crate) fn keys_before(
+    rows: Vec<(String, String)>,
+    before: OffsetDateTime,
+) -> Result<Vec<String>, StateError> {
+    let mut keys = Vec::new();
+    for (key, at) in rows {
+        let at = OffsetDateTime::parse(&at, &time::format_description::well_known::Rfc3339)
+            .map_err(|e| StateError::Serialization(e.to_string()))?;
+        if at < before {
+            keys.push(key);
+        }
+    }
+    Ok(keys)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use news_tagger_domain::{ClassifyOutput, SourcePost, TagMatch};
+    use time::{Date, Month};
+    use uuid::Uuid;
+
+    fn at(month: Month, day: u8, hour: u8) -> OffsetDateTime {
+        Date::from_calendar_date(2026, month, day)
+            .unwrap()
+            .with_hms(hour, 0, 0)
+            .unwrap()
+            .assume_utc()
+    }
+
+    fn record(tags: &[&str], classified_at: OffsetDateTime) -> ClassificationRecord {
+        ClassificationRecord {
+            record_id: Uuid::new_v4(),
+            source_post: SourcePost {
+                id: "1".to_string(),
+                text: "Some text".to_string(),
+                author: "user".to_string(),
+                url: "https://example.com/1".to_string(),
+                created_at: classified_at,
+                is_repost: false,
+                is_reply: false,
+                reply_to_id: None,
+                context: vec![],
+            },
+            classification: ClassifyOutput::new(
+                "Summary".to_string(),
+                tags.iter()
+                    .map(|id| TagMatch {
+                        id: id.to_string(),
+                        confidence: 0.9,
+                        rationale: String::new(),
+                        evidence: vec![],
+                    })
+                    .collect(),
+            ),
+            taxonomy_hash: "hash".to_string(),
+            classified_at,
+        }
+    }
+
+    #[test]
+    fn test_count_tags_by_month() {
+        let records = [
+            record(&["a", "b"], at(Month::January, 31, 23)),
+            record(&["a"], at(Month::January, 2, 0)),
+            record(&["a"], at(Month::February, 1, 0)),
+        ];
+
+        let counts = count_tags(&records);
+        let summary: Vec<_> = counts
+            .iter()
+            .map(|c| (c.tag.as_str(), c.month.as_str(), c.count))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("a", "2026-01", 2),
+                ("a", "2026-02", 1),
+                ("b", "2026-01", 1)
+            ]
+        );
+    }
+}
@@ -1,14 +1,30 @@
 //! In-memory state store for testing and offline mode
 
 use async_trait::async_trait;
-use news_tagger_domain::{AccountState, PublishedRecord, StateError, StateStore};
+use news_tagger_domain::{
+    AccountState, ClassificationRecord, FeedCursor, PostRetry, PublishRetry, PublishedRecord,
+    PublishedTarget, RateLimitDeferral, RelayStatus, ResolvedAccount, StateError, StateStore,
+};
 use std::collections::HashMap;
 use std::sync::RwLock;
+use std::time::Duration;
+use time::OffsetDateTime;
+use uuid::Uuid;
 
 /// In-memory state store implementation
 pub struct InMemoryStateStore {
     accounts: RwLock<HashMap<String, AccountState>>,
     published: RwLock<HashMap<String, PublishedRecord>>,
+    targets: RwLock<HashMap<(Uuid, String), PublishedTarget>>,
+    retries: RwLock<HashMap<(Uuid, String), PublishRetry>>,
+    post_retries: RwLock<HashMap<String, PostRetry>>,
+    classifications: RwLock<HashMap<Uuid, ClassificationRecord>>,
+    resolved_accounts: RwLock<HashMap<String, ResolvedAccount>>,
+    deferrals: RwLock<HashMap<String, RateLimitDeferral>>,
+    feed_cursors: RwLock<HashMap<String, FeedCursor>>,
+    relay_statuses: RwLock<HashMap<String, RelayStatus>>,
+    /// Post claims and account leases: key -> (worker, expiry)
+    leases: RwLock<HashMap<String, (String, OffsetDateTime)>>,
 }
 
 impl InMemoryStateStore {
@@ -16,12 +32,42 @@ impl InMemoryStateStore {
         Self {
             accounts: RwLock::new(HashMap::new()),
             published: RwLock::new(HashMap::new()),
+            targets: RwLock::new(HashMap::new()),
+            retries: RwLock::new(HashMap::new()),
+            post_retries: RwLock::new(HashMap::new()),
+            classifications: RwLock::new(HashMap::new()),
+            resolved_accounts: RwLock::new(HashMap::new()),
+            deferrals: RwLock::new(HashMap::new()),
+            feed_cursors: RwLock::new(HashMap::new()),
+            relay_statuses: RwLock::new(HashMap::new()),
+            leases: RwLock::new(HashMap::new()),
         }
     }
 
     fn make_published_key(source_post_id: &str, taxonomy_hash: &str) -> String {
         format!("{}:{}", source_post_id, taxonomy_hash)
     }
+
+    fn take_lease(
+        &self,
+        key: String,
+        worker_id: &str,
+        lease: Duration,
+    ) -> Result<bool, StateError> {
+        let now = OffsetDateTime::now_utc();
+        let mut leases = self
+            .leases
+            .write()
+            .map_err(|e| StateError::Database(e.to_string()))?;
+        if let Some((holder, expires_at)) = leases.get(&key)
+            && holder != worker_id
+            && *expires_at > now
+        {
+            return Ok(false);
+        }
+        leases.insert(key, (worker_id.to_string(), now + lease));
+        Ok(true)
+    }
 }
 
 impl Default for InMemoryStateStore {
@@ -49,6 +95,16 @@ impl StateStore for InMemoryStateStore {
         Ok(())
     }
 
+    async fn list_account_states(&self) -> Result<Vec<AccountState>, StateError> {
+        let accounts = self
+            .accounts
+            .read()
+            .map_err(|e| StateError::Database(e.to_string()))?;
+        let mut list: Vec<AccountState> = accounts.values().cloned().collect();
+        list.sort_by(|a, b| a.account.cmp(&b.account));
+        Ok(list)
+    }
+
     async fn is_processed(
         &self,
         source_post_id: &str,
@@ -68,7 +124,7 @@ impl StateStore for InMemoryStateStore {
             .published
             .write()
             .map_err(|e| StateError::Database(e.to_string()))?;
-        published.insert(key, record.clone());
+        published.entry(key).or_insert_with(|| record.clone());
         Ok(())
     }
 
@@ -84,6 +140,283 @@ impl StateStore for InMemoryStateStore {
             .map_err(|e| StateError::Database(e.to_string()))?;
         Ok(published.get(&key).cloned())
     }
+
+    async fn list_published(&self) -> Result<Vec<PublishedRecord>, StateError> {
+        let published = self
+            .published
+            .read()
+            .map_err(|e| StateError::Database(e.to_string()))?;
+        let mut list: Vec<PublishedRecord> = published.values().cloned().collect();
+        list.sort_by_key(|r| r.published_at);
+        Ok(list)
+    }
+
+    async fn record_target(&self, target: &PublishedTarget) -> Result<(), StateError> {
+        let mut targets = self
+            .targets
+            .write()
+            .map_err(|e| StateError::Database(e.to_string()))?;
+        targets.insert((target.record_id, target.platform.clone()), target.clone());
+        Ok(())
+    }
+
+    async fn list_targets(&self, record_id: Uuid) -> Result<Vec<PublishedTarget>, StateError> {
+        let targets = self
+            .targets
+            .read()
+            .map_err(|e| StateError::Database(e.to_string()))?;
+        let mut list: Vec<PublishedTarget> = targets
+            .values()
+            .filter(|t| t.record_id == record_id)
+            .cloned()
+            .collect();
+        list.sort_by(|a, b| a.platform.cmp(&b.platform));
+        Ok(list)
+    }
+
+    async fn record_classification(&self, record: &ClassificationRecord) -> Result<(), StateError> {
+        let mut classifications = self
+            .classifications
+            .write()
+            .map_err(|e| StateError::Database(e.to_string()))?;
+        classifications.insert(record.record_id, record.clone());
+        Ok(())
+    }
+
+    async fn list_classifications(&self) -> Result<Vec<ClassificationRecord>, StateError> {
+        let classifications = self
+            .classifications
+            .read()
+            .map_err(|e| StateError::Database(e.to_string()))?;
+        let mut list: Vec<ClassificationRecord> = classifications.values().cloned().collect();
+        list.sort_by_key(|r| r.classified_at);
+        Ok(list)
+    }
+
+    async fn set_retry(&self, retry: &PublishRetry) -> Result<(), StateError> {
+        let mut retries = self
+            .retries
+            .write()
+            .map_err(|e| StateError::Database(e.to_string()))?;
+        retries.insert((retry.record_id, retry.platform.clone()), retry.clone());
+        Ok(())
+    }
+
+    async fn list_retries(&self) -> Result<Vec<PublishRetry>, StateError> {
+        let retries = self
+            .retries
+            .read()
+            .map_err(|e| StateError::Database(e.to_string()))?;
+        let mut list: Vec<PublishRetry> = retries.values().cloned().collect();
+        list.sort_by(|a, b| {
+            a.next_attempt_at
+                .cmp(&b.next_attempt_at)
+                .then_with(|| a.platform.cmp(&b.platform))
+        });
+        Ok(list)
+    }
+
+    async fn list_retries_for_record(
+        &self,
+        record_id: Uuid,
+    ) -> Result<Vec<PublishRetry>, StateError> {
+        let retries = self
+            .retries
+            .read()
+            .map_err(|e| StateError::Database(e.to_string()))?;
+        let mut list: Vec<PublishRetry> = retries
+            .values()
+            .filter(|r| r.record_id == record_id)
+            .cloned()
+            .collect();
+        list.sort_by(|a, b| a.platform.cmp(&b.platform));
+        Ok(list)
+    }
+
+    async fn delete_retry(&self, record_id: Uuid, platform: &str) -> Result<(), StateError> {
+        let mut retries = self
+            .retries
+            .write()
+            .map_err(|e| StateError::Database(e.to_string()))?;
+        retries.remove(&(record_id, platform.to_string()));
+        Ok(())
+    }
+
+    async fn set_post_retry(&self, retry: &PostRetry) -> Result<(), StateError> {
+        let mut retries = self
+            .post_retries
+            .write()
+            .map_err(|e| StateError::Database(e.to_string()))?;
+        retries.insert(retry.source_post_id.clone(), retry.clone());
+        Ok(())
+    }
+
+    async fn list_post_retries(&self, account: &str) -> Result<Vec<PostRetry>, StateError> {
+        let retries = self
+            .post_retries
+            .read()
+            .map_err(|e| StateError::Database(e.to_string()))?;
+        let mut list: Vec<PostRetry> = retries
+            .values()
+            .filter(|r| r.account == account)
+            .cloned()
+            .collect();
+        list.sort_by(|a, b| a.source_post_id.cmp(&b.source_post_id));
+        Ok(list)
+    }
+
+    async fn delete_post_retry(&self, source_post_id: &str) -> Result<(), StateError> {
+        let mut retries = self
+            .post_retries
+            .write()
+            .map_err(|e| StateError::Database(e.to_string()))?;
+        retries.remove(source_post_id);
+        Ok(())
+    }
+
+    async fn get_resolved_account(
+        &self,
+        account: &str,
+    ) -> Result<Option<ResolvedAccount>, StateError> {
+        let resolved = self
+            .resolved_accounts
+            .read()
+            .map_err(|e| StateError::Database(e.to_string()))?;
+        Ok(resolved.get(&account.to_ascii_lowercase()).cloned())
+    }
+
+    async fn set_resolved_account(&self, resolved: &ResolvedAccount) -> Result<(), StateError> {
+        let mut accounts = self
+            .resolved_accounts
+            .write()
+            .map_err(|e| StateError::Database(e.to_string()))?;
+        let key = resolved.account.to_ascii_lowercase();
+        let mut resolved = resolved.clone();
+        if resolved.user_id.is_none() {
+            resolved.user_id = accounts.get(&key).and_then(|a| a.user_id.clone());
+        }
+        accounts.insert(key, resolved);
+        Ok(())
+    }
+
+    async fn list_resolved_accounts(&self) -> Result<Vec<ResolvedAccount>, StateError> {
+        let resolved = self
+            .resolved_accounts
+            .read()
+            .map_err(|e| StateError::Database(e.to_string()))?;
+        let mut accounts: Vec<_> = resolved.values().cloned().collect();
+        accounts.sort_by(|a, b| a.account.cmp(&b.account));
+        Ok(accounts)
+    }
+
+    async fn list_deferrals(&self) -> Result<Vec<RateLimitDeferral>, StateError> {
+        let deferrals = self
+            .deferrals
+            .read()
+            .map_err(|e| StateError::Database(e.to_string()))?;
+        let mut deferrals: Vec<_> = deferrals.values().cloned().collect();
+        deferrals.sort_by(|a, b| a.scope.cmp(&b.scope));
+        Ok(deferrals)
+    }
+
+    async fn set_deferral(&self, deferral: &RateLimitDeferral) -> Result<(), StateError> {
+        let mut deferrals = self
+            .deferrals
+            .write()
+            .map_err(|e| StateError::Database(e.to_string()))?;
+        deferrals.insert(deferral.scope.clone(), deferral.clone());
+        Ok(())
+    }
+
+    async fn get_feed_cursor(&self, feed: &str) -> Result<Option<FeedCursor>, StateError> {
+        let cursors = self
+            .feed_cursors
+            .read()
+            .map_err(|e| StateError::Database(e.to_string()))?;
+        Ok(cursors.get(feed).cloned())
+    }
+
+    async fn set_feed_cursor(&self, cursor: &FeedCursor) -> Result<(), StateError> {
+        let mut cursors = self
+            .feed_cursors
+            .write()
+            .map_err(|e| StateError::Database(e.to_string()))?;
+        cursors.insert(cursor.feed.clone(), cursor.clone());
+        Ok(())
+    }
+
+    async fn get_relay_status(&self, relay: &str) -> Result<Option<RelayStatus>, StateError> {
+        let statuses = self
+            .relay_statuses
+            .read()
+            .map_err(|e| StateError::Database(e.to_string()))?;
+        Ok(statuses.get(relay).cloned())
+    }
+
+    async fn set_relay_status(&self, status: &RelayStatus) -> Result<(), StateError> {
+        let mut statuses = self
+            .relay_statuses
+            .write()
+            .map_err(|e| StateError::Database(e.to_string()))?;
+        statuses.insert(status.relay.clone(), status.clone());
+        Ok(())
+    }
+
+    async fn list_relay_statuses(&self) -> Result<Vec<RelayStatus>, StateError> {
+        let statuses = self
+            .relay_statuses
+            .read()
+            .map_err(|e| StateError::Database(e.to_string()))?;
+        let mut list: Vec<RelayStatus> = statuses.values().cloned().collect();
+        list.sort_by(|a, b| a.relay.cmp(&b.relay));
+        Ok(list)
+    }
+
+    async fn claim_post(
+        &self,
+        source_post_id: &str,
+        taxonomy_hash: &str,
+        worker_id: &str,
+        lease: Duration,
+    ) -> Result<bool, StateError> {
+        let key = format!(
+            "post:{}",
+            Self::make_published_key(source_post_id, taxonomy_hash)
+        );
+        self.take_lease(key, worker_id, lease)
+    }
+
+    async fn release_post(
+        &self,
+        source_post_id: &str,
+        taxonomy_hash: &str,
+        worker_id: &str,
+    ) -> Result<(), StateError> {
+        let key = format!(
+            "post:{}",
+            Self::make_published_key(source_post_id, taxonomy_hash)
+        );
+        let mut leases = self
+            .leases
+            .write()
+            .map_err(|e| StateError::Database(e.to_string()))?;
+        if leases
+            .get(&key)
+            .is_some_and(|(holder, _)| holder == worker_id)
+        {
+            leases.remove(&key);
+        }
+        Ok(())
+    }
+
+    async fn claim_account(
+        &self,
+        account: &str,
+        worker_id: &str,
+        lease: Duration,
+    ) -> Result<bool, StateError> {
+        self.take_lease(format!("account:{}", account), worker_id, lease)
+    }
 }
 
 #[cfg(test)]
@@ -124,19 +457,30 @@ mod tests {
             id: Uuid::new_v4(),
             source_post_id: "post123".to_string(),
             taxonomy_hash: "hash456".to_string(),
-            x_post_id: Some("xpost789".to_string()),
-            nostr_event_id: None,
             published_at: OffsetDateTime::now_utc(),
         };
 
         store.record_published(&record).await.unwrap();
+        store
+            .record_target(&PublishedTarget {
+                record_id: record.id,
+                platform: "x".to_string(),
+                target_id: "xpost789".to_string(),
+                url: None,
+                published_at: OffsetDateTime::now_utc(),
+            })
+            .await
+            .unwrap();
 
         let is_processed = store.is_processed("post123", "hash456").await.unwrap();
         assert!(is_processed);
 
         let retrieved = store.get_published("post123", "hash456").await.unwrap();
-        assert!(retrieved.is_some());
-        assert_eq!(retrieved.unwrap().x_post_id, Some("xpost789".to_string()));
+        assert_eq!(retrieved.unwrap().id, record.id);
+
+        let targets = store.list_targets(record.id).await.unwrap();
+        assert_eq!(targets.len(), 1);
+        assert_eq!(targets[0].target_id, "xpost789");
     }
 
     #[tokio::test]
@@ -147,8 +491,6 @@ mod tests {
             id: Uuid::new_v4(),
             source_post_id: "post123".to_string(),
             taxonomy_hash: "hash456".to_string(),
-            x_post_id: None,
-            nostr_event_id: None,
             published_at: OffsetDateTime::now_utc(),
         };
 
@@ -161,4 +503,38 @@ mod tests {
             .unwrap();
         assert!(!is_processed);
     }
+
+    #[tokio::test]
+    async fn test_post_claims_and_account_leases() {
+        let store = InMemoryStateStore::new();
+        let minute = Duration::from_secs(60);
+
+        assert!(store.claim_post("1", "hash", "a", minute).await.unwrap());
+        assert!(!store.claim_post("1", "hash", "b", minute).await.unwrap());
+        assert!(store.claim_post("1", "hash", "a", minute).await.unwrap());
+        assert!(store.claim_post("1", "other", "b", minute).await.unwrap());
+
+        store.release_post("1", "hash", "b").await.unwrap();
+        assert!(!store.claim_post("1", "hash", "b", minute).await.unwrap());
+        store.release_post("1", "hash", "a").await.unwrap();
+        assert!(
+            store
+                .claim_post("1", "hash", "b", Duration::ZERO)
+                .await
+                .unwrap()
+        );
+
+        // An expired lease goes to whoever asks next
+        assert!(store.claim_post("1", "hash", "a", minute).await.unwrap());
+
+        assert!(
+            store
+                .claim_account("alice", "a", Duration::ZERO)
+                .await
+                .unwrap()
+        );
+        assert!(store.claim_account("alice", "b", minute).await.unwrap());
+        assert!(!store.claim_account("alice", "a", minute).await.unwrap());
+        assert!(store.claim_account("alice", "b", minute).await.unwrap());
+    }
 }
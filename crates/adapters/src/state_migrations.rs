@@ -0,0 +1,253 @@
+//! Versioned schema migrations shared by the SQL state stores
+//!
+//! Each migration is applied once, inside a transaction, and recorded in
+//! `schema_migrations` with a checksum of its SQL. Applied migrations must
+//! never be edited; schema changes go into a new migration at the end.
+//!
+//! The SQL sticks to types and statements both SQLite and PostgreSQL accept.
+
+use news_tagger_domain::StateError;
+use sha2::{Digest, Sha256};
+
+/// A numbered schema change
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Migration {
+    pub version: i64,
+    pub name: &'static str,
+    pub sql: &'static str,
+}
+
+impl Migration {
+    /// SHA-256 of the migration SQL, recorded when it is applied
+    pub fn checksum(&self) -> String {
+        format!("{:x}", Sha256::digest(self.sql.as_bytes()))
+    }
+}
+
+/// A row of `schema_migrations`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct AppliedMigration {
+    pub version: i64,
+    pub name: String,
+    pub checksum: String,
+}
+
+pub(crate) const CREATE_SCHEMA_MIGRATIONS: &str = r#"
+CREATE TABLE IF NOT EXISTS schema_migrations (
+    version BIGINT PRIMARY KEY,
+    name TEXT NOT NULL,
+    checksum TEXT NOT NULL,
+    applied_at TEXT NOT NULL
+)
+"#;
+
+/// Every migration, in version order
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        name: "initial_schema",
+        // Uses IF NOT EXISTS so databases created before versioning adopt it as-is
+        sql: r#"
+CREATE TABLE IF NOT EXISTS account_state (
+    account TEXT PRIMARY KEY,
+    since_id TEXT,
+    updated_at TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS published_records (
+    id TEXT PRIMARY KEY,
+    source_post_id TEXT NOT NULL,
+    taxonomy_hash TEXT NOT NULL,
+    published_at TEXT NOT NULL,
+    UNIQUE(source_post_id, taxonomy_hash)
+);
+
+CREATE INDEX IF NOT EXISTS idx_published_lookup
+ON published_records(source_post_id, taxonomy_hash);
+
+CREATE TABLE IF NOT EXISTS published_targets (
+    record_id TEXT NOT NULL,
+    platform TEXT NOT NULL,
+    target_id TEXT NOT NULL,
+    url TEXT,
+    published_at TEXT NOT NULL,
+    PRIMARY KEY(record_id, platform)
+);
+
+CREATE TABLE IF NOT EXISTS classifications (
+    record_id TEXT PRIMARY KEY,
+    source_post TEXT NOT NULL,
+    classification TEXT NOT NULL,
+    taxonomy_hash TEXT NOT NULL,
+    classified_at TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS publish_retries (
+    record_id TEXT NOT NULL,
+    platform TEXT NOT NULL,
+    source_post TEXT NOT NULL,
+    classification TEXT NOT NULL,
+    taxonomy_hash TEXT NOT NULL,
+    attempts BIGINT NOT NULL,
+    status TEXT NOT NULL,
+    last_error TEXT NOT NULL,
+    next_attempt_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL,
+    PRIMARY KEY(record_id, platform)
+);
+
+CREATE TABLE IF NOT EXISTS resolved_accounts (
+    account TEXT PRIMARY KEY,
+    user_id TEXT,
+    status TEXT NOT NULL,
+    renamed_to TEXT,
+    resolved_at TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS rate_limit_deferrals (
+    scope TEXT PRIMARY KEY,
+    until TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS feed_cursors (
+    feed TEXT PRIMARY KEY,
+    etag TEXT,
+    last_modified TEXT,
+    newest_id TEXT,
+    updated_at TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS relay_statuses (
+    relay TEXT PRIMARY KEY,
+    successes BIGINT NOT NULL,
+    failures BIGINT NOT NULL,
+    last_success_at TEXT,
+    last_error TEXT,
+    updated_at TEXT NOT NULL
+);
+"#,
+    },
+    Migration {
+        version: 2,
+        name: "worker_leases",
+        // Expiry is Unix milliseconds so leases compare numerically in SQL
+        sql: r#"
+CREATE TABLE post_claims (
+    source_post_id TEXT NOT NULL,
+    taxonomy_hash TEXT NOT NULL,
+    worker_id TEXT NOT NULL,
+    expires_at BIGINT NOT NULL,
+    PRIMARY KEY(source_post_id, taxonomy_hash)
+);
+
+CREATE TABLE account_leases (
+    account TEXT PRIMARY KEY,
+    worker_id TEXT NOT NULL,
+    expires_at BIGINT NOT NULL
+);
+"#,
+    },
+    Migration {
+        version: 3,
+        name: "tag_counts",
+        sql: r#"
+CREATE TABLE tag_counts (
+    tag TEXT NOT NULL,
+    month TEXT NOT NULL,
+    count BIGINT NOT NULL,
+    PRIMARY KEY(tag, month)
+);
+"#,
+    },
+    Migration {
+        version: 4,
+        name: "post_retries",
+        sql: r#"
+CREATE TABLE post_retries (
+    source_post_id TEXT PRIMARY KEY,
+    account TEXT NOT NULL,
+    attempts BIGINT NOT NULL,
+    status TEXT NOT NULL,
+    last_error TEXT NOT NULL,
+    next_attempt_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL
+);
+
+CREATE INDEX idx_post_retries_account ON post_retries(account);
+"#,
+    },
+];
+
+/// Migrations still to apply given the recorded ones
+///
+/// Fails when the database has a migration this binary does not know (it was
+/// migrated by a newer version) or when an applied migration was edited.
+pub(crate) fn pending(applied: &[AppliedMigration]) -> Result<Vec<Migration>, StateError> {
+    let latest = MIGRATIONS.last().map_or(0, |m| m.version);
+
+    for row in applied {
+        let Some(known) = MIGRATIONS.iter().find(|m| m.version == row.version) else {
+            return Err(StateError::Migration(format!(
+                "database has migration {} ({}) but this binary only knows up to {}; upgrade news-tagger",
+                row.version, row.name, latest
+            )));
+        };
+        if known.checksum() != row.checksum {
+            return Err(StateError::Migration(format!(
+                "checksum mismatch for applied migration {} ({})",
+                row.version, row.name
+            )));
+        }
+    }
+
+    Ok(MIGRATIONS
+        .iter()
+        .filter(|m| !applied.iter().any(|row| row.version == m.version))
+        .copied()
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn applied(migration: &Migration) -> AppliedMigration {
+        AppliedMigration {
+            version: migration.version,
+            name: migration.name.to_string(),
+            checksum: migration.checksum(),
+        }
+    }
+
+    #[test]
+    fn test_versions_are_increasing() {
+        assert!(MIGRATIONS.windows(2).all(|w| w[0].version < w[1].version));
+        assert_eq!(MIGRATIONS[0].version, 1);
+    }
+
+    #[test]
+    fn test_pending_skips_applied_migrations() {
+        assert_eq!(pending(&[]).unwrap(), MIGRATIONS.to_vec());
+        let all: Vec<_> = MIGRATIONS.iter().map(applied).collect();
+        assert!(pending(&all).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_pending_refuses_newer_database() {
+        let newer = AppliedMigration {
+            version: 9999,
+            name: "from_the_future".to_string(),
+            checksum: String::new(),
+        };
+        let err = pending(&[newer]).unwrap_err();
+        assert!(err.to_string().contains("upgrade news-tagger"));
+    }
+
+    #[test]
+    fn test_pending_refuses_edited_migration() {
+        let mut edited = applied(&MIGRATIONS[0]);
+        edited.checksum = "0".repeat(64);
+        let err = pending(&[edited]).unwrap_err();
+        assert!(err.to_string().contains("checksum mismatch"));
+    }
+}
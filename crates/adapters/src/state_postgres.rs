@@ -0,0 +1,1617 @@
+//! PostgreSQL state store implementation
+//!
+//! Shares the schema and migration set with the SQLite store so several
+//! instances can run against one database.
+
+use async_trait::async_trait;
+use news_tagger_domain::{
+    AccountState, ClassificationRecord, FeedCursor, PostRetry, PublishRetry, PublishedRecord,
+    PublishedTarget, RateLimitDeferral, RelayStatus, ResolvedAccount, StateError, StateStore,
+};
+use sqlx::{
+    PgConnection, PgPool,
+    postgres::{PgConnectOptions, PgPoolOptions},
+};
+use std::collections::HashSet;
+use std::str::FromStr;
+use std::time::Duration;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::state_maintenance::{
+    PRUNED_TABLES, PruneReport, StateStats, TABLES, TableStats, TagCount, count_tags, keys_before,
+};
+use crate::state_migrations::{self, AppliedMigration, CREATE_SCHEMA_MIGRATIONS, Migration};
+use crate::state_rows::{
+    AccountStateRow, ClassificationRow, FeedCursorRow, PostRetryRow, PublishRetryRow,
+    PublishedRecordRow, PublishedTargetRow, RelayStatusRow, ResolvedAccountRow,
+    account_state_from_row, classification_from_row, lease_window, post_retry_from_row,
+    publish_retry_from_row, published_record_from_row, published_target_from_row,
+    relay_status_from_row, resolved_account_from_row, unix_millis,
+};
+
+/// Advisory lock key held while migrating, so concurrent instances apply
+/// each migration once
+const MIGRATION_LOCK_KEY: i64 = 0x6e65_7773_7461_6767;
+
+/// PostgreSQL-backed state store
+pub struct PostgresStateStore {
+    pool: PgPool,
+}
+
+impl PostgresStateStore {
+    /// Connect to a PostgreSQL database, applying pending migrations
+    pub async fn new(url: &str) -> Result<Self, StateError> {
+        let store = Self::open(url).await?;
+        store.migrate().await?;
+        Ok(store)
+    }
+
+    /// Connect without migrating, e.g. to list pending migrations
+    pub async fn open(url: &str) -> Result<Self, StateError> {
+        // Keeps the "already exists, skipping" notices of idempotent DDL out of the logs
+        let options = PgConnectOptions::from_str(url)
+            .map_err(|e| StateError::Database(e.to_string()))?
+            .options([("client_min_messages", "warning")]);
+        let pool = PgPoolOptions::new()
+            .max_connections(5)
+            .connect_with(options)
+            .await
+            .map_err(|e| StateError::Database(e.to_string()))?;
+
+        Ok(Self { pool })
+    }
+
+    /// Migrations not yet applied to this database
+    ///
+    /// Fails when the database was migrated by a newer binary or an applied
+    /// migration no longer matches its checksum.
+    pub async fn pending_migrations(&self) -> Result<Vec<Migration>, StateError> {
+        let mut conn = self
+            .pool
+            .acquire()
+            .await
+            .map_err(|e| StateError::Database(e.to_string()))?;
+        pending_migrations(&mut conn).await
+    }
+
+    /// Apply pending migrations, each in its own transaction, and return them
+    pub async fn migrate(&self) -> Result<Vec<Migration>, StateError> {
+        let mut conn = self
+            .pool
+            .acquire()
+            .await
+            .map_err(|e| StateError::Database(e.to_string()))?;
+
+        sqlx::query("SELECT pg_advisory_lock($1)")
+            .bind(MIGRATION_LOCK_KEY)
+            .execute(&mut *conn)
+            .await
+            .map_err(|e| StateError::Database(e.to_string()))?;
+        let result = apply_pending(&mut conn).await;
+        sqlx::query("SELECT pg_advisory_unlock($1)")
+            .bind(MIGRATION_LOCK_KEY)
+            .execute(&mut *conn)
+            .await
+            .map_err(|e| StateError::Database(e.to_string()))?;
+
+        result
+    }
+
+    /// Delete published records from before `before`, with their targets,
+    /// classifications and queued retries, along with post retries last
+    /// updated before `before` and expired claims and deferrals
+    ///
+    /// The tags of the deleted classifications are added to the per-tag
+    /// monthly counts in the same transaction, so totals survive the prune.
+    pub async fn prune(&self, before: OffsetDateTime) -> Result<PruneReport, StateError> {
+        let old: HashSet<Uuid> = self
+            .list_published()
+            .await?
+            .into_iter()
+            .filter(|r| r.published_at < before)
+            .map(|r| r.id)
+            .collect();
+        let classifications = self.list_classifications().await?;
+        let counts = count_tags(
+            classifications
+                .iter()
+                .filter(|c| old.contains(&c.record_id)),
+        );
+        let now = OffsetDateTime::now_utc();
+        let post_retries = keys_before(
+            sqlx::query_as("SELECT source_post_id, updated_at FROM post_retries")
+                .fetch_all(&self.pool)
+                .await
+                .map_err(|e| StateError::Database(e.to_string()))?,
+            before,
+        )?;
+        let deferrals = keys_before(
+            sqlx::query_as("SELECT scope, until FROM rate_limit_deferrals")
+                .fetch_all(&self.pool)
+                .await
+                .map_err(|e| StateError::Database(e.to_string()))?,
+            now,
+        )?;
+
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| StateError::Database(e.to_string()))?;
+        for count in &counts {
+            sqlx::query(
+                r#"
+                INSERT INTO tag_counts (tag, month, count)
+                VALUES ($1, $2, $3)
+                ON CONFLICT(tag, month) DO UPDATE SET count = tag_counts.count + excluded.count
+                "#,
+            )
+            .bind(&count.tag)
+            .bind(&count.month)
+            .bind(count.count as i64)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| StateError::Database(e.to_string()))?;
+        }
+
+        let mut removed = [0u64; PRUNED_TABLES.len()];
+        for id in &old {
+            for (n, (table, column)) in PRUNED_TABLES.iter().enumerate() {
+                let result = sqlx::query(&format!("DELETE FROM {table} WHERE {column} = $1"))
+                    .bind(id.to_string())
+                    .execute(&mut *tx)
+                    .await
+                    .map_err(|e| StateError::Database(e.to_string()))?;
+                removed[n] += result.rows_affected();
+            }
+        }
+        let mut report = PruneReport::default();
+        for id in &post_retries {
+            let result = sqlx::query("DELETE FROM post_retries WHERE source_post_id = $1")
+                .bind(id)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| StateError::Database(e.to_string()))?;
+            report.post_retries += result.rows_affected();
+        }
+        for scope in &deferrals {
+            let result = sqlx::query("DELETE FROM rate_limit_deferrals WHERE scope = $1")
+                .bind(scope)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| StateError::Database(e.to_string()))?;
+            report.deferrals += result.rows_affected();
+        }
+        report.claims = sqlx::query("DELETE FROM post_claims WHERE expires_at <= $1")
+            .bind(unix_millis(now))
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| StateError::Database(e.to_string()))?
+            .rows_affected();
+        tx.commit()
+            .await
+            .map_err(|e| StateError::Database(e.to_string()))?;
+
+        let [targets, classifications, retries, published] = removed;
+        Ok(PruneReport {
+            published,
+            targets,
+            classifications,
+            retries,
+            ..report
+        })
+    }
+
+    /// Tag counts kept from pruned classifications, by tag and month
+    pub async fn tag_counts(&self) -> Result<Vec<TagCount>, StateError> {
+        let rows: Vec<(String, String, i64)> =
+            sqlx::query_as("SELECT tag, month, count FROM tag_counts ORDER BY tag, month")
+                .fetch_all(&self.pool)
+                .await
+                .map_err(|e| StateError::Database(e.to_string()))?;
+
+        Ok(rows
+            .into_iter()
+            .map(|(tag, month, count)| TagCount {
+                tag,
+                month,
+                count: count.max(0) as u64,
+            })
+            .collect())
+    }
+
+    /// Reclaim the space left by deleted rows
+    pub async fn vacuum(&self) -> Result<(), StateError> {
+        sqlx::raw_sql("VACUUM")
+            .execute(&self.pool)
+            .await
+            .map_err(|e| StateError::Database(e.to_string()))?;
+        Ok(())
+    }
+
+    /// Row counts and sizes of the state tables
+    pub async fn stats(&self) -> Result<StateStats, StateError> {
+        let mut tables = Vec::new();
+        for table in TABLES {
+            let (rows,): (i64,) = sqlx::query_as(&format!("SELECT COUNT(*) FROM {table}"))
+                .fetch_one(&self.pool)
+                .await
+                .map_err(|e| StateError::Database(e.to_string()))?;
+            let bytes: Option<(Option<i64>,)> =
+                sqlx::query_as("SELECT pg_total_relation_size(to_regclass($1))")
+                    .bind(table)
+                    .fetch_one(&self.pool)
+                    .await
+                    .ok();
+            tables.push(TableStats {
+                table: table.to_string(),
+                rows: rows.max(0) as u64,
+                bytes: bytes.and_then(|(bytes,)| bytes).map(|b| b.max(0) as u64),
+            });
+        }
+
+        let (database_bytes,): (i64,) =
+            sqlx::query_as("SELECT pg_database_size(current_database())")
+                .fetch_one(&self.pool)
+                .await
+                .map_err(|e| StateError::Database(e.to_string()))?;
+
+        Ok(StateStats {
+            tables,
+            database_bytes: database_bytes.max(0) as u64,
+        })
+    }
+}
+
+async fn pending_migrations(conn: &mut PgConnection) -> Result<Vec<Migration>, StateError> {
+    let (exists,): (bool,) = sqlx::query_as("SELECT to_regclass('schema_migrations') IS NOT NULL")
+        .fetch_one(&mut *conn)
+        .await
+        .map_err(|e| StateError::Database(e.to_string()))?;
+    if !exists {
+        return state_migrations::pending(&[]);
+    }
+
+    let rows: Vec<(i64, String, String)> =
+        sqlx::query_as("SELECT version, name, checksum FROM schema_migrations ORDER BY version")
+            .fetch_all(&mut *conn)
+            .await
+            .map_err(|e| StateError::Database(e.to_string()))?;
+    let applied: Vec<AppliedMigration> = rows
+        .into_iter()
+        .map(|(version, name, checksum)| AppliedMigration {
+            version,
+            name,
+            checksum,
+        })
+        .collect();
+
+    state_migrations::pending(&applied)
+}
+
+async fn apply_pending(conn: &mut PgConnection) -> Result<Vec<Migration>, StateError> {
+    sqlx::raw_sql(CREATE_SCHEMA_MIGRATIONS)
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| StateError::Database(e.to_string()))?;
+
+    let pending = pending_migrations(conn).await?;
+    for migration in &pending {
+        let applied_at = OffsetDateTime::now_utc()
+            .format(&time::format_description::well_known::Rfc3339)
+            .map_err(|e| StateError::Serialization(e.to_string()))?;
+
+        let mut tx = sqlx::Connection::begin(&mut *conn)
+            .await
+            .map_err(|e| StateError::Database(e.to_string()))?;
+        sqlx::raw_sql(migration.sql)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| {
+                StateError::Migration(format!(
+                    "migration {} ({}) failed: {}",
+                    migration.version, migration.name, e
+                ))
+            })?;
+        sqlx::query(
+            "INSERT INTO schema_migrations (version, name, checksum, applied_at) VALUES ($1, $2, $3, $4)",
+        )
+        .bind(migration.version)
+        .bind(migration.name)
+        .bind(migration.checksum())
+        .bind(&applied_at)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| StateError::Database(e.to_string()))?;
+        tx.commit()
+            .await
+            .map_err(|e| StateError::Database(e.to_string()))?;
+
+        tracing::info!(
+            version = migration.version,
+            name = migration.name,
+            "Applied state migration"
+        );
+    }
+
+    Ok(pending)
+}
+
+#[async_trait]
+impl StateStore for PostgresStateStore {
+    async fn get_account_state(&self, account: &str) -> Result<Option<AccountState>, StateError> {
+        let row: Option<(String, Option<String>, String)> = sqlx::query_as(
+            "SELECT account, since_id, updated_at FROM account_state WHERE account = $1",
+        )
+        .bind(account)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| StateError::Database(e.to_string()))?;
+
+        match row {
+            Some((account, since_id, updated_at_str)) => {
+                let updated_at = OffsetDateTime::parse(
+                    &updated_at_str,
+                    &time::format_description::well_known::Rfc3339,
+                )
+                .map_err(|e| StateError::Serialization(e.to_string()))?;
+
+                Ok(Some(AccountState {
+                    account,
+                    since_id,
+                    updated_at,
+                }))
+            }
+            None => Ok(None),
+        }
+    }
+
+    async fn set_account_state(&self, state: &AccountState) -> Result<(), StateError> {
+        let updated_at_str = state
+            .updated_at
+            .format(&time::format_description::well_known::Rfc3339)
+            .map_err(|e| StateError::Serialization(e.to_string()))?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO account_state (account, since_id, updated_at)
+            VALUES ($1, $2, $3)
+            ON CONFLICT(account) DO UPDATE SET
+                since_id = excluded.since_id,
+                updated_at = excluded.updated_at
+            "#,
+        )
+        .bind(&state.account)
+        .bind(&state.since_id)
+        .bind(&updated_at_str)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| StateError::Database(e.to_string()))?;
+
+        Ok(())
+    }
+
+    async fn list_account_states(&self) -> Result<Vec<AccountState>, StateError> {
+        let rows: Vec<AccountStateRow> = sqlx::query_as(
+            "SELECT account, since_id, updated_at FROM account_state ORDER BY account",
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| StateError::Database(e.to_string()))?;
+
+        rows.into_iter().map(account_state_from_row).collect()
+    }
+
+    async fn is_processed(
+        &self,
+        source_post_id: &str,
+        taxonomy_hash: &str,
+    ) -> Result<bool, StateError> {
+        let count: (i64,) = sqlx::query_as(
+            "SELECT COUNT(*) FROM published_records WHERE source_post_id = $1 AND taxonomy_hash = $2",
+        )
+        .bind(source_post_id)
+        .bind(taxonomy_hash)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| StateError::Database(e.to_string()))?;
+
+        Ok(count.0 > 0)
+    }
+
+    async fn record_published(&self, record: &PublishedRecord) -> Result<(), StateError> {
+        let published_at_str = record
+            .published_at
+            .format(&time::format_description::well_known::Rfc3339)
+            .map_err(|e| StateError::Serialization(e.to_string()))?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO published_records
+            (id, source_post_id, taxonomy_hash, published_at)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT(source_post_id, taxonomy_hash) DO NOTHING
+            "#,
+        )
+        .bind(record.id.to_string())
+        .bind(&record.source_post_id)
+        .bind(&record.taxonomy_hash)
+        .bind(&published_at_str)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| StateError::Database(e.to_string()))?;
+
+        Ok(())
+    }
+
+    async fn get_published(
+        &self,
+        source_post_id: &str,
+        taxonomy_hash: &str,
+    ) -> Result<Option<PublishedRecord>, StateError> {
+        let row: Option<(String, String, String, String)> = sqlx::query_as(
+            r#"
+                SELECT id, source_post_id, taxonomy_hash, published_at
+                FROM published_records
+                WHERE source_post_id = $1 AND taxonomy_hash = $2
+                "#,
+        )
+        .bind(source_post_id)
+        .bind(taxonomy_hash)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| StateError::Database(e.to_string()))?;
+
+        match row {
+            Some((id, source_post_id, taxonomy_hash, published_at_str)) => {
+                let id =
+                    Uuid::parse_str(&id).map_err(|e| StateError::Serialization(e.to_string()))?;
+
+                let published_at = OffsetDateTime::parse(
+                    &published_at_str,
+                    &time::format_description::well_known::Rfc3339,
+                )
+                .map_err(|e| StateError::Serialization(e.to_string()))?;
+
+                Ok(Some(PublishedRecord {
+                    id,
+                    source_post_id,
+                    taxonomy_hash,
+                    published_at,
+                }))
+            }
+            None => Ok(None),
+        }
+    }
+
+    async fn list_published(&self) -> Result<Vec<PublishedRecord>, StateError> {
+        let rows: Vec<PublishedRecordRow> = sqlx::query_as(
+            "SELECT id, source_post_id, taxonomy_hash, published_at FROM published_records",
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| StateError::Database(e.to_string()))?;
+
+        let mut records = rows
+            .into_iter()
+            .map(published_record_from_row)
+            .collect::<Result<Vec<_>, _>>()?;
+        records.sort_by_key(|r| r.published_at);
+        Ok(records)
+    }
+
+    async fn record_target(&self, target: &PublishedTarget) -> Result<(), StateError> {
+        let published_at_str = target
+            .published_at
+            .format(&time::format_description::well_known::Rfc3339)
+            .map_err(|e| StateError::Serialization(e.to_string()))?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO published_targets
+            (record_id, platform, target_id, url, published_at)
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT(record_id, platform) DO UPDATE SET
+                target_id = excluded.target_id,
+                url = excluded.url,
+                published_at = excluded.published_at
+            "#,
+        )
+        .bind(target.record_id.to_string())
+        .bind(&target.platform)
+        .bind(&target.target_id)
+        .bind(&target.url)
+        .bind(&published_at_str)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| StateError::Database(e.to_string()))?;
+
+        Ok(())
+    }
+
+    async fn list_targets(&self, record_id: Uuid) -> Result<Vec<PublishedTarget>, StateError> {
+        let rows: Vec<PublishedTargetRow> = sqlx::query_as(
+            r#"
+            SELECT record_id, platform, target_id, url, published_at
+            FROM published_targets
+            WHERE record_id = $1
+            ORDER BY platform
+            "#,
+        )
+        .bind(record_id.to_string())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| StateError::Database(e.to_string()))?;
+
+        rows.into_iter().map(published_target_from_row).collect()
+    }
+
+    async fn record_classification(&self, record: &ClassificationRecord) -> Result<(), StateError> {
+        let classified_at_str = record
+            .classified_at
+            .format(&time::format_description::well_known::Rfc3339)
+            .map_err(|e| StateError::Serialization(e.to_string()))?;
+        let source_post = serde_json::to_string(&record.source_post)
+            .map_err(|e| StateError::Serialization(e.to_string()))?;
+        let classification = serde_json::to_string(&record.classification)
+            .map_err(|e| StateError::Serialization(e.to_string()))?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO classifications
+            (record_id, source_post, classification, taxonomy_hash, classified_at)
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT(record_id) DO UPDATE SET
+                source_post = excluded.source_post,
+                classification = excluded.classification,
+                taxonomy_hash = excluded.taxonomy_hash,
+                classified_at = excluded.classified_at
+            "#,
+        )
+        .bind(record.record_id.to_string())
+        .bind(&source_post)
+        .bind(&classification)
+        .bind(&record.taxonomy_hash)
+        .bind(&classified_at_str)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| StateError::Database(e.to_string()))?;
+
+        Ok(())
+    }
+
+    async fn list_classifications(&self) -> Result<Vec<ClassificationRecord>, StateError> {
+        let rows: Vec<ClassificationRow> = sqlx::query_as(
+            r#"
+            SELECT record_id, source_post, classification, taxonomy_hash, classified_at
+            FROM classifications
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| StateError::Database(e.to_string()))?;
+
+        let mut records = rows
+            .into_iter()
+            .map(classification_from_row)
+            .collect::<Result<Vec<_>, _>>()?;
+        records.sort_by_key(|r| r.classified_at);
+        Ok(records)
+    }
+
+    async fn set_retry(&self, retry: &PublishRetry) -> Result<(), StateError> {
+        let format = |value: OffsetDateTime| {
+            value
+                .format(&time::format_description::well_known::Rfc3339)
+                .map_err(|e| StateError::Serialization(e.to_string()))
+        };
+        let source_post = serde_json::to_string(&retry.source_post)
+            .map_err(|e| StateError::Serialization(e.to_string()))?;
+        let classification = serde_json::to_string(&retry.classification)
+            .map_err(|e| StateError::Serialization(e.to_string()))?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO publish_retries
+            (record_id, platform, source_post, classification, taxonomy_hash, attempts, status,
+             last_error, next_attempt_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+            ON CONFLICT(record_id, platform) DO UPDATE SET
+                source_post = excluded.source_post,
+                classification = excluded.classification,
+                taxonomy_hash = excluded.taxonomy_hash,
+                attempts = excluded.attempts,
+                status = excluded.status,
+                last_error = excluded.last_error,
+                next_attempt_at = excluded.next_attempt_at,
+                updated_at = excluded.updated_at
+            "#,
+        )
+        .bind(retry.record_id.to_string())
+        .bind(&retry.platform)
+        .bind(&source_post)
+        .bind(&classification)
+        .bind(&retry.taxonomy_hash)
+        .bind(i64::from(retry.attempts))
+        .bind(retry.status.as_str())
+        .bind(&retry.last_error)
+        .bind(format(retry.next_attempt_at)?)
+        .bind(format(retry.updated_at)?)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| StateError::Database(e.to_string()))?;
+
+        Ok(())
+    }
+
+    async fn list_retries(&self) -> Result<Vec<PublishRetry>, StateError> {
+        let rows: Vec<PublishRetryRow> = sqlx::query_as(
+            r#"
+            SELECT record_id, platform, source_post, classification, taxonomy_hash, attempts,
+                   status, last_error, next_attempt_at, updated_at
+            FROM publish_retries
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| StateError::Database(e.to_string()))?;
+
+        let mut retries = rows
+            .into_iter()
+            .map(publish_retry_from_row)
+            .collect::<Result<Vec<_>, _>>()?;
+        // RFC 3339 strings with offsets do not sort chronologically in SQL
+        retries.sort_by(|a, b| {
+            a.next_attempt_at
+                .cmp(&b.next_attempt_at)
+                .then_with(|| a.platform.cmp(&b.platform))
+        });
+        Ok(retries)
+    }
+
+    async fn list_retries_for_record(
+        &self,
+        record_id: Uuid,
+    ) -> Result<Vec<PublishRetry>, StateError> {
+        let rows: Vec<PublishRetryRow> = sqlx::query_as(
+            r#"
+            SELECT record_id, platform, source_post, classification, taxonomy_hash, attempts,
+                   status, last_error, next_attempt_at, updated_at
+            FROM publish_retries
+            WHERE record_id = $1
+            ORDER BY platform
+            "#,
+        )
+        .bind(record_id.to_string())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| StateError::Database(e.to_string()))?;
+
+        rows.into_iter().map(publish_retry_from_row).collect()
+    }
+
+    async fn delete_retry(&self, record_id: Uuid, platform: &str) -> Result<(), StateError> {
+        sqlx::query("DELETE FROM publish_retries WHERE record_id = $1 AND platform = $2")
+            .bind(record_id.to_string())
+            .bind(platform)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| StateError::Database(e.to_string()))?;
+
+        Ok(())
+    }
+
+    async fn set_post_retry(&self, retry: &PostRetry) -> Result<(), StateError> {
+        let format = |value: OffsetDateTime| {
+            value
+                .format(&time::format_description::well_known::Rfc3339)
+                .map_err(|e| StateError::Serialization(e.to_string()))
+        };
+
+        sqlx::query(
+            r#"
+            INSERT INTO post_retries
+            (source_post_id, account, attempts, status, last_error, next_attempt_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            ON CONFLICT(source_post_id) DO UPDATE SET
+                account = excluded.account,
+                attempts = excluded.attempts,
+                status = excluded.status,
+                last_error = excluded.last_error,
+                next_attempt_at = excluded.next_attempt_at,
+                updated_at = excluded.updated_at
+            "#,
+        )
+        .bind(&retry.source_post_id)
+        .bind(&retry.account)
+        .bind(i64::from(retry.attempts))
+        .bind(retry.status.as_str())
+        .bind(&retry.last_error)
+        .bind(format(retry.next_attempt_at)?)
+        .bind(format(retry.updated_at)?)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| StateError::Database(e.to_string()))?;
+
+        Ok(())
+    }
+
+    async fn list_post_retries(&self, account: &str) -> Result<Vec<PostRetry>, StateError> {
+        let rows: Vec<PostRetryRow> = sqlx::query_as(
+            r#"
+            SELECT source_post_id, account, attempts, status, last_error, next_attempt_at,
+                   updated_at
+            FROM post_retries
+            WHERE account = $1
+            ORDER BY source_post_id
+            "#,
+        )
+        .bind(account)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| StateError::Database(e.to_string()))?;
+
+        rows.into_iter().map(post_retry_from_row).collect()
+    }
+
+    async fn delete_post_retry(&self, source_post_id: &str) -> Result<(), StateError> {
+        sqlx::query("DELETE FROM post_retries WHERE source_post_id = $1")
+            .bind(source_post_id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| StateError::Database(e.to_string()))?;
+
+        Ok(())
+    }
+
+    async fn get_resolved_account(
+        &self,
+        account: &str,
+    ) -> Result<Option<ResolvedAccount>, StateError> {
+        let row: Option<ResolvedAccountRow> = sqlx::query_as(
+            r#"
+            SELECT account, user_id, status, renamed_to, resolved_at
+            FROM resolved_accounts
+            WHERE account = $1
+            "#,
+        )
+        .bind(account.to_ascii_lowercase())
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| StateError::Database(e.to_string()))?;
+
+        row.map(resolved_account_from_row).transpose()
+    }
+
+    async fn set_resolved_account(&self, resolved: &ResolvedAccount) -> Result<(), StateError> {
+        let resolved_at_str = resolved
+            .resolved_at
+            .format(&time::format_description::well_known::Rfc3339)
+            .map_err(|e| StateError::Serialization(e.to_string()))?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO resolved_accounts (account, user_id, status, renamed_to, resolved_at)
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT(account) DO UPDATE SET
+                user_id = COALESCE(excluded.user_id, resolved_accounts.user_id),
+                status = excluded.status,
+                renamed_to = excluded.renamed_to,
+                resolved_at = excluded.resolved_at
+            "#,
+        )
+        .bind(resolved.account.to_ascii_lowercase())
+        .bind(&resolved.user_id)
+        .bind(resolved.status.as_str())
+        .bind(&resolved.renamed_to)
+        .bind(&resolved_at_str)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| StateError::Database(e.to_string()))?;
+
+        Ok(())
+    }
+
+    async fn list_resolved_accounts(&self) -> Result<Vec<ResolvedAccount>, StateError> {
+        let rows: Vec<ResolvedAccountRow> = sqlx::query_as(
+            r#"
+            SELECT account, user_id, status, renamed_to, resolved_at
+            FROM resolved_accounts
+            ORDER BY account
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| StateError::Database(e.to_string()))?;
+
+        rows.into_iter().map(resolved_account_from_row).collect()
+    }
+
+    async fn list_deferrals(&self) -> Result<Vec<RateLimitDeferral>, StateError> {
+        let rows: Vec<(String, String)> = sqlx::query_as(
+            r#"
+            SELECT scope, until
+            FROM rate_limit_deferrals
+            ORDER BY scope
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| StateError::Database(e.to_string()))?;
+
+        rows.into_iter()
+            .map(|(scope, until_str)| {
+                let until = OffsetDateTime::parse(
+                    &until_str,
+                    &time::format_description::well_known::Rfc3339,
+                )
+                .map_err(|e| StateError::Serialization(e.to_string()))?;
+                Ok(RateLimitDeferral { scope, until })
+            })
+            .collect()
+    }
+
+    async fn set_deferral(&self, deferral: &RateLimitDeferral) -> Result<(), StateError> {
+        let until_str = deferral
+            .until
+            .format(&time::format_description::well_known::Rfc3339)
+            .map_err(|e| StateError::Serialization(e.to_string()))?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO rate_limit_deferrals (scope, until)
+            VALUES ($1, $2)
+            ON CONFLICT(scope) DO UPDATE SET until = excluded.until
+            "#,
+        )
+        .bind(&deferral.scope)
+        .bind(&until_str)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| StateError::Database(e.to_string()))?;
+
+        Ok(())
+    }
+
+    async fn get_feed_cursor(&self, feed: &str) -> Result<Option<FeedCursor>, StateError> {
+        let row: Option<FeedCursorRow> = sqlx::query_as(
+            r#"
+            SELECT feed, etag, last_modified, newest_id, updated_at
+            FROM feed_cursors
+            WHERE feed = $1
+            "#,
+        )
+        .bind(feed)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| StateError::Database(e.to_string()))?;
+
+        match row {
+            Some((feed, etag, last_modified, newest_id, updated_at_str)) => {
+                let updated_at = OffsetDateTime::parse(
+                    &updated_at_str,
+                    &time::format_description::well_known::Rfc3339,
+                )
+                .map_err(|e| StateError::Serialization(e.to_string()))?;
+
+                Ok(Some(FeedCursor {
+                    feed,
+                    etag,
+                    last_modified,
+                    newest_id,
+                    updated_at,
+                }))
+            }
+            None => Ok(None),
+        }
+    }
+
+    async fn set_feed_cursor(&self, cursor: &FeedCursor) -> Result<(), StateError> {
+        let updated_at_str = cursor
+            .updated_at
+            .format(&time::format_description::well_known::Rfc3339)
+            .map_err(|e| StateError::Serialization(e.to_string()))?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO feed_cursors (feed, etag, last_modified, newest_id, updated_at)
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT(feed) DO UPDATE SET
+                etag = excluded.etag,
+                last_modified = excluded.last_modified,
+                newest_id = excluded.newest_id,
+                updated_at = excluded.updated_at
+            "#,
+        )
+        .bind(&cursor.feed)
+        .bind(&cursor.etag)
+        .bind(&cursor.last_modified)
+        .bind(&cursor.newest_id)
+        .bind(&updated_at_str)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| StateError::Database(e.to_string()))?;
+
+        Ok(())
+    }
+
+    async fn get_relay_status(&self, relay: &str) -> Result<Option<RelayStatus>, StateError> {
+        let row: Option<RelayStatusRow> = sqlx::query_as(
+            r#"
+            SELECT relay, successes, failures, last_success_at, last_error, updated_at
+            FROM relay_statuses
+            WHERE relay = $1
+            "#,
+        )
+        .bind(relay)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| StateError::Database(e.to_string()))?;
+
+        row.map(relay_status_from_row).transpose()
+    }
+
+    async fn set_relay_status(&self, status: &RelayStatus) -> Result<(), StateError> {
+        let format = |value: OffsetDateTime| {
+            value
+                .format(&time::format_description::well_known::Rfc3339)
+                .map_err(|e| StateError::Serialization(e.to_string()))
+        };
+        let last_success_at_str = status.last_success_at.map(format).transpose()?;
+        let updated_at_str = format(status.updated_at)?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO relay_statuses
+                (relay, successes, failures, last_success_at, last_error, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            ON CONFLICT(relay) DO UPDATE SET
+                successes = excluded.successes,
+                failures = excluded.failures,
+                last_success_at = excluded.last_success_at,
+                last_error = excluded.last_error,
+                updated_at = excluded.updated_at
+            "#,
+        )
+        .bind(&status.relay)
+        .bind(status.successes as i64)
+        .bind(status.failures as i64)
+        .bind(&last_success_at_str)
+        .bind(&status.last_error)
+        .bind(&updated_at_str)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| StateError::Database(e.to_string()))?;
+
+        Ok(())
+    }
+
+    async fn list_relay_statuses(&self) -> Result<Vec<RelayStatus>, StateError> {
+        let rows: Vec<RelayStatusRow> = sqlx::query_as(
+            r#"
+            SELECT relay, successes, failures, last_success_at, last_error, updated_at
+            FROM relay_statuses
+            ORDER BY relay
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| StateError::Database(e.to_string()))?;
+
+        rows.into_iter().map(relay_status_from_row).collect()
+    }
+
+    async fn claim_post(
+        &self,
+        source_post_id: &str,
+        taxonomy_hash: &str,
+        worker_id: &str,
+        lease: Duration,
+    ) -> Result<bool, StateError> {
+        let (now, expires_at) = lease_window(lease);
+
+        let result = sqlx::query(
+            r#"
+            INSERT INTO post_claims (source_post_id, taxonomy_hash, worker_id, expires_at)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT(source_post_id, taxonomy_hash) DO UPDATE SET
+                worker_id = excluded.worker_id,
+                expires_at = excluded.expires_at
+            WHERE post_claims.worker_id = excluded.worker_id OR post_claims.expires_at <= $5
+            "#,
+        )
+        .bind(source_post_id)
+        .bind(taxonomy_hash)
+        .bind(worker_id)
+        .bind(expires_at)
+        .bind(now)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| StateError::Database(e.to_string()))?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn release_post(
+        &self,
+        source_post_id: &str,
+        taxonomy_hash: &str,
+        worker_id: &str,
+    ) -> Result<(), StateError> {
+        sqlx::query(
+            "DELETE FROM post_claims WHERE source_post_id = $1 AND taxonomy_hash = $2 AND worker_id = $3",
+        )
+        .bind(source_post_id)
+        .bind(taxonomy_hash)
+        .bind(worker_id)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| StateError::Database(e.to_string()))?;
+
+        Ok(())
+    }
+
+    async fn claim_account(
+        &self,
+        account: &str,
+        worker_id: &str,
+        lease: Duration,
+    ) -> Result<bool, StateError> {
+        let (now, expires_at) = lease_window(lease);
+
+        let result = sqlx::query(
+            r#"
+            INSERT INTO account_leases (account, worker_id, expires_at)
+            VALUES ($1, $2, $3)
+            ON CONFLICT(account) DO UPDATE SET
+                worker_id = excluded.worker_id,
+                expires_at = excluded.expires_at
+            WHERE account_leases.worker_id = excluded.worker_id OR account_leases.expires_at <= $4
+            "#,
+        )
+        .bind(account)
+        .bind(worker_id)
+        .bind(expires_at)
+        .bind(now)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| StateError::Database(e.to_string()))?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state_migrations::MIGRATIONS;
+    use news_tagger_domain::{AccountStatus, ClassifyOutput, RetryStatus, SourcePost};
+    use std::process::Command;
+
+    /// Database URL for tests; each test gets its own schema in it
+    const TEST_URL_ENV: &str = "NEWS_TAGGER_TEST_POSTGRES_URL";
+
+    /// A throwaway cluster started with `initdb`/`pg_ctl`, stopped on drop
+    struct TempCluster {
+        dir: tempfile::TempDir,
+        url: String,
+    }
+
+    impl TempCluster {
+        fn start() -> Option<Self> {
+            let dir = tempfile::TempDir::new().ok()?;
+            let data = dir.path().join("data");
+            let initdb = Command::new("initdb")
+                .args(["--auth=trust", "--username=postgres", "--no-sync", "-D"])
+                .arg(&data)
+                .output()
+                .ok()?;
+            if !initdb.status.success() {
+                return None;
+            }
+
+            let port = std::net::TcpListener::bind("127.0.0.1:0")
+                .ok()?
+                .local_addr()
+                .ok()?
+                .port();
+            let options = format!(
+                "-p {port} -c listen_addresses=127.0.0.1 -c unix_socket_directories={}",
+                dir.path().display()
+            );
+            let started = Command::new("pg_ctl")
+                .args(["-w", "-s", "-D"])
+                .arg(&data)
+                .args(["-l"])
+                .arg(dir.path().join("log"))
+                .args(["-o", &options, "start"])
+                .status()
+                .ok()?;
+            if !started.success() {
+                return None;
+            }
+
+            Some(Self {
+                url: format!("postgres://postgres@127.0.0.1:{port}/postgres"),
+                dir,
+            })
+        }
+    }
+
+    impl Drop for TempCluster {
+        fn drop(&mut self) {
+            let _ = Command::new("pg_ctl")
+                .args(["-s", "-m", "immediate", "-D"])
+                .arg(self.dir.path().join("data"))
+                .arg("stop")
+                .status();
+        }
+    }
+
+    struct TestDb {
+        url: String,
+        schema: String,
+        _cluster: Option<TempCluster>,
+    }
+
+    impl TestDb {
+        /// Use `NEWS_TAGGER_TEST_POSTGRES_URL` or start a local cluster;
+        /// `None` skips the test
+        async fn new() -> Option<Self> {
+            let (url, cluster) = match std::env::var(TEST_URL_ENV) {
+                Ok(url) => (url, None),
+                Err(_) => match TempCluster::start() {
+                    Some(cluster) => (cluster.url.clone(), Some(cluster)),
+                    None => {
+                        eprintln!("skipping: set {TEST_URL_ENV} or install initdb/pg_ctl");
+                        return None;
+                    }
+                },
+            };
+
+            let schema = format!("test_{}", Uuid::new_v4().simple());
+            let pool = PgPool::connect(&url).await.unwrap();
+            sqlx::query(&format!("CREATE SCHEMA {schema}"))
+                .execute(&pool)
+                .await
+                .unwrap();
+            pool.close().await;
+
+            Some(Self {
+                url,
+                schema,
+                _cluster: cluster,
+            })
+        }
+
+        /// A store whose connections only see this test's schema
+        async fn open(&self) -> PostgresStateStore {
+            let options = PgConnectOptions::from_str(&self.url)
+                .unwrap()
+                .options([("search_path", self.schema.as_str())]);
+            let pool = PgPoolOptions::new()
+                .max_connections(2)
+                .connect_with(options)
+                .await
+                .unwrap();
+            PostgresStateStore { pool }
+        }
+
+        async fn store(&self) -> PostgresStateStore {
+            let store = self.open().await;
+            store.migrate().await.unwrap();
+            store
+        }
+    }
+
+    fn record(source_post_id: &str) -> PublishedRecord {
+        PublishedRecord {
+            id: Uuid::new_v4(),
+            source_post_id: source_post_id.to_string(),
+            taxonomy_hash: "hash".to_string(),
+            published_at: OffsetDateTime::from_unix_timestamp(1_700_000_000).unwrap(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_migrations_are_recorded_and_checked() {
+        let Some(db) = TestDb::new().await else {
+            return;
+        };
+
+        let store = db.open().await;
+        assert_eq!(
+            store.pending_migrations().await.unwrap().len(),
+            MIGRATIONS.len()
+        );
+        assert_eq!(store.migrate().await.unwrap().len(), MIGRATIONS.len());
+        assert!(store.migrate().await.unwrap().is_empty());
+
+        sqlx::query("INSERT INTO schema_migrations VALUES (9999, 'future', '', '')")
+            .execute(&store.pool)
+            .await
+            .unwrap();
+        let err = store.migrate().await.unwrap_err();
+        assert!(matches!(err, StateError::Migration(_)));
+    }
+
+    #[tokio::test]
+    async fn test_record_published_keeps_first_record() {
+        let Some(db) = TestDb::new().await else {
+            return;
+        };
+        let store = db.store().await;
+
+        let first = record("post-1");
+        store.record_published(&first).await.unwrap();
+        store.record_published(&record("post-1")).await.unwrap();
+
+        assert!(store.is_processed("post-1", "hash").await.unwrap());
+        let stored = store
+            .get_published("post-1", "hash")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.id, first.id);
+        assert_eq!(stored.published_at, first.published_at);
+    }
+
+    #[tokio::test]
+    async fn test_targets_and_retries_roundtrip() {
+        let Some(db) = TestDb::new().await else {
+            return;
+        };
+        let store = db.store().await;
+        let published = record("post-1");
+        let now = published.published_at;
+        store.record_published(&published).await.unwrap();
+
+        let target = PublishedTarget {
+            record_id: published.id,
+            platform: "mastodon".to_string(),
+            target_id: "109".to_string(),
+            url: None,
+            published_at: now,
+        };
+        store.record_target(&target).await.unwrap();
+        store
+            .record_target(&PublishedTarget {
+                url: Some("https://example.social/@bot/109".to_string()),
+                ..target.clone()
+            })
+            .await
+            .unwrap();
+        let targets = store.list_targets(published.id).await.unwrap();
+        assert_eq!(targets.len(), 1);
+        assert_eq!(
+            targets[0].url.as_deref(),
+            Some("https://example.social/@bot/109")
+        );
+
+        let mut retry = PublishRetry {
+            record_id: published.id,
+            platform: "webhook".to_string(),
+            source_post: SourcePost {
+                id: "post-1".to_string(),
+                text: "Some text".to_string(),
+                author: "user".to_string(),
+                url: "https://example.com/post-1".to_string(),
+                created_at: now,
+                is_repost: false,
+                is_reply: false,
+                reply_to_id: None,
+                context: vec![],
+            },
+            classification: ClassifyOutput::new("Summary".to_string(), vec![]),
+            taxonomy_hash: "hash".to_string(),
+            attempts: 1,
+            status: RetryStatus::Pending,
+            last_error: "timeout".to_string(),
+            next_attempt_at: now,
+            updated_at: now,
+        };
+        store.set_retry(&retry).await.unwrap();
+        retry.attempts = 2;
+        retry.status = RetryStatus::Failed;
+        store.set_retry(&retry).await.unwrap();
+
+        let retries = store.list_retries().await.unwrap();
+        assert_eq!(retries.len(), 1);
+        assert_eq!(retries[0].attempts, 2);
+        assert_eq!(retries[0].status, RetryStatus::Failed);
+        assert_eq!(
+            store
+                .list_retries_for_record(published.id)
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+
+        store.delete_retry(published.id, "webhook").await.unwrap();
+        assert!(store.list_retries().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_account_state_upsert() {
+        let Some(db) = TestDb::new().await else {
+            return;
+        };
+        let store = db.store().await;
+
+        for since_id in ["1", "2"] {
+            store
+                .set_account_state(&AccountState {
+                    account: "testuser".to_string(),
+                    since_id: Some(since_id.to_string()),
+                    updated_at: OffsetDateTime::now_utc(),
+                })
+                .await
+                .unwrap();
+        }
+
+        let state = store.get_account_state("testuser").await.unwrap().unwrap();
+        assert_eq!(state.since_id.as_deref(), Some("2"));
+    }
+
+    #[tokio::test]
+    async fn test_bookkeeping_tables_roundtrip() {
+        let Some(db) = TestDb::new().await else {
+            return;
+        };
+        let store = db.store().await;
+        let now = OffsetDateTime::from_unix_timestamp(1_700_000_000).unwrap();
+
+        store
+            .set_resolved_account(&ResolvedAccount {
+                account: "alice".to_string(),
+                user_id: Some("42".to_string()),
+                status: AccountStatus::Active,
+                renamed_to: None,
+                resolved_at: now,
+            })
+            .await
+            .unwrap();
+        let resolved = store.get_resolved_account("alice").await.unwrap().unwrap();
+        assert_eq!(resolved.user_id.as_deref(), Some("42"));
+        assert_eq!(store.list_resolved_accounts().await.unwrap().len(), 1);
+
+        for until in [now, now + time::Duration::minutes(5)] {
+            store
+                .set_deferral(&RateLimitDeferral {
+                    scope: "publisher:x".to_string(),
+                    until,
+                })
+                .await
+                .unwrap();
+        }
+        let deferrals = store.list_deferrals().await.unwrap();
+        assert_eq!(deferrals.len(), 1);
+        assert_eq!(deferrals[0].until, now + time::Duration::minutes(5));
+
+        store
+            .set_feed_cursor(&FeedCursor {
+                feed: "https://example.com/feed.xml".to_string(),
+                etag: Some("\"abc\"".to_string()),
+                last_modified: None,
+                newest_id: Some("item-3".to_string()),
+                updated_at: now,
+            })
+            .await
+            .unwrap();
+        let cursor = store
+            .get_feed_cursor("https://example.com/feed.xml")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(cursor.newest_id.as_deref(), Some("item-3"));
+
+        store
+            .set_relay_status(&RelayStatus {
+                relay: "wss://relay.example".to_string(),
+                successes: 3,
+                failures: 1,
+                last_success_at: Some(now),
+                last_error: Some("timeout".to_string()),
+                updated_at: now,
+            })
+            .await
+            .unwrap();
+        let relays = store.list_relay_statuses().await.unwrap();
+        assert_eq!(relays.len(), 1);
+        assert_eq!(relays[0].successes, 3);
+
+        let published = record("post-1");
+        store
+            .record_classification(&ClassificationRecord {
+                record_id: published.id,
+                source_post: SourcePost {
+                    id: "post-1".to_string(),
+                    text: "Some text".to_string(),
+                    author: "user".to_string(),
+                    url: "https://example.com/post-1".to_string(),
+                    created_at: now,
+                    is_repost: false,
+                    is_reply: false,
+                    reply_to_id: None,
+                    context: vec![],
+                },
+                classification: ClassifyOutput::new("Summary".to_string(), vec![]),
+                taxonomy_hash: "hash".to_string(),
+                classified_at: now,
+            })
+            .await
+            .unwrap();
+        let records = store.list_classifications().await.unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].classified_at, now);
+    }
+
+    #[tokio::test]
+    async fn test_post_claims_and_account_leases() {
+        let Some(db) = TestDb::new().await else {
+            return;
+        };
+        let store = db.store().await;
+        let minute = Duration::from_secs(60);
+
+        assert!(store.claim_post("1", "hash", "a", minute).await.unwrap());
+        assert!(!store.claim_post("1", "hash", "b", minute).await.unwrap());
+        assert!(store.claim_post("1", "hash", "a", minute).await.unwrap());
+        assert!(store.claim_post("1", "other", "b", minute).await.unwrap());
+
+        store.release_post("1", "hash", "b").await.unwrap();
+        assert!(!store.claim_post("1", "hash", "b", minute).await.unwrap());
+        store.release_post("1", "hash", "a").await.unwrap();
+        assert!(
+            store
+                .claim_post("1", "hash", "b", Duration::ZERO)
+                .await
+                .unwrap()
+        );
+
+        // An expired lease goes to whoever asks next
+        assert!(store.claim_post("1", "hash", "a", minute).await.unwrap());
+
+        assert!(
+            store
+                .claim_account("alice", "a", Duration::ZERO)
+                .await
+                .unwrap()
+        );
+        assert!(store.claim_account("alice", "b", minute).await.unwrap());
+        assert!(!store.claim_account("alice", "a", minute).await.unwrap());
+        assert!(store.claim_account("alice", "b", minute).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_prune_keeps_tag_counts() {
+        let Some(db) = TestDb::new().await else {
+            return;
+        };
+        let store = db.store().await;
+        let now = OffsetDateTime::from_unix_timestamp(1_700_000_000).unwrap();
+        let old = now - time::Duration::days(120);
+
+        for (post_id, at) in [("old", old), ("new", now)] {
+            let record = PublishedRecord {
+                id: Uuid::new_v4(),
+                source_post_id: post_id.to_string(),
+                taxonomy_hash: "hash".to_string(),
+                published_at: at,
+            };
+            store.record_published(&record).await.unwrap();
+            store
+                .record_target(&PublishedTarget {
+                    record_id: record.id,
+                    platform: "x".to_string(),
+                    target_id: format!("x-{post_id}"),
+                    url: None,
+                    published_at: at,
+                })
+                .await
+                .unwrap();
+            store
+                .record_classification(&ClassificationRecord {
+                    record_id: record.id,
+                    source_post: news_tagger_domain::SourcePost {
+                        id: post_id.to_string(),
+                        text: "Some text".to_string(),
+                        author: "user".to_string(),
+                        url: format!("https://example.com/{post_id}"),
+                        created_at: at,
+                        is_repost: false,
+                        is_reply: false,
+                        reply_to_id: None,
+                        context: vec![],
+                    },
+                    classification: news_tagger_domain::ClassifyOutput::new(
+                        "Summary".to_string(),
+                        vec![news_tagger_domain::TagMatch {
+                            id: "spin".to_string(),
+                            confidence: 0.8,
+                            rationale: String::new(),
+                            evidence: vec![],
+                        }],
+                    ),
+                    taxonomy_hash: "hash".to_string(),
+                    classified_at: at,
+                })
+                .await
+                .unwrap();
+        }
+
+        for (post_id, at) in [("old", old), ("new", now)] {
+            store
+                .set_post_retry(&PostRetry {
+                    source_post_id: post_id.to_string(),
+                    account: "alice".to_string(),
+                    attempts: 1,
+                    status: RetryStatus::Pending,
+                    last_error: "Classification failed".to_string(),
+                    next_attempt_at: at,
+                    updated_at: at,
+                })
+                .await
+                .unwrap();
+        }
+        for (scope, until) in [
+            ("ended", now),
+            (
+                "active",
+                OffsetDateTime::now_utc() + time::Duration::hours(1),
+            ),
+        ] {
+            store
+                .set_deferral(&RateLimitDeferral {
+                    scope: scope.to_string(),
+                    until,
+                })
+                .await
+                .unwrap();
+        }
+        for (post_id, lease) in [
+            ("expired", Duration::ZERO),
+            ("held", Duration::from_secs(60)),
+        ] {
+            assert!(store.claim_post(post_id, "hash", "a", lease).await.unwrap());
+        }
+
+        let report = store.prune(now - time::Duration::days(90)).await.unwrap();
+        assert_eq!(
+            report,
+            PruneReport {
+                published: 1,
+                targets: 1,
+                classifications: 1,
+                retries: 0,
+                post_retries: 1,
+                claims: 1,
+                deferrals: 1,
+            }
+        );
+        let published = store.list_published().await.unwrap();
+        assert_eq!(published.len(), 1);
+        assert_eq!(published[0].source_post_id, "new");
+        assert_eq!(store.list_classifications().await.unwrap().len(), 1);
+
+        let counts = store.tag_counts().await.unwrap();
+        assert_eq!(counts.len(), 1);
+        assert_eq!((counts[0].tag.as_str(), counts[0].count), ("spin", 1));
+
+        // Nothing left to prune the second time
+        let report = store.prune(now - time::Duration::days(90)).await.unwrap();
+        assert_eq!(report, PruneReport::default());
+
+        store.vacuum().await.unwrap();
+        let stats = store.stats().await.unwrap();
+        let rows = |name: &str| stats.tables.iter().find(|t| t.table == name).unwrap().rows;
+        assert_eq!(rows("published_records"), 1);
+        assert_eq!(rows("tag_counts"), 1);
+        assert_eq!(rows("post_retries"), 1);
+        assert_eq!(rows("post_claims"), 1);
+        assert_eq!(rows("rate_limit_deferrals"), 1);
+        assert!(stats.database_bytes > 0);
+    }
+}
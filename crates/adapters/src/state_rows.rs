@@ -0,0 +1,235 @@
+//! Row types and conversions shared by the SQL state stores
+
+use news_tagger_domain::{
+    AccountState, AccountStatus, ClassificationRecord, PostRetry, PublishRetry, PublishedRecord,
+    PublishedTarget, RelayStatus, ResolvedAccount, RetryStatus, StateError,
+};
+use std::time::Duration;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+/// `t` in Unix milliseconds, as lease expiries are stored
+pub(crate) fn unix_millis(t: OffsetDateTime) -> i64 {
+    (t.unix_timestamp_nanos() / 1_000_000) as i64
+}
+
+/// The current time and the end of a lease starting now, in Unix milliseconds
+pub(crate) fn lease_window(lease: Duration) -> (i64, i64) {
+    let now = OffsetDateTime::now_utc();
+    (unix_millis(now), unix_millis(now + lease))
+}
+
+pub(crate) type AccountStateRow = (String, Option<String>, String);
+
+pub(crate) fn account_state_from_row(row: AccountStateRow) -> Result<AccountState, StateError> {
+    let (account, since_id, updated_at) = row;
+    Ok(AccountState {
+        account,
+        since_id,
+        updated_at: OffsetDateTime::parse(
+            &updated_at,
+            &time::format_description::well_known::Rfc3339,
+        )
+        .map_err(|e| StateError::Serialization(e.to_string()))?,
+    })
+}
+
+pub(crate) type PublishedRecordRow = (String, String, String, String);
+
+pub(crate) fn published_record_from_row(
+    row: PublishedRecordRow,
+) -> Result<PublishedRecord, StateError> {
+    let (id, source_post_id, taxonomy_hash, published_at) = row;
+    Ok(PublishedRecord {
+        id: Uuid::parse_str(&id).map_err(|e| StateError::Serialization(e.to_string()))?,
+        source_post_id,
+        taxonomy_hash,
+        published_at: OffsetDateTime::parse(
+            &published_at,
+            &time::format_description::well_known::Rfc3339,
+        )
+        .map_err(|e| StateError::Serialization(e.to_string()))?,
+    })
+}
+
+pub(crate) type PublishedTargetRow = (String, String, String, Option<String>, String);
+
+pub(crate) fn published_target_from_row(
+    row: PublishedTargetRow,
+) -> Result<PublishedTarget, StateError> {
+    let (record_id, platform, target_id, url, published_at_str) = row;
+
+    let record_id =
+        Uuid::parse_str(&record_id).map_err(|e| StateError::Serialization(e.to_string()))?;
+    let published_at = OffsetDateTime::parse(
+        &published_at_str,
+        &time::format_description::well_known::Rfc3339,
+    )
+    .map_err(|e| StateError::Serialization(e.to_string()))?;
+
+    Ok(PublishedTarget {
+        record_id,
+        platform,
+        target_id,
+        url,
+        published_at,
+    })
+}
+
+pub(crate) type ClassificationRow = (String, String, String, String, String);
+
+pub(crate) fn classification_from_row(
+    row: ClassificationRow,
+) -> Result<ClassificationRecord, StateError> {
+    let (record_id, source_post, classification, taxonomy_hash, classified_at_str) = row;
+
+    Ok(ClassificationRecord {
+        record_id: Uuid::parse_str(&record_id)
+            .map_err(|e| StateError::Serialization(e.to_string()))?,
+        source_post: serde_json::from_str(&source_post)
+            .map_err(|e| StateError::Serialization(e.to_string()))?,
+        classification: serde_json::from_str(&classification)
+            .map_err(|e| StateError::Serialization(e.to_string()))?,
+        taxonomy_hash,
+        classified_at: OffsetDateTime::parse(
+            &classified_at_str,
+            &time::format_description::well_known::Rfc3339,
+        )
+        .map_err(|e| StateError::Serialization(e.to_string()))?,
+    })
+}
+
+pub(crate) type PublishRetryRow = (
+    String,
+    String,
+    String,
+    String,
+    String,
+    i64,
+    String,
+    String,
+    String,
+    String,
+);
+
+pub(crate) fn publish_retry_from_row(row: PublishRetryRow) -> Result<PublishRetry, StateError> {
+    let (
+        record_id,
+        platform,
+        source_post,
+        classification,
+        taxonomy_hash,
+        attempts,
+        status,
+        last_error,
+        next_attempt_at_str,
+        updated_at_str,
+    ) = row;
+
+    let parse = |value: &str| {
+        OffsetDateTime::parse(value, &time::format_description::well_known::Rfc3339)
+            .map_err(|e| StateError::Serialization(e.to_string()))
+    };
+
+    Ok(PublishRetry {
+        record_id: Uuid::parse_str(&record_id)
+            .map_err(|e| StateError::Serialization(e.to_string()))?,
+        platform,
+        source_post: serde_json::from_str(&source_post)
+            .map_err(|e| StateError::Serialization(e.to_string()))?,
+        classification: serde_json::from_str(&classification)
+            .map_err(|e| StateError::Serialization(e.to_string()))?,
+        taxonomy_hash,
+        attempts: attempts.clamp(0, u32::MAX as i64) as u32,
+        status: RetryStatus::parse(&status).ok_or_else(|| {
+            StateError::Serialization(format!("Unknown retry status: {}", status))
+        })?,
+        last_error,
+        next_attempt_at: parse(&next_attempt_at_str)?,
+        updated_at: parse(&updated_at_str)?,
+    })
+}
+
+pub(crate) type PostRetryRow = (String, String, i64, String, String, String, String);
+
+pub(crate) fn post_retry_from_row(row: PostRetryRow) -> Result<PostRetry, StateError> {
+    let (
+        source_post_id,
+        account,
+        attempts,
+        status,
+        last_error,
+        next_attempt_at_str,
+        updated_at_str,
+    ) = row;
+
+    let parse = |value: &str| {
+        OffsetDateTime::parse(value, &time::format_description::well_known::Rfc3339)
+            .map_err(|e| StateError::Serialization(e.to_string()))
+    };
+
+    Ok(PostRetry {
+        source_post_id,
+        account,
+        attempts: attempts.clamp(0, u32::MAX as i64) as u32,
+        status: RetryStatus::parse(&status).ok_or_else(|| {
+            StateError::Serialization(format!("Unknown retry status: {}", status))
+        })?,
+        last_error,
+        next_attempt_at: parse(&next_attempt_at_str)?,
+        updated_at: parse(&updated_at_str)?,
+    })
+}
+
+pub(crate) type ResolvedAccountRow = (String, Option<String>, String, Option<String>, String);
+
+pub(crate) type FeedCursorRow = (
+    String,
+    Option<String>,
+    Option<String>,
+    Option<String>,
+    String,
+);
+
+pub(crate) type RelayStatusRow = (String, i64, i64, Option<String>, Option<String>, String);
+
+pub(crate) fn relay_status_from_row(row: RelayStatusRow) -> Result<RelayStatus, StateError> {
+    let (relay, successes, failures, last_success_at_str, last_error, updated_at_str) = row;
+
+    let parse = |value: &str| {
+        OffsetDateTime::parse(value, &time::format_description::well_known::Rfc3339)
+            .map_err(|e| StateError::Serialization(e.to_string()))
+    };
+
+    Ok(RelayStatus {
+        relay,
+        successes: successes.max(0) as u64,
+        failures: failures.max(0) as u64,
+        last_success_at: last_success_at_str.as_deref().map(parse).transpose()?,
+        last_error,
+        updated_at: parse(&updated_at_str)?,
+    })
+}
+
+pub(crate) fn resolved_account_from_row(
+    row: ResolvedAccountRow,
+) -> Result<ResolvedAccount, StateError> {
+    let (account, user_id, status, renamed_to, resolved_at_str) = row;
+
+    let status = AccountStatus::parse(&status)
+        .ok_or_else(|| StateError::Serialization(format!("Unknown account status: {}", status)))?;
+
+    let resolved_at = OffsetDateTime::parse(
+        &resolved_at_str,
+        &time::format_description::well_known::Rfc3339,
+    )
+    .map_err(|e| StateError::Serialization(e.to_string()))?;
+
+    Ok(ResolvedAccount {
+        account,
+        user_id,
+        status,
+        renamed_to,
+        resolved_at,
+    })
+}
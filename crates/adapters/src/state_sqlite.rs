@@ -1,20 +1,44 @@
 //! SQLite state store implementation
 
 use async_trait::async_trait;
-use news_tagger_domain::{AccountState, PublishedRecord, StateError, StateStore};
-use sqlx::{SqlitePool, sqlite::SqlitePoolOptions};
+use news_tagger_domain::{
+    AccountState, ClassificationRecord, FeedCursor, PostRetry, PublishRetry, PublishedRecord,
+    PublishedTarget, RateLimitDeferral, RelayStatus, ResolvedAccount, StateError, StateStore,
+};
+use sqlx::{SqliteConnection, SqlitePool, sqlite::SqlitePoolOptions};
+use std::collections::HashSet;
 use std::path::Path;
+use std::time::Duration;
 use time::OffsetDateTime;
 use uuid::Uuid;
 
+use crate::state_maintenance::{
+    PRUNED_TABLES, PruneReport, StateStats, TABLES, TableStats, TagCount, count_tags, keys_before,
+};
+use crate::state_migrations::{self, AppliedMigration, CREATE_SCHEMA_MIGRATIONS, Migration};
+use crate::state_rows::{
+    AccountStateRow, ClassificationRow, FeedCursorRow, PostRetryRow, PublishRetryRow,
+    PublishedRecordRow, PublishedTargetRow, RelayStatusRow, ResolvedAccountRow,
+    account_state_from_row, classification_from_row, lease_window, post_retry_from_row,
+    publish_retry_from_row, published_record_from_row, published_target_from_row,
+    relay_status_from_row, resolved_account_from_row, unix_millis,
+};
+
 /// SQLite-backed state store
 pub struct SqliteStateStore {
     pool: SqlitePool,
 }
 
 impl SqliteStateStore {
-    /// Create a new SQLite state store, initializing the database if needed
+    /// Create a new SQLite state store, applying pending migrations
     pub async fn new(db_path: impl AsRef<Path>) -> Result<Self, StateError> {
+        let store = Self::open(db_path).await?;
+        store.migrate().await?;
+        Ok(store)
+    }
+
+    /// Open the database without migrating it, e.g. to list pending migrations
+    pub async fn open(db_path: impl AsRef<Path>) -> Result<Self, StateError> {
         let db_path = db_path.as_ref();
 
         // Create parent directories if needed
@@ -31,10 +55,7 @@ impl SqliteStateStore {
             .await
             .map_err(|e| StateError::Database(e.to_string()))?;
 
-        let store = Self { pool };
-        store.run_migrations().await?;
-
-        Ok(store)
+        Ok(Self { pool })
     }
 
     /// Create an in-memory SQLite store (for testing)
@@ -46,55 +67,296 @@ impl SqliteStateStore {
             .map_err(|e| StateError::Database(e.to_string()))?;
 
         let store = Self { pool };
-        store.run_migrations().await?;
+        store.migrate().await?;
 
         Ok(store)
     }
 
-    async fn run_migrations(&self) -> Result<(), StateError> {
-        // Create tables
-        sqlx::query(
-            r#"
-            CREATE TABLE IF NOT EXISTS account_state (
-                account TEXT PRIMARY KEY,
-                since_id TEXT,
-                updated_at TEXT NOT NULL
-            )
-            "#,
+    /// Migrations not yet applied to this database
+    ///
+    /// Fails when the database was migrated by a newer binary or an applied
+    /// migration no longer matches its checksum.
+    pub async fn pending_migrations(&self) -> Result<Vec<Migration>, StateError> {
+        let (tables,): (i64,) = sqlx::query_as(
+            "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = 'schema_migrations'",
         )
-        .execute(&self.pool)
+        .fetch_one(&self.pool)
         .await
         .map_err(|e| StateError::Database(e.to_string()))?;
+        if tables == 0 {
+            return state_migrations::pending(&[]);
+        }
 
-        sqlx::query(
-            r#"
-            CREATE TABLE IF NOT EXISTS published_records (
-                id TEXT PRIMARY KEY,
-                source_post_id TEXT NOT NULL,
-                taxonomy_hash TEXT NOT NULL,
-                x_post_id TEXT,
-                nostr_event_id TEXT,
-                published_at TEXT NOT NULL,
-                UNIQUE(source_post_id, taxonomy_hash)
-            )
-            "#,
+        let rows: Vec<(i64, String, String)> = sqlx::query_as(
+            "SELECT version, name, checksum FROM schema_migrations ORDER BY version",
         )
-        .execute(&self.pool)
+        .fetch_all(&self.pool)
         .await
         .map_err(|e| StateError::Database(e.to_string()))?;
+        let applied: Vec<AppliedMigration> = rows
+            .into_iter()
+            .map(|(version, name, checksum)| AppliedMigration {
+                version,
+                name,
+                checksum,
+            })
+            .collect();
 
-        // Create index for lookups
-        sqlx::query(
-            r#"
-            CREATE INDEX IF NOT EXISTS idx_published_lookup
-            ON published_records(source_post_id, taxonomy_hash)
-            "#,
+        state_migrations::pending(&applied)
+    }
+
+    /// Apply pending migrations, each in its own transaction, and return them
+    pub async fn migrate(&self) -> Result<Vec<Migration>, StateError> {
+        sqlx::raw_sql(CREATE_SCHEMA_MIGRATIONS)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| StateError::Database(e.to_string()))?;
+
+        let pending = self.pending_migrations().await?;
+        for migration in &pending {
+            let applied_at = OffsetDateTime::now_utc()
+                .format(&time::format_description::well_known::Rfc3339)
+                .map_err(|e| StateError::Serialization(e.to_string()))?;
+
+            let mut tx = self
+                .pool
+                .begin()
+                .await
+                .map_err(|e| StateError::Database(e.to_string()))?;
+            sqlx::raw_sql(migration.sql)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| {
+                    StateError::Migration(format!(
+                        "migration {} ({}) failed: {}",
+                        migration.version, migration.name, e
+                    ))
+                })?;
+            // In the same transaction, so a failed copy is retried with the migration
+            if migration.version == 1 {
+                Self::migrate_legacy_targets(&mut tx).await?;
+            }
+            sqlx::query(
+                "INSERT INTO schema_migrations (version, name, checksum, applied_at) VALUES (?, ?, ?, ?)",
+            )
+            .bind(migration.version)
+            .bind(migration.name)
+            .bind(migration.checksum())
+            .bind(&applied_at)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| StateError::Database(e.to_string()))?;
+            tx.commit()
+                .await
+                .map_err(|e| StateError::Database(e.to_string()))?;
+
+            tracing::info!(
+                version = migration.version,
+                name = migration.name,
+                "Applied state migration"
+            );
+        }
+
+        Ok(pending)
+    }
+
+    /// Copy the X and Nostr IDs of databases created before per-platform
+    /// targets into `published_targets`
+    async fn migrate_legacy_targets(conn: &mut SqliteConnection) -> Result<(), StateError> {
+        let columns: Vec<(String,)> =
+            sqlx::query_as("SELECT name FROM pragma_table_info('published_records')")
+                .fetch_all(&mut *conn)
+                .await
+                .map_err(|e| StateError::Database(e.to_string()))?;
+        if !columns.iter().any(|(name,)| name == "x_post_id") {
+            return Ok(());
+        }
+
+        for (platform, column) in [("x", "x_post_id"), ("nostr", "nostr_event_id")] {
+            sqlx::query(&format!(
+                r#"
+                INSERT OR IGNORE INTO published_targets
+                (record_id, platform, target_id, url, published_at)
+                SELECT id, ?, {column}, NULL, published_at
+                FROM published_records
+                WHERE {column} IS NOT NULL
+                "#
+            ))
+            .bind(platform)
+            .execute(&mut *conn)
+            .await
+            .map_err(|e| StateError::Database(e.to_string()))?;
+        }
+
+        Ok(())
+    }
+
+    /// Delete published records from before `before`, with their targets,
+    /// classifications and queued retries, along with post retries last
+    /// updated before `before` and expired claims and deferrals
+    ///
+    /// The tags of the deleted classifications are added to the per-tag
+    /// monthly counts in the same transaction, so totals survive the prune.
+    pub async fn prune(&self, before: OffsetDateTime) -> Result<PruneReport, StateError> {
+        let old: HashSet<Uuid> = self
+            .list_published()
+            .await?
+            .into_iter()
+            .filter(|r| r.published_at < before)
+            .map(|r| r.id)
+            .collect();
+        let classifications = self.list_classifications().await?;
+        let counts = count_tags(
+            classifications
+                .iter()
+                .filter(|c| old.contains(&c.record_id)),
+        );
+        let now = OffsetDateTime::now_utc();
+        let post_retries = keys_before(
+            sqlx::query_as("SELECT source_post_id, updated_at FROM post_retries")
+                .fetch_all(&self.pool)
+                .await
+                .map_err(|e| StateError::Database(e.to_string()))?,
+            before,
+        )?;
+        let deferrals = keys_before(
+            sqlx::query_as("SELECT scope, until FROM rate_limit_deferrals")
+                .fetch_all(&self.pool)
+                .await
+                .map_err(|e| StateError::Database(e.to_string()))?,
+            now,
+        )?;
+
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| StateError::Database(e.to_string()))?;
+        for count in &counts {
+            sqlx::query(
+                r#"
+                INSERT INTO tag_counts (tag, month, count)
+                VALUES (?, ?, ?)
+                ON CONFLICT(tag, month) DO UPDATE SET count = tag_counts.count + excluded.count
+                "#,
+            )
+            .bind(&count.tag)
+            .bind(&count.month)
+            .bind(count.count as i64)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| StateError::Database(e.to_string()))?;
+        }
+
+        let mut removed = [0u64; PRUNED_TABLES.len()];
+        for id in &old {
+            for (n, (table, column)) in PRUNED_TABLES.iter().enumerate() {
+                let result = sqlx::query(&format!("DELETE FROM {table} WHERE {column} = ?"))
+                    .bind(id.to_string())
+                    .execute(&mut *tx)
+                    .await
+                    .map_err(|e| StateError::Database(e.to_string()))?;
+                removed[n] += result.rows_affected();
+            }
+        }
+        let mut report = PruneReport::default();
+        for id in &post_retries {
+            let result = sqlx::query("DELETE FROM post_retries WHERE source_post_id = ?")
+                .bind(id)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| StateError::Database(e.to_string()))?;
+            report.post_retries += result.rows_affected();
+        }
+        for scope in &deferrals {
+            let result = sqlx::query("DELETE FROM rate_limit_deferrals WHERE scope = ?")
+                .bind(scope)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| StateError::Database(e.to_string()))?;
+            report.deferrals += result.rows_affected();
+        }
+        report.claims = sqlx::query("DELETE FROM post_claims WHERE expires_at <= ?")
+            .bind(unix_millis(now))
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| StateError::Database(e.to_string()))?
+            .rows_affected();
+        tx.commit()
+            .await
+            .map_err(|e| StateError::Database(e.to_string()))?;
+
+        let [targets, classifications, retries, published] = removed;
+        Ok(PruneReport {
+            published,
+            targets,
+            classifications,
+            retries,
+            ..report
+        })
+    }
+
+    /// Tag counts kept from pruned classifications, by tag and month
+    pub async fn tag_counts(&self) -> Result<Vec<TagCount>, StateError> {
+        let rows: Vec<(String, String, i64)> =
+            sqlx::query_as("SELECT tag, month, count FROM tag_counts ORDER BY tag, month")
+                .fetch_all(&self.pool)
+                .await
+                .map_err(|e| StateError::Database(e.to_string()))?;
+
+        Ok(rows
+            .into_iter()
+            .map(|(tag, month, count)| TagCount {
+                tag,
+                month,
+                count: count.max(0) as u64,
+            })
+            .collect())
+    }
+
+    /// Reclaim the space left by deleted rows
+    pub async fn vacuum(&self) -> Result<(), StateError> {
+        sqlx::raw_sql("VACUUM")
+            .execute(&self.pool)
+            .await
+            .map_err(|e| StateError::Database(e.to_string()))?;
+        Ok(())
+    }
+
+    /// Row counts and sizes of the state tables
+    pub async fn stats(&self) -> Result<StateStats, StateError> {
+        let mut tables = Vec::new();
+        for table in TABLES {
+            let (rows,): (i64,) = sqlx::query_as(&format!("SELECT COUNT(*) FROM {table}"))
+                .fetch_one(&self.pool)
+                .await
+                .map_err(|e| StateError::Database(e.to_string()))?;
+            // dbstat is optional in SQLite builds, so sizes may be missing
+            let bytes: Option<(Option<i64>,)> = sqlx::query_as(
+                "SELECT SUM(pgsize) FROM dbstat WHERE name IN (SELECT name FROM sqlite_master WHERE tbl_name = ?)",
+            )
+            .bind(table)
+            .fetch_one(&self.pool)
+            .await
+            .ok();
+            tables.push(TableStats {
+                table: table.to_string(),
+                rows: rows.max(0) as u64,
+                bytes: bytes.and_then(|(bytes,)| bytes).map(|b| b.max(0) as u64),
+            });
+        }
+
+        let (database_bytes,): (i64,) = sqlx::query_as(
+            "SELECT page_count * page_size FROM pragma_page_count(), pragma_page_size()",
         )
-        .execute(&self.pool)
+        .fetch_one(&self.pool)
         .await
         .map_err(|e| StateError::Database(e.to_string()))?;
 
-        Ok(())
+        Ok(StateStats {
+            tables,
+            database_bytes: database_bytes.max(0) as u64,
+        })
     }
 }
 
@@ -152,6 +414,17 @@ impl StateStore for SqliteStateStore {
         Ok(())
     }
 
+    async fn list_account_states(&self) -> Result<Vec<AccountState>, StateError> {
+        let rows: Vec<AccountStateRow> = sqlx::query_as(
+            "SELECT account, since_id, updated_at FROM account_state ORDER BY account",
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| StateError::Database(e.to_string()))?;
+
+        rows.into_iter().map(account_state_from_row).collect()
+    }
+
     async fn is_processed(
         &self,
         source_post_id: &str,
@@ -178,18 +451,14 @@ impl StateStore for SqliteStateStore {
         sqlx::query(
             r#"
             INSERT INTO published_records
-            (id, source_post_id, taxonomy_hash, x_post_id, nostr_event_id, published_at)
-            VALUES (?, ?, ?, ?, ?, ?)
-            ON CONFLICT(source_post_id, taxonomy_hash) DO UPDATE SET
-                x_post_id = COALESCE(excluded.x_post_id, published_records.x_post_id),
-                nostr_event_id = COALESCE(excluded.nostr_event_id, published_records.nostr_event_id)
+            (id, source_post_id, taxonomy_hash, published_at)
+            VALUES (?, ?, ?, ?)
+            ON CONFLICT(source_post_id, taxonomy_hash) DO NOTHING
             "#,
         )
         .bind(record.id.to_string())
         .bind(&record.source_post_id)
         .bind(&record.taxonomy_hash)
-        .bind(&record.x_post_id)
-        .bind(&record.nostr_event_id)
         .bind(&published_at_str)
         .execute(&self.pool)
         .await
@@ -203,16 +472,9 @@ impl StateStore for SqliteStateStore {
         source_post_id: &str,
         taxonomy_hash: &str,
     ) -> Result<Option<PublishedRecord>, StateError> {
-        let row: Option<(
-            String,
-            String,
-            String,
-            Option<String>,
-            Option<String>,
-            String,
-        )> = sqlx::query_as(
+        let row: Option<(String, String, String, String)> = sqlx::query_as(
             r#"
-                SELECT id, source_post_id, taxonomy_hash, x_post_id, nostr_event_id, published_at
+                SELECT id, source_post_id, taxonomy_hash, published_at
                 FROM published_records
                 WHERE source_post_id = ? AND taxonomy_hash = ?
                 "#,
@@ -224,14 +486,7 @@ impl StateStore for SqliteStateStore {
         .map_err(|e| StateError::Database(e.to_string()))?;
 
         match row {
-            Some((
-                id,
-                source_post_id,
-                taxonomy_hash,
-                x_post_id,
-                nostr_event_id,
-                published_at_str,
-            )) => {
+            Some((id, source_post_id, taxonomy_hash, published_at_str)) => {
                 let id =
                     Uuid::parse_str(&id).map_err(|e| StateError::Serialization(e.to_string()))?;
 
@@ -245,79 +500,1206 @@ impl StateStore for SqliteStateStore {
                     id,
                     source_post_id,
                     taxonomy_hash,
-                    x_post_id,
-                    nostr_event_id,
                     published_at,
                 }))
             }
             None => Ok(None),
         }
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    async fn list_published(&self) -> Result<Vec<PublishedRecord>, StateError> {
+        let rows: Vec<PublishedRecordRow> = sqlx::query_as(
+            "SELECT id, source_post_id, taxonomy_hash, published_at FROM published_records",
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| StateError::Database(e.to_string()))?;
 
-    #[tokio::test]
-    async fn test_account_state_roundtrip() {
-        let store = SqliteStateStore::in_memory().await.unwrap();
+        let mut records = rows
+            .into_iter()
+            .map(published_record_from_row)
+            .collect::<Result<Vec<_>, _>>()?;
+        records.sort_by_key(|r| r.published_at);
+        Ok(records)
+    }
 
-        let state = AccountState {
-            account: "testuser".to_string(),
-            since_id: Some("12345".to_string()),
-            updated_at: OffsetDateTime::now_utc(),
-        };
+    async fn record_target(&self, target: &PublishedTarget) -> Result<(), StateError> {
+        let published_at_str = target
+            .published_at
+            .format(&time::format_description::well_known::Rfc3339)
+            .map_err(|e| StateError::Serialization(e.to_string()))?;
 
-        store.set_account_state(&state).await.unwrap();
-        let retrieved = store.get_account_state("testuser").await.unwrap();
+        sqlx::query(
+            r#"
+            INSERT INTO published_targets
+            (record_id, platform, target_id, url, published_at)
+            VALUES (?, ?, ?, ?, ?)
+            ON CONFLICT(record_id, platform) DO UPDATE SET
+                target_id = excluded.target_id,
+                url = excluded.url,
+                published_at = excluded.published_at
+            "#,
+        )
+        .bind(target.record_id.to_string())
+        .bind(&target.platform)
+        .bind(&target.target_id)
+        .bind(&target.url)
+        .bind(&published_at_str)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| StateError::Database(e.to_string()))?;
 
-        assert!(retrieved.is_some());
-        assert_eq!(retrieved.unwrap().since_id, Some("12345".to_string()));
+        Ok(())
     }
 
-    #[tokio::test]
-    async fn test_published_record_roundtrip() {
-        let store = SqliteStateStore::in_memory().await.unwrap();
+    async fn list_targets(&self, record_id: Uuid) -> Result<Vec<PublishedTarget>, StateError> {
+        let rows: Vec<PublishedTargetRow> = sqlx::query_as(
+            r#"
+            SELECT record_id, platform, target_id, url, published_at
+            FROM published_targets
+            WHERE record_id = ?
+            ORDER BY platform
+            "#,
+        )
+        .bind(record_id.to_string())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| StateError::Database(e.to_string()))?;
 
-        let record = PublishedRecord {
-            id: Uuid::new_v4(),
-            source_post_id: "post123".to_string(),
-            taxonomy_hash: "hash456".to_string(),
-            x_post_id: Some("xpost789".to_string()),
-            nostr_event_id: None,
-            published_at: OffsetDateTime::now_utc(),
-        };
+        rows.into_iter().map(published_target_from_row).collect()
+    }
 
-        store.record_published(&record).await.unwrap();
+    async fn record_classification(&self, record: &ClassificationRecord) -> Result<(), StateError> {
+        let classified_at_str = record
+            .classified_at
+            .format(&time::format_description::well_known::Rfc3339)
+            .map_err(|e| StateError::Serialization(e.to_string()))?;
+        let source_post = serde_json::to_string(&record.source_post)
+            .map_err(|e| StateError::Serialization(e.to_string()))?;
+        let classification = serde_json::to_string(&record.classification)
+            .map_err(|e| StateError::Serialization(e.to_string()))?;
 
-        let is_processed = store.is_processed("post123", "hash456").await.unwrap();
-        assert!(is_processed);
+        sqlx::query(
+            r#"
+            INSERT INTO classifications
+            (record_id, source_post, classification, taxonomy_hash, classified_at)
+            VALUES (?, ?, ?, ?, ?)
+            ON CONFLICT(record_id) DO UPDATE SET
+                source_post = excluded.source_post,
+                classification = excluded.classification,
+                taxonomy_hash = excluded.taxonomy_hash,
+                classified_at = excluded.classified_at
+            "#,
+        )
+        .bind(record.record_id.to_string())
+        .bind(&source_post)
+        .bind(&classification)
+        .bind(&record.taxonomy_hash)
+        .bind(&classified_at_str)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| StateError::Database(e.to_string()))?;
 
-        let retrieved = store.get_published("post123", "hash456").await.unwrap();
-        assert!(retrieved.is_some());
-        assert_eq!(retrieved.unwrap().x_post_id, Some("xpost789".to_string()));
+        Ok(())
     }
 
-    #[tokio::test]
-    async fn test_upsert_account_state() {
-        let store = SqliteStateStore::in_memory().await.unwrap();
+    async fn list_classifications(&self) -> Result<Vec<ClassificationRecord>, StateError> {
+        let rows: Vec<ClassificationRow> = sqlx::query_as(
+            r#"
+            SELECT record_id, source_post, classification, taxonomy_hash, classified_at
+            FROM classifications
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| StateError::Database(e.to_string()))?;
 
-        let state1 = AccountState {
-            account: "testuser".to_string(),
-            since_id: Some("111".to_string()),
-            updated_at: OffsetDateTime::now_utc(),
+        let mut records = rows
+            .into_iter()
+            .map(classification_from_row)
+            .collect::<Result<Vec<_>, _>>()?;
+        records.sort_by_key(|r| r.classified_at);
+        Ok(records)
+    }
+
+    async fn set_retry(&self, retry: &PublishRetry) -> Result<(), StateError> {
+        let format = |value: OffsetDateTime| {
+            value
+                .format(&time::format_description::well_known::Rfc3339)
+                .map_err(|e| StateError::Serialization(e.to_string()))
         };
-        store.set_account_state(&state1).await.unwrap();
+        let source_post = serde_json::to_string(&retry.source_post)
+            .map_err(|e| StateError::Serialization(e.to_string()))?;
+        let classification = serde_json::to_string(&retry.classification)
+            .map_err(|e| StateError::Serialization(e.to_string()))?;
 
-        let state2 = AccountState {
-            account: "testuser".to_string(),
-            since_id: Some("222".to_string()),
-            updated_at: OffsetDateTime::now_utc(),
+        sqlx::query(
+            r#"
+            INSERT INTO publish_retries
+            (record_id, platform, source_post, classification, taxonomy_hash, attempts, status,
+             last_error, next_attempt_at, updated_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT(record_id, platform) DO UPDATE SET
+                source_post = excluded.source_post,
+                classification = excluded.classification,
+                taxonomy_hash = excluded.taxonomy_hash,
+                attempts = excluded.attempts,
+                status = excluded.status,
+                last_error = excluded.last_error,
+                next_attempt_at = excluded.next_attempt_at,
+                updated_at = excluded.updated_at
+            "#,
+        )
+        .bind(retry.record_id.to_string())
+        .bind(&retry.platform)
+        .bind(&source_post)
+        .bind(&classification)
+        .bind(&retry.taxonomy_hash)
+        .bind(i64::from(retry.attempts))
+        .bind(retry.status.as_str())
+        .bind(&retry.last_error)
+        .bind(format(retry.next_attempt_at)?)
+        .bind(format(retry.updated_at)?)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| StateError::Database(e.to_string()))?;
+
+        Ok(())
+    }
+
+    async fn list_retries(&self) -> Result<Vec<PublishRetry>, StateError> {
+        let rows: Vec<PublishRetryRow> = sqlx::query_as(
+            r#"
+            SELECT record_id, platform, source_post, classification, taxonomy_hash, attempts,
+                   status, last_error, next_attempt_at, updated_at
+            FROM publish_retries
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| StateError::Database(e.to_string()))?;
+
+        let mut retries = rows
+            .into_iter()
+            .map(publish_retry_from_row)
+            .collect::<Result<Vec<_>, _>>()?;
+        // RFC 3339 strings with offsets do not sort chronologically in SQL
+        retries.sort_by(|a, b| {
+            a.next_attempt_at
+                .cmp(&b.next_attempt_at)
+                .then_with(|| a.platform.cmp(&b.platform))
+        });
+        Ok(retries)
+    }
+
+    async fn list_retries_for_record(
+        &self,
+        record_id: Uuid,
+    ) -> Result<Vec<PublishRetry>, StateError> {
+        let rows: Vec<PublishRetryRow> = sqlx::query_as(
+            r#"
+            SELECT record_id, platform, source_post, classification, taxonomy_hash, attempts,
+                   status, last_error, next_attempt_at, updated_at
+            FROM publish_retries
+            WHERE record_id = ?
+            ORDER BY platform
+            "#,
+        )
+        .bind(record_id.to_string())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| StateError::Database(e.to_string()))?;
+
+        rows.into_iter().map(publish_retry_from_row).collect()
+    }
+
+    async fn delete_retry(&self, record_id: Uuid, platform: &str) -> Result<(), StateError> {
+        sqlx::query("DELETE FROM publish_retries WHERE record_id = ? AND platform = ?")
+            .bind(record_id.to_string())
+            .bind(platform)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| StateError::Database(e.to_string()))?;
+
+        Ok(())
+    }
+
+    async fn set_post_retry(&self, retry: &PostRetry) -> Result<(), StateError> {
+        let format = |value: OffsetDateTime| {
+            value
+                .format(&time::format_description::well_known::Rfc3339)
+                .map_err(|e| StateError::Serialization(e.to_string()))
         };
-        store.set_account_state(&state2).await.unwrap();
 
-        let retrieved = store.get_account_state("testuser").await.unwrap().unwrap();
-        assert_eq!(retrieved.since_id, Some("222".to_string()));
+        sqlx::query(
+            r#"
+            INSERT INTO post_retries
+            (source_post_id, account, attempts, status, last_error, next_attempt_at, updated_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT(source_post_id) DO UPDATE SET
+                account = excluded.account,
+                attempts = excluded.attempts,
+                status = excluded.status,
+                last_error = excluded.last_error,
+                next_attempt_at = excluded.next_attempt_at,
+                updated_at = excluded.updated_at
+            "#,
+        )
+        .bind(&retry.source_post_id)
+        .bind(&retry.account)
+        .bind(i64::from(retry.attempts))
+        .bind(retry.status.as_str())
+        .bind(&retry.last_error)
+        .bind(format(retry.next_attempt_at)?)
+        .bind(format(retry.updated_at)?)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| StateError::Database(e.to_string()))?;
+
+        Ok(())
+    }
+
+    async fn list_post_retries(&self, account: &str) -> Result<Vec<PostRetry>, StateError> {
+        let rows: Vec<PostRetryRow> = sqlx::query_as(
+            r#"
+            SELECT source_post_id, account, attempts, status, last_error, next_attempt_at,
+                   updated_at
+            FROM post_retries
+            WHERE account = ?
+            ORDER BY source_post_id
+            "#,
+        )
+        .bind(account)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| StateError::Database(e.to_string()))?;
+
+        rows.into_iter().map(post_retry_from_row).collect()
+    }
+
+    async fn delete_post_retry(&self, source_post_id: &str) -> Result<(), StateError> {
+        sqlx::query("DELETE FROM post_retries WHERE source_post_id = ?")
+            .bind(source_post_id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| StateError::Database(e.to_string()))?;
+
+        Ok(())
+    }
+
+    async fn get_resolved_account(
+        &self,
+        account: &str,
+    ) -> Result<Option<ResolvedAccount>, StateError> {
+        let row: Option<ResolvedAccountRow> = sqlx::query_as(
+            r#"
+            SELECT account, user_id, status, renamed_to, resolved_at
+            FROM resolved_accounts
+            WHERE account = ?
+            "#,
+        )
+        .bind(account.to_ascii_lowercase())
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| StateError::Database(e.to_string()))?;
+
+        row.map(resolved_account_from_row).transpose()
+    }
+
+    async fn set_resolved_account(&self, resolved: &ResolvedAccount) -> Result<(), StateError> {
+        let resolved_at_str = resolved
+            .resolved_at
+            .format(&time::format_description::well_known::Rfc3339)
+            .map_err(|e| StateError::Serialization(e.to_string()))?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO resolved_accounts (account, user_id, status, renamed_to, resolved_at)
+            VALUES (?, ?, ?, ?, ?)
+            ON CONFLICT(account) DO UPDATE SET
+                user_id = COALESCE(excluded.user_id, resolved_accounts.user_id),
+                status = excluded.status,
+                renamed_to = excluded.renamed_to,
+                resolved_at = excluded.resolved_at
+            "#,
+        )
+        .bind(resolved.account.to_ascii_lowercase())
+        .bind(&resolved.user_id)
+        .bind(resolved.status.as_str())
+        .bind(&resolved.renamed_to)
+        .bind(&resolved_at_str)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| StateError::Database(e.to_string()))?;
+
+        Ok(())
+    }
+
+    async fn list_resolved_accounts(&self) -> Result<Vec<ResolvedAccount>, StateError> {
+        let rows: Vec<ResolvedAccountRow> = sqlx::query_as(
+            r#"
+            SELECT account, user_id, status, renamed_to, resolved_at
+            FROM resolved_accounts
+            ORDER BY account
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| StateError::Database(e.to_string()))?;
+
+        rows.into_iter().map(resolved_account_from_row).collect()
+    }
+
+    async fn list_deferrals(&self) -> Result<Vec<RateLimitDeferral>, StateError> {
+        let rows: Vec<(String, String)> = sqlx::query_as(
+            r#"
+            SELECT scope, until
+            FROM rate_limit_deferrals
+            ORDER BY scope
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| StateError::Database(e.to_string()))?;
+
+        rows.into_iter()
+            .map(|(scope, until_str)| {
+                let until = OffsetDateTime::parse(
+                    &until_str,
+                    &time::format_description::well_known::Rfc3339,
+                )
+                .map_err(|e| StateError::Serialization(e.to_string()))?;
+                Ok(RateLimitDeferral { scope, until })
+            })
+            .collect()
+    }
+
+    async fn set_deferral(&self, deferral: &RateLimitDeferral) -> Result<(), StateError> {
+        let until_str = deferral
+            .until
+            .format(&time::format_description::well_known::Rfc3339)
+            .map_err(|e| StateError::Serialization(e.to_string()))?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO rate_limit_deferrals (scope, until)
+            VALUES (?, ?)
+            ON CONFLICT(scope) DO UPDATE SET until = excluded.until
+            "#,
+        )
+        .bind(&deferral.scope)
+        .bind(&until_str)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| StateError::Database(e.to_string()))?;
+
+        Ok(())
+    }
+
+    async fn get_feed_cursor(&self, feed: &str) -> Result<Option<FeedCursor>, StateError> {
+        let row: Option<FeedCursorRow> = sqlx::query_as(
+            r#"
+            SELECT feed, etag, last_modified, newest_id, updated_at
+            FROM feed_cursors
+            WHERE feed = ?
+            "#,
+        )
+        .bind(feed)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| StateError::Database(e.to_string()))?;
+
+        match row {
+            Some((feed, etag, last_modified, newest_id, updated_at_str)) => {
+                let updated_at = OffsetDateTime::parse(
+                    &updated_at_str,
+                    &time::format_description::well_known::Rfc3339,
+                )
+                .map_err(|e| StateError::Serialization(e.to_string()))?;
+
+                Ok(Some(FeedCursor {
+                    feed,
+                    etag,
+                    last_modified,
+                    newest_id,
+                    updated_at,
+                }))
+            }
+            None => Ok(None),
+        }
+    }
+
+    async fn set_feed_cursor(&self, cursor: &FeedCursor) -> Result<(), StateError> {
+        let updated_at_str = cursor
+            .updated_at
+            .format(&time::format_description::well_known::Rfc3339)
+            .map_err(|e| StateError::Serialization(e.to_string()))?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO feed_cursors (feed, etag, last_modified, newest_id, updated_at)
+            VALUES (?, ?, ?, ?, ?)
+            ON CONFLICT(feed) DO UPDATE SET
+                etag = excluded.etag,
+                last_modified = excluded.last_modified,
+                newest_id = excluded.newest_id,
+                updated_at = excluded.updated_at
+            "#,
+        )
+        .bind(&cursor.feed)
+        .bind(&cursor.etag)
+        .bind(&cursor.last_modified)
+        .bind(&cursor.newest_id)
+        .bind(&updated_at_str)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| StateError::Database(e.to_string()))?;
+
+        Ok(())
+    }
+
+    async fn get_relay_status(&self, relay: &str) -> Result<Option<RelayStatus>, StateError> {
+        let row: Option<RelayStatusRow> = sqlx::query_as(
+            r#"
+            SELECT relay, successes, failures, last_success_at, last_error, updated_at
+            FROM relay_statuses
+            WHERE relay = ?
+            "#,
+        )
+        .bind(relay)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| StateError::Database(e.to_string()))?;
+
+        row.map(relay_status_from_row).transpose()
+    }
+
+    async fn set_relay_status(&self, status: &RelayStatus) -> Result<(), StateError> {
+        let format = |value: OffsetDateTime| {
+            value
+                .format(&time::format_description::well_known::Rfc3339)
+                .map_err(|e| StateError::Serialization(e.to_string()))
+        };
+        let last_success_at_str = status.last_success_at.map(format).transpose()?;
+        let updated_at_str = format(status.updated_at)?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO relay_statuses
+                (relay, successes, failures, last_success_at, last_error, updated_at)
+            VALUES (?, ?, ?, ?, ?, ?)
+            ON CONFLICT(relay) DO UPDATE SET
+                successes = excluded.successes,
+                failures = excluded.failures,
+                last_success_at = excluded.last_success_at,
+                last_error = excluded.last_error,
+                updated_at = excluded.updated_at
+            "#,
+        )
+        .bind(&status.relay)
+        .bind(status.successes as i64)
+        .bind(status.failures as i64)
+        .bind(&last_success_at_str)
+        .bind(&status.last_error)
+        .bind(&updated_at_str)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| StateError::Database(e.to_string()))?;
+
+        Ok(())
+    }
+
+    async fn list_relay_statuses(&self) -> Result<Vec<RelayStatus>, StateError> {
+        let rows: Vec<RelayStatusRow> = sqlx::query_as(
+            r#"
+            SELECT relay, successes, failures, last_success_at, last_error, updated_at
+            FROM relay_statuses
+            ORDER BY relay
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| StateError::Database(e.to_string()))?;
+
+        rows.into_iter().map(relay_status_from_row).collect()
+    }
+
+    async fn claim_post(
+        &self,
+        source_post_id: &str,
+        taxonomy_hash: &str,
+        worker_id: &str,
+        lease: Duration,
+    ) -> Result<bool, StateError> {
+        let (now, expires_at) = lease_window(lease);
+
+        let result = sqlx::query(
+            r#"
+            INSERT INTO post_claims (source_post_id, taxonomy_hash, worker_id, expires_at)
+            VALUES (?, ?, ?, ?)
+            ON CONFLICT(source_post_id, taxonomy_hash) DO UPDATE SET
+                worker_id = excluded.worker_id,
+                expires_at = excluded.expires_at
+            WHERE post_claims.worker_id = excluded.worker_id OR post_claims.expires_at <= ?
+            "#,
+        )
+        .bind(source_post_id)
+        .bind(taxonomy_hash)
+        .bind(worker_id)
+        .bind(expires_at)
+        .bind(now)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| StateError::Database(e.to_string()))?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn release_post(
+        &self,
+        source_post_id: &str,
+        taxonomy_hash: &str,
+        worker_id: &str,
+    ) -> Result<(), StateError> {
+        sqlx::query(
+            "DELETE FROM post_claims WHERE source_post_id = ? AND taxonomy_hash = ? AND worker_id = ?",
+        )
+        .bind(source_post_id)
+        .bind(taxonomy_hash)
+        .bind(worker_id)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| StateError::Database(e.to_string()))?;
+
+        Ok(())
+    }
+
+    async fn claim_account(
+        &self,
+        account: &str,
+        worker_id: &str,
+        lease: Duration,
+    ) -> Result<bool, StateError> {
+        let (now, expires_at) = lease_window(lease);
+
+        let result = sqlx::query(
+            r#"
+            INSERT INTO account_leases (account, worker_id, expires_at)
+            VALUES (?, ?, ?)
+            ON CONFLICT(account) DO UPDATE SET
+                worker_id = excluded.worker_id,
+                expires_at = excluded.expires_at
+            WHERE account_leases.worker_id = excluded.worker_id OR account_leases.expires_at <= ?
+            "#,
+        )
+        .bind(account)
+        .bind(worker_id)
+        .bind(expires_at)
+        .bind(now)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| StateError::Database(e.to_string()))?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state_migrations::MIGRATIONS;
+    use news_tagger_domain::{AccountStatus, RetryStatus};
+
+    #[tokio::test]
+    async fn test_account_state_roundtrip() {
+        let store = SqliteStateStore::in_memory().await.unwrap();
+
+        let state = AccountState {
+            account: "testuser".to_string(),
+            since_id: Some("12345".to_string()),
+            updated_at: OffsetDateTime::now_utc(),
+        };
+
+        store.set_account_state(&state).await.unwrap();
+        let retrieved = store.get_account_state("testuser").await.unwrap();
+
+        assert!(retrieved.is_some());
+        assert_eq!(retrieved.unwrap().since_id, Some("12345".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_deferral_upsert() {
+        let store = SqliteStateStore::in_memory().await.unwrap();
+        let now = OffsetDateTime::now_utc().replace_nanosecond(0).unwrap();
+
+        for until in [now, now + time::Duration::minutes(15)] {
+            store
+                .set_deferral(&RateLimitDeferral {
+                    scope: "account:testuser".to_string(),
+                    until,
+                })
+                .await
+                .unwrap();
+        }
+
+        let deferrals = store.list_deferrals().await.unwrap();
+        assert_eq!(deferrals.len(), 1);
+        assert_eq!(deferrals[0].until, now + time::Duration::minutes(15));
+    }
+
+    #[tokio::test]
+    async fn test_relay_status_roundtrip() {
+        let store = SqliteStateStore::in_memory().await.unwrap();
+        let now = OffsetDateTime::now_utc().replace_nanosecond(0).unwrap();
+
+        let status = RelayStatus {
+            relay: "wss://relay.example.com".to_string(),
+            successes: 3,
+            failures: 1,
+            last_success_at: Some(now),
+            last_error: Some("auth-required: sign in first".to_string()),
+            updated_at: now,
+        };
+        store.set_relay_status(&status).await.unwrap();
+        store
+            .set_relay_status(&RelayStatus {
+                successes: 4,
+                last_error: None,
+                ..status.clone()
+            })
+            .await
+            .unwrap();
+
+        let retrieved = store
+            .get_relay_status("wss://relay.example.com")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(retrieved.successes, 4);
+        assert_eq!(retrieved.failures, 1);
+        assert_eq!(retrieved.last_success_at, Some(now));
+        assert_eq!(retrieved.last_error, None);
+        assert_eq!(store.list_relay_statuses().await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_published_record_roundtrip() {
+        let store = SqliteStateStore::in_memory().await.unwrap();
+
+        let record = PublishedRecord {
+            id: Uuid::new_v4(),
+            source_post_id: "post123".to_string(),
+            taxonomy_hash: "hash456".to_string(),
+            published_at: OffsetDateTime::now_utc(),
+        };
+
+        store.record_published(&record).await.unwrap();
+        let target = PublishedTarget {
+            record_id: record.id,
+            platform: "x".to_string(),
+            target_id: "xpost789".to_string(),
+            url: Some("https://x.com/i/status/xpost789".to_string()),
+            published_at: OffsetDateTime::now_utc(),
+        };
+        store.record_target(&target).await.unwrap();
+
+        let is_processed = store.is_processed("post123", "hash456").await.unwrap();
+        assert!(is_processed);
+
+        // Recording again keeps the original record so its targets stay attached
+        store
+            .record_published(&PublishedRecord {
+                id: Uuid::new_v4(),
+                ..record.clone()
+            })
+            .await
+            .unwrap();
+        let retrieved = store.get_published("post123", "hash456").await.unwrap();
+        assert_eq!(retrieved.unwrap().id, record.id);
+
+        let targets = store.list_targets(record.id).await.unwrap();
+        assert_eq!(targets.len(), 1);
+        assert_eq!(targets[0].platform, "x");
+        assert_eq!(targets[0].target_id, "xpost789");
+        assert_eq!(targets[0].url, target.url);
+    }
+
+    #[tokio::test]
+    async fn test_publish_retry_roundtrip() {
+        let store = SqliteStateStore::in_memory().await.unwrap();
+        let now = OffsetDateTime::from_unix_timestamp(1_700_000_000).unwrap();
+        let retry = PublishRetry {
+            record_id: Uuid::new_v4(),
+            platform: "nostr".to_string(),
+            source_post: news_tagger_domain::SourcePost {
+                id: "post1".to_string(),
+                text: "Some text".to_string(),
+                author: "user".to_string(),
+                url: "https://example.com/post1".to_string(),
+                created_at: now,
+                is_repost: false,
+                is_reply: false,
+                reply_to_id: None,
+                context: vec![],
+            },
+            classification: news_tagger_domain::ClassifyOutput::new("Summary".to_string(), vec![]),
+            taxonomy_hash: "hash".to_string(),
+            attempts: 1,
+            status: RetryStatus::Pending,
+            last_error: "relay down".to_string(),
+            next_attempt_at: now,
+            updated_at: now,
+        };
+        store.set_retry(&retry).await.unwrap();
+        store
+            .set_retry(&PublishRetry {
+                attempts: 2,
+                status: RetryStatus::Failed,
+                ..retry.clone()
+            })
+            .await
+            .unwrap();
+
+        let retries = store.list_retries().await.unwrap();
+        assert_eq!(retries.len(), 1);
+        assert_eq!(retries[0].attempts, 2);
+        assert_eq!(retries[0].status, RetryStatus::Failed);
+        assert_eq!(retries[0].source_post.id, "post1");
+        assert_eq!(retries[0].next_attempt_at, now);
+        assert_eq!(
+            store
+                .list_retries_for_record(retry.record_id)
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+        assert!(
+            store
+                .list_retries_for_record(Uuid::new_v4())
+                .await
+                .unwrap()
+                .is_empty()
+        );
+
+        store.delete_retry(retry.record_id, "nostr").await.unwrap();
+        assert!(store.list_retries().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_post_retry_roundtrip() {
+        let store = SqliteStateStore::in_memory().await.unwrap();
+        let now = OffsetDateTime::from_unix_timestamp(1_700_000_000).unwrap();
+        let retry = PostRetry {
+            source_post_id: "post1".to_string(),
+            account: "alice".to_string(),
+            attempts: 1,
+            status: RetryStatus::Pending,
+            last_error: "Classification failed".to_string(),
+            next_attempt_at: now,
+            updated_at: now,
+        };
+        store.set_post_retry(&retry).await.unwrap();
+        store
+            .set_post_retry(&PostRetry {
+                attempts: 2,
+                status: RetryStatus::Failed,
+                ..retry.clone()
+            })
+            .await
+            .unwrap();
+
+        assert!(store.list_post_retries("bob").await.unwrap().is_empty());
+        let retries = store.list_post_retries("alice").await.unwrap();
+        assert_eq!(retries.len(), 1);
+        assert_eq!(retries[0].account, "alice");
+        assert_eq!(retries[0].attempts, 2);
+        assert_eq!(retries[0].status, RetryStatus::Failed);
+        assert_eq!(retries[0].next_attempt_at, now);
+
+        store.delete_post_retry("post1").await.unwrap();
+        assert!(store.list_post_retries("alice").await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_classification_roundtrip() {
+        let store = SqliteStateStore::in_memory().await.unwrap();
+        let now = OffsetDateTime::from_unix_timestamp(1_700_000_000).unwrap();
+        let record = ClassificationRecord {
+            record_id: Uuid::new_v4(),
+            source_post: news_tagger_domain::SourcePost {
+                id: "post1".to_string(),
+                text: "Some text".to_string(),
+                author: "user".to_string(),
+                url: "https://example.com/post1".to_string(),
+                created_at: now,
+                is_repost: false,
+                is_reply: false,
+                reply_to_id: None,
+                context: vec![],
+            },
+            classification: news_tagger_domain::ClassifyOutput::new("First".to_string(), vec![]),
+            taxonomy_hash: "hash".to_string(),
+            classified_at: now,
+        };
+        store.record_classification(&record).await.unwrap();
+        store
+            .record_classification(&ClassificationRecord {
+                classification: news_tagger_domain::ClassifyOutput::new(
+                    "Second".to_string(),
+                    vec![],
+                ),
+                ..record.clone()
+            })
+            .await
+            .unwrap();
+
+        let records = store.list_classifications().await.unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].classification.summary, "Second");
+        assert_eq!(records[0].classified_at, now);
+    }
+
+    #[tokio::test]
+    async fn test_migrations_are_recorded_and_checked() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("state.sqlite");
+
+        let store = SqliteStateStore::open(&path).await.unwrap();
+        assert_eq!(
+            store.pending_migrations().await.unwrap().len(),
+            MIGRATIONS.len()
+        );
+        store.migrate().await.unwrap();
+        assert!(store.pending_migrations().await.unwrap().is_empty());
+
+        sqlx::query("INSERT INTO schema_migrations VALUES (9999, 'future', '', '')")
+            .execute(&store.pool)
+            .await
+            .unwrap();
+        drop(store);
+
+        let err = SqliteStateStore::new(&path).await.err().unwrap();
+        assert!(matches!(err, StateError::Migration(_)));
+    }
+
+    #[tokio::test]
+    async fn test_legacy_published_ids_become_targets() {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::query(
+            r#"
+            CREATE TABLE published_records (
+                id TEXT PRIMARY KEY,
+                source_post_id TEXT NOT NULL,
+                taxonomy_hash TEXT NOT NULL,
+                x_post_id TEXT,
+                nostr_event_id TEXT,
+                published_at TEXT NOT NULL,
+                UNIQUE(source_post_id, taxonomy_hash)
+            )
+            "#,
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        let id = Uuid::new_v4();
+        sqlx::query(
+            "INSERT INTO published_records VALUES (?, 'post1', 'hash', 'x1', NULL, '2024-01-01T00:00:00Z')",
+        )
+        .bind(id.to_string())
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let store = SqliteStateStore { pool };
+        assert_eq!(store.migrate().await.unwrap().len(), MIGRATIONS.len());
+        // Running again applies nothing and keeps the targets
+        assert!(store.migrate().await.unwrap().is_empty());
+
+        let targets = store.list_targets(id).await.unwrap();
+        assert_eq!(targets.len(), 1);
+        assert_eq!(targets[0].platform, "x");
+        assert_eq!(targets[0].target_id, "x1");
+
+        // New records still insert with the legacy columns present
+        store
+            .record_published(&PublishedRecord {
+                id: Uuid::new_v4(),
+                source_post_id: "post2".to_string(),
+                taxonomy_hash: "hash".to_string(),
+                published_at: OffsetDateTime::now_utc(),
+            })
+            .await
+            .unwrap();
+        assert!(store.is_processed("post2", "hash").await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_failed_legacy_copy_leaves_initial_migration_pending() {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        // A targets table the copy cannot insert into
+        sqlx::raw_sql(
+            r#"
+            CREATE TABLE published_records (
+                id TEXT PRIMARY KEY,
+                source_post_id TEXT NOT NULL,
+                taxonomy_hash TEXT NOT NULL,
+                x_post_id TEXT,
+                nostr_event_id TEXT,
+                published_at TEXT NOT NULL,
+                UNIQUE(source_post_id, taxonomy_hash)
+            );
+            CREATE TABLE published_targets (record_id TEXT NOT NULL);
+            INSERT INTO published_records
+            VALUES ('00000000-0000-0000-0000-000000000001', 'post1', 'hash', 'x1', NULL,
+                    '2024-01-01T00:00:00Z');
+            "#,
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let store = SqliteStateStore { pool };
+        assert!(store.migrate().await.is_err());
+        assert_eq!(
+            store.pending_migrations().await.unwrap(),
+            MIGRATIONS.to_vec()
+        );
+
+        // Once the table is fixed the copy runs with the migration
+        sqlx::raw_sql("DROP TABLE published_targets")
+            .execute(&store.pool)
+            .await
+            .unwrap();
+        assert_eq!(store.migrate().await.unwrap().len(), MIGRATIONS.len());
+        let targets = store.list_targets(Uuid::from_u128(1)).await.unwrap();
+        assert_eq!(targets[0].target_id, "x1");
+    }
+
+    #[tokio::test]
+    async fn test_resolved_account_keeps_user_id_when_lookup_fails() {
+        let store = SqliteStateStore::in_memory().await.unwrap();
+
+        store
+            .set_resolved_account(&ResolvedAccount {
+                account: "TestUser".to_string(),
+                user_id: Some("42".to_string()),
+                status: AccountStatus::Active,
+                renamed_to: None,
+                resolved_at: OffsetDateTime::now_utc(),
+            })
+            .await
+            .unwrap();
+
+        store
+            .set_resolved_account(&ResolvedAccount {
+                account: "testuser".to_string(),
+                user_id: None,
+                status: AccountStatus::Suspended,
+                renamed_to: None,
+                resolved_at: OffsetDateTime::now_utc(),
+            })
+            .await
+            .unwrap();
+
+        let resolved = store
+            .get_resolved_account("testuser")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(resolved.user_id.as_deref(), Some("42"));
+        assert_eq!(resolved.status, AccountStatus::Suspended);
+        assert_eq!(store.list_resolved_accounts().await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_upsert_account_state() {
+        let store = SqliteStateStore::in_memory().await.unwrap();
+
+        let state1 = AccountState {
+            account: "testuser".to_string(),
+            since_id: Some("111".to_string()),
+            updated_at: OffsetDateTime::now_utc(),
+        };
+        store.set_account_state(&state1).await.unwrap();
+
+        let state2 = AccountState {
+            account: "testuser".to_string(),
+            since_id: Some("222".to_string()),
+            updated_at: OffsetDateTime::now_utc(),
+        };
+        store.set_account_state(&state2).await.unwrap();
+
+        let retrieved = store.get_account_state("testuser").await.unwrap().unwrap();
+        assert_eq!(retrieved.since_id, Some("222".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_post_claims_and_account_leases() {
+        let store = SqliteStateStore::in_memory().await.unwrap();
+        let minute = Duration::from_secs(60);
+
+        assert!(store.claim_post("1", "hash", "a", minute).await.unwrap());
+        assert!(!store.claim_post("1", "hash", "b", minute).await.unwrap());
+        assert!(store.claim_post("1", "hash", "a", minute).await.unwrap());
+        assert!(store.claim_post("1", "other", "b", minute).await.unwrap());
+
+        store.release_post("1", "hash", "b").await.unwrap();
+        assert!(!store.claim_post("1", "hash", "b", minute).await.unwrap());
+        store.release_post("1", "hash", "a").await.unwrap();
+        assert!(
+            store
+                .claim_post("1", "hash", "b", Duration::ZERO)
+                .await
+                .unwrap()
+        );
+
+        // An expired lease goes to whoever asks next
+        assert!(store.claim_post("1", "hash", "a", minute).await.unwrap());
+
+        assert!(
+            store
+                .claim_account("alice", "a", Duration::ZERO)
+                .await
+                .unwrap()
+        );
+        assert!(store.claim_account("alice", "b", minute).await.unwrap());
+        assert!(!store.claim_account("alice", "a", minute).await.unwrap());
+        assert!(store.claim_account("alice", "b", minute).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_prune_keeps_tag_counts() {
+        let store = SqliteStateStore::in_memory().await.unwrap();
+        let now = OffsetDateTime::from_unix_timestamp(1_700_000_000).unwrap();
+        let old = now - time::Duration::days(120);
+
+        for (post_id, at) in [("old", old), ("new", now)] {
+            let record = PublishedRecord {
+                id: Uuid::new_v4(),
+                source_post_id: post_id.to_string(),
+                taxonomy_hash: "hash".to_string(),
+                published_at: at,
+            };
+            store.record_published(&record).await.unwrap();
+            store
+                .record_target(&PublishedTarget {
+                    record_id: record.id,
+                    platform: "x".to_string(),
+                    target_id: format!("x-{post_id}"),
+                    url: None,
+                    published_at: at,
+                })
+                .await
+                .unwrap();
+            store
+                .record_classification(&ClassificationRecord {
+                    record_id: record.id,
+                    source_post: news_tagger_domain::SourcePost {
+                        id: post_id.to_string(),
+                        text: "Some text".to_string(),
+                        author: "user".to_string(),
+                        url: format!("https://example.com/{post_id}"),
+                        created_at: at,
+                        is_repost: false,
+                        is_reply: false,
+                        reply_to_id: None,
+                        context: vec![],
+                    },
+                    classification: news_tagger_domain::ClassifyOutput::new(
+                        "Summary".to_string(),
+                        vec![news_tagger_domain::TagMatch {
+                            id: "spin".to_string(),
+                            confidence: 0.8,
+                            rationale: String::new(),
+                            evidence: vec![],
+                        }],
+                    ),
+                    taxonomy_hash: "hash".to_string(),
+                    classified_at: at,
+                })
+                .await
+                .unwrap();
+        }
+
+        for (post_id, at) in [("old", old), ("new", now)] {
+            store
+                .set_post_retry(&PostRetry {
+                    source_post_id: post_id.to_string(),
+                    account: "alice".to_string(),
+                    attempts: 1,
+                    status: RetryStatus::Pending,
+                    last_error: "Classification failed".to_string(),
+                    next_attempt_at: at,
+                    updated_at: at,
+                })
+                .await
+                .unwrap();
+        }
+        for (scope, until) in [
+            ("ended", now),
+            (
+                "active",
+                OffsetDateTime::now_utc() + time::Duration::hours(1),
+            ),
+        ] {
+            store
+                .set_deferral(&RateLimitDeferral {
+                    scope: scope.to_string(),
+                    until,
+                })
+                .await
+                .unwrap();
+        }
+        for (post_id, lease) in [
+            ("expired", Duration::ZERO),
+            ("held", Duration::from_secs(60)),
+        ] {
+            assert!(store.claim_post(post_id, "hash", "a", lease).await.unwrap());
+        }
+
+        let report = store.prune(now - time::Duration::days(90)).await.unwrap();
+        assert_eq!(
+            report,
+            PruneReport {
+                published: 1,
+                targets: 1,
+                classifications: 1,
+                retries: 0,
+                post_retries: 1,
+                claims: 1,
+                deferrals: 1,
+            }
+        );
+        let published = store.list_published().await.unwrap();
+        assert_eq!(published.len(), 1);
+        assert_eq!(published[0].source_post_id, "new");
+        assert_eq!(store.list_classifications().await.unwrap().len(), 1);
+
+        let counts = store.tag_counts().await.unwrap();
+        assert_eq!(counts.len(), 1);
+        assert_eq!((counts[0].tag.as_str(), counts[0].count), ("spin", 1));
+
+        // Nothing left to prune the second time
+        let report = store.prune(now - time::Duration::days(90)).await.unwrap();
+        assert_eq!(report, PruneReport::default());
+
+        store.vacuum().await.unwrap();
+        let stats = store.stats().await.unwrap();
+        let rows = |name: &str| stats.tables.iter().find(|t| t.table == name).unwrap().rows;
+        assert_eq!(rows("published_records"), 1);
+        assert_eq!(rows("tag_counts"), 1);
+        assert_eq!(rows("post_retries"), 1);
+        assert_eq!(rows("post_claims"), 1);
+        assert_eq!(rows("rate_limit_deferrals"), 1);
+        assert!(stats.database_bytes > 0);
     }
 }
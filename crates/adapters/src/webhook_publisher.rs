@@ -0,0 +1,454 @@
+//! Webhook publisher pushing signed classification envelopes to HTTP endpoints
+
+use async_trait::async_trait;
+use hmac::{Hmac, Mac};
+use news_tagger_domain::{
+    ClassifyOutput, PublishError, PublishResult, Publisher, RenderedPost, SourcePost,
+};
+use reqwest::{Client, StatusCode};
+use secrecy::{ExposeSecret, SecretString};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::time::Duration;
+use time::OffsetDateTime;
+use time::format_description::well_known::Rfc3339;
+use uuid::Uuid;
+
+use crate::outbox::OutboxWriter;
+
+/// `sha256=<hex>` HMAC of `<timestamp>.<body>` keyed with the shared secret
+pub const SIGNATURE_HEADER: &str = "X-News-Tagger-Signature";
+/// Unix timestamp the signature covers, so receivers can reject replays
+pub const TIMESTAMP_HEADER: &str = "X-News-Tagger-Timestamp";
+/// Envelope ID, the same across retries and endpoints
+///
+/// It is derived from the source post ID and taxonomy hash, so a publish the
+/// run loop retries later carries the ID of the first attempt.
+pub const EVENT_ID_HEADER: &str = "X-News-Tagger-Event-Id";
+
+/// Publisher POSTing each classification as a signed JSON envelope to every configured URL
+///
+/// Network errors, `429` and `5xx` responses are retried with exponential
+/// backoff. Envelopes an endpoint never accepted are appended to the
+/// dead-letter file, if one is set, together with the URL and last error.
+/// When no endpoint accepted an envelope the publish fails and the run loop
+/// may retry it, so a dead letter can be superseded by a later delivery with
+/// the same event ID.
+pub struct WebhookPublisher {
+    client: Client,
+    urls: Vec<String>,
+    secret: SecretString,
+    max_attempts: u32,
+    base_delay: Duration,
+    dead_letter: Option<OutboxWriter>,
+}
+
+#[derive(Serialize)]
+struct WebhookEnvelope<'a> {
+    id: String,
+    event: &'static str,
+    created_at: String,
+    source_post: &'a SourcePost,
+    classification: &'a ClassifyOutput,
+    rendered_text: &'a str,
+    taxonomy_hash: &'a str,
+}
+
+#[derive(Serialize)]
+struct DeadLetter<'a> {
+    url: &'a str,
+    attempts: u32,
+    error: String,
+    failed_at: String,
+    envelope: serde_json::Value,
+}
+
+/// Why a delivery attempt failed, and whether trying again could help
+struct DeliveryError {
+    message: String,
+    retryable: bool,
+}
+
+impl WebhookPublisher {
+    pub fn new(urls: Vec<String>, secret: SecretString) -> Self {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(30))
+            .build()
+            .expect("Failed to build HTTP client");
+
+        Self {
+            client,
+            urls,
+            secret,
+            max_attempts: 3,
+            base_delay: Duration::from_secs(1),
+            dead_letter: None,
+        }
+    }
+
+    /// Attempts per endpoint, and the delay before the first retry (doubled after each)
+    pub fn with_retries(mut self, max_attempts: u32, base_delay: Duration) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self.base_delay = base_delay;
+        self
+    }
+
+    /// Append undeliverable envelopes to this JSONL file
+    pub fn with_dead_letter(mut self, writer: OutboxWriter) -> Self {
+        self.dead_letter = Some(writer);
+        self
+    }
+
+    fn sign(&self, timestamp: i64, body: &str) -> String {
+        let mut mac = Hmac::<Sha256>::new_from_slice(self.secret.expose_secret().as_bytes())
+            .expect("HMAC accepts keys of any length");
+        mac.update(timestamp.to_string().as_bytes());
+        mac.update(b".");
+        mac.update(body.as_bytes());
+        format!("sha256={:x}", mac.finalize().into_bytes())
+    }
+
+    async fn send(&self, url: &str, id: &str, body: &str) -> Result<(), DeliveryError> {
+        let timestamp = OffsetDateTime::now_utc().unix_timestamp();
+        let response = self
+            .client
+            .post(url)
+            .header("Content-Type", "application/json")
+            .header(EVENT_ID_HEADER, id)
+            .header(TIMESTAMP_HEADER, timestamp.to_string())
+            .header(SIGNATURE_HEADER, self.sign(timestamp, body))
+            .body(body.to_string())
+            .send()
+            .await
+            .map_err(|e| DeliveryError {
+                message: format!("Request failed: {}", e),
+                retryable: true,
+            })?;
+
+        let status = response.status();
+        if status.is_success() {
+            return Ok(());
+        }
+
+        let body = response.text().await.unwrap_or_default();
+        Err(DeliveryError {
+            message: format!("HTTP {}: {}", status, body),
+            retryable: status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error(),
+        })
+    }
+
+    /// Deliver to one endpoint, retrying transient failures; returns attempts and last error
+    async fn deliver(&self, url: &str, id: &str, body: &str) -> Result<(), (u32, String)> {
+        let mut delay = self.base_delay;
+        let mut attempt = 1;
+        loop {
+            match self.send(url, id, body).await {
+                Ok(()) => return Ok(()),
+                Err(e) if e.retryable && attempt < self.max_attempts => {
+                    tracing::warn!(
+                        url = %url,
+                        attempt,
+                        error = %e.message,
+                        "Webhook delivery failed, retrying"
+                    );
+                    tokio::time::sleep(delay).await;
+                    delay *= 2;
+                    attempt += 1;
+                }
+                Err(e) => return Err((attempt, e.message)),
+            }
+        }
+    }
+
+    async fn dead_letter(&self, url: &str, attempts: u32, error: String, body: &str) {
+        let Some(writer) = &self.dead_letter else {
+            return;
+        };
+
+        let entry = DeadLetter {
+            url,
+            attempts,
+            error,
+            failed_at: OffsetDateTime::now_utc()
+                .format(&Rfc3339)
+                .unwrap_or_default(),
+            envelope: serde_json::from_str(body).unwrap_or_default(),
+        };
+        if let Err(e) = writer.append(&entry).await {
+            tracing::error!(url = %url, error = %e, "Failed to write webhook dead letter");
+        }
+    }
+}
+
+/// Name-based UUIDv8 from the SHA-256 of the post ID and taxonomy hash
+fn event_id(source_post_id: &str, taxonomy_hash: &str) -> Uuid {
+    let mut hasher = Sha256::new();
+    hasher.update(source_post_id.as_bytes());
+    hasher.update([0]);
+    hasher.update(taxonomy_hash.as_bytes());
+    let digest = hasher.finalize();
+    let mut bytes = [0u8; 16];
+    bytes.copy_from_slice(&digest[..16]);
+    uuid::Builder::from_custom_bytes(bytes).into_uuid()
+}
+
+#[async_trait]
+impl Publisher for WebhookPublisher {
+    async fn publish(&self, post: &RenderedPost) -> Result<PublishResult, PublishError> {
+        if self.urls.is_empty() {
+            return Err(PublishError::Api("No webhook URLs configured".to_string()));
+        }
+
+        let analysis = post.analysis.as_ref().ok_or_else(|| {
+            PublishError::Api("Webhook publisher needs the analysis behind the post".to_string())
+        })?;
+
+        let id = event_id(&analysis.source_post.id, &analysis.taxonomy_hash).to_string();
+        let envelope = WebhookEnvelope {
+            id: id.clone(),
+            event: "classification",
+            created_at: OffsetDateTime::now_utc()
+                .format(&Rfc3339)
+                .map_err(|e| PublishError::Api(e.to_string()))?,
+            source_post: &analysis.source_post,
+            classification: &analysis.classification,
+            rendered_text: &post.text,
+            taxonomy_hash: &analysis.taxonomy_hash,
+        };
+        let body =
+            serde_json::to_string(&envelope).map_err(|e| PublishError::Api(e.to_string()))?;
+
+        let mut delivered = 0;
+        let mut last_error = None;
+        for url in &self.urls {
+            match self.deliver(url, &id, &body).await {
+                Ok(()) => {
+                    tracing::info!(url = %url, event_id = %id, "Delivered webhook");
+                    delivered += 1;
+                }
+                Err((attempts, error)) => {
+                    tracing::error!(url = %url, attempts, error = %error, "Webhook undeliverable");
+                    self.dead_letter(url, attempts, error.clone(), &body).await;
+                    last_error = Some(error);
+                }
+            }
+        }
+
+        if delivered == 0 {
+            return Err(PublishError::Api(format!(
+                "Webhook delivery failed: {}",
+                last_error.unwrap_or_default()
+            )));
+        }
+
+        Ok(PublishResult { id, url: None })
+    }
+
+    fn is_enabled(&self) -> bool {
+        true
+    }
+
+    fn platform(&self) -> &'static str {
+        "webhook"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use news_tagger_domain::{PostAnalysis, TagMatch};
+    use serde_json::Value;
+    use std::sync::Arc;
+    use tempfile::TempDir;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn analysed_post() -> RenderedPost {
+        let source_post = SourcePost {
+            id: "123".to_string(),
+            text: "Original post".to_string(),
+            author: "user".to_string(),
+            url: "https://x.com/user/status/123".to_string(),
+            created_at: OffsetDateTime::UNIX_EPOCH,
+            is_repost: false,
+            is_reply: false,
+            reply_to_id: None,
+            context: vec![],
+        };
+        let classification = ClassifyOutput::new(
+            "Summary".to_string(),
+            vec![TagMatch {
+                id: "test_tag".to_string(),
+                confidence: 0.85,
+                rationale: "Because".to_string(),
+                evidence: vec!["Original".to_string()],
+            }],
+        );
+
+        RenderedPost {
+            text: "Tags: test_tag (0.85)".to_string(),
+            source_post_id: "123".to_string(),
+            source_post_url: "https://x.com/user/status/123".to_string(),
+            source_author: "user".to_string(),
+            labels: vec![],
+            article: None,
+            analysis: Some(Arc::new(PostAnalysis {
+                source_post,
+                classification,
+                taxonomy_hash: "abc123".to_string(),
+            })),
+        }
+    }
+
+    fn publisher(urls: Vec<String>) -> WebhookPublisher {
+        WebhookPublisher::new(urls, SecretString::new("shh".into()))
+            .with_retries(3, Duration::from_millis(10))
+    }
+
+    #[tokio::test]
+    async fn test_posts_signed_envelope() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .and(path("/hook"))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let publisher = publisher(vec![format!("{}/hook", mock_server.uri())]);
+        let result = publisher.publish(&analysed_post()).await.unwrap();
+
+        let requests = mock_server.received_requests().await.unwrap();
+        let request = &requests[0];
+        let header = |name: &str| request.headers.get(name).unwrap().to_str().unwrap();
+        let body = String::from_utf8(request.body.clone()).unwrap();
+        let timestamp: i64 = header(TIMESTAMP_HEADER).parse().unwrap();
+
+        assert_eq!(header(EVENT_ID_HEADER), result.id);
+        assert_eq!(header(SIGNATURE_HEADER), publisher.sign(timestamp, &body));
+        assert!(header(SIGNATURE_HEADER).starts_with("sha256="));
+
+        let envelope: Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(envelope["id"], result.id);
+        assert_eq!(envelope["source_post"]["id"], "123");
+        assert_eq!(envelope["classification"]["tags"][0]["id"], "test_tag");
+        assert_eq!(envelope["classification"]["summary"], "Summary");
+        assert_eq!(envelope["rendered_text"], "Tags: test_tag (0.85)");
+        assert_eq!(envelope["taxonomy_hash"], "abc123");
+    }
+
+    #[tokio::test]
+    async fn test_event_id_is_stable_across_publishes() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(2)
+            .mount(&mock_server)
+            .await;
+
+        let publisher = publisher(vec![mock_server.uri()]);
+        let first = publisher.publish(&analysed_post()).await.unwrap();
+        let second = publisher.publish(&analysed_post()).await.unwrap();
+
+        assert_eq!(first.id, second.id);
+        assert_eq!(first.id, event_id("123", "abc123").to_string());
+        assert_ne!(first.id, event_id("123", "def456").to_string());
+        assert_eq!(Uuid::parse_str(&first.id).unwrap().get_version_num(), 8);
+    }
+
+    #[test]
+    fn test_signature_matches_reference_hmac() {
+        let publisher = publisher(vec![]);
+        // HMAC-SHA256 of "1700000000.{}" keyed with "shh"
+        let mut mac = Hmac::<Sha256>::new_from_slice(b"shh").unwrap();
+        mac.update(b"1700000000.{}");
+        let expected = format!("sha256={:x}", mac.finalize().into_bytes());
+
+        assert_eq!(publisher.sign(1_700_000_000, "{}"), expected);
+        assert_ne!(publisher.sign(1_700_000_001, "{}"), expected);
+    }
+
+    #[tokio::test]
+    async fn test_retries_server_errors() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(503))
+            .up_to_n_times(2)
+            .expect(2)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let result = publisher(vec![mock_server.uri()])
+            .publish(&analysed_post())
+            .await;
+
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_undeliverable_envelopes_are_dead_lettered() {
+        let dir = TempDir::new().expect("temp dir");
+        let dead_letter_path = dir.path().join("dead-letter.jsonl");
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .and(path("/gone"))
+            .respond_with(ResponseTemplate::new(410))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/down"))
+            .respond_with(ResponseTemplate::new(500))
+            .expect(3)
+            .mount(&mock_server)
+            .await;
+
+        let writer = OutboxWriter::new(dead_letter_path.clone())
+            .await
+            .expect("writer");
+        let publisher = publisher(vec![
+            format!("{}/gone", mock_server.uri()),
+            format!("{}/down", mock_server.uri()),
+        ])
+        .with_dead_letter(writer);
+
+        let result = publisher.publish(&analysed_post()).await;
+        assert!(matches!(result, Err(PublishError::Api(_))));
+
+        let contents = tokio::fs::read_to_string(&dead_letter_path)
+            .await
+            .expect("read dead letters");
+        let entries: Vec<Value> = contents
+            .lines()
+            .map(|line| serde_json::from_str(line).expect("valid json"))
+            .collect();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0]["attempts"], 1);
+        assert_eq!(entries[1]["attempts"], 3);
+        assert_eq!(entries[1]["envelope"]["taxonomy_hash"], "abc123");
+        assert_eq!(entries[0]["envelope"]["id"], entries[1]["envelope"]["id"]);
+    }
+
+    #[tokio::test]
+    async fn test_requires_analysis() {
+        let post = RenderedPost {
+            analysis: None,
+            ..analysed_post()
+        };
+
+        let result = publisher(vec!["http://127.0.0.1:1".to_string()])
+            .publish(&post)
+            .await;
+
+        assert!(matches!(result, Err(PublishError::Api(_))));
+    }
+}
@@ -0,0 +1,77 @@
+//! Per-endpoint request budgets tracked from X rate-limit headers
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+use time::OffsetDateTime;
+
+/// Remaining requests for one endpoint in the current window
+#[derive(Debug, Clone, Copy)]
+struct Budget {
+    remaining: u64,
+    reset_at: i64,
+}
+
+/// Tracks `x-rate-limit-remaining` / `x-rate-limit-reset` per endpoint so an
+/// exhausted endpoint is not called again until its window resets
+#[derive(Debug, Default)]
+pub(super) struct RateBudgets {
+    endpoints: Mutex<HashMap<&'static str, Budget>>,
+}
+
+impl RateBudgets {
+    /// Time to wait before `endpoint` may be called, if its budget is exhausted
+    pub(super) fn wait(&self, endpoint: &'static str) -> Option<Duration> {
+        let endpoints = self.endpoints.lock().expect("rate budget lock poisoned");
+        let budget = endpoints.get(endpoint)?;
+        let now = OffsetDateTime::now_utc().unix_timestamp();
+        if budget.remaining == 0 && budget.reset_at > now {
+            Some(Duration::from_secs((budget.reset_at - now) as u64))
+        } else {
+            None
+        }
+    }
+
+    /// Record the budget reported by a response from `endpoint`
+    pub(super) fn record(&self, endpoint: &'static str, response: &reqwest::Response) {
+        let header = |name: &str| {
+            response
+                .headers()
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .and_then(|s| s.trim().parse::<i64>().ok())
+        };
+
+        let Some(reset_at) = header("x-rate-limit-reset") else {
+            return;
+        };
+        let remaining = if response.status() == 429 {
+            Some(0)
+        } else {
+            header("x-rate-limit-remaining")
+        };
+        let Some(remaining) = remaining else {
+            return;
+        };
+
+        if remaining <= 1 {
+            tracing::debug!(
+                endpoint,
+                remaining,
+                reset_at,
+                "X rate-limit budget nearly exhausted"
+            );
+        }
+
+        self.endpoints
+            .lock()
+            .expect("rate budget lock poisoned")
+            .insert(
+                endpoint,
+                Budget {
+                    remaining: remaining.max(0) as u64,
+                    reset_at,
+                },
+            );
+    }
+}
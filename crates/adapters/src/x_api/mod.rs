@@ -1,6 +1,8 @@
 //! X (Twitter) API adapters
 
+mod budget;
 mod read;
+mod users;
 mod write;
 
 pub use read::XPostSource;
@@ -10,6 +12,21 @@ use async_trait::async_trait;
 use news_tagger_domain::{
     PostSource, PostSourceError, PublishError, PublishResult, Publisher, RenderedPost, SourcePost,
 };
+use std::time::Duration;
+use time::OffsetDateTime;
+
+/// Parse the `x-rate-limit-reset` header into a wait duration
+fn retry_after(response: &reqwest::Response) -> Option<Duration> {
+    response
+        .headers()
+        .get("x-rate-limit-reset")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|s| s.parse::<u64>().ok())
+        .map(|ts| {
+            let now = OffsetDateTime::now_utc().unix_timestamp() as u64;
+            Duration::from_secs(ts.saturating_sub(now))
+        })
+}
 
 /// Stub post source for testing
 pub struct StubPostSource {
@@ -1,18 +1,36 @@
 //! X API read adapter for fetching posts
 
 use async_trait::async_trait;
-use news_tagger_domain::{PostSource, PostSourceError, SourcePost, compare_post_ids};
+use news_tagger_domain::{
+    ContextRelation, PostContext, PostSource, PostSourceError, SourcePost, StateStore,
+    compare_post_ids,
+};
 use reqwest::Client;
 use secrecy::{ExposeSecret, SecretString};
 use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::Arc;
 use std::time::Duration;
 use time::OffsetDateTime;
 
+use super::budget::RateBudgets;
+use super::retry_after;
+use super::users::UserResolver;
+
+/// Budget key for the user timeline endpoint
+const USER_TWEETS_ENDPOINT: &str = "GET /2/users/:id/tweets";
+
+/// Budget key for the tweet lookup endpoint
+const TWEET_LOOKUP_ENDPOINT: &str = "GET /2/tweets";
+
 /// X API post source for reading user timelines
 pub struct XPostSource {
     client: Client,
     bearer_token: SecretString,
     base_url: String,
+    include_conversation_root: bool,
+    users: UserResolver,
+    budgets: Arc<RateBudgets>,
 }
 
 impl XPostSource {
@@ -26,59 +44,38 @@ impl XPostSource {
             .build()
             .expect("Failed to build HTTP client");
 
+        let budgets = Arc::new(RateBudgets::default());
+        let users = UserResolver::new(
+            client.clone(),
+            bearer_token.clone(),
+            base_url.clone(),
+            Arc::clone(&budgets),
+        );
+
         Self {
             client,
             bearer_token,
             base_url,
+            include_conversation_root: false,
+            users,
+            budgets,
         }
     }
 
-    /// Look up user ID by username
-    async fn get_user_id(&self, username: &str) -> Result<String, PostSourceError> {
-        let url = format!("{}/2/users/by/username/{}", self.base_url, username);
-
-        let response = self
-            .client
-            .get(&url)
-            .header(
-                "Authorization",
-                format!("Bearer {}", self.bearer_token.expose_secret()),
-            )
-            .send()
-            .await
-            .map_err(|e| PostSourceError::Network(e.to_string()))?;
-
-        if response.status() == 401 {
-            return Err(PostSourceError::Auth("Invalid bearer token".to_string()));
-        }
-
-        if response.status() == 429 {
-            let retry_after = response
-                .headers()
-                .get("x-rate-limit-reset")
-                .and_then(|v| v.to_str().ok())
-                .and_then(|s| s.parse::<u64>().ok())
-                .map(|ts| {
-                    let now = OffsetDateTime::now_utc().unix_timestamp() as u64;
-                    Duration::from_secs(ts.saturating_sub(now))
-                });
-            return Err(PostSourceError::RateLimited(retry_after));
-        }
-
-        if !response.status().is_success() {
-            let body = response.text().await.unwrap_or_default();
-            return Err(PostSourceError::Api(format!(
-                "Failed to get user: {}",
-                body
-            )));
-        }
-
-        let user_response: UserResponse = response
-            .json()
-            .await
-            .map_err(|e| PostSourceError::Api(e.to_string()))?;
+    /// Persist resolved user IDs in the state store, refreshing them after `refresh_interval`
+    pub fn with_user_id_cache(
+        mut self,
+        state_store: Arc<dyn StateStore>,
+        refresh_interval: Duration,
+    ) -> Self {
+        self.users = self.users.with_state_store(state_store, refresh_interval);
+        self
+    }
 
-        Ok(user_response.data.id)
+    /// Also attach the root post of each reply's conversation as context
+    pub fn with_conversation_root(mut self, enabled: bool) -> Self {
+        self.include_conversation_root = enabled;
+        self
     }
 
     /// Fetch tweets for a user
@@ -89,14 +86,18 @@ impl XPostSource {
         since_id: Option<&str>,
     ) -> Result<Vec<SourcePost>, PostSourceError> {
         let mut url = format!(
-            "{}/2/users/{}/tweets?tweet.fields=created_at,referenced_tweets&max_results=100",
-            self.base_url, user_id
+            "{}/2/users/{}/tweets?{}&max_results=100",
+            self.base_url, user_id, TWEET_EXPANSION_PARAMS
         );
 
         if let Some(since_id) = since_id {
             url.push_str(&format!("&since_id={}", since_id));
         }
 
+        if let Some(wait) = self.budgets.wait(USER_TWEETS_ENDPOINT) {
+            return Err(PostSourceError::RateLimited(Some(wait)));
+        }
+
         let response = self
             .client
             .get(&url)
@@ -107,22 +108,14 @@ impl XPostSource {
             .send()
             .await
             .map_err(|e| PostSourceError::Network(e.to_string()))?;
+        self.budgets.record(USER_TWEETS_ENDPOINT, &response);
 
         if response.status() == 401 {
             return Err(PostSourceError::Auth("Invalid bearer token".to_string()));
         }
 
         if response.status() == 429 {
-            let retry_after = response
-                .headers()
-                .get("x-rate-limit-reset")
-                .and_then(|v| v.to_str().ok())
-                .and_then(|s| s.parse::<u64>().ok())
-                .map(|ts| {
-                    let now = OffsetDateTime::now_utc().unix_timestamp() as u64;
-                    Duration::from_secs(ts.saturating_sub(now))
-                });
-            return Err(PostSourceError::RateLimited(retry_after));
+            return Err(PostSourceError::RateLimited(retry_after(&response)));
         }
 
         if !response.status().is_success() {
@@ -138,9 +131,28 @@ impl XPostSource {
             .await
             .map_err(|e| PostSourceError::Api(e.to_string()))?;
 
-        let posts = tweets_response
-            .data
-            .unwrap_or_default()
+        let includes = tweets_response.includes.unwrap_or_default();
+        let referenced = ReferencedLookup::new(includes);
+        let tweets = tweets_response.data.unwrap_or_default();
+
+        let roots = if self.include_conversation_root {
+            let root_ids = missing_conversation_roots(&tweets, &referenced);
+            match self.lookup_tweets(&root_ids).await {
+                Ok(roots) => roots,
+                Err(e) => {
+                    tracing::warn!(
+                        account = %username,
+                        error = %e,
+                        "Failed to fetch conversation roots, continuing without them"
+                    );
+                    ReferencedLookup::default()
+                }
+            }
+        } else {
+            ReferencedLookup::default()
+        };
+
+        let posts = tweets
             .into_iter()
             .map(|tweet| {
                 let is_repost = tweet
@@ -170,6 +182,8 @@ impl XPostSource {
                     })
                     .unwrap_or_else(OffsetDateTime::now_utc);
 
+                let context = self.build_context(&tweet, &referenced, &roots);
+
                 SourcePost {
                     id: tweet.id.clone(),
                     text: tweet.text,
@@ -179,27 +193,180 @@ impl XPostSource {
                     is_repost,
                     is_reply,
                     reply_to_id,
+                    context,
                 }
             })
             .collect();
 
         Ok(posts)
     }
+
+    /// Fetch tweets by ID in a single batched lookup
+    async fn lookup_tweets(&self, ids: &[String]) -> Result<ReferencedLookup, PostSourceError> {
+        if ids.is_empty() {
+            return Ok(ReferencedLookup::default());
+        }
+
+        let url = format!(
+            "{}/2/tweets?ids={}&tweet.fields=author_id&expansions=author_id&user.fields=username",
+            self.base_url,
+            ids.join(",")
+        );
+
+        if let Some(wait) = self.budgets.wait(TWEET_LOOKUP_ENDPOINT) {
+            return Err(PostSourceError::RateLimited(Some(wait)));
+        }
+
+        let response = self
+            .client
+            .get(&url)
+            .header(
+                "Authorization",
+                format!("Bearer {}", self.bearer_token.expose_secret()),
+            )
+            .send()
+            .await
+            .map_err(|e| PostSourceError::Network(e.to_string()))?;
+        self.budgets.record(TWEET_LOOKUP_ENDPOINT, &response);
+
+        if response.status() == 401 {
+            return Err(PostSourceError::Auth("Invalid bearer token".to_string()));
+        }
+
+        if response.status() == 429 {
+            return Err(PostSourceError::RateLimited(retry_after(&response)));
+        }
+
+        if !response.status().is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(PostSourceError::Api(format!(
+                "Failed to look up tweets: {}",
+                body
+            )));
+        }
+
+        let lookup: TweetsResponse = response
+            .json()
+            .await
+            .map_err(|e| PostSourceError::Api(e.to_string()))?;
+
+        let mut includes = lookup.includes.unwrap_or_default();
+        includes.tweets.extend(lookup.data.unwrap_or_default());
+        Ok(ReferencedLookup::new(includes))
+    }
+
+    /// Collect quoted, replied-to and (optionally) conversation root posts
+    fn build_context(
+        &self,
+        tweet: &Tweet,
+        referenced: &ReferencedLookup,
+        roots: &ReferencedLookup,
+    ) -> Vec<PostContext> {
+        let mut context = Vec::new();
+
+        for reference in tweet.referenced_tweets.iter().flatten() {
+            let relation = match reference.r#type.as_str() {
+                "quoted" => ContextRelation::Quoted,
+                "replied_to" => ContextRelation::RepliedTo,
+                _ => continue,
+            };
+            if let Some(item) = referenced.context(&reference.id, relation) {
+                context.push(item);
+            }
+        }
+
+        if self.include_conversation_root {
+            if let Some(root_id) = tweet.conversation_id.as_deref() {
+                let already_present =
+                    root_id == tweet.id || context.iter().any(|c| c.id == root_id);
+                if !already_present {
+                    let root = referenced
+                        .context(root_id, ContextRelation::ConversationRoot)
+                        .or_else(|| roots.context(root_id, ContextRelation::ConversationRoot));
+                    if let Some(item) = root {
+                        context.push(item);
+                    }
+                }
+            }
+        }
+
+        context
+    }
 }
 
-#[derive(Deserialize)]
-struct UserResponse {
-    data: UserData,
+/// Query parameters requesting referenced tweets and their authors
+const TWEET_EXPANSION_PARAMS: &str = "tweet.fields=created_at,referenced_tweets,conversation_id,author_id&expansions=referenced_tweets.id,referenced_tweets.id.author_id&user.fields=username";
+
+/// Conversation roots that are neither the tweet itself nor already expanded
+fn missing_conversation_roots(tweets: &[Tweet], referenced: &ReferencedLookup) -> Vec<String> {
+    let mut ids: Vec<String> = tweets
+        .iter()
+        .filter_map(|t| t.conversation_id.as_ref().filter(|root| **root != t.id))
+        .filter(|root| !referenced.tweets.contains_key(*root))
+        .filter(|root| !tweets.iter().any(|t| &t.id == *root))
+        .cloned()
+        .collect();
+    ids.sort();
+    ids.dedup();
+    ids
 }
 
-#[derive(Deserialize)]
-struct UserData {
-    id: String,
+/// Index of expanded tweets and users from an `includes` payload
+#[derive(Default)]
+struct ReferencedLookup {
+    tweets: HashMap<String, Tweet>,
+    usernames: HashMap<String, String>,
+}
+
+impl ReferencedLookup {
+    fn new(includes: Includes) -> Self {
+        Self {
+            tweets: includes
+                .tweets
+                .into_iter()
+                .map(|t| (t.id.clone(), t))
+                .collect(),
+            usernames: includes
+                .users
+                .into_iter()
+                .map(|u| (u.id, u.username))
+                .collect(),
+        }
+    }
+
+    fn context(&self, id: &str, relation: ContextRelation) -> Option<PostContext> {
+        let tweet = self.tweets.get(id)?;
+        Some(PostContext {
+            relation,
+            id: tweet.id.clone(),
+            author: tweet
+                .author_id
+                .as_ref()
+                .and_then(|author_id| self.usernames.get(author_id))
+                .cloned(),
+            text: tweet.text.clone(),
+        })
+    }
 }
 
 #[derive(Deserialize)]
 struct TweetsResponse {
     data: Option<Vec<Tweet>>,
+    includes: Option<Includes>,
+}
+
+#[derive(Deserialize, Default)]
+struct Includes {
+    #[serde(default)]
+    tweets: Vec<Tweet>,
+    #[serde(default)]
+    users: Vec<IncludedUser>,
+}
+
+#[derive(Deserialize)]
+struct IncludedUser {
+    id: String,
+    username: String,
 }
 
 #[derive(Deserialize)]
@@ -207,6 +374,8 @@ struct Tweet {
     id: String,
     text: String,
     created_at: Option<String>,
+    author_id: Option<String>,
+    conversation_id: Option<String>,
     referenced_tweets: Option<Vec<ReferencedTweet>>,
 }
 
@@ -218,6 +387,10 @@ struct ReferencedTweet {
 
 #[async_trait]
 impl PostSource for XPostSource {
+    async fn prepare_accounts(&self, accounts: &[String]) -> Result<(), PostSourceError> {
+        self.users.resolve(accounts).await
+    }
+
     async fn fetch_posts(
         &self,
         account: &str,
@@ -225,8 +398,8 @@ impl PostSource for XPostSource {
     ) -> Result<Vec<SourcePost>, PostSourceError> {
         tracing::info!(account = %account, since_id = ?since_id, "Fetching posts from X");
 
-        // Get user ID from username
-        let user_id = self.get_user_id(account).await?;
+        // Get user ID from username (cached across polls)
+        let user_id = self.users.user_id(account).await?;
 
         // Fetch tweets
         let mut posts = self.fetch_user_tweets(&user_id, account, since_id).await?;
@@ -243,7 +416,7 @@ impl PostSource for XPostSource {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use wiremock::matchers::{header, method, path, path_regex};
+    use wiremock::matchers::{header, method, path, path_regex, query_param};
     use wiremock::{Mock, MockServer, ResponseTemplate};
 
     #[tokio::test]
@@ -252,12 +425,13 @@ mod tests {
 
         // Mock user lookup
         Mock::given(method("GET"))
-            .and(path("/2/users/by/username/testuser"))
+            .and(path("/2/users/by"))
+            .and(query_param("usernames", "testuser"))
             .and(header("Authorization", "Bearer test-token"))
             .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
-                "data": {
-                    "id": "123456789"
-                }
+                "data": [
+                    {"id": "123456789", "username": "testuser"}
+                ]
             })))
             .mount(&mock_server)
             .await;
@@ -297,12 +471,124 @@ mod tests {
         assert!(posts[1].is_reply);
     }
 
+    async fn mount_user_lookup(mock_server: &MockServer) {
+        Mock::given(method("GET"))
+            .and(path("/2/users/by"))
+            .and(query_param("usernames", "testuser"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "data": [{"id": "123456789", "username": "testuser"}]
+            })))
+            .mount(mock_server)
+            .await;
+    }
+
+    #[tokio::test]
+    async fn test_fetch_posts_attaches_referenced_context() {
+        let mock_server = MockServer::start().await;
+        mount_user_lookup(&mock_server).await;
+
+        Mock::given(method("GET"))
+            .and(path("/2/users/123456789/tweets"))
+            .and(query_param(
+                "expansions",
+                "referenced_tweets.id,referenced_tweets.id.author_id",
+            ))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "data": [
+                    {
+                        "id": "200",
+                        "text": "this is exactly it",
+                        "conversation_id": "200",
+                        "referenced_tweets": [{"type": "quoted", "id": "150"}]
+                    },
+                    {
+                        "id": "201",
+                        "text": "agreed",
+                        "conversation_id": "100",
+                        "referenced_tweets": [{"type": "replied_to", "id": "160"}]
+                    }
+                ],
+                "includes": {
+                    "tweets": [
+                        {"id": "150", "text": "The quoted claim", "author_id": "9"},
+                        {"id": "160", "text": "The parent reply", "author_id": "9"}
+                    ],
+                    "users": [{"id": "9", "username": "origin"}]
+                }
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let source =
+            XPostSource::with_base_url(SecretString::new("test-token".into()), mock_server.uri());
+
+        let posts = source.fetch_posts("testuser", None).await.unwrap();
+
+        assert_eq!(posts[0].context.len(), 1);
+        assert_eq!(posts[0].context[0].relation, ContextRelation::Quoted);
+        assert_eq!(posts[0].context[0].text, "The quoted claim");
+        assert_eq!(posts[0].context[0].author.as_deref(), Some("origin"));
+        assert_eq!(posts[1].context.len(), 1);
+        assert_eq!(posts[1].context[0].relation, ContextRelation::RepliedTo);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_posts_attaches_conversation_root_when_enabled() {
+        let mock_server = MockServer::start().await;
+        mount_user_lookup(&mock_server).await;
+
+        Mock::given(method("GET"))
+            .and(path("/2/users/123456789/tweets"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "data": [{
+                    "id": "201",
+                    "text": "agreed",
+                    "conversation_id": "100",
+                    "referenced_tweets": [{"type": "replied_to", "id": "160"}]
+                }],
+                "includes": {
+                    "tweets": [{"id": "160", "text": "The parent reply"}]
+                }
+            })))
+            .mount(&mock_server)
+            .await;
+
+        Mock::given(method("GET"))
+            .and(path("/2/tweets"))
+            .and(query_param("ids", "100"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "data": [{"id": "100", "text": "Thread starter", "author_id": "7"}],
+                "includes": { "users": [{"id": "7", "username": "root_author"}] }
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let source =
+            XPostSource::with_base_url(SecretString::new("test-token".into()), mock_server.uri())
+                .with_conversation_root(true);
+
+        let posts = source.fetch_posts("testuser", None).await.unwrap();
+
+        let relations: Vec<_> = posts[0].context.iter().map(|c| c.relation).collect();
+        assert_eq!(
+            relations,
+            vec![
+                ContextRelation::RepliedTo,
+                ContextRelation::ConversationRoot
+            ]
+        );
+        assert_eq!(posts[0].context[1].text, "Thread starter");
+        assert_eq!(posts[0].context[1].author.as_deref(), Some("root_author"));
+    }
+
     #[tokio::test]
     async fn test_fetch_posts_rate_limited() {
         let mock_server = MockServer::start().await;
 
         Mock::given(method("GET"))
-            .and(path("/2/users/by/username/testuser"))
+            .and(path("/2/users/by"))
+            .and(query_param("usernames", "testuser"))
             .respond_with(ResponseTemplate::new(429))
             .mount(&mock_server)
             .await;
@@ -315,12 +601,49 @@ mod tests {
         assert!(matches!(result, Err(PostSourceError::RateLimited(_))));
     }
 
+    #[tokio::test]
+    async fn test_exhausted_budget_fails_fast_until_reset() {
+        let mock_server = MockServer::start().await;
+        mount_user_lookup(&mock_server).await;
+
+        let reset = OffsetDateTime::now_utc().unix_timestamp() + 600;
+        Mock::given(method("GET"))
+            .and(path("/2/users/123456789/tweets"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("x-rate-limit-remaining", "0")
+                    .insert_header("x-rate-limit-reset", reset.to_string().as_str())
+                    .set_body_json(serde_json::json!({ "data": [] })),
+            )
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let source =
+            XPostSource::with_base_url(SecretString::new("test-token".into()), mock_server.uri());
+
+        assert!(
+            source
+                .fetch_posts("testuser", None)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+
+        let result = source.fetch_posts("testuser", None).await;
+        assert!(matches!(
+            result,
+            Err(PostSourceError::RateLimited(Some(wait))) if wait > Duration::from_secs(590)
+        ));
+    }
+
     #[tokio::test]
     async fn test_fetch_posts_auth_error() {
         let mock_server = MockServer::start().await;
 
         Mock::given(method("GET"))
-            .and(path("/2/users/by/username/testuser"))
+            .and(path("/2/users/by"))
+            .and(query_param("usernames", "testuser"))
             .respond_with(ResponseTemplate::new(401))
             .mount(&mock_server)
             .await;
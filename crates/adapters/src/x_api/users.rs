@@ -0,0 +1,495 @@
+//! X username to user ID resolution with a persistent cache
+
+use news_tagger_domain::{AccountStatus, PostSourceError, ResolvedAccount, StateStore};
+use reqwest::Client;
+use secrecy::{ExposeSecret, SecretString};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use time::OffsetDateTime;
+
+use super::budget::RateBudgets;
+use super::retry_after;
+
+/// Maximum usernames or IDs per batched user lookup
+const MAX_USERS_PER_LOOKUP: usize = 100;
+
+/// Default time before a cached resolution is looked up again
+const DEFAULT_REFRESH_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Resolves watched handles to user IDs, batching lookups and caching results
+pub(super) struct UserResolver {
+    client: Client,
+    bearer_token: SecretString,
+    base_url: String,
+    state_store: Option<Arc<dyn StateStore>>,
+    refresh_interval: Duration,
+    cache: Mutex<HashMap<String, ResolvedAccount>>,
+    budgets: Arc<RateBudgets>,
+}
+
+impl UserResolver {
+    pub(super) fn new(
+        client: Client,
+        bearer_token: SecretString,
+        base_url: String,
+        budgets: Arc<RateBudgets>,
+    ) -> Self {
+        Self {
+            client,
+            bearer_token,
+            base_url,
+            state_store: None,
+            refresh_interval: DEFAULT_REFRESH_INTERVAL,
+            cache: Mutex::new(HashMap::new()),
+            budgets,
+        }
+    }
+
+    pub(super) fn with_state_store(
+        mut self,
+        state_store: Arc<dyn StateStore>,
+        refresh_interval: Duration,
+    ) -> Self {
+        self.state_store = Some(state_store);
+        self.refresh_interval = refresh_interval;
+        self
+    }
+
+    /// Look up every account whose cached resolution is missing or stale
+    pub(super) async fn resolve(&self, accounts: &[String]) -> Result<(), PostSourceError> {
+        let now = OffsetDateTime::now_utc();
+        let mut stale = Vec::new();
+
+        for account in accounts {
+            let key = account.to_ascii_lowercase();
+            if stale.iter().any(|(k, _)| *k == key) {
+                continue;
+            }
+            match self.cached(&key).await {
+                Some(resolved) if !self.is_stale(&resolved, now) => {}
+                cached => stale.push((key, cached.and_then(|r| r.user_id))),
+            }
+        }
+
+        for chunk in stale.chunks(MAX_USERS_PER_LOOKUP) {
+            self.refresh(chunk, now).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Get the user ID for an account, failing if it is suspended or gone
+    pub(super) async fn user_id(&self, account: &str) -> Result<String, PostSourceError> {
+        self.resolve(&[account.to_string()]).await?;
+
+        let key = account.to_ascii_lowercase();
+        let resolved = self
+            .cached(&key)
+            .await
+            .ok_or_else(|| PostSourceError::Api(format!("No user ID resolved for @{}", account)))?;
+
+        match (resolved.status, resolved.user_id) {
+            (AccountStatus::Active, Some(user_id)) => Ok(user_id),
+            (AccountStatus::Renamed, Some(user_id)) => {
+                tracing::warn!(
+                    account = %account,
+                    renamed_to = ?resolved.renamed_to,
+                    "X account was renamed; update watch.accounts"
+                );
+                Ok(user_id)
+            }
+            (AccountStatus::Suspended, _) => Err(PostSourceError::AccountUnavailable(format!(
+                "@{} is suspended",
+                account
+            ))),
+            _ => Err(PostSourceError::AccountUnavailable(format!(
+                "@{} does not exist (renamed or deleted)",
+                account
+            ))),
+        }
+    }
+
+    fn is_stale(&self, resolved: &ResolvedAccount, now: OffsetDateTime) -> bool {
+        let age = (now - resolved.resolved_at).whole_seconds();
+        age < 0 || age as u64 >= self.refresh_interval.as_secs()
+    }
+
+    async fn cached(&self, key: &str) -> Option<ResolvedAccount> {
+        if let Some(resolved) = self
+            .cache
+            .lock()
+            .expect("user cache lock poisoned")
+            .get(key)
+        {
+            return Some(resolved.clone());
+        }
+
+        let state_store = self.state_store.as_ref()?;
+        match state_store.get_resolved_account(key).await {
+            Ok(Some(resolved)) => {
+                self.cache
+                    .lock()
+                    .expect("user cache lock poisoned")
+                    .insert(key.to_string(), resolved.clone());
+                Some(resolved)
+            }
+            Ok(None) => None,
+            Err(e) => {
+                tracing::warn!(account = %key, error = %e, "Failed to read cached user ID");
+                None
+            }
+        }
+    }
+
+    async fn store(&self, resolved: ResolvedAccount) {
+        if let Some(state_store) = &self.state_store {
+            if let Err(e) = state_store.set_resolved_account(&resolved).await {
+                tracing::warn!(account = %resolved.account, error = %e, "Failed to persist user ID");
+            }
+        }
+        self.cache
+            .lock()
+            .expect("user cache lock poisoned")
+            .insert(resolved.account.clone(), resolved);
+    }
+
+    /// Resolve one batch of handles, checking missing ones by their previous ID
+    async fn refresh(
+        &self,
+        batch: &[(String, Option<String>)],
+        now: OffsetDateTime,
+    ) -> Result<(), PostSourceError> {
+        let usernames: Vec<&str> = batch.iter().map(|(key, _)| key.as_str()).collect();
+        let by_name = self.lookup(false, &usernames).await?;
+
+        let missing_ids: Vec<&str> = batch
+            .iter()
+            .filter(|(key, _)| !by_name.found.contains_key(key))
+            .filter_map(|(_, user_id)| user_id.as_deref())
+            .collect();
+        let by_id = if missing_ids.is_empty() {
+            UserLookup::default()
+        } else {
+            self.lookup(true, &missing_ids).await?
+        };
+
+        for (key, previous_id) in batch {
+            let mut resolved = ResolvedAccount {
+                account: key.clone(),
+                user_id: previous_id.clone(),
+                status: AccountStatus::NotFound,
+                renamed_to: None,
+                resolved_at: now,
+            };
+
+            if let Some(user) = by_name.found.get(key) {
+                if previous_id.as_ref().is_some_and(|id| *id != user.id) {
+                    tracing::warn!(
+                        account = %key,
+                        previous_id = ?previous_id,
+                        user_id = %user.id,
+                        "X handle now belongs to a different account"
+                    );
+                }
+                resolved.user_id = Some(user.id.clone());
+                resolved.status = AccountStatus::Active;
+            } else if let Some(user) = previous_id.as_ref().and_then(|id| by_id.found.get(id)) {
+                resolved.status = AccountStatus::Renamed;
+                resolved.renamed_to = Some(user.username.clone());
+            } else if by_name.suspended(key)
+                || previous_id.as_ref().is_some_and(|id| by_id.suspended(id))
+            {
+                resolved.status = AccountStatus::Suspended;
+            }
+
+            match resolved.status {
+                AccountStatus::Active => {
+                    tracing::debug!(account = %key, user_id = ?resolved.user_id, "Resolved X user ID");
+                }
+                AccountStatus::Renamed => {
+                    tracing::warn!(
+                        account = %key,
+                        renamed_to = ?resolved.renamed_to,
+                        "X account was renamed; update watch.accounts"
+                    );
+                }
+                AccountStatus::Suspended => {
+                    tracing::warn!(account = %key, "X account is suspended");
+                }
+                AccountStatus::NotFound => {
+                    tracing::warn!(account = %key, "X account not found");
+                }
+            }
+
+            self.store(resolved).await;
+        }
+
+        Ok(())
+    }
+
+    /// Batched lookup through `/2/users/by?usernames=` or `/2/users?ids=`
+    async fn lookup(&self, by_id: bool, values: &[&str]) -> Result<UserLookup, PostSourceError> {
+        let (endpoint, budget_key) = if by_id {
+            ("/2/users?ids=", "GET /2/users")
+        } else {
+            ("/2/users/by?usernames=", "GET /2/users/by")
+        };
+        let url = format!(
+            "{}{}{}&user.fields=username",
+            self.base_url,
+            endpoint,
+            values.join(",")
+        );
+
+        if let Some(wait) = self.budgets.wait(budget_key) {
+            return Err(PostSourceError::RateLimited(Some(wait)));
+        }
+
+        let response = self
+            .client
+            .get(&url)
+            .header(
+                "Authorization",
+                format!("Bearer {}", self.bearer_token.expose_secret()),
+            )
+            .send()
+            .await
+            .map_err(|e| PostSourceError::Network(e.to_string()))?;
+        self.budgets.record(budget_key, &response);
+
+        if response.status() == 401 {
+            return Err(PostSourceError::Auth("Invalid bearer token".to_string()));
+        }
+
+        if response.status() == 429 {
+            return Err(PostSourceError::RateLimited(retry_after(&response)));
+        }
+
+        if !response.status().is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(PostSourceError::Api(format!(
+                "Failed to look up users: {}",
+                body
+            )));
+        }
+
+        let users: UsersResponse = response
+            .json()
+            .await
+            .map_err(|e| PostSourceError::Api(e.to_string()))?;
+
+        let found = users
+            .data
+            .unwrap_or_default()
+            .into_iter()
+            .map(|user| {
+                let key = if by_id {
+                    user.id.clone()
+                } else {
+                    user.username.to_ascii_lowercase()
+                };
+                (key, user)
+            })
+            .collect();
+
+        let errors = users
+            .errors
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|error| {
+                let value = error.value.clone().or_else(|| error.resource_id.clone())?;
+                let key = if by_id {
+                    value
+                } else {
+                    value.to_ascii_lowercase()
+                };
+                Some((key, error))
+            })
+            .collect();
+
+        Ok(UserLookup { found, errors })
+    }
+}
+
+#[derive(Default)]
+struct UserLookup {
+    found: HashMap<String, UserData>,
+    errors: HashMap<String, LookupError>,
+}
+
+impl UserLookup {
+    fn suspended(&self, key: &str) -> bool {
+        self.errors.get(key).is_some_and(|error| {
+            error.title.as_deref() == Some("Forbidden")
+                || error
+                    .detail
+                    .as_deref()
+                    .is_some_and(|d| d.to_ascii_lowercase().contains("suspended"))
+        })
+    }
+}
+
+#[derive(Deserialize)]
+struct UsersResponse {
+    data: Option<Vec<UserData>>,
+    errors: Option<Vec<LookupError>>,
+}
+
+#[derive(Deserialize)]
+struct UserData {
+    id: String,
+    username: String,
+}
+
+#[derive(Deserialize)]
+struct LookupError {
+    value: Option<String>,
+    resource_id: Option<String>,
+    title: Option<String>,
+    detail: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::InMemoryStateStore;
+    use wiremock::matchers::{method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn resolver(mock_server: &MockServer, store: Arc<InMemoryStateStore>) -> UserResolver {
+        UserResolver::new(
+            Client::new(),
+            SecretString::new("test-token".into()),
+            mock_server.uri(),
+            Arc::new(RateBudgets::default()),
+        )
+        .with_state_store(store, DEFAULT_REFRESH_INTERVAL)
+    }
+
+    #[tokio::test]
+    async fn test_resolve_batches_usernames_and_persists() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/2/users/by"))
+            .and(query_param("usernames", "alice,bob"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "data": [
+                    {"id": "1", "username": "Alice"},
+                    {"id": "2", "username": "bob"}
+                ]
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let store = Arc::new(InMemoryStateStore::new());
+        let users = resolver(&mock_server, Arc::clone(&store));
+
+        users
+            .resolve(&["Alice".to_string(), "bob".to_string()])
+            .await
+            .unwrap();
+
+        // Served from cache: the mock expects exactly one request
+        assert_eq!(users.user_id("alice").await.unwrap(), "1");
+        assert_eq!(users.user_id("bob").await.unwrap(), "2");
+
+        let persisted = store.get_resolved_account("alice").await.unwrap().unwrap();
+        assert_eq!(persisted.user_id.as_deref(), Some("1"));
+        assert_eq!(persisted.status, AccountStatus::Active);
+    }
+
+    #[tokio::test]
+    async fn test_resolve_uses_persisted_ids_without_lookup() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(500))
+            .expect(0)
+            .mount(&mock_server)
+            .await;
+
+        let store = Arc::new(InMemoryStateStore::new());
+        store
+            .set_resolved_account(&ResolvedAccount {
+                account: "alice".to_string(),
+                user_id: Some("1".to_string()),
+                status: AccountStatus::Active,
+                renamed_to: None,
+                resolved_at: OffsetDateTime::now_utc(),
+            })
+            .await
+            .unwrap();
+
+        let users = resolver(&mock_server, store);
+
+        assert_eq!(users.user_id("alice").await.unwrap(), "1");
+    }
+
+    #[tokio::test]
+    async fn test_resolve_detects_renamed_and_suspended_accounts() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/2/users/by"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "errors": [
+                    {
+                        "value": "oldname",
+                        "title": "Not Found Error",
+                        "detail": "Could not find user with usernames: [oldname]."
+                    },
+                    {
+                        "value": "banned",
+                        "title": "Forbidden",
+                        "detail": "User has been suspended: [banned]."
+                    }
+                ]
+            })))
+            .mount(&mock_server)
+            .await;
+
+        Mock::given(method("GET"))
+            .and(path("/2/users"))
+            .and(query_param("ids", "1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "data": [{"id": "1", "username": "newname"}]
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let store = Arc::new(InMemoryStateStore::new());
+        let stale = OffsetDateTime::now_utc() - time::Duration::days(2);
+        store
+            .set_resolved_account(&ResolvedAccount {
+                account: "oldname".to_string(),
+                user_id: Some("1".to_string()),
+                status: AccountStatus::Active,
+                renamed_to: None,
+                resolved_at: stale,
+            })
+            .await
+            .unwrap();
+
+        let users = resolver(&mock_server, Arc::clone(&store));
+
+        // Renamed accounts keep polling by their stable user ID
+        assert_eq!(users.user_id("oldname").await.unwrap(), "1");
+        let renamed = store
+            .get_resolved_account("oldname")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(renamed.status, AccountStatus::Renamed);
+        assert_eq!(renamed.renamed_to.as_deref(), Some("newname"));
+
+        let result = users.user_id("banned").await;
+        assert!(matches!(
+            result,
+            Err(PostSourceError::AccountUnavailable(ref msg)) if msg.contains("suspended")
+        ));
+    }
+}
@@ -7,6 +7,12 @@ use secrecy::{ExposeSecret, SecretString};
 use serde::{Deserialize, Serialize};
 use std::time::Duration;
 
+use super::budget::RateBudgets;
+use super::retry_after;
+
+/// Budget key for the create-tweet endpoint
+const CREATE_TWEET_ENDPOINT: &str = "POST /2/tweets";
+
 /// X API publisher for creating posts
 pub struct XPublisher {
     client: Client,
@@ -15,6 +21,7 @@ pub struct XPublisher {
     mode: XPublishMode,
     max_chars: usize,
     enabled: bool,
+    budgets: RateBudgets,
 }
 
 impl XPublisher {
@@ -47,6 +54,7 @@ impl XPublisher {
             mode,
             max_chars,
             enabled,
+            budgets: RateBudgets::default(),
         }
     }
 
@@ -59,6 +67,7 @@ impl XPublisher {
             mode: XPublishMode::default(),
             max_chars: 280,
             enabled: false,
+            budgets: RateBudgets::default(),
         }
     }
 }
@@ -124,6 +133,10 @@ impl Publisher for XPublisher {
 
         let url = format!("{}/2/tweets", self.base_url);
 
+        if let Some(wait) = self.budgets.wait(CREATE_TWEET_ENDPOINT) {
+            return Err(PublishError::RateLimited(Some(wait)));
+        }
+
         let response = self
             .client
             .post(&url)
@@ -136,13 +149,14 @@ impl Publisher for XPublisher {
             .send()
             .await
             .map_err(|e| PublishError::Api(e.to_string()))?;
+        self.budgets.record(CREATE_TWEET_ENDPOINT, &response);
 
         if response.status() == 401 {
             return Err(PublishError::Auth("Invalid user token".to_string()));
         }
 
         if response.status() == 429 {
-            return Err(PublishError::RateLimited);
+            return Err(PublishError::RateLimited(retry_after(&response)));
         }
 
         if !response.status().is_success() {
@@ -187,6 +201,10 @@ mod tests {
             text: "Tags: test_tag (0.85)\nTest rationale".to_string(),
             source_post_id: "original_tweet_id".to_string(),
             source_post_url: "https://x.com/user/status/original_tweet_id".to_string(),
+            source_author: "user".to_string(),
+            labels: vec![],
+            article: None,
+            analysis: None,
         }
     }
 
@@ -291,7 +309,7 @@ mod tests {
 
         let result = publisher.publish(&sample_post()).await;
 
-        assert!(matches!(result, Err(PublishError::RateLimited)));
+        assert!(matches!(result, Err(PublishError::RateLimited(_))));
     }
 
     #[tokio::test]
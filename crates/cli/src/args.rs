@@ -1,7 +1,9 @@
 //! CLI argument definitions
 
 use clap::{Args, Parser, Subcommand};
+use std::net::SocketAddr;
 use std::path::PathBuf;
+use std::time::Duration;
 
 /// news-tagger: CLI tool for classifying posts using LLM-powered narrative tagging
 #[derive(Parser, Debug)]
@@ -42,6 +44,18 @@ pub enum Commands {
 
     /// Interactive TUI for curating post classifications
     Curate(CurateArgs),
+
+    /// Inspect, retry or drop queued publish retries
+    Queue(QueueArgs),
+
+    /// Export stored classifications as a static site
+    Site(SiteArgs),
+
+    /// Manage the state database
+    State(StateArgs),
+
+    /// Serve a local HTTP API, optionally running the watch loop alongside
+    Serve(ServeArgs),
 }
 
 #[derive(Args, Debug)]
@@ -62,7 +76,7 @@ pub struct RunArgs {
     #[arg(long)]
     pub outbox: Option<PathBuf>,
 
-    /// Output full classification results as JSON (use with --once)
+    /// Output published, deferred and failed posts as JSON (use with --once)
     #[arg(long)]
     pub json: bool,
 
@@ -173,3 +187,178 @@ pub struct CurateArgs {
     #[arg(long)]
     pub definitions_dir: Option<PathBuf>,
 }
+
+#[derive(Args, Debug)]
+pub struct QueueArgs {
+    #[command(subcommand)]
+    pub command: QueueCommands,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum QueueCommands {
+    /// List queued publish retries
+    List {
+        /// Only show retries that ran out of attempts
+        #[arg(long)]
+        failed: bool,
+
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Make queued retries due on the next run cycle with a fresh attempt count
+    Retry {
+        /// Source post ID of the retries
+        #[arg(required_unless_present = "all")]
+        post_id: Option<String>,
+
+        /// Only the retry for this platform
+        #[arg(long)]
+        platform: Option<String>,
+
+        /// Every queued retry
+        #[arg(long, conflicts_with = "post_id")]
+        all: bool,
+    },
+
+    /// Remove queued retries without publishing them
+    Drop {
+        /// Source post ID of the retries
+        post_id: String,
+
+        /// Only the retry for this platform
+        #[arg(long)]
+        platform: Option<String>,
+    },
+}
+
+#[derive(Args, Debug)]
+pub struct SiteArgs {
+    #[command(subcommand)]
+    pub command: SiteCommands,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum SiteCommands {
+    /// Render HTML pages, Atom feeds and a JSON API mirror into a directory
+    Build {
+        /// Output directory (rebuilt in place, only changed files are rewritten)
+        #[arg(long, default_value = "./public")]
+        out: PathBuf,
+
+        /// Public URL the directory is served from, for absolute feed links
+        #[arg(long)]
+        base_url: Option<String>,
+
+        /// Site title
+        #[arg(long, default_value = "news-tagger")]
+        title: String,
+
+        /// Override definitions directory (used for tag titles)
+        #[arg(long)]
+        definitions_dir: Option<PathBuf>,
+    },
+}
+
+#[derive(Args, Debug)]
+pub struct StateArgs {
+    #[command(subcommand)]
+    pub command: StateCommands,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum StateCommands {
+    /// Apply pending schema migrations
+    Migrate {
+        /// Show pending migrations and their SQL without applying them
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Write account cursors, published records and classifications as JSONL
+    Export {
+        /// Output file (default: stdout)
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+    },
+
+    /// Merge a JSONL export into the state database (safe to repeat)
+    Import {
+        /// File written by `state export`, or `-` for stdin
+        input: PathBuf,
+    },
+
+    /// Delete old published records, keeping per-tag monthly counts
+    Prune {
+        /// Age of the records to delete, e.g. `90d`, `2w` or `12h`
+        #[arg(long, value_parser = parse_age)]
+        older_than: Duration,
+    },
+
+    /// Reclaim the space left by deleted rows
+    Vacuum,
+
+    /// Show row counts and sizes of the state tables
+    Stats,
+}
+
+#[derive(Args, Debug)]
+pub struct ServeArgs {
+    /// Address to listen on
+    #[arg(long, default_value = "127.0.0.1:8080")]
+    pub listen: SocketAddr,
+
+    /// Also run the continuous watch loop in this process
+    #[arg(long)]
+    pub watch: bool,
+
+    /// Run the watch loop in dry-run mode and never publish approved outbox entries
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// Have the watch loop write rendered posts to the outbox for approval
+    #[arg(long)]
+    pub require_approval: bool,
+
+    /// Path to the outbox file that approvals are read from and recorded in
+    #[arg(long)]
+    pub outbox: Option<PathBuf>,
+
+    /// Use a JSONL file as the watch loop's post source instead of X API
+    #[arg(long)]
+    pub source: Option<PathBuf>,
+}
+
+/// Parse an age such as `90d`: a number followed by `s`, `m`, `h`, `d` or `w`
+fn parse_age(value: &str) -> Result<Duration, String> {
+    let split = value.len().saturating_sub(1);
+    let (number, unit) = value.split_at(split);
+    let number: u64 = number
+        .parse()
+        .map_err(|_| format!("expected an age like 90d, got {value:?}"))?;
+    let unit_secs = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        "w" => 7 * 24 * 60 * 60,
+        _ => return Err(format!("unknown unit in {value:?}; use s, m, h, d or w")),
+    };
+    Ok(Duration::from_secs(number.saturating_mul(unit_secs)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_age() {
+        assert_eq!(parse_age("90d").unwrap(), Duration::from_secs(90 * 86_400));
+        assert_eq!(parse_age("12h").unwrap(), Duration::from_secs(12 * 3_600));
+        assert_eq!(parse_age("2w").unwrap(), Duration::from_secs(14 * 86_400));
+        assert!(parse_age("90").is_err());
+        assert!(parse_age("d").is_err());
+        assert!(parse_age("3y").is_err());
+    }
+}
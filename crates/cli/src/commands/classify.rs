@@ -63,6 +63,7 @@ pub async fn execute(args: ClassifyArgs, config_path: Option<PathBuf>) -> Result
         is_repost: false,
         is_reply: false,
         reply_to_id: None,
+        context: vec![],
     };
 
     // Run classification (same prefilter behavior as main loop)
@@ -205,7 +206,7 @@ fn adapter_llm_config(config: &crate::config::LlmConfig) -> AdapterLlmConfig {
     }
 }
 
-fn classify_config_from_config(config: &AppConfig) -> ClassifyConfig {
+pub(crate) fn classify_config_from_config(config: &AppConfig) -> ClassifyConfig {
     let prefilter_top_k = if config.llm.prefilter_top_k == 0 {
         None
     } else {
@@ -294,6 +295,7 @@ mod tests {
                 is_repost: false,
                 is_reply: false,
                 reply_to_id: None,
+                context: vec![],
             },
             definitions: vec![TagDefinition {
                 id: "test_tag".to_string(),
@@ -276,6 +276,7 @@ impl App {
         Ok(())
     }
 
+    #[allow(clippy::collapsible_match)]
     fn handle_normal_key(&mut self, key: KeyCode) -> Result<bool> {
         match key {
             KeyCode::Char('q') => return Ok(true),
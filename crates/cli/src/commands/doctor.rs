@@ -2,11 +2,12 @@
 
 use anyhow::Result;
 use news_tagger_adapters::definitions::FilesystemDefinitionsRepo;
-use news_tagger_domain::DefinitionsRepo;
+use news_tagger_domain::{AccountStatus, DefinitionsRepo};
 use serde::Serialize;
 use std::path::PathBuf;
 
 use crate::args::DoctorArgs;
+use crate::commands::state::open_state_store;
 use crate::config::AppConfig;
 
 #[derive(Debug, Serialize)]
@@ -15,6 +16,7 @@ struct DoctorReport {
     definitions: CheckResult,
     llm: CheckResult,
     x_read: CheckResult,
+    x_accounts: CheckResult,
     x_write: CheckResult,
     nostr: CheckResult,
     overall: String,
@@ -72,6 +74,7 @@ pub async fn execute(args: DoctorArgs, config_path: Option<PathBuf>) -> Result<(
         definitions: CheckResult::error("Not checked"),
         llm: CheckResult::error("Not checked"),
         x_read: CheckResult::error("Not checked"),
+        x_accounts: CheckResult::error("Not checked"),
         x_write: CheckResult::error("Not checked"),
         nostr: CheckResult::error("Not checked"),
         overall: "error".to_string(),
@@ -99,6 +102,9 @@ pub async fn execute(args: DoctorArgs, config_path: Option<PathBuf>) -> Result<(
         // Check X read
         report.x_read = check_x_read(config);
 
+        // Check cached account resolutions
+        report.x_accounts = check_x_accounts(config).await;
+
         // Check X write
         report.x_write = check_x_write(config);
 
@@ -264,7 +270,7 @@ fn check_x_read(config: &AppConfig) -> CheckResult {
         return CheckResult::error("No bearer token env var configured");
     }
 
-    let accounts = &config.watch.accounts;
+    let accounts = &config.watch.account_names();
     if accounts.is_empty() {
         return CheckResult::warn("No accounts configured to watch");
     }
@@ -283,6 +289,66 @@ fn check_x_read(config: &AppConfig) -> CheckResult {
     }
 }
 
+async fn check_x_accounts(config: &AppConfig) -> CheckResult {
+    let accounts = &config.watch.account_names();
+    if accounts.is_empty() {
+        return CheckResult::ok("No accounts configured to watch");
+    }
+
+    if config.general.state_db_url.is_none() && !config.general.state_db_path.exists() {
+        return CheckResult::ok("No account lookups cached yet");
+    }
+
+    let store = match open_state_store(config).await {
+        Ok(store) => store,
+        Err(e) => return CheckResult::warn(format!("Failed to open state DB: {}", e)),
+    };
+
+    let resolved = match store.list_resolved_accounts().await {
+        Ok(resolved) => resolved,
+        Err(e) => return CheckResult::warn(format!("Failed to read cached accounts: {}", e)),
+    };
+
+    let mut problems = Vec::new();
+    let mut unresolved = Vec::new();
+    for account in accounts {
+        let key = account.to_ascii_lowercase();
+        match resolved.iter().find(|r| r.account == key) {
+            None => unresolved.push(account.clone()),
+            Some(r) => match r.status {
+                AccountStatus::Active => {}
+                AccountStatus::Renamed => problems.push(format!(
+                    "@{} renamed to @{}",
+                    account,
+                    r.renamed_to.as_deref().unwrap_or("?")
+                )),
+                AccountStatus::Suspended => problems.push(format!("@{} suspended", account)),
+                AccountStatus::NotFound => problems.push(format!("@{} not found", account)),
+            },
+        }
+    }
+
+    let watched: Vec<_> = resolved
+        .iter()
+        .filter(|r| accounts.iter().any(|a| a.eq_ignore_ascii_case(&r.account)))
+        .collect();
+    let details = serde_json::json!({
+        "resolved": watched,
+        "unresolved": unresolved,
+    });
+
+    if problems.is_empty() {
+        CheckResult::ok(format!(
+            "{} of {} accounts resolved",
+            accounts.len() - unresolved.len(),
+            accounts.len()
+        ))
+        .with_details(details)
+    } else {
+        CheckResult::warn(problems.join(", ")).with_details(details)
+    }
+}
+
 fn check_x_write(config: &AppConfig) -> CheckResult {
     if !config.x.write.enabled {
         return CheckResult::ok("X write disabled");
@@ -345,6 +411,7 @@ fn print_report(report: &DoctorReport) {
     print_check("Definitions", &report.definitions);
     print_check("LLM Provider", &report.llm);
     print_check("X Read", &report.x_read);
+    print_check("X Accounts", &report.x_accounts);
     print_check("X Write", &report.x_write);
     print_check("Nostr", &report.nostr);
 
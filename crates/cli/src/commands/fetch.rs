@@ -1,29 +1,32 @@
 //! Fetch command - collect posts from X and save as JSONL
 
 use anyhow::{Context, Result};
-use news_tagger_adapters::x::XPostSource;
-use news_tagger_domain::{PostSource, SourcePost, compare_post_ids};
+use news_tagger_domain::{SourcePost, compare_post_ids};
 use std::path::PathBuf;
 use tokio::fs::OpenOptions;
 use tokio::io::AsyncWriteExt;
 
 use crate::args::FetchArgs;
-use crate::commands::classify::load_api_key;
+use crate::commands::run::build_post_source;
+use crate::commands::state::open_state_store;
 use crate::config::AppConfig;
 
 pub async fn execute(args: FetchArgs, config_path: Option<PathBuf>) -> Result<()> {
     let config = AppConfig::load(config_path.as_deref())?;
 
-    let accounts = args
-        .accounts
-        .unwrap_or_else(|| config.watch.accounts.clone());
+    let accounts = args.accounts.unwrap_or_else(|| config.watched_accounts());
 
     if accounts.is_empty() {
         anyhow::bail!("No accounts configured. Use --accounts or set watch.accounts in config.");
     }
 
-    let bearer_token = load_api_key(&config.x.read.bearer_token_env, "x_read")?;
-    let post_source = XPostSource::new(bearer_token);
+    let state_store = open_state_store(&config).await?;
+    let post_source = build_post_source(&config, state_store, &accounts)?;
+
+    // Resolve all user IDs in one batched lookup
+    if let Err(e) = post_source.prepare_accounts(&accounts).await {
+        tracing::warn!(error = %e, "Failed to resolve accounts, continuing");
+    }
 
     // Load existing posts to find the latest ID per account (for incremental fetches)
     let existing_max_ids = load_max_ids_per_account(&args.output);
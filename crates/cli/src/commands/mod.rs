@@ -6,4 +6,8 @@ pub mod curate;
 pub mod definitions;
 pub mod doctor;
 pub mod fetch;
+pub mod queue;
 pub mod run;
+pub mod serve;
+pub mod site;
+pub mod state;
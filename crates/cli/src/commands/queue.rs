@@ -0,0 +1,128 @@
+//! Queue command - inspect, retry and drop queued publish retries
+
+use anyhow::{Context, Result, bail};
+use news_tagger_domain::{PublishRetry, RetryStatus, StateStore};
+use std::path::PathBuf;
+use time::OffsetDateTime;
+
+use crate::args::{QueueArgs, QueueCommands};
+use crate::commands::state::open_state_store;
+use crate::config::AppConfig;
+
+pub async fn execute(args: QueueArgs, config_path: Option<PathBuf>) -> Result<()> {
+    let config = AppConfig::load(config_path.as_deref())?;
+    let store = open_state_store(&config).await?;
+    let store = store.as_ref();
+
+    match args.command {
+        QueueCommands::List { failed, json } => list(store, failed, json).await,
+        QueueCommands::Retry {
+            post_id,
+            platform,
+            all,
+        } => {
+            let post_id = if all { None } else { post_id };
+            retry(store, post_id.as_deref(), platform.as_deref()).await
+        }
+        QueueCommands::Drop { post_id, platform } => {
+            drop_retries(store, &post_id, platform.as_deref()).await
+        }
+    }
+}
+
+async fn list(store: &dyn StateStore, failed_only: bool, json: bool) -> Result<()> {
+    let retries: Vec<PublishRetry> = store
+        .list_retries()
+        .await
+        .context("Failed to load retry queue")?
+        .into_iter()
+        .filter(|r| !failed_only || r.status == RetryStatus::Failed)
+        .collect();
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&retries)?);
+        return Ok(());
+    }
+
+    if retries.is_empty() {
+        println!("Retry queue is empty");
+        return Ok(());
+    }
+
+    println!("Publish retries ({})", retries.len());
+    println!("====================");
+    for retry in &retries {
+        println!();
+        println!("{} -> {}", retry.source_post.id, retry.platform);
+        println!("  Status: {}", retry.status.as_str());
+        println!("  Attempts: {}", retry.attempts);
+        if retry.status == RetryStatus::Pending {
+            println!("  Next attempt: {}", retry.next_attempt_at);
+        }
+        println!("  Last error: {}", retry.last_error);
+        println!("  Post: {}", retry.source_post.url);
+    }
+
+    Ok(())
+}
+
+async fn retry(
+    store: &dyn StateStore,
+    post_id: Option<&str>,
+    platform: Option<&str>,
+) -> Result<()> {
+    let selected = select(store, post_id, platform).await?;
+    let now = OffsetDateTime::now_utc();
+
+    for mut retry in selected {
+        retry.status = RetryStatus::Pending;
+        retry.attempts = 0;
+        retry.next_attempt_at = now;
+        retry.updated_at = now;
+        store
+            .set_retry(&retry)
+            .await
+            .context("Failed to update retry")?;
+        println!(
+            "Scheduled {} -> {} for the next run cycle",
+            retry.source_post.id, retry.platform
+        );
+    }
+
+    Ok(())
+}
+
+async fn drop_retries(store: &dyn StateStore, post_id: &str, platform: Option<&str>) -> Result<()> {
+    for retry in select(store, Some(post_id), platform).await? {
+        store
+            .delete_retry(retry.record_id, &retry.platform)
+            .await
+            .context("Failed to remove retry")?;
+        println!("Dropped {} -> {}", retry.source_post.id, retry.platform);
+    }
+
+    Ok(())
+}
+
+/// Queued retries for `post_id` (all posts when `None`), optionally limited
+/// to one platform; fails when nothing matches
+async fn select(
+    store: &dyn StateStore,
+    post_id: Option<&str>,
+    platform: Option<&str>,
+) -> Result<Vec<PublishRetry>> {
+    let selected: Vec<PublishRetry> = store
+        .list_retries()
+        .await
+        .context("Failed to load retry queue")?
+        .into_iter()
+        .filter(|r| post_id.is_none_or(|id| r.source_post.id == id))
+        .filter(|r| platform.is_none_or(|p| r.platform.eq_ignore_ascii_case(p)))
+        .collect();
+
+    if selected.is_empty() {
+        bail!("No queued retries match");
+    }
+
+    Ok(selected)
+}
@@ -2,31 +2,139 @@
 
 use anyhow::{Context, Result, bail};
 use news_tagger_adapters::{
+    bluesky::{BlueskyPostSource, BlueskyPublishMode, BlueskyPublisher, BlueskySession},
     definitions::FilesystemDefinitionsRepo,
+    feed::{FeedPostSource, FeedSubscription},
     jsonl::JsonlPostSource,
-    nostr::NostrPublisher,
+    mastodon::{MastodonPostSource, MastodonPublishMode, MastodonPublisher},
+    metrics::PrometheusMetrics,
+    nostr::{NostrPostSource, NostrPublisher},
     outbox::{OutboxPublisher, OutboxWriter},
-    state::SqliteStateStore,
+    routed::RoutedPostSource,
+    webhook::WebhookPublisher,
     x::{XPostSource, XPublisher},
 };
 use news_tagger_domain::{
-    Classifier, PostSource, ProcessResult, Publisher, SystemClock, XPublishMode,
-    usecases::{ClassifyConfig, RenderConfig, RunLoop, RunLoopConfig},
+    AccountSchedule, ActiveHours, Classifier, PostRetry, PostSource, ProcessResult, Publisher,
+    StateStore, SystemClock, XPublishMode,
+    usecases::{ClassifyConfig, RenderConfig, RunLoop, RunLoopConfig, RunLoopError},
 };
 use secrecy::ExposeSecret;
 use serde::Serialize;
+use std::collections::HashMap;
 use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::Duration;
+use time::Time;
+use tokio::net::TcpListener;
 use tokio::time::interval;
 
 use crate::args::RunArgs;
 use crate::commands::classify::{build_classifier, load_api_key};
-use crate::config::AppConfig;
+use crate::commands::serve::metrics_router;
+use crate::commands::state::open_state_store;
+use crate::config::{AppConfig, WatchAccount};
 
 pub async fn execute(args: RunArgs, config_path: Option<PathBuf>) -> Result<()> {
     let config = AppConfig::load(config_path.as_deref())?;
+    let mut run_loop = build_run_loop(&config, &args).await?;
 
+    // Metrics are only served while polling continuously
+    let metrics = (config.metrics.enabled && !args.once).then(|| Arc::new(build_metrics(&config)));
+    if let Some(metrics) = &metrics {
+        run_loop = run_loop.with_metrics(metrics.clone());
+    }
+
+    // Execute
+    if args.once {
+        tracing::info!("Running single poll cycle");
+        let results = poll_until_drained(&run_loop).await?;
+        tracing::info!(processed = results.len(), "Poll cycle complete");
+
+        let mut json_results: Vec<JsonResult> = Vec::new();
+
+        for (post_id, result) in results {
+            match result {
+                ProcessResult::Published {
+                    source_post,
+                    classification,
+                    targets,
+                } => {
+                    tracing::info!(
+                        post_id = %post_id,
+                        tags = ?classification.tags.iter().map(|t| &t.id).collect::<Vec<_>>(),
+                        targets = ?targets
+                            .iter()
+                            .map(|t| format!("{}:{}", t.platform, t.target_id))
+                            .collect::<Vec<_>>(),
+                        "Published"
+                    );
+                    if args.json {
+                        json_results.push(JsonResult::Published {
+                            post_id,
+                            author: source_post.author,
+                            text: source_post.text,
+                            url: source_post.url,
+                            classification,
+                        });
+                    }
+                }
+                ProcessResult::Skipped { reason } => {
+                    tracing::debug!(post_id = %post_id, reason = %reason, "Skipped");
+                }
+                ProcessResult::Deferred { reason } => {
+                    tracing::info!(post_id = %post_id, reason = %reason, "Deferred");
+                    if args.json {
+                        json_results.push(JsonResult::Deferred { post_id, reason });
+                    }
+                }
+                ProcessResult::Failed { error, retry } => {
+                    tracing::error!(post_id = %post_id, error = %error, "Failed");
+                    if args.json {
+                        json_results.push(JsonResult::Failed {
+                            post_id,
+                            error,
+                            retry,
+                        });
+                    }
+                }
+            }
+        }
+
+        if args.json {
+            let json = serde_json::to_string_pretty(&json_results)
+                .context("Failed to serialize results")?;
+            println!("{}", json);
+        }
+    } else {
+        if let Some(metrics) = metrics {
+            let listener = TcpListener::bind(config.metrics.listen)
+                .await
+                .with_context(|| format!("Failed to listen on {}", config.metrics.listen))?;
+            tracing::info!(listen = %config.metrics.listen, "Serving metrics");
+            tokio::spawn(async move {
+                if let Err(e) = axum::serve(listener, metrics_router(metrics)).await {
+                    tracing::error!(error = %e, "Metrics server failed");
+                }
+            });
+        }
+
+        run_continuously(&run_loop, config.watch.poll_tick()).await;
+    }
+
+    tracing::info!("news-tagger run completed");
+    Ok(())
+}
+
+/// The run loop as built from the configuration
+pub(crate) type ConfiguredRunLoop =
+    RunLoop<dyn PostSource, FilesystemDefinitionsRepo, dyn Classifier, dyn StateStore, SystemClock>;
+
+/// Build the run loop for `args` (`once` and `json` only matter to the caller)
+pub(crate) async fn build_run_loop(
+    config: &AppConfig,
+    args: &RunArgs,
+) -> Result<ConfiguredRunLoop> {
     let require_approval = args.require_approval;
     let outbox_path = if require_approval {
         Some(args.outbox.clone().unwrap_or_else(default_outbox_path))
@@ -49,7 +157,8 @@ pub async fn execute(args: RunArgs, config_path: Option<PathBuf>) -> Result<()>
         once = args.once,
         require_approval = require_approval,
         outbox = ?outbox_path,
-        accounts = ?config.watch.accounts,
+        accounts = ?config.watched_accounts(),
+        worker_id = %config.worker_id(),
         "Starting news-tagger run"
     );
 
@@ -59,56 +168,20 @@ pub async fn execute(args: RunArgs, config_path: Option<PathBuf>) -> Result<()>
             .context("Failed to initialize definitions repository")?,
     );
 
-    let state_store = Arc::new(
-        SqliteStateStore::new(&config.general.state_db_path)
-            .await
-            .context("Failed to initialize SQLite state store")?,
-    );
+    let state_store = open_state_store(config).await?;
 
     let post_source: Arc<dyn PostSource> = if let Some(ref source_path) = args.source {
         Arc::new(JsonlPostSource::new(vec![source_path.clone()]))
     } else {
-        Arc::new(build_post_source(&config)?)
+        build_post_source(config, state_store.clone(), &config.watched_accounts())?
     };
-    let classifier: Arc<dyn Classifier> = Arc::from(build_classifier(&config)?);
+    let classifier: Arc<dyn Classifier> = Arc::from(build_classifier(config)?);
 
     let x_mode = parse_x_publish_mode(&config.x.write.mode)?;
-    let (x_publisher, nostr_publisher): (Arc<dyn Publisher>, Arc<dyn Publisher>) =
-        if require_approval {
-            let outbox_path = outbox_path.expect("outbox path set when require_approval");
-            let writer = OutboxWriter::new(outbox_path.clone())
-                .await
-                .context("Failed to initialize outbox writer")?;
-
-            tracing::info!(
-                outbox = %outbox_path.display(),
-                "Writing approvals to outbox"
-            );
-
-            if !config.x.write.enabled && !config.nostr.enabled {
-                tracing::warn!("Require approval enabled but no publishers are configured");
-            }
-
-            let x_publisher: Arc<dyn Publisher> = if config.x.write.enabled {
-                Arc::new(OutboxPublisher::new(writer.clone(), "x"))
-            } else {
-                Arc::new(XPublisher::disabled())
-            };
-
-            let nostr_publisher: Arc<dyn Publisher> = if config.nostr.enabled {
-                Arc::new(OutboxPublisher::new(writer, "nostr"))
-            } else {
-                Arc::new(NostrPublisher::disabled())
-            };
-
-            (x_publisher, nostr_publisher)
-        } else {
-            let x_publisher: Arc<dyn Publisher> =
-                Arc::new(build_x_publisher(&config, dry_run, x_mode)?);
-            let nostr_publisher: Arc<dyn Publisher> =
-                Arc::new(build_nostr_publisher(&config, dry_run)?);
-            (x_publisher, nostr_publisher)
-        };
+    let publishers = match outbox_path {
+        Some(outbox_path) => build_outbox_publishers(config, outbox_path).await?,
+        None => build_publishers(config, dry_run, state_store.clone()).await?,
+    };
 
     let clock = Arc::new(SystemClock);
 
@@ -116,128 +189,305 @@ pub async fn execute(args: RunArgs, config_path: Option<PathBuf>) -> Result<()>
     let accounts = if args.source.is_some() && config.watch.accounts.is_empty() {
         vec!["*".to_string()]
     } else {
-        config.watch.accounts.clone()
+        config.watched_accounts()
     };
     let loop_config = RunLoopConfig {
         accounts,
         include_replies: config.watch.include_replies,
         include_reposts: config.watch.include_reposts,
         ignore_patterns: config.watch.ignore_patterns.clone(),
+        poll_interval: Some(Duration::from_secs(config.watch.poll_interval_secs)),
+        schedules: account_schedules_from_config(config)?,
         dry_run,
         max_concurrent: config.general.max_concurrent,
         rate_limit_per_minute: rate_limit_from_config(config.general.rate_limit_per_minute),
         rate_limit_per_hour: rate_limit_from_config(config.general.rate_limit_per_hour),
-        classify_config: classify_config_from_config(&config),
+        rate_limit_backoff: Duration::from_secs(config.general.rate_limit_backoff_secs),
+        retry_max_attempts: config.general.retry_max_attempts.max(1),
+        retry_base_delay: Duration::from_secs(config.general.retry_base_delay_secs),
+        worker_id: config.worker_id(),
+        claim_lease: Duration::from_secs(config.general.lease_secs),
+        // Renewed every poll, so it has to outlast the poll interval
+        account_lease: Duration::from_secs(config.general.lease_secs)
+            .max(config.watch.longest_poll_interval() * 2),
+        drain_timeout: Duration::from_secs(config.general.shutdown_timeout_secs),
+        classify_config: classify_config_from_config(config),
         render_config: RenderConfig {
             x_max_chars: config.x.write.max_chars,
             x_publish_mode: x_mode,
+            mastodon_max_chars: config.mastodon.write.max_chars,
+            bluesky_max_chars: config.bluesky.write.max_chars,
+            nostr_long_form: config.nostr.long_form,
+            tag_definitions_url: config.nostr.tag_definitions_url.clone(),
             ..Default::default()
         },
     };
 
-    // Create run loop
-    let run_loop = RunLoop::new(
+    Ok(RunLoop::new(
         post_source,
         definitions_repo,
         classifier,
-        x_publisher,
-        nostr_publisher,
+        publishers,
         state_store,
         clock,
         loop_config,
-    );
+    ))
+}
 
-    // Execute
-    if args.once {
-        tracing::info!("Running single poll cycle");
-        let results = run_loop.poll_once().await?;
-        tracing::info!(processed = results.len(), "Poll cycle complete");
+/// Prometheus metrics priced with the configured LLM token costs
+pub(crate) fn build_metrics(config: &AppConfig) -> PrometheusMetrics {
+    PrometheusMetrics::new().with_token_prices(
+        config.llm.input_cost_per_million_tokens,
+        config.llm.output_cost_per_million_tokens,
+    )
+}
 
-        let mut json_results: Vec<JsonResult> = Vec::new();
+/// Resolves on Ctrl+C, or SIGTERM on Unix
+pub(crate) async fn shutdown_signal() {
+    let ctrl_c = async {
+        tokio::signal::ctrl_c()
+            .await
+            .expect("Failed to install Ctrl+C handler");
+    };
 
-        for (post_id, result) in results {
-            match result {
-                ProcessResult::Published {
-                    source_post,
-                    classification,
-                    x_post_id,
-                    nostr_event_id,
-                } => {
-                    tracing::info!(
-                        post_id = %post_id,
-                        tags = ?classification.tags.iter().map(|t| &t.id).collect::<Vec<_>>(),
-                        x_post_id = ?x_post_id,
-                        nostr_event_id = ?nostr_event_id,
-                        "Published"
-                    );
-                    if args.json {
-                        json_results.push(JsonResult {
-                            post_id,
-                            author: source_post.author,
-                            text: source_post.text,
-                            url: source_post.url,
-                            classification,
-                        });
-                    }
-                }
-                ProcessResult::Skipped { reason } => {
-                    tracing::debug!(post_id = %post_id, reason = %reason, "Skipped");
-                }
-                ProcessResult::Failed { error } => {
-                    tracing::error!(post_id = %post_id, error = %error, "Failed");
-                }
-            }
+    #[cfg(unix)]
+    let terminate = async {
+        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+            .expect("Failed to install SIGTERM handler")
+            .recv()
+            .await;
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {}
+        _ = terminate => {}
+    }
+    tracing::info!("Shutdown signal received");
+}
+
+/// Run one poll cycle; on a shutdown signal, finish the posts in flight
+/// and return what completed
+async fn poll_until_drained(
+    run_loop: &ConfiguredRunLoop,
+) -> Result<Vec<(String, ProcessResult)>, RunLoopError> {
+    let cycle = run_loop.poll_once();
+    tokio::pin!(cycle);
+
+    tokio::select! {
+        results = &mut cycle => results,
+        _ = shutdown_signal() => {
+            tracing::info!("Draining in-flight posts");
+            run_loop.shutdown();
+            cycle.await
         }
+    }
+}
 
-        if args.json {
-            let json = serde_json::to_string_pretty(&json_results)
-                .context("Failed to serialize results")?;
-            println!("{}", json);
+/// Poll the accounts that are due every `poll_interval` until Ctrl+C or
+/// SIGTERM, letting a cycle in progress drain first
+pub(crate) async fn run_continuously(run_loop: &ConfiguredRunLoop, poll_interval: Duration) {
+    let mut ticker = interval(poll_interval);
+
+    let shutdown = shutdown_signal();
+    tokio::pin!(shutdown);
+
+    loop {
+        tokio::select! {
+            _ = ticker.tick() => {}
+            _ = &mut shutdown => break,
         }
-    } else {
-        // Continuous polling loop
-        let poll_interval = Duration::from_secs(config.watch.poll_interval_secs);
-        let mut ticker = interval(poll_interval);
 
-        // Set up graceful shutdown
-        let shutdown = async {
-            tokio::signal::ctrl_c()
-                .await
-                .expect("Failed to install Ctrl+C handler");
-            tracing::info!("Shutdown signal received");
+        let cycle = run_loop.poll_once();
+        tokio::pin!(cycle);
+        let results = tokio::select! {
+            results = &mut cycle => results,
+            _ = &mut shutdown => {
+                tracing::info!("Draining in-flight posts");
+                run_loop.shutdown();
+                cycle.await
+            }
         };
-
-        tokio::pin!(shutdown);
-
-        loop {
-            tokio::select! {
-                _ = ticker.tick() => {
-                    match run_loop.poll_once().await {
-                        Ok(results) => {
-                            if !results.is_empty() {
-                                tracing::info!(processed = results.len(), "Poll cycle complete");
-                            }
-                        }
-                        Err(e) => {
-                            tracing::error!(error = %e, "Poll cycle failed");
-                        }
-                    }
-                }
-                _ = &mut shutdown => {
-                    tracing::info!("Shutting down gracefully");
-                    break;
+        match results {
+            Ok(results) => {
+                if !results.is_empty() {
+                    tracing::info!(processed = results.len(), "Poll cycle complete");
                 }
             }
+            Err(e) => {
+                tracing::error!(error = %e, "Poll cycle failed");
+            }
+        }
+        if run_loop.is_shutting_down() {
+            break;
         }
     }
+    tracing::info!("Shutting down gracefully");
+}
 
-    tracing::info!("news-tagger run completed");
-    Ok(())
+/// Publishers that write every enabled platform's posts to the outbox instead
+async fn build_outbox_publishers(
+    config: &AppConfig,
+    outbox_path: PathBuf,
+) -> Result<Vec<Arc<dyn Publisher>>> {
+    let writer = OutboxWriter::new(outbox_path.clone())
+        .await
+        .context("Failed to initialize outbox writer")?;
+
+    tracing::info!(
+        outbox = %outbox_path.display(),
+        "Writing approvals to outbox"
+    );
+
+    if !config.x.write.enabled
+        && !config.nostr.enabled
+        && !config.mastodon.write.enabled
+        && !config.bluesky.write.enabled
+        && !config.webhook.enabled
+    {
+        tracing::warn!("Require approval enabled but no publishers are configured");
+    }
+
+    let x_publisher: Arc<dyn Publisher> = if config.x.write.enabled {
+        Arc::new(OutboxPublisher::new(writer.clone(), "x"))
+    } else {
+        Arc::new(XPublisher::disabled())
+    };
+
+    let nostr_publisher: Arc<dyn Publisher> = if config.nostr.enabled {
+        Arc::new(OutboxPublisher::new(writer.clone(), "nostr"))
+    } else {
+        Arc::new(NostrPublisher::disabled())
+    };
+
+    let mastodon_publisher: Arc<dyn Publisher> = if config.mastodon.write.enabled {
+        Arc::new(OutboxPublisher::new(writer.clone(), "mastodon"))
+    } else {
+        Arc::new(MastodonPublisher::disabled())
+    };
+
+    let bluesky_publisher: Arc<dyn Publisher> = if config.bluesky.write.enabled {
+        Arc::new(OutboxPublisher::new(writer.clone(), "bluesky"))
+    } else {
+        Arc::new(BlueskyPublisher::disabled())
+    };
+
+    let mut publishers = vec![
+        x_publisher,
+        nostr_publisher,
+        mastodon_publisher,
+        bluesky_publisher,
+    ];
+    if config.webhook.enabled {
+        publishers.push(Arc::new(OutboxPublisher::new(writer, "webhook")));
+    }
+    Ok(publishers)
 }
 
-fn build_post_source(config: &AppConfig) -> Result<XPostSource> {
-    let bearer_token = load_api_key(&config.x.read.bearer_token_env, "x_read")?;
-    Ok(XPostSource::new(bearer_token))
+/// Publishers for every platform; disabled ones (and all of them in dry-run)
+/// never publish
+pub(crate) async fn build_publishers(
+    config: &AppConfig,
+    dry_run: bool,
+    state_store: Arc<dyn StateStore>,
+) -> Result<Vec<Arc<dyn Publisher>>> {
+    let x_mode = parse_x_publish_mode(&config.x.write.mode)?;
+    let mastodon_mode = parse_mastodon_publish_mode(&config.mastodon.write.mode)?;
+    let bluesky_mode = parse_bluesky_publish_mode(&config.bluesky.write.mode)?;
+
+    let x_publisher: Arc<dyn Publisher> = Arc::new(build_x_publisher(config, dry_run, x_mode)?);
+    let nostr_publisher: Arc<dyn Publisher> =
+        Arc::new(build_nostr_publisher(config, dry_run, state_store)?);
+    let mastodon_publisher: Arc<dyn Publisher> =
+        Arc::new(build_mastodon_publisher(config, dry_run, mastodon_mode)?);
+    let bluesky_publisher: Arc<dyn Publisher> =
+        Arc::new(build_bluesky_publisher(config, dry_run, bluesky_mode)?);
+    let mut publishers = vec![
+        x_publisher,
+        nostr_publisher,
+        mastodon_publisher,
+        bluesky_publisher,
+    ];
+    if let Some(webhook_publisher) = build_webhook_publisher(config, dry_run).await? {
+        publishers.push(Arc::new(webhook_publisher));
+    }
+    Ok(publishers)
+}
+
+/// Build the post source for `accounts`, routing `feed:<name>` to configured
+/// feeds, `mastodon:<acct>` to the Mastodon instance, `bluesky:<actor>` to
+/// Bluesky, `nostr:<npub>` to the Nostr relays and everything else to X (only
+/// set up when actually needed)
+pub(crate) fn build_post_source(
+    config: &AppConfig,
+    state_store: Arc<dyn StateStore>,
+    accounts: &[String],
+) -> Result<Arc<dyn PostSource>> {
+    let mut source = RoutedPostSource::new();
+
+    let routed = |account: &String| {
+        account.starts_with("feed:")
+            || account.starts_with("mastodon:")
+            || account.starts_with("bluesky:")
+            || account.starts_with("nostr:")
+    };
+    if accounts.iter().any(|account| !routed(account)) {
+        let bearer_token = load_api_key(&config.x.read.bearer_token_env, "x_read")?;
+        let refresh_interval = Duration::from_secs(config.x.read.user_id_refresh_hours * 3600);
+        let x_source = XPostSource::new(bearer_token)
+            .with_conversation_root(config.x.read.include_conversation_root)
+            .with_user_id_cache(state_store.clone(), refresh_interval);
+        source = source.with_default(Arc::new(x_source));
+    }
+
+    if accounts
+        .iter()
+        .any(|account| account.starts_with("mastodon:"))
+    {
+        if config.mastodon.instance_url.is_empty() {
+            bail!("Mastodon accounts configured but mastodon.instance_url is empty");
+        }
+        // Public timelines usually need no token; use one when it is available
+        let access_token = load_api_key(&config.mastodon.access_token_env, "mastodon").ok();
+        let mastodon_source =
+            MastodonPostSource::new(config.mastodon.instance_url.clone(), access_token);
+        source = source.with_route("mastodon", Arc::new(mastodon_source));
+    }
+
+    if accounts
+        .iter()
+        .any(|account| account.starts_with("bluesky:"))
+    {
+        let mut bluesky_source = BlueskyPostSource::new(config.bluesky.appview_url.clone());
+        if let Some(session) = build_bluesky_session(config)? {
+            bluesky_source = bluesky_source.with_session(session);
+        }
+        source = source.with_route("bluesky", Arc::new(bluesky_source));
+    }
+
+    if accounts.iter().any(|account| account.starts_with("nostr:")) {
+        if config.nostr.relays.is_empty() {
+            bail!("Nostr authors configured but no relays configured");
+        }
+        let nostr_source = NostrPostSource::new(config.nostr.relays.clone());
+        source = source.with_route("nostr", Arc::new(nostr_source));
+    }
+
+    if !config.feeds.is_empty() {
+        let feeds = config
+            .feeds
+            .iter()
+            .map(|feed| FeedSubscription {
+                name: feed.name.clone(),
+                url: feed.url.clone(),
+            })
+            .collect();
+        let feed_source = FeedPostSource::new(feeds).with_state_store(state_store);
+        source = source.with_route("feed", Arc::new(feed_source));
+    }
+
+    Ok(Arc::new(source))
 }
 
 fn build_x_publisher(config: &AppConfig, dry_run: bool, mode: XPublishMode) -> Result<XPublisher> {
@@ -249,7 +499,11 @@ fn build_x_publisher(config: &AppConfig, dry_run: bool, mode: XPublishMode) -> R
     Ok(XPublisher::new(user_token, mode, config.x.write.max_chars))
 }
 
-fn build_nostr_publisher(config: &AppConfig, dry_run: bool) -> Result<NostrPublisher> {
+fn build_nostr_publisher(
+    config: &AppConfig,
+    dry_run: bool,
+    state_store: Arc<dyn StateStore>,
+) -> Result<NostrPublisher> {
     if dry_run || !config.nostr.enabled {
         return Ok(NostrPublisher::disabled());
     }
@@ -259,7 +513,121 @@ fn build_nostr_publisher(config: &AppConfig, dry_run: bool) -> Result<NostrPubli
     }
 
     let secret_key = load_api_key(&config.nostr.secret_key_env, "nostr")?;
-    NostrPublisher::new(secret_key.expose_secret(), config.nostr.relays.clone())
+    let mut publisher =
+        NostrPublisher::new(secret_key.expose_secret(), config.nostr.relays.clone())?
+            .with_state_store(state_store);
+    if config.nostr.label_events {
+        publisher = publisher.with_label_events(config.nostr.label_namespace.clone());
+    }
+    if config.nostr.publish_relay_list {
+        publisher = publisher.with_relay_list();
+    }
+    if config.nostr.discover_relays {
+        publisher = publisher.with_relay_discovery();
+    }
+    Ok(publisher)
+}
+
+async fn build_webhook_publisher(
+    config: &AppConfig,
+    dry_run: bool,
+) -> Result<Option<WebhookPublisher>> {
+    if dry_run || !config.webhook.enabled {
+        return Ok(None);
+    }
+
+    if config.webhook.urls.is_empty() {
+        bail!("Webhook publishing enabled but webhook.urls is empty");
+    }
+
+    let secret = load_api_key(&config.webhook.secret_env, "webhook")?;
+    let dead_letter = OutboxWriter::new(config.webhook.dead_letter_path.clone())
+        .await
+        .with_context(|| {
+            format!(
+                "Failed to open webhook dead-letter file {}",
+                config.webhook.dead_letter_path.display()
+            )
+        })?;
+
+    Ok(Some(
+        WebhookPublisher::new(config.webhook.urls.clone(), secret)
+            .with_retries(
+                config.webhook.max_attempts,
+                Duration::from_secs(config.webhook.retry_delay_secs),
+            )
+            .with_dead_letter(dead_letter),
+    ))
+}
+
+fn build_mastodon_publisher(
+    config: &AppConfig,
+    dry_run: bool,
+    mode: MastodonPublishMode,
+) -> Result<MastodonPublisher> {
+    if dry_run || !config.mastodon.write.enabled {
+        return Ok(MastodonPublisher::disabled());
+    }
+
+    if config.mastodon.instance_url.is_empty() {
+        bail!("Mastodon publishing enabled but mastodon.instance_url is empty");
+    }
+
+    let access_token = load_api_key(&config.mastodon.access_token_env, "mastodon")?;
+    Ok(MastodonPublisher::new(
+        config.mastodon.instance_url.clone(),
+        access_token,
+        mode,
+        config.mastodon.write.max_chars,
+    ))
+}
+
+/// Session for the configured Bluesky account, if an identifier is set
+fn build_bluesky_session(config: &AppConfig) -> Result<Option<Arc<BlueskySession>>> {
+    if config.bluesky.identifier.trim().is_empty() {
+        return Ok(None);
+    }
+
+    let app_password = load_api_key(&config.bluesky.app_password_env, "bluesky")?;
+    Ok(Some(Arc::new(BlueskySession::new(
+        config.bluesky.service_url.clone(),
+        config.bluesky.identifier.clone(),
+        app_password,
+    ))))
+}
+
+fn build_bluesky_publisher(
+    config: &AppConfig,
+    dry_run: bool,
+    mode: BlueskyPublishMode,
+) -> Result<BlueskyPublisher> {
+    if dry_run || !config.bluesky.write.enabled {
+        return Ok(BlueskyPublisher::disabled());
+    }
+
+    let session = build_bluesky_session(config)?
+        .context("Bluesky publishing enabled but bluesky.identifier is empty")?;
+    Ok(BlueskyPublisher::new(
+        session,
+        mode,
+        config.bluesky.write.max_chars,
+    ))
+}
+
+fn parse_bluesky_publish_mode(mode: &str) -> Result<BlueskyPublishMode> {
+    match mode.trim() {
+        "reply" => Ok(BlueskyPublishMode::Reply),
+        "quote" => Ok(BlueskyPublishMode::Quote),
+        other => bail!("Invalid Bluesky publish mode: {}", other),
+    }
+}
+
+fn parse_mastodon_publish_mode(mode: &str) -> Result<MastodonPublishMode> {
+    match mode.trim() {
+        "reply" => Ok(MastodonPublishMode::Reply),
+        "quote" => Ok(MastodonPublishMode::Quote),
+        other => bail!("Invalid Mastodon publish mode: {}", other),
+    }
 }
 
 fn parse_x_publish_mode(mode: &str) -> Result<XPublishMode> {
@@ -285,19 +653,184 @@ fn classify_config_from_config(config: &AppConfig) -> ClassifyConfig {
     }
 }
 
+/// Per-account settings of the `watch.accounts` tables
+fn account_schedules_from_config(config: &AppConfig) -> Result<HashMap<String, AccountSchedule>> {
+    config
+        .watch
+        .accounts
+        .iter()
+        .filter_map(|account| match account {
+            WatchAccount::Scheduled(account) => Some(account),
+            WatchAccount::Name(_) => None,
+        })
+        .map(|account| {
+            let time_zone = account
+                .time_zone
+                .as_deref()
+                .map(|name| {
+                    time_tz::timezones::get_by_name(name)
+                        .with_context(|| format!("Unknown time zone: {}", name))
+                })
+                .transpose()?;
+            let active_hours = account
+                .active_hours
+                .iter()
+                .map(|window| parse_active_hours(window))
+                .collect::<Result<_>>()?;
+            let schedule = AccountSchedule {
+                poll_interval: account.poll_interval_secs.map(Duration::from_secs),
+                include_replies: account.include_replies,
+                include_reposts: account.include_reposts,
+                ignore_patterns: account.ignore_patterns.clone(),
+                tags: account.tags.clone(),
+                active_hours,
+                time_zone,
+            };
+            Ok((account.name.clone(), schedule))
+        })
+        .collect::<Result<_>>()
+        .context("Invalid watch.accounts entry")
+}
+
+/// Parse an `HH:MM-HH:MM` window
+fn parse_active_hours(window: &str) -> Result<ActiveHours> {
+    let parse_time = |value: &str| {
+        let (hour, minute) = value
+            .trim()
+            .split_once(':')
+            .with_context(|| format!("Expected HH:MM, got {:?}", value))?;
+        Time::from_hms(hour.parse()?, minute.parse()?, 0)
+            .with_context(|| format!("Invalid time {:?}", value))
+    };
+    let (start, end) = window
+        .split_once('-')
+        .with_context(|| format!("Expected HH:MM-HH:MM, got {:?}", window))?;
+    Ok(ActiveHours {
+        start: parse_time(start)?,
+        end: parse_time(end)?,
+    })
+}
+
 fn rate_limit_from_config(value: u32) -> Option<u32> {
     if value == 0 { None } else { Some(value) }
 }
 
-fn default_outbox_path() -> PathBuf {
+pub(crate) fn default_outbox_path() -> PathBuf {
     PathBuf::from("./outbox.jsonl")
 }
 
+/// A post in the `--json` output; skipped posts are left out
 #[derive(Serialize)]
-struct JsonResult {
-    post_id: String,
-    author: String,
-    text: String,
-    url: String,
-    classification: news_tagger_domain::ClassifyOutput,
+#[serde(tag = "status", rename_all = "snake_case")]
+enum JsonResult {
+    Published {
+        post_id: String,
+        author: String,
+        text: String,
+        url: String,
+        classification: news_tagger_domain::ClassifyOutput,
+    },
+    /// Left for a later cycle, including failed posts not yet due for a retry
+    Deferred { post_id: String, reason: String },
+    Failed {
+        post_id: String,
+        error: String,
+        /// Attempts so far and when the post is tried again, or that it was
+        /// given up
+        retry: Option<PostRetry>,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn example_account_schedules_load() {
+        let dir = TempDir::new().expect("temp dir");
+        let path = dir.path().join("config.toml");
+        std::fs::write(&path, AppConfig::example_toml()).expect("write config");
+        let config = AppConfig::load(Some(&path)).expect("load config");
+
+        assert_eq!(
+            config.watch.account_names(),
+            ["example_account_1", "example_account_2"]
+        );
+        assert_eq!(config.watch.poll_tick(), Duration::from_secs(60));
+        assert_eq!(
+            config.watch.longest_poll_interval(),
+            Duration::from_secs(900)
+        );
+
+        let schedules = account_schedules_from_config(&config).expect("schedules");
+        assert_eq!(schedules.len(), 1);
+        let schedule = &schedules["example_account_2"];
+        assert_eq!(schedule.poll_interval, Some(Duration::from_secs(900)));
+        assert_eq!(schedule.include_replies, Some(true));
+        assert_eq!(
+            schedule.active_hours,
+            [ActiveHours {
+                start: Time::from_hms(7, 0, 0).unwrap(),
+                end: Time::from_hms(23, 0, 0).unwrap(),
+            }]
+        );
+        assert!(schedule.time_zone.is_some());
+    }
+
+    #[test]
+    fn invalid_account_schedules_are_rejected() {
+        assert!(parse_active_hours("22:00-06:30").is_ok());
+        assert!(parse_active_hours("22:00").is_err());
+        assert!(parse_active_hours("25:00-06:00").is_err());
+
+        let mut config = AppConfig::default();
+        config.watch.accounts = vec![WatchAccount::Scheduled(
+            toml::from_str("name = \"alice\"\ntime_zone = \"Mars/Olympus\"").unwrap(),
+        )];
+        assert!(account_schedules_from_config(&config).is_err());
+    }
+
+    #[test]
+    fn accounts_given_settings_are_watched_once() {
+        let mut config = AppConfig::default();
+        config.feeds = vec![crate::config::FeedConfig {
+            name: "news".to_string(),
+            url: "https://news.example.com/rss.xml".to_string(),
+        }];
+        config.watch.accounts = vec![
+            WatchAccount::Name("alice".to_string()),
+            WatchAccount::Scheduled(toml::from_str("name = \"feed:news\"").unwrap()),
+        ];
+
+        assert_eq!(config.watched_accounts(), ["alice", "feed:news"]);
+    }
+
+    #[test]
+    fn default_worker_id_is_per_process() {
+        let mut config = AppConfig::default();
+        let id = config.worker_id();
+        assert!(id.ends_with(&format!("-{}", std::process::id())));
+        assert!(id.len() > format!("-{}", std::process::id()).len());
+
+        config.general.worker_id = Some("worker-1".to_string());
+        assert_eq!(config.worker_id(), "worker-1");
+    }
+
+    #[test]
+    fn other_networks_need_no_x_token() {
+        let mut config = AppConfig::default();
+        config.x.read.bearer_token_env = "NEWS_TAGGER_TEST_UNSET_X_TOKEN".to_string();
+        config.nostr.relays = vec!["wss://relay.example.com".to_string()];
+        let state_store: Arc<dyn StateStore> =
+            Arc::new(news_tagger_adapters::state::InMemoryStateStore::new());
+        let build = |accounts: &[&str]| {
+            let accounts: Vec<String> = accounts.iter().map(|a| a.to_string()).collect();
+            build_post_source(&config, state_store.clone(), &accounts)
+        };
+
+        assert!(build(&["bluesky:alice.bsky.social"]).is_ok());
+        assert!(build(&["nostr:npub1alice"]).is_ok());
+        assert!(build(&["alice"]).is_err());
+    }
 }
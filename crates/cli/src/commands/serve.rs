@@ -0,0 +1,748 @@
+//! Serve command - local HTTP API over classification, state and the outbox
+//!
+//! Endpoints:
+//! - `GET /healthz`
+//! - `POST /classify` with `{"text": ...}`, answered like `classify --json`
+//! - `GET /posts` and `GET /classifications`, newest first, filtered by
+//!   `author`, `tag`, `since`, `until` (RFC 3339) and `limit`
+//! - `GET /tags`: the loaded taxonomy
+//! - `GET /outbox`: entries waiting for approval
+//! - `POST /outbox/{id}/approve`: publish an outbox entry
+//! - `GET /metrics`: Prometheus metrics of the watch loop (with `--watch`)
+
+use anyhow::{Context, Result};
+use axum::extract::{Path, Query, State};
+use axum::http::{StatusCode, header};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use news_tagger_adapters::{
+    definitions::FilesystemDefinitionsRepo,
+    metrics::PrometheusMetrics,
+    outbox::{OutboxApproval, OutboxEntry, OutboxWriter, read_outbox},
+};
+use news_tagger_domain::usecases::{ClassifyConfig, ClassifyUseCase};
+use news_tagger_domain::{
+    ClassificationRecord, Classifier, ClassifyOutput, DefinitionsRepo, PublishedTarget, Publisher,
+    SourcePost, StateStore, Taxonomy,
+};
+use serde::{Deserialize, Serialize};
+use std::future::IntoFuture;
+use std::path::PathBuf;
+use std::sync::Arc;
+use time::OffsetDateTime;
+use tokio::net::TcpListener;
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+use crate::args::{RunArgs, ServeArgs};
+use crate::commands::classify::{build_classifier, classify_config_from_config};
+use crate::commands::run::{
+    build_metrics, build_publishers, build_run_loop, default_outbox_path, run_continuously,
+    shutdown_signal,
+};
+use crate::commands::state::open_state_store;
+use crate::config::AppConfig;
+
+/// Records returned when a request sets no `limit`
+const DEFAULT_LIMIT: usize = 100;
+
+pub async fn execute(args: ServeArgs, config_path: Option<PathBuf>) -> Result<()> {
+    let config = AppConfig::load(config_path.as_deref())?;
+
+    let definitions_repo = Arc::new(
+        FilesystemDefinitionsRepo::new(&config.general.definitions_dir)
+            .context("Failed to initialize definitions repository")?,
+    );
+    let state_store = open_state_store(&config).await?;
+    let outbox_path = args.outbox.clone().unwrap_or_else(default_outbox_path);
+    let outbox = OutboxWriter::new(outbox_path.clone())
+        .await
+        .context("Failed to initialize outbox writer")?;
+
+    // Approving is the human sign-off, so it publishes even when the
+    // configuration defaults to dry-run; only `--dry-run` holds it back
+    let publishers = build_publishers(&config, args.dry_run, state_store.clone()).await?;
+
+    let state = Arc::new(ServeState {
+        definitions_repo,
+        classifier: Arc::from(build_classifier(&config)?),
+        classify_config: classify_config_from_config(&config),
+        state_store,
+        publishers,
+        outbox,
+        approvals: Mutex::new(()),
+    });
+
+    let (run_loop, metrics) = if args.watch {
+        let run_args = RunArgs {
+            dry_run: args.dry_run,
+            once: false,
+            require_approval: args.require_approval,
+            outbox: Some(outbox_path),
+            json: false,
+            source: args.source.clone(),
+        };
+        let metrics = Arc::new(build_metrics(&config));
+        let run_loop = build_run_loop(&config, &run_args)
+            .await?
+            .with_metrics(metrics.clone());
+        (Some(run_loop), Some(metrics))
+    } else {
+        (None, None)
+    };
+
+    let listener = TcpListener::bind(args.listen)
+        .await
+        .with_context(|| format!("Failed to listen on {}", args.listen))?;
+    tracing::info!(
+        listen = %listener.local_addr()?,
+        watch = args.watch,
+        "Serving HTTP API"
+    );
+
+    let mut app = router(state);
+    if let Some(metrics) = metrics {
+        app = app.merge(metrics_router(metrics));
+    }
+    let server = axum::serve(listener, app)
+        .with_graceful_shutdown(shutdown_signal())
+        .into_future();
+
+    match run_loop {
+        Some(run_loop) => {
+            let poll_interval = config.watch.poll_tick();
+            let (served, ()) = tokio::join!(server, run_continuously(&run_loop, poll_interval));
+            served.context("HTTP server failed")?;
+        }
+        None => server.await.context("HTTP server failed")?,
+    }
+
+    tracing::info!("news-tagger serve stopped");
+    Ok(())
+}
+
+struct ServeState {
+    definitions_repo: Arc<dyn DefinitionsRepo>,
+    classifier: Arc<dyn Classifier>,
+    classify_config: ClassifyConfig,
+    state_store: Arc<dyn StateStore>,
+    /// Publishers approved outbox entries go out through
+    publishers: Vec<Arc<dyn Publisher>>,
+    outbox: OutboxWriter,
+    /// Held while approving, so an entry cannot be published twice
+    approvals: Mutex<()>,
+}
+
+fn router(state: Arc<ServeState>) -> Router {
+    Router::new()
+        .route("/healthz", get(healthz))
+        .route("/classify", post(classify))
+        .route("/posts", get(posts))
+        .route("/classifications", get(classifications))
+        .route("/tags", get(tags))
+        .route("/outbox", get(outbox))
+        .route("/outbox/{id}/approve", post(approve))
+        .with_state(state)
+}
+
+/// Router serving `metrics` at `/metrics`
+pub(crate) fn metrics_router(metrics: Arc<PrometheusMetrics>) -> Router {
+    Router::new()
+        .route("/metrics", get(render_metrics))
+        .with_state(metrics)
+}
+
+async fn render_metrics(State(metrics): State<Arc<PrometheusMetrics>>) -> impl IntoResponse {
+    (
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        metrics.render(),
+    )
+}
+
+/// Error answered as `{"error": "..."}`
+struct ApiError {
+    status: StatusCode,
+    message: String,
+}
+
+impl ApiError {
+    fn new(status: StatusCode, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            message: message.into(),
+        }
+    }
+
+    fn internal(error: impl std::fmt::Display) -> Self {
+        tracing::error!(error = %error, "Request failed");
+        Self::new(StatusCode::INTERNAL_SERVER_ERROR, error.to_string())
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let body = serde_json::json!({ "error": self.message });
+        (self.status, Json(body)).into_response()
+    }
+}
+
+async fn healthz() -> Json<serde_json::Value> {
+    Json(serde_json::json!({ "status": "ok" }))
+}
+
+#[derive(Deserialize)]
+struct ClassifyRequest {
+    text: String,
+    #[serde(default)]
+    author: Option<String>,
+    #[serde(default)]
+    url: Option<String>,
+}
+
+async fn classify(
+    State(state): State<Arc<ServeState>>,
+    Json(request): Json<ClassifyRequest>,
+) -> Result<Json<ClassifyOutput>, ApiError> {
+    if request.text.trim().is_empty() {
+        return Err(ApiError::new(
+            StatusCode::BAD_REQUEST,
+            "No text provided for classification",
+        ));
+    }
+
+    let definitions = state
+        .definitions_repo
+        .load()
+        .await
+        .map_err(ApiError::internal)?;
+    if definitions.is_empty() {
+        return Err(ApiError::internal("No definitions found"));
+    }
+
+    let post = SourcePost {
+        id: "api-input".to_string(),
+        text: request.text,
+        author: request.author.unwrap_or_else(|| "api".to_string()),
+        url: request.url.unwrap_or_default(),
+        created_at: OffsetDateTime::now_utc(),
+        is_repost: false,
+        is_reply: false,
+        reply_to_id: None,
+        context: vec![],
+    };
+
+    let usecase = ClassifyUseCase::new(&*state.classifier, state.classify_config.clone());
+    let output = usecase.classify(&post, &definitions).await.map_err(|e| {
+        ApiError::new(
+            StatusCode::BAD_GATEWAY,
+            format!("Classification failed: {e}"),
+        )
+    })?;
+
+    Ok(Json(output))
+}
+
+/// Query parameters of `/posts` and `/classifications`
+#[derive(Debug, Default, Deserialize)]
+struct RecordFilter {
+    author: Option<String>,
+    tag: Option<String>,
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    since: Option<OffsetDateTime>,
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    until: Option<OffsetDateTime>,
+    limit: Option<usize>,
+}
+
+impl RecordFilter {
+    /// Matching records, newest first, with `at` picking the time the
+    /// `since`/`until` bounds apply to
+    fn apply(
+        &self,
+        mut records: Vec<ClassificationRecord>,
+        at: fn(&ClassificationRecord) -> OffsetDateTime,
+    ) -> Vec<ClassificationRecord> {
+        records.retain(|record| {
+            self.author
+                .as_deref()
+                .is_none_or(|author| record.source_post.author.eq_ignore_ascii_case(author))
+                && self
+                    .tag
+                    .as_deref()
+                    .is_none_or(|tag| record.classification.tags.iter().any(|t| t.id == tag))
+                && self.since.is_none_or(|since| at(record) >= since)
+                && self.until.is_none_or(|until| at(record) < until)
+        });
+        records.sort_by_key(|record| std::cmp::Reverse(at(record)));
+        records.truncate(self.limit.unwrap_or(DEFAULT_LIMIT));
+        records
+    }
+}
+
+/// A processed post with its tags and where the analysis was published
+#[derive(Serialize)]
+struct PostView {
+    record_id: Uuid,
+    post: SourcePost,
+    tags: Vec<String>,
+    targets: Vec<PublishedTarget>,
+}
+
+/// Processed posts; `since`/`until` apply to when the post was created
+async fn posts(
+    State(state): State<Arc<ServeState>>,
+    Query(filter): Query<RecordFilter>,
+) -> Result<Json<Vec<PostView>>, ApiError> {
+    let records = state
+        .state_store
+        .list_classifications()
+        .await
+        .map_err(ApiError::internal)?;
+
+    let mut views = Vec::new();
+    for record in filter.apply(records, |record| record.source_post.created_at) {
+        let targets = state
+            .state_store
+            .list_targets(record.record_id)
+            .await
+            .map_err(ApiError::internal)?;
+        views.push(PostView {
+            record_id: record.record_id,
+            tags: record
+                .classification
+                .tags
+                .into_iter()
+                .map(|tag| tag.id)
+                .collect(),
+            post: record.source_post,
+            targets,
+        });
+    }
+
+    Ok(Json(views))
+}
+
+/// Stored classifications; `since`/`until` apply to when the post was classified
+async fn classifications(
+    State(state): State<Arc<ServeState>>,
+    Query(filter): Query<RecordFilter>,
+) -> Result<Json<Vec<ClassificationRecord>>, ApiError> {
+    let records = state
+        .state_store
+        .list_classifications()
+        .await
+        .map_err(ApiError::internal)?;
+
+    Ok(Json(filter.apply(records, |record| record.classified_at)))
+}
+
+async fn tags(State(state): State<Arc<ServeState>>) -> Result<Json<serde_json::Value>, ApiError> {
+    let definitions = state
+        .definitions_repo
+        .load()
+        .await
+        .map_err(ApiError::internal)?;
+    let taxonomy = Taxonomy::new(definitions);
+
+    Ok(Json(serde_json::json!({
+        "taxonomy_hash": taxonomy.hash,
+        "count": taxonomy.definitions.len(),
+        "definitions": taxonomy.definitions.iter().map(|d| serde_json::json!({
+            "id": d.id,
+            "title": d.title,
+            "aliases": d.aliases,
+            "short": d.short,
+            "file_path": d.file_path,
+        })).collect::<Vec<_>>()
+    })))
+}
+
+/// Outbox entries that have not been approved yet, oldest first
+async fn outbox(State(state): State<Arc<ServeState>>) -> Result<Json<Vec<OutboxEntry>>, ApiError> {
+    let items = read_outbox(state.outbox.path())
+        .await
+        .map_err(ApiError::internal)?;
+
+    Ok(Json(
+        items
+            .into_iter()
+            .filter(|item| item.approval.is_none() && !item.entry.id.is_empty())
+            .map(|item| item.entry)
+            .collect(),
+    ))
+}
+
+/// Publish an outbox entry to its platform and record the approval
+async fn approve(
+    State(state): State<Arc<ServeState>>,
+    Path(id): Path<String>,
+) -> Result<Json<OutboxApproval>, ApiError> {
+    let _approving = state.approvals.lock().await;
+
+    let item = read_outbox(state.outbox.path())
+        .await
+        .map_err(ApiError::internal)?
+        .into_iter()
+        .find(|item| !item.entry.id.is_empty() && item.entry.id == id)
+        .ok_or_else(|| ApiError::new(StatusCode::NOT_FOUND, format!("No outbox entry {id}")))?;
+    if item.approval.is_some() {
+        return Err(ApiError::new(
+            StatusCode::CONFLICT,
+            format!("Outbox entry {id} is already approved"),
+        ));
+    }
+
+    let entry = item.entry;
+    let publisher = state
+        .publishers
+        .iter()
+        .find(|p| p.is_enabled() && p.platform() == entry.platform)
+        .ok_or_else(|| {
+            ApiError::new(
+                StatusCode::CONFLICT,
+                format!("No enabled publisher for {}", entry.platform),
+            )
+        })?;
+
+    let result = publisher
+        .publish(&entry.rendered())
+        .await
+        .map_err(|e| ApiError::new(StatusCode::BAD_GATEWAY, format!("Publish failed: {e}")))?;
+    tracing::info!(
+        outbox_id = %id,
+        platform = %entry.platform,
+        target_id = %result.id,
+        "Published approved outbox entry"
+    );
+
+    let approval = OutboxApproval {
+        approved: id,
+        target_id: result.id,
+        url: result.url,
+        approved_at: OffsetDateTime::now_utc(),
+    };
+    state
+        .outbox
+        .record_approval(&approval)
+        .await
+        .map_err(ApiError::internal)?;
+
+    if let Err(e) = replace_outbox_target(state.state_store.as_ref(), &entry, &approval).await {
+        tracing::warn!(error = %e, "Failed to record approved target");
+    }
+
+    Ok(Json(approval))
+}
+
+/// Point the target recorded for the outbox entry at the published post
+async fn replace_outbox_target(
+    store: &dyn StateStore,
+    entry: &OutboxEntry,
+    approval: &OutboxApproval,
+) -> Result<()> {
+    let records = store.list_published().await?;
+    for record in records
+        .iter()
+        .filter(|record| record.source_post_id == entry.source_post_id)
+    {
+        let targets = store.list_targets(record.id).await?;
+        if let Some(target) = targets
+            .into_iter()
+            .find(|t| t.platform == entry.platform && t.target_id == entry.id)
+        {
+            store
+                .record_target(&PublishedTarget {
+                    target_id: approval.target_id.clone(),
+                    url: approval.url.clone(),
+                    published_at: approval.approved_at,
+                    ..target
+                })
+                .await?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::{Body, to_bytes};
+    use axum::http::Request;
+    use news_tagger_adapters::llm::StubClassifier;
+    use news_tagger_adapters::outbox::OutboxPublisher;
+    use news_tagger_adapters::state::InMemoryStateStore;
+    use news_tagger_adapters::x::StubXPublisher;
+    use news_tagger_domain::{PublishedRecord, RenderedPost, RunMetrics, TagMatch};
+    use serde_json::{Value, json};
+    use tempfile::TempDir;
+    use time::format_description::well_known::Rfc3339;
+    use tower::ServiceExt;
+
+    struct TestServer {
+        _dir: TempDir,
+        app: Router,
+        store: Arc<InMemoryStateStore>,
+        outbox: OutboxWriter,
+        x_publisher: Arc<StubXPublisher>,
+    }
+
+    async fn test_server() -> TestServer {
+        let dir = TempDir::new().expect("temp dir");
+        let definitions_dir = dir.path().join("definitions");
+        std::fs::create_dir(&definitions_dir).expect("definitions dir");
+        std::fs::write(
+            definitions_dir.join("example.md"),
+            "---\nid: example_narrative\ntitle: Example Narrative\n---\n\nDefinition content.\n",
+        )
+        .expect("write definition");
+
+        let store = Arc::new(InMemoryStateStore::new());
+        let outbox = OutboxWriter::new(dir.path().join("outbox.jsonl"))
+            .await
+            .expect("outbox");
+        let x_publisher = Arc::new(StubXPublisher::new(true));
+        let state = Arc::new(ServeState {
+            definitions_repo: Arc::new(
+                FilesystemDefinitionsRepo::new(&definitions_dir).expect("definitions repo"),
+            ),
+            classifier: Arc::new(StubClassifier::echo()),
+            classify_config: classify_config_from_config(&AppConfig::default()),
+            state_store: store.clone(),
+            publishers: vec![x_publisher.clone()],
+            outbox: outbox.clone(),
+            approvals: Mutex::new(()),
+        });
+
+        TestServer {
+            _dir: dir,
+            app: router(state),
+            store,
+            outbox,
+            x_publisher,
+        }
+    }
+
+    async fn request(
+        app: &Router,
+        method: &str,
+        uri: &str,
+        body: Option<Value>,
+    ) -> (StatusCode, Value) {
+        let request = Request::builder()
+            .method(method)
+            .uri(uri)
+            .header("content-type", "application/json")
+            .body(body.map_or_else(Body::empty, |b| Body::from(b.to_string())))
+            .expect("request");
+        let response = app.clone().oneshot(request).await.expect("response");
+        let status = response.status();
+        let bytes = to_bytes(response.into_body(), usize::MAX)
+            .await
+            .expect("body");
+        (
+            status,
+            serde_json::from_slice(&bytes).unwrap_or(Value::Null),
+        )
+    }
+
+    fn at(timestamp: &str) -> OffsetDateTime {
+        OffsetDateTime::parse(timestamp, &Rfc3339).expect("timestamp")
+    }
+
+    fn record(
+        id: &str,
+        author: &str,
+        tag: &str,
+        classified_at: OffsetDateTime,
+    ) -> ClassificationRecord {
+        ClassificationRecord {
+            record_id: Uuid::new_v4(),
+            source_post: SourcePost {
+                id: id.to_string(),
+                text: format!("Post {id}"),
+                author: author.to_string(),
+                url: format!("https://x.com/{author}/status/{id}"),
+                created_at: classified_at,
+                is_repost: false,
+                is_reply: false,
+                reply_to_id: None,
+                context: vec![],
+            },
+            classification: ClassifyOutput::new(
+                format!("Summary of {id}"),
+                vec![TagMatch {
+                    id: tag.to_string(),
+                    confidence: 0.9,
+                    rationale: "Matches".to_string(),
+                    evidence: vec![],
+                }],
+            ),
+            taxonomy_hash: "hash".to_string(),
+            classified_at,
+        }
+    }
+
+    #[tokio::test]
+    async fn classify_and_tags_use_loaded_definitions() {
+        let server = test_server().await;
+
+        let (status, body) = request(&server.app, "GET", "/healthz", None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["status"], "ok");
+
+        let (status, body) = request(&server.app, "GET", "/tags", None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["count"], 1);
+        assert_eq!(body["definitions"][0]["id"], "example_narrative");
+
+        let text = json!({ "text": "This mentions example narrative in the post" });
+        let (status, body) = request(&server.app, "POST", "/classify", Some(text)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["tags"][0]["id"], "example_narrative");
+
+        let (status, body) = request(
+            &server.app,
+            "POST",
+            "/classify",
+            Some(json!({ "text": " " })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(body["error"].as_str().unwrap().contains("No text"));
+    }
+
+    #[tokio::test]
+    async fn classifications_and_posts_are_filtered_newest_first() {
+        let server = test_server().await;
+        for record in [
+            record("1", "alice", "economy", at("2026-01-01T00:00:00Z")),
+            record("2", "bob", "economy", at("2026-02-01T00:00:00Z")),
+            record("3", "alice", "health", at("2026-03-01T00:00:00Z")),
+            record("4", "alice", "economy", at("2026-04-01T00:00:00Z")),
+        ] {
+            server.store.record_classification(&record).await.unwrap();
+        }
+
+        let ids = |body: &Value, pointer: &str| -> Vec<String> {
+            body.as_array()
+                .unwrap()
+                .iter()
+                .map(|item| item.pointer(pointer).unwrap().as_str().unwrap().to_string())
+                .collect()
+        };
+
+        let (status, body) = request(
+            &server.app,
+            "GET",
+            "/classifications?author=ALICE&tag=economy",
+            None,
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(ids(&body, "/source_post/id"), ["4", "1"]);
+
+        let (_, body) = request(
+            &server.app,
+            "GET",
+            "/posts?since=2026-02-01T00:00:00Z&until=2026-04-01T00:00:00Z",
+            None,
+        )
+        .await;
+        assert_eq!(ids(&body, "/post/id"), ["3", "2"]);
+        assert_eq!(body[0]["tags"], json!(["health"]));
+
+        let (_, body) = request(&server.app, "GET", "/posts?limit=1", None).await;
+        assert_eq!(ids(&body, "/post/id"), ["4"]);
+
+        let (status, _) = request(&server.app, "GET", "/posts?since=yesterday", None).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn approving_publishes_entry_once_and_replaces_target() {
+        let server = test_server().await;
+
+        // What `run --require-approval` leaves behind: an outbox entry whose
+        // ID is recorded as the post's X target
+        let post = RenderedPost {
+            text: "Analysis of 123".to_string(),
+            source_post_id: "123".to_string(),
+            source_author: "alice".to_string(),
+            ..Default::default()
+        };
+        let pending = OutboxPublisher::new(server.outbox.clone(), "x")
+            .publish(&post)
+            .await
+            .expect("write outbox");
+        let published = PublishedRecord {
+            id: Uuid::new_v4(),
+            source_post_id: "123".to_string(),
+            taxonomy_hash: "hash".to_string(),
+            published_at: OffsetDateTime::now_utc(),
+        };
+        server.store.record_published(&published).await.unwrap();
+        server
+            .store
+            .record_target(&PublishedTarget {
+                record_id: published.id,
+                platform: "x".to_string(),
+                target_id: pending.id.clone(),
+                url: None,
+                published_at: published.published_at,
+            })
+            .await
+            .unwrap();
+
+        let (_, body) = request(&server.app, "GET", "/outbox", None).await;
+        assert_eq!(body[0]["id"], pending.id);
+
+        let uri = format!("/outbox/{}/approve", pending.id);
+        let (status, body) = request(&server.app, "POST", &uri, None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["target_id"], "stub_123");
+        assert_eq!(
+            server.x_publisher.get_published()[0].text,
+            "Analysis of 123"
+        );
+
+        let targets = server.store.list_targets(published.id).await.unwrap();
+        assert_eq!(targets[0].target_id, "stub_123");
+
+        let (status, _) = request(&server.app, "POST", &uri, None).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert_eq!(server.x_publisher.get_published().len(), 1);
+
+        let (_, body) = request(&server.app, "GET", "/outbox", None).await;
+        assert_eq!(body, json!([]));
+
+        let (status, _) = request(&server.app, "POST", "/outbox/missing/approve", None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn metrics_are_served_as_prometheus_text() {
+        let metrics = Arc::new(PrometheusMetrics::new());
+        metrics.posts_fetched("alice", 2);
+        let app = metrics_router(metrics);
+
+        let request = Request::builder()
+            .uri("/metrics")
+            .body(Body::empty())
+            .expect("request");
+        let response = app.oneshot(request).await.expect("response");
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(
+            response.headers()[header::CONTENT_TYPE]
+                .to_str()
+                .unwrap()
+                .starts_with("text/plain")
+        );
+        let bytes = to_bytes(response.into_body(), usize::MAX)
+            .await
+            .expect("body");
+        let text = String::from_utf8(bytes.to_vec()).expect("utf-8");
+        assert!(text.contains(r#"news_tagger_posts_fetched_total{account="alice"} 2"#));
+    }
+}
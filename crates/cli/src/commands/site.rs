@@ -0,0 +1,71 @@
+//! Site command - export stored classifications as a static site
+
+use anyhow::{Context, Result};
+use news_tagger_adapters::{definitions::FilesystemDefinitionsRepo, site::SiteBuilder};
+use news_tagger_domain::DefinitionsRepo;
+use std::path::PathBuf;
+
+use crate::args::{SiteArgs, SiteCommands};
+use crate::commands::state::open_state_store;
+use crate::config::AppConfig;
+
+pub async fn execute(args: SiteArgs, config_path: Option<PathBuf>) -> Result<()> {
+    match args.command {
+        SiteCommands::Build {
+            out,
+            base_url,
+            title,
+            definitions_dir,
+        } => build(out, base_url, title, definitions_dir, config_path).await,
+    }
+}
+
+async fn build(
+    out: PathBuf,
+    base_url: Option<String>,
+    title: String,
+    definitions_dir: Option<PathBuf>,
+    config_path: Option<PathBuf>,
+) -> Result<()> {
+    let config = AppConfig::load(config_path.as_deref())?;
+    let store = open_state_store(&config).await?;
+    let records = store
+        .list_classifications()
+        .await
+        .context("Failed to load stored classifications")?;
+
+    // Tag titles are cosmetic, so missing definitions only fall back to tag IDs
+    let dir = definitions_dir.unwrap_or(config.general.definitions_dir);
+    let definitions = match FilesystemDefinitionsRepo::new(&dir) {
+        Ok(repo) => repo.load().await.unwrap_or_else(|e| {
+            tracing::warn!(error = %e, "Failed to load definitions, using tag IDs as titles");
+            vec![]
+        }),
+        Err(e) => {
+            tracing::warn!(error = %e, "Failed to open definitions, using tag IDs as titles");
+            vec![]
+        }
+    };
+
+    let mut builder = SiteBuilder::new(&out)
+        .with_title(title)
+        .with_definitions(&definitions);
+    if let Some(base_url) = base_url {
+        builder = builder.with_base_url(base_url);
+    }
+
+    let report = builder
+        .build(&records)
+        .with_context(|| format!("Failed to build site in {}", out.display()))?;
+
+    println!(
+        "Built site in {} from {} classifications: {} written, {} unchanged, {} removed",
+        out.display(),
+        records.len(),
+        report.written,
+        report.unchanged,
+        report.removed
+    );
+
+    Ok(())
+}
@@ -7,19 +7,25 @@ use tracing_subscriber::{EnvFilter, fmt, prelude::*};
 mod args;
 mod commands;
 mod config;
+mod telemetry;
 
 use args::{Cli, Commands};
+use config::AppConfig;
+use telemetry::Telemetry;
 
 #[tokio::main]
 async fn main() -> Result<()> {
     let cli = Cli::parse();
 
-    // Initialize logging
+    // Initialize logging; a config that fails to load is reported by the command
     let log_level = cli.log_level.as_deref().unwrap_or("info");
-    init_logging(log_level)?;
+    let telemetry_config = AppConfig::load(cli.config.as_deref())
+        .map(|config| config.telemetry)
+        .unwrap_or_default();
+    let telemetry = init_logging(log_level, &telemetry_config)?;
 
     // Execute command
-    match cli.command {
+    let result = match cli.command {
         Commands::Run(args) => commands::run::execute(args, cli.config).await,
         Commands::Fetch(args) => commands::fetch::execute(args, cli.config).await,
         Commands::Classify(args) => commands::classify::execute(args, cli.config).await,
@@ -27,16 +33,34 @@ async fn main() -> Result<()> {
         Commands::Config(args) => commands::config::execute(args).await,
         Commands::Doctor(args) => commands::doctor::execute(args, cli.config).await,
         Commands::Curate(args) => commands::curate::execute(args, cli.config).await,
+        Commands::Queue(args) => commands::queue::execute(args, cli.config).await,
+        Commands::Site(args) => commands::site::execute(args, cli.config).await,
+        Commands::State(args) => commands::state::execute(args, cli.config).await,
+        Commands::Serve(args) => commands::serve::execute(args, cli.config).await,
+    };
+
+    if let Some(telemetry) = telemetry {
+        telemetry.shutdown();
     }
+
+    result
 }
 
-fn init_logging(level: &str) -> Result<()> {
+fn init_logging(
+    level: &str,
+    telemetry_config: &config::TelemetryConfig,
+) -> Result<Option<Telemetry>> {
     let filter = EnvFilter::try_from_default_env().or_else(|_| EnvFilter::try_new(level))?;
+    let telemetry = telemetry_config
+        .enabled
+        .then(|| Telemetry::new(telemetry_config))
+        .transpose()?;
 
     tracing_subscriber::registry()
         .with(fmt::layer().with_target(true).with_writer(std::io::stderr))
+        .with(telemetry.as_ref().map(Telemetry::layer))
         .with(filter)
         .init();
 
-    Ok(())
+    Ok(telemetry)
 }
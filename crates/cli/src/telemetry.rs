@@ -0,0 +1,194 @@
+//! OpenTelemetry trace export
+//!
+//! Spans from the `tracing` instrumentation of the news-tagger crates are
+//! exported over OTLP/HTTP when `[telemetry]` is enabled.
+
+use anyhow::{Context, Result};
+use opentelemetry::trace::TracerProvider as _;
+use opentelemetry_otlp::{SpanExporter, WithExportConfig};
+use opentelemetry_sdk::{
+    Resource,
+    trace::{Sampler, SdkTracerProvider},
+};
+use tracing_subscriber::{Layer, filter::filter_fn, registry::LookupSpan};
+
+use crate::config::TelemetryConfig;
+
+/// Exports spans until shut down
+pub struct Telemetry {
+    provider: SdkTracerProvider,
+}
+
+impl Telemetry {
+    /// Set up the OTLP exporter described by `config`
+    pub fn new(config: &TelemetryConfig) -> Result<Self> {
+        let exporter = SpanExporter::builder()
+            .with_http()
+            .with_endpoint(&config.endpoint)
+            .build()
+            .context("Failed to create OTLP span exporter")?;
+
+        let provider = SdkTracerProvider::builder()
+            .with_sampler(Sampler::ParentBased(Box::new(Sampler::TraceIdRatioBased(
+                config.sample_ratio,
+            ))))
+            .with_resource(
+                Resource::builder()
+                    .with_service_name(config.service_name.clone())
+                    .build(),
+            )
+            .with_batch_exporter(exporter)
+            .build();
+
+        Ok(Self { provider })
+    }
+
+    /// Layer feeding our own spans to the exporter
+    ///
+    /// Spans of dependencies are left out, so the HTTP client sending the
+    /// export never traces itself.
+    pub fn layer<S>(&self) -> impl Layer<S> + use<S>
+    where
+        S: tracing::Subscriber + for<'span> LookupSpan<'span>,
+    {
+        tracing_opentelemetry::layer()
+            .with_tracer(self.provider.tracer("news-tagger"))
+            .with_filter(filter_fn(|metadata| {
+                metadata.target().starts_with("news_tagger")
+            }))
+    }
+
+    /// Flush the remaining spans to the collector
+    pub fn shutdown(self) {
+        if let Err(e) = self.provider.shutdown() {
+            eprintln!("Failed to flush traces: {e}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use news_tagger_adapters::{
+        definitions::FilesystemDefinitionsRepo,
+        llm::StubClassifier,
+        state::InMemoryStateStore,
+        x::{StubPostSource, StubXPublisher},
+    };
+    use news_tagger_domain::{
+        ClassifyOutput, SourcePost, SystemClock, TagMatch, TokenUsage,
+        usecases::{RunLoop, RunLoopConfig},
+    };
+    use opentelemetry::{Value, trace::SpanId};
+    use opentelemetry_sdk::trace::{InMemorySpanExporter, SpanData};
+    use std::sync::Arc;
+    use tempfile::TempDir;
+    use time::OffsetDateTime;
+    use tracing_subscriber::prelude::*;
+
+    fn span<'a>(spans: &'a [SpanData], name: &str) -> &'a SpanData {
+        spans
+            .iter()
+            .find(|s| s.name == name)
+            .unwrap_or_else(|| panic!("no {name} span"))
+    }
+
+    fn attribute(span: &SpanData, key: &str) -> Value {
+        span.attributes
+            .iter()
+            .find(|kv| kv.key.as_str() == key)
+            .map(|kv| kv.value.clone())
+            .unwrap_or_else(|| panic!("no {key} on {}", span.name))
+    }
+
+    #[tokio::test]
+    async fn post_span_links_fetch_to_classify_render_and_publish() {
+        let dir = TempDir::new().expect("temp dir");
+        std::fs::write(
+            dir.path().join("example.md"),
+            "---\nid: example_narrative\ntitle: Example Narrative\n---\n\nDefinition content.\n",
+        )
+        .expect("write definition");
+
+        let exporter = InMemorySpanExporter::default();
+        let telemetry = Telemetry {
+            provider: SdkTracerProvider::builder()
+                .with_simple_exporter(exporter.clone())
+                .build(),
+        };
+        let _guard = tracing::subscriber::set_default(
+            tracing_subscriber::registry().with(telemetry.layer()),
+        );
+
+        let post = SourcePost {
+            id: "123".to_string(),
+            text: "Example post".to_string(),
+            author: "alice".to_string(),
+            url: "https://x.com/alice/status/123".to_string(),
+            created_at: OffsetDateTime::now_utc(),
+            is_repost: false,
+            is_reply: false,
+            reply_to_id: None,
+            context: vec![],
+        };
+        let classification = ClassifyOutput {
+            usage: Some(TokenUsage {
+                input_tokens: 120,
+                output_tokens: 30,
+            }),
+            ..ClassifyOutput::new(
+                "Summary".to_string(),
+                vec![TagMatch {
+                    id: "example_narrative".to_string(),
+                    confidence: 0.9,
+                    rationale: "Matches".to_string(),
+                    evidence: vec![],
+                }],
+            )
+        };
+        let run_loop = RunLoop::new(
+            Arc::new(StubPostSource::with_posts(vec![post])),
+            Arc::new(FilesystemDefinitionsRepo::new(dir.path()).expect("definitions repo")),
+            Arc::new(StubClassifier::with_response(classification)),
+            vec![Arc::new(StubXPublisher::new(true))],
+            Arc::new(InMemoryStateStore::new()),
+            Arc::new(SystemClock),
+            RunLoopConfig {
+                accounts: vec!["alice".to_string()],
+                dry_run: false,
+                ..Default::default()
+            },
+        );
+        run_loop.poll_once().await.expect("poll");
+
+        let spans = exporter.get_finished_spans().expect("spans");
+        let fetch = span(&spans, "fetch_posts");
+        let post = span(&spans, "process_post");
+        let classify = span(&spans, "classify");
+        let render = span(&spans, "render");
+        let publish = span(&spans, "publish");
+
+        let post_id = post.span_context.span_id();
+        assert_ne!(post_id, SpanId::INVALID);
+        assert_eq!(
+            post.links.links[0].span_context.span_id(),
+            fetch.span_context.span_id()
+        );
+        assert_eq!(classify.parent_span_id, post_id);
+        assert_eq!(render.parent_span_id, post_id);
+        assert_eq!(publish.parent_span_id, post_id);
+
+        assert_eq!(attribute(post, "post_id"), Value::from("123"));
+        assert_eq!(attribute(post, "account"), Value::from("alice"));
+        assert_eq!(attribute(post, "provider"), Value::from("stub"));
+        assert_eq!(attribute(post, "outcome"), Value::from("published"));
+        assert!(
+            attribute(post, "tag_ids")
+                .as_str()
+                .contains("example_narrative")
+        );
+        assert_eq!(attribute(classify, "input_tokens"), Value::I64(120));
+        assert_eq!(attribute(classify, "output_tokens"), Value::I64(30));
+        assert_eq!(attribute(publish, "platform"), Value::from("x"));
+    }
+}
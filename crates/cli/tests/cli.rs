@@ -73,3 +73,185 @@ fn classify_outputs_valid_json() {
     assert!(value.get("summary").is_some());
     assert!(value.get("tags").is_some());
 }
+
+#[tokio::test]
+async fn queue_retry_resets_failed_entries_and_drop_removes_them() {
+    use news_tagger_adapters::state::SqliteStateStore;
+    use news_tagger_domain::{ClassifyOutput, PublishRetry, RetryStatus, SourcePost, StateStore};
+    use time::OffsetDateTime;
+
+    let dir = TempDir::new().expect("temp dir");
+    let db_path = dir.path().join("state.sqlite");
+    let now = OffsetDateTime::now_utc();
+    let store = SqliteStateStore::new(&db_path).await.expect("open store");
+    store
+        .set_retry(&PublishRetry {
+            record_id: uuid::Uuid::new_v4(),
+            platform: "nostr".to_string(),
+            source_post: SourcePost {
+                id: "post1".to_string(),
+                text: "Some text".to_string(),
+                author: "user".to_string(),
+                url: "https://example.com/post1".to_string(),
+                created_at: now,
+                is_repost: false,
+                is_reply: false,
+                reply_to_id: None,
+                context: vec![],
+            },
+            classification: ClassifyOutput::new("Summary".to_string(), vec![]),
+            taxonomy_hash: "hash".to_string(),
+            attempts: 5,
+            status: RetryStatus::Failed,
+            last_error: "relay down".to_string(),
+            next_attempt_at: now,
+            updated_at: now,
+        })
+        .await
+        .expect("seed retry");
+
+    let queue = |args: &[&str]| {
+        let mut cmd = cargo_bin_cmd!("news-tagger");
+        cmd.env("NEWS_TAGGER__GENERAL__STATE_DB_PATH", &db_path)
+            .arg("queue")
+            .args(args)
+            .output()
+            .expect("run queue")
+    };
+
+    let output = queue(&["list", "--failed", "--json"]);
+    assert!(output.status.success());
+    let value: Value = serde_json::from_slice(&output.stdout).expect("valid json");
+    assert_eq!(value[0]["platform"], "nostr");
+    assert_eq!(value[0]["status"], "failed");
+
+    assert!(queue(&["retry", "post1"]).status.success());
+    let retries = store.list_retries().await.expect("list retries");
+    assert_eq!(retries[0].status, RetryStatus::Pending);
+    assert_eq!(retries[0].attempts, 0);
+
+    assert!(
+        !queue(&["drop", "post1", "--platform", "x"])
+            .status
+            .success()
+    );
+    assert!(queue(&["drop", "post1"]).status.success());
+    assert!(store.list_retries().await.expect("list retries").is_empty());
+}
+
+#[tokio::test]
+async fn state_export_import_and_prune() {
+    use news_tagger_adapters::state::SqliteStateStore;
+    use news_tagger_domain::{
+        AccountState, ClassificationRecord, ClassifyOutput, PublishedRecord, PublishedTarget,
+        SourcePost, StateStore, TagMatch,
+    };
+    use time::{Duration, OffsetDateTime};
+
+    let dir = TempDir::new().expect("temp dir");
+    let source_db = dir.path().join("source.sqlite");
+    let target_db = dir.path().join("target.sqlite");
+    let export = dir.path().join("state.jsonl");
+    let now = OffsetDateTime::now_utc();
+
+    let store = SqliteStateStore::new(&source_db).await.expect("open store");
+    store
+        .set_account_state(&AccountState {
+            account: "alice".to_string(),
+            since_id: Some("200".to_string()),
+            updated_at: now,
+        })
+        .await
+        .expect("seed account");
+    for (post_id, at) in [("100", now - Duration::days(200)), ("200", now)] {
+        let record = PublishedRecord {
+            id: uuid::Uuid::new_v4(),
+            source_post_id: post_id.to_string(),
+            taxonomy_hash: "hash".to_string(),
+            published_at: at,
+        };
+        store.record_published(&record).await.expect("seed record");
+        store
+            .record_target(&PublishedTarget {
+                record_id: record.id,
+                platform: "x".to_string(),
+                target_id: format!("x{post_id}"),
+                url: None,
+                published_at: at,
+            })
+            .await
+            .expect("seed target");
+        store
+            .record_classification(&ClassificationRecord {
+                record_id: record.id,
+                source_post: SourcePost {
+                    id: post_id.to_string(),
+                    text: "Some text".to_string(),
+                    author: "alice".to_string(),
+                    url: format!("https://x.com/alice/status/{post_id}"),
+                    created_at: at,
+                    is_repost: false,
+                    is_reply: false,
+                    reply_to_id: None,
+                    context: vec![],
+                },
+                classification: ClassifyOutput::new(
+                    "Summary".to_string(),
+                    vec![TagMatch {
+                        id: "spin".to_string(),
+                        confidence: 0.9,
+                        rationale: String::new(),
+                        evidence: vec![],
+                    }],
+                ),
+                taxonomy_hash: "hash".to_string(),
+                classified_at: at,
+            })
+            .await
+            .expect("seed classification");
+    }
+
+    let state = |db: &std::path::Path, args: &[&str]| {
+        let mut cmd = cargo_bin_cmd!("news-tagger");
+        cmd.env("NEWS_TAGGER__GENERAL__STATE_DB_PATH", db)
+            .arg("state")
+            .args(args)
+            .output()
+            .expect("run state")
+    };
+
+    let output = state(
+        &source_db,
+        &["export", "--output", export.to_str().unwrap()],
+    );
+    assert!(output.status.success());
+    assert_eq!(fs::read_to_string(&export).unwrap().lines().count(), 5);
+
+    let import = export.to_str().unwrap();
+    let output = state(&target_db, &["import", import]);
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("1 account cursor(s), 2 published"));
+
+    let output = state(&target_db, &["import", import]);
+    assert!(output.status.success());
+    assert!(
+        String::from_utf8_lossy(&output.stdout)
+            .contains("Imported 0 account cursor(s), 0 published record(s), 0 target(s) and 0 classification(s); 5 entries")
+    );
+
+    let output = state(&target_db, &["prune", "--older-than", "90d"]);
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Pruned 1 published record(s)"));
+
+    let imported = SqliteStateStore::new(&target_db).await.expect("open store");
+    let records = imported.list_published().await.expect("list records");
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].source_post_id, "200");
+    assert_eq!(imported.list_targets(records[0].id).await.unwrap().len(), 1);
+    let counts = imported.tag_counts().await.expect("tag counts");
+    assert_eq!(counts[0].count, 1);
+
+    let output = state(&target_db, &["stats"]);
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("tag_counts"));
+}
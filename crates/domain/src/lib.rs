@@ -21,8 +21,14 @@ use std::cmp::Ordering;
 ///
 /// If both IDs contain only ASCII digits, compares by numeric value without parsing
 /// (length first, then lexicographic), which works for arbitrarily large values.
+/// AT-URIs (`at://<did>/<collection>/<rkey>`) compare by record key, since TIDs
+/// sort chronologically across authors while the DID prefix does not.
 /// Otherwise falls back to plain lexicographic comparison.
 pub fn compare_post_ids(a: &str, b: &str) -> Ordering {
+    if let (Some(a_key), Some(b_key)) = (at_uri_record_key(a), at_uri_record_key(b)) {
+        return a_key.cmp(b_key).then_with(|| a.cmp(b));
+    }
+
     let a_is_digits = a.as_bytes().iter().all(|byte| byte.is_ascii_digit());
     let b_is_digits = b.as_bytes().iter().all(|byte| byte.is_ascii_digit());
 
@@ -38,6 +44,12 @@ pub fn compare_post_ids(a: &str, b: &str) -> Ordering {
     a.cmp(b)
 }
 
+fn at_uri_record_key(id: &str) -> Option<&str> {
+    id.strip_prefix("at://")?
+        .rsplit_once('/')
+        .map(|(_, rkey)| rkey)
+}
+
 fn normalize_numeric_id(id: &str) -> &str {
     let trimmed = id.trim_start_matches('0');
     if trimmed.is_empty() { "0" } else { trimmed }
@@ -81,4 +93,13 @@ mod tests {
         assert_eq!(compare_post_ids("tweet1", "tweet2"), Ordering::Less);
         assert_eq!(compare_post_ids("abc", "100"), Ordering::Greater);
     }
+
+    #[test]
+    fn compare_at_uris_by_record_key() {
+        let older = "at://did:plc:zzzz/app.bsky.feed.post/3kabcdefghi22";
+        let newer = "at://did:plc:aaaa/app.bsky.feed.post/3lzzzzzzzzz22";
+        assert_eq!(compare_post_ids(older, newer), Ordering::Less);
+        assert_eq!(compare_post_ids(newer, older), Ordering::Greater);
+        assert_eq!(compare_post_ids(newer, newer), Ordering::Equal);
+    }
 }
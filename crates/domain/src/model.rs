@@ -2,7 +2,9 @@
 
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
-use time::OffsetDateTime;
+use std::sync::Arc;
+use time::{OffsetDateTime, Time, UtcOffset};
+use time_tz::{OffsetDateTimeExt, Tz};
 use uuid::Uuid;
 
 /// A source post from a watched platform (e.g., X/Twitter)
@@ -25,6 +27,36 @@ pub struct SourcePost {
     pub is_reply: bool,
     /// ID of post being replied to, if any
     pub reply_to_id: Option<String>,
+    /// Quoted, replied-to, or thread root posts shown to the classifier as context
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub context: Vec<PostContext>,
+}
+
+/// How a context post relates to the post being classified
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ContextRelation {
+    /// The post quotes this one
+    Quoted,
+    /// The post is a reply to this one
+    RepliedTo,
+    /// First post of the conversation the post belongs to
+    ConversationRoot,
+}
+
+/// A related post that helps interpret the target post
+///
+/// Context is never classified itself and must not be used as evidence.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PostContext {
+    /// Relationship to the target post
+    pub relation: ContextRelation,
+    /// Platform-specific post ID
+    pub id: String,
+    /// Author username/handle, if known
+    pub author: Option<String>,
+    /// Post text content
+    pub text: String,
 }
 
 /// A user-defined narrative tag definition loaded from markdown
@@ -117,6 +149,9 @@ pub struct ClassifyOutput {
     pub summary: String,
     /// Matched tags with confidences
     pub tags: Vec<TagMatch>,
+    /// Tokens the provider reported for producing this output
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub usage: Option<TokenUsage>,
 }
 
 impl ClassifyOutput {
@@ -128,10 +163,18 @@ impl ClassifyOutput {
             version: Self::SCHEMA_VERSION.to_string(),
             summary,
             tags,
+            usage: None,
         }
     }
 }
 
+/// Tokens consumed by one LLM call
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenUsage {
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+}
+
 /// Publishing mode for X posts
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
 #[serde(rename_all = "snake_case")]
@@ -146,7 +189,7 @@ pub enum XPublishMode {
 }
 
 /// Rendered content ready for publishing
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct RenderedPost {
     /// The text content
     pub text: String,
@@ -154,6 +197,52 @@ pub struct RenderedPost {
     pub source_post_id: String,
     /// Source post URL
     pub source_post_url: String,
+    /// Author of the source post, as the source reports it
+    pub source_author: String,
+    /// Tags shown in the text, for publishers that also emit structured labels
+    pub labels: Vec<TagLabel>,
+    /// Long-form version of the analysis, published alongside the text where supported
+    pub article: Option<RenderedArticle>,
+    /// Everything the text was rendered from, for publishers that forward raw results
+    pub analysis: Option<Arc<PostAnalysis>>,
+}
+
+/// A classified post and the taxonomy it was classified against
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PostAnalysis {
+    pub source_post: SourcePost,
+    pub classification: ClassifyOutput,
+    pub taxonomy_hash: String,
+}
+
+/// A long-form markdown analysis of a source post
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RenderedArticle {
+    /// Stable identifier derived from the source post, so re-analysis replaces the article
+    pub identifier: String,
+    /// Article title
+    pub title: String,
+    /// One-paragraph summary
+    pub summary: String,
+    /// Markdown body
+    pub content: String,
+}
+
+impl RenderedArticle {
+    /// Identifier for the article about a source post, stable across re-analysis
+    pub fn identifier_for(source_post_url: &str) -> String {
+        let hash = format!("{:x}", Sha256::digest(source_post_url.as_bytes()));
+        format!("news-tagger-{}", &hash[..16])
+    }
+}
+
+/// A matched tag as a machine-readable label
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TagLabel {
+    /// Tag ID
+    pub id: String,
+    /// Confidence score 0.0-1.0
+    pub confidence: f64,
 }
 
 /// Record of a published post (for idempotency)
@@ -165,15 +254,111 @@ pub struct PublishedRecord {
     pub source_post_id: String,
     /// Taxonomy hash at time of publishing
     pub taxonomy_hash: String,
-    /// X post ID if published to X
-    pub x_post_id: Option<String>,
-    /// Nostr event ID if published to Nostr
-    pub nostr_event_id: Option<String>,
     /// When published
     #[serde(with = "time::serde::rfc3339")]
     pub published_at: OffsetDateTime,
 }
 
+/// A platform a published record went out to (one per record and platform)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PublishedTarget {
+    /// The [`PublishedRecord`] this belongs to
+    pub record_id: Uuid,
+    /// Publisher platform name, e.g. `x` or `nostr`
+    pub platform: String,
+    /// ID the platform assigned to our post
+    pub target_id: String,
+    /// URL of our post, if the platform has one
+    pub url: Option<String>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub published_at: OffsetDateTime,
+}
+
+/// Stored classification of a source post, kept for exports such as the static site
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClassificationRecord {
+    /// The [`PublishedRecord`] the classification belongs to
+    pub record_id: Uuid,
+    pub source_post: SourcePost,
+    pub classification: ClassifyOutput,
+    pub taxonomy_hash: String,
+    #[serde(with = "time::serde::rfc3339")]
+    pub classified_at: OffsetDateTime,
+}
+
+/// State of a queued publish or post retry
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RetryStatus {
+    /// Will be attempted again once `next_attempt_at` passes
+    Pending,
+    /// Gave up after the maximum number of attempts
+    Failed,
+}
+
+impl RetryStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::Failed => "failed",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "pending" => Some(Self::Pending),
+            "failed" => Some(Self::Failed),
+            _ => None,
+        }
+    }
+}
+
+/// A failed publish to one platform, queued for another attempt
+///
+/// Holds the source post and its classification so the retry can be
+/// rendered again without re-fetching or re-classifying.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PublishRetry {
+    /// The [`PublishedRecord`] the publish belongs to
+    pub record_id: Uuid,
+    /// Publisher platform name
+    pub platform: String,
+    pub source_post: SourcePost,
+    pub classification: ClassifyOutput,
+    pub taxonomy_hash: String,
+    /// Publish attempts made so far, including the original one
+    pub attempts: u32,
+    pub status: RetryStatus,
+    /// Error from the most recent attempt
+    pub last_error: String,
+    /// Earliest time of the next attempt
+    #[serde(with = "time::serde::rfc3339")]
+    pub next_attempt_at: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339")]
+    pub updated_at: OffsetDateTime,
+}
+
+/// A post whose processing failed, retried when its account is polled again
+///
+/// The account's cursor stays before the post until it succeeds or is given
+/// up, so it keeps being fetched.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PostRetry {
+    pub source_post_id: String,
+    /// Account the post was fetched for
+    pub account: String,
+    /// Failed attempts so far
+    pub attempts: u32,
+    pub status: RetryStatus,
+    /// Error from the most recent attempt
+    pub last_error: String,
+    /// Earliest time of the next attempt
+    #[serde(with = "time::serde::rfc3339")]
+    pub next_attempt_at: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339")]
+    pub updated_at: OffsetDateTime,
+}
+
 /// Account watch state
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AccountState {
@@ -186,6 +371,155 @@ pub struct AccountState {
     pub updated_at: OffsetDateTime,
 }
 
+/// Per-account overrides of the run loop's watch settings
+#[derive(Debug, Clone, Default)]
+pub struct AccountSchedule {
+    /// Time between polls of the account (None = the run loop's interval)
+    pub poll_interval: Option<std::time::Duration>,
+    /// Overrides whether replies are processed
+    pub include_replies: Option<bool>,
+    /// Overrides whether reposts are processed
+    pub include_reposts: Option<bool>,
+    /// Regex patterns ignored in addition to the run loop's
+    pub ignore_patterns: Vec<String>,
+    /// Tag IDs the account's posts are classified against (None = all)
+    pub tags: Option<Vec<String>>,
+    /// Daily windows in which the account is polled (empty = always)
+    pub active_hours: Vec<ActiveHours>,
+    /// Time zone of `active_hours` (None = UTC)
+    pub time_zone: Option<&'static Tz>,
+}
+
+impl AccountSchedule {
+    /// Whether `now` falls in one of the active-hours windows
+    pub fn is_active(&self, now: OffsetDateTime) -> bool {
+        if self.active_hours.is_empty() {
+            return true;
+        }
+        let local = match self.time_zone {
+            Some(tz) => now.to_timezone(tz),
+            None => now.to_offset(UtcOffset::UTC),
+        };
+        self.active_hours
+            .iter()
+            .any(|window| window.contains(local.time()))
+    }
+}
+
+/// A daily window of local time, from `start` up to `end`
+///
+/// A window ending before it starts runs past midnight.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ActiveHours {
+    pub start: Time,
+    pub end: Time,
+}
+
+impl ActiveHours {
+    /// Whether `time` falls in the window
+    pub fn contains(&self, time: Time) -> bool {
+        if self.start <= self.end {
+            self.start <= time && time < self.end
+        } else {
+            time >= self.start || time < self.end
+        }
+    }
+}
+
+/// Lookup status of a watched account handle
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AccountStatus {
+    /// Handle resolves to the cached user ID
+    Active,
+    /// Cached user ID now belongs to a different handle
+    Renamed,
+    /// Account exists but is suspended
+    Suspended,
+    /// Handle does not resolve to any account
+    NotFound,
+}
+
+impl AccountStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Active => "active",
+            Self::Renamed => "renamed",
+            Self::Suspended => "suspended",
+            Self::NotFound => "not_found",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "active" => Some(Self::Active),
+            "renamed" => Some(Self::Renamed),
+            "suspended" => Some(Self::Suspended),
+            "not_found" => Some(Self::NotFound),
+            _ => None,
+        }
+    }
+}
+
+/// Cached resolution of an account handle to a platform user ID
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResolvedAccount {
+    /// Account handle as configured (lowercased)
+    pub account: String,
+    /// Platform user ID, if the handle ever resolved
+    pub user_id: Option<String>,
+    /// Current lookup status
+    pub status: AccountStatus,
+    /// New handle when the account was renamed
+    pub renamed_to: Option<String>,
+    /// When the handle was last looked up
+    #[serde(with = "time::serde::rfc3339")]
+    pub resolved_at: OffsetDateTime,
+}
+
+/// HTTP validators and newest entry seen for a polled feed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeedCursor {
+    /// Feed name as configured
+    pub feed: String,
+    /// `ETag` from the last successful response
+    pub etag: Option<String>,
+    /// `Last-Modified` from the last successful response
+    pub last_modified: Option<String>,
+    /// Highest entry ID in the last successful response
+    pub newest_id: Option<String>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub updated_at: OffsetDateTime,
+}
+
+/// A scope that must not call its rate-limited endpoint again before a given time
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RateLimitDeferral {
+    /// Scope key, e.g. `account:alice`, `publisher:x` or `classifier`
+    pub scope: String,
+    /// When the scope may be retried
+    #[serde(with = "time::serde::rfc3339")]
+    pub until: OffsetDateTime,
+}
+
+/// Delivery history of a Nostr relay, updated after every publish attempt
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RelayStatus {
+    /// Relay URL as configured or discovered
+    pub relay: String,
+    /// Events the relay accepted
+    pub successes: u64,
+    /// Events the relay rejected or could not be reached for
+    pub failures: u64,
+    /// When the relay last accepted an event
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub last_success_at: Option<OffsetDateTime>,
+    /// Error from the most recent attempt, cleared on success
+    pub last_error: Option<String>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub updated_at: OffsetDateTime,
+}
+
 /// Processing result for a single post
 #[derive(Debug)]
 pub enum ProcessResult {
@@ -193,11 +527,29 @@ pub enum ProcessResult {
     Published {
         source_post: Box<SourcePost>,
         classification: ClassifyOutput,
-        x_post_id: Option<String>,
-        nostr_event_id: Option<String>,
+        /// Platforms published to in this cycle
+        targets: Vec<PublishedTarget>,
     },
     /// Post was skipped (already processed, filtered, etc.)
     Skipped { reason: String },
+    /// Post was left for a later cycle because a dependency is rate limited
+    Deferred { reason: String },
     /// Classification or publishing failed
-    Failed { error: String },
+    Failed {
+        error: String,
+        /// The post's retry after this failure, once recorded
+        retry: Option<PostRetry>,
+    },
+}
+
+impl ProcessResult {
+    /// Short name of the variant, for logs and traces
+    pub fn outcome(&self) -> &'static str {
+        match self {
+            ProcessResult::Published { .. } => "published",
+            ProcessResult::Skipped { .. } => "skipped",
+            ProcessResult::Deferred { .. } => "deferred",
+            ProcessResult::Failed { .. } => "failed",
+        }
+    }
 }
@@ -6,10 +6,12 @@
 use async_trait::async_trait;
 use thiserror::Error;
 use time::OffsetDateTime;
+use uuid::Uuid;
 
 use crate::model::{
-    AccountState, ClassifyInput, ClassifyOutput, PublishedRecord, RenderedPost, SourcePost,
-    TagDefinition,
+    AccountState, ClassificationRecord, ClassifyInput, ClassifyOutput, FeedCursor, PostRetry,
+    ProcessResult, PublishRetry, PublishedRecord, PublishedTarget, RateLimitDeferral, RelayStatus,
+    RenderedPost, ResolvedAccount, SourcePost, TagDefinition, TokenUsage,
 };
 
 /// Error type for post source operations
@@ -23,11 +25,20 @@ pub enum PostSourceError {
     Auth(String),
     #[error("Network error: {0}")]
     Network(String),
+    #[error("Account unavailable: {0}")]
+    AccountUnavailable(String),
 }
 
 /// Port for fetching posts from a source platform
 #[async_trait]
 pub trait PostSource: Send + Sync {
+    /// Resolve account metadata ahead of a poll cycle
+    ///
+    /// Sources that need per-account lookups can batch them here; the default does nothing.
+    async fn prepare_accounts(&self, _accounts: &[String]) -> Result<(), PostSourceError> {
+        Ok(())
+    }
+
     /// Fetch posts for an account since the given ID
     async fn fetch_posts(
         &self,
@@ -43,8 +54,8 @@ pub enum ClassifyError {
     Api(String),
     #[error("Invalid response format: {0}")]
     InvalidFormat(String),
-    #[error("Rate limited")]
-    RateLimited,
+    #[error("Rate limited, retry after: {0:?}")]
+    RateLimited(Option<std::time::Duration>),
     #[error("Timeout")]
     Timeout,
     #[error("Configuration error: {0}")]
@@ -56,6 +67,11 @@ pub enum ClassifyError {
 pub trait Classifier: Send + Sync {
     /// Classify a post against the provided definitions
     async fn classify(&self, input: ClassifyInput) -> Result<ClassifyOutput, ClassifyError>;
+
+    /// Provider name, e.g. `openai`, used to label metrics
+    fn provider(&self) -> &str {
+        "unknown"
+    }
 }
 
 /// Error type for publisher operations
@@ -63,8 +79,8 @@ pub trait Classifier: Send + Sync {
 pub enum PublishError {
     #[error("API error: {0}")]
     Api(String),
-    #[error("Rate limited")]
-    RateLimited,
+    #[error("Rate limited, retry after: {0:?}")]
+    RateLimited(Option<std::time::Duration>),
     #[error("Authentication failed: {0}")]
     Auth(String),
     #[error("Content too long: {len} > {max}")]
@@ -129,6 +145,8 @@ pub enum StateError {
     NotFound(String),
     #[error("Serialization error: {0}")]
     Serialization(String),
+    #[error("Migration error: {0}")]
+    Migration(String),
 }
 
 /// Port for persisting application state
@@ -140,6 +158,9 @@ pub trait StateStore: Send + Sync {
     /// Update account state
     async fn set_account_state(&self, state: &AccountState) -> Result<(), StateError>;
 
+    /// List the state of every account, by account
+    async fn list_account_states(&self) -> Result<Vec<AccountState>, StateError>;
+
     /// Check if a post has been processed with the given taxonomy
     async fn is_processed(
         &self,
@@ -156,8 +177,152 @@ pub trait StateStore: Send + Sync {
         source_post_id: &str,
         taxonomy_hash: &str,
     ) -> Result<Option<PublishedRecord>, StateError>;
+
+    /// List published records, oldest first
+    async fn list_published(&self) -> Result<Vec<PublishedRecord>, StateError>;
+
+    /// Record that a published record went out to a platform
+    async fn record_target(&self, target: &PublishedTarget) -> Result<(), StateError>;
+
+    /// List the platforms a published record went out to
+    async fn list_targets(&self, record_id: Uuid) -> Result<Vec<PublishedTarget>, StateError>;
+
+    /// Store or replace the classification of a published record
+    async fn record_classification(&self, record: &ClassificationRecord) -> Result<(), StateError>;
+
+    /// List stored classifications, oldest first
+    async fn list_classifications(&self) -> Result<Vec<ClassificationRecord>, StateError>;
+
+    /// Store or replace the queued retry for a record and platform
+    async fn set_retry(&self, retry: &PublishRetry) -> Result<(), StateError>;
+
+    /// List queued retries (pending and failed), soonest next attempt first
+    async fn list_retries(&self) -> Result<Vec<PublishRetry>, StateError>;
+
+    /// List the queued retries of one record, by platform
+    async fn list_retries_for_record(
+        &self,
+        record_id: Uuid,
+    ) -> Result<Vec<PublishRetry>, StateError>;
+
+    /// Remove the queued retry for a record and platform, if any
+    async fn delete_retry(&self, record_id: Uuid, platform: &str) -> Result<(), StateError>;
+
+    /// Store or replace the retry of a failed post
+    async fn set_post_retry(&self, retry: &PostRetry) -> Result<(), StateError>;
+
+    /// List retries of an account's failed posts (pending and given up), by
+    /// post ID
+    async fn list_post_retries(&self, account: &str) -> Result<Vec<PostRetry>, StateError>;
+
+    /// Remove the retry of a post, if any
+    async fn delete_post_retry(&self, source_post_id: &str) -> Result<(), StateError>;
+
+    /// Get the cached user ID resolution for an account handle
+    async fn get_resolved_account(
+        &self,
+        account: &str,
+    ) -> Result<Option<ResolvedAccount>, StateError>;
+
+    /// Store a user ID resolution for an account handle
+    async fn set_resolved_account(&self, resolved: &ResolvedAccount) -> Result<(), StateError>;
+
+    /// List all cached account resolutions
+    async fn list_resolved_accounts(&self) -> Result<Vec<ResolvedAccount>, StateError>;
+
+    /// List stored rate-limit deferrals (including expired ones)
+    async fn list_deferrals(&self) -> Result<Vec<RateLimitDeferral>, StateError>;
+
+    /// Store or replace the rate-limit deferral for a scope
+    async fn set_deferral(&self, deferral: &RateLimitDeferral) -> Result<(), StateError>;
+
+    /// Get the conditional-request cursor for a feed
+    async fn get_feed_cursor(&self, feed: &str) -> Result<Option<FeedCursor>, StateError>;
+
+    /// Store or replace the conditional-request cursor for a feed
+    async fn set_feed_cursor(&self, cursor: &FeedCursor) -> Result<(), StateError>;
+
+    /// Get the delivery history of a Nostr relay
+    async fn get_relay_status(&self, relay: &str) -> Result<Option<RelayStatus>, StateError>;
+
+    /// Store or replace the delivery history of a Nostr relay
+    async fn set_relay_status(&self, status: &RelayStatus) -> Result<(), StateError>;
+
+    /// List the delivery history of every relay seen so far
+    async fn list_relay_statuses(&self) -> Result<Vec<RelayStatus>, StateError>;
+
+    /// Claim a post for `worker_id` for `lease` from now
+    ///
+    /// Returns `true` when the post was unclaimed, its lease had expired or
+    /// `worker_id` already held it (renewing the lease), and `false` while
+    /// another worker holds it. The check and the claim are one atomic step,
+    /// so workers sharing the store never both win.
+    async fn claim_post(
+        &self,
+        source_post_id: &str,
+        taxonomy_hash: &str,
+        worker_id: &str,
+        lease: std::time::Duration,
+    ) -> Result<bool, StateError>;
+
+    /// Give up a claim on a post, if `worker_id` holds it
+    async fn release_post(
+        &self,
+        source_post_id: &str,
+        taxonomy_hash: &str,
+        worker_id: &str,
+    ) -> Result<(), StateError>;
+
+    /// Take or renew the lease to poll an account, with the same rules as
+    /// [`StateStore::claim_post`]
+    async fn claim_account(
+        &self,
+        account: &str,
+        worker_id: &str,
+        lease: std::time::Duration,
+    ) -> Result<bool, StateError>;
 }
 
+/// Port for run loop metrics; every method defaults to recording nothing
+pub trait RunMetrics: Send + Sync {
+    /// Posts returned by the post source for `account`
+    fn posts_fetched(&self, _account: &str, _count: usize) {}
+
+    /// Posts of `account` dropped by the reply, repost and ignore filters
+    fn posts_filtered(&self, _account: &str, _count: usize) {}
+
+    /// Outcome of processing one post of `account`
+    fn post_processed(&self, _account: &str, _result: &ProcessResult) {}
+
+    /// One classification call, with the tokens it used if it succeeded and
+    /// the provider reported them
+    fn classification(
+        &self,
+        _provider: &str,
+        _elapsed: std::time::Duration,
+        _usage: Option<TokenUsage>,
+    ) {
+    }
+
+    /// One publish attempt on `platform`, including queued retries
+    fn publish(&self, _platform: &str, _success: bool) {}
+
+    /// Time a post waited for the configured per-minute/per-hour limits
+    fn rate_limit_wait(&self, _waited: std::time::Duration) {}
+
+    /// Queued publish retries, reported once per poll cycle
+    fn retry_queue(&self, _pending: usize, _failed: usize) {}
+
+    /// Hash of the taxonomy loaded for the current poll cycle
+    fn taxonomy(&self, _hash: &str) {}
+}
+
+/// Metrics sink that records nothing
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoopMetrics;
+
+impl RunMetrics for NoopMetrics {}
+
 /// Port for time/clock operations (enables deterministic testing)
 pub trait Clock: Send + Sync {
     /// Get the current time
@@ -1,5 +1,7 @@
 //! Classification use case
 
+use tracing::field;
+
 use crate::{
     model::{ClassifyInput, ClassifyOutput, SourcePost, TagDefinition},
     ports::{Classifier, ClassifyError},
@@ -38,6 +40,16 @@ impl<C: Classifier> ClassifyUseCase<C> {
     }
 
     /// Classify a post against the given definitions
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            post_id = %post.id,
+            provider = self.classifier.provider(),
+            input_tokens = field::Empty,
+            output_tokens = field::Empty,
+            tag_ids = field::Empty,
+        )
+    )]
     pub async fn classify(
         &self,
         post: &SourcePost,
@@ -58,7 +70,21 @@ impl<C: Classifier> ClassifyUseCase<C> {
             policy_text: self.config.policy_text.clone(),
         };
 
-        self.classifier.classify(input).await
+        let output = self.classifier.classify(input).await?;
+
+        let span = tracing::Span::current();
+        if let Some(usage) = output.usage {
+            // Signed, so OpenTelemetry exports them as numbers
+            span.record("input_tokens", usage.input_tokens as i64);
+            span.record("output_tokens", usage.output_tokens as i64);
+        }
+        let output = strip_context_evidence(post, output);
+        span.record(
+            "tag_ids",
+            field::debug(output.tags.iter().map(|t| &t.id).collect::<Vec<_>>()),
+        );
+
+        Ok(output)
     }
 
     /// Select definitions to include based on prefilter config
@@ -139,10 +165,36 @@ impl<C: Classifier> ClassifyUseCase<C> {
     }
 }
 
+/// Drop evidence that was quoted from context posts instead of the target post
+fn strip_context_evidence(post: &SourcePost, mut output: ClassifyOutput) -> ClassifyOutput {
+    if post.context.is_empty() {
+        return output;
+    }
+
+    for tag in &mut output.tags {
+        let before = tag.evidence.len();
+        tag.evidence.retain(|quote| {
+            let quote = quote.trim();
+            post.text.contains(quote) || !post.context.iter().any(|c| c.text.contains(quote))
+        });
+
+        if tag.evidence.len() < before {
+            tracing::warn!(
+                post_id = %post.id,
+                tag = %tag.id,
+                dropped = before - tag.evidence.len(),
+                "Dropped evidence quoted from context instead of the post"
+            );
+        }
+    }
+
+    output
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::model::TagMatch;
+    use crate::model::{ContextRelation, PostContext, TagMatch};
     use async_trait::async_trait;
     use time::OffsetDateTime;
 
@@ -167,6 +219,7 @@ mod tests {
             is_repost: false,
             is_reply: false,
             reply_to_id: None,
+            context: vec![],
         }
     }
 
@@ -217,6 +270,38 @@ mod tests {
         assert_eq!(result.tags[0].id, "climate_fear");
     }
 
+    #[tokio::test]
+    async fn test_classify_drops_evidence_quoted_from_context() {
+        let response = ClassifyOutput::new(
+            "Reply agreeing with a quoted claim".to_string(),
+            vec![TagMatch {
+                id: "climate_fear".to_string(),
+                confidence: 0.8,
+                rationale: "Endorses catastrophic framing".to_string(),
+                evidence: vec![
+                    "unprecedented disasters".to_string(),
+                    "we are all doomed".to_string(),
+                ],
+            }],
+        );
+
+        let mut post = sample_post();
+        post.context = vec![PostContext {
+            relation: ContextRelation::Quoted,
+            id: "100".to_string(),
+            author: Some("other".to_string()),
+            text: "Scientists say we are all doomed".to_string(),
+        }];
+
+        let usecase = ClassifyUseCase::new(FakeClassifier { response }, ClassifyConfig::default());
+        let result = usecase
+            .classify(&post, &sample_definitions())
+            .await
+            .unwrap();
+
+        assert_eq!(result.tags[0].evidence, vec!["unprecedented disasters"]);
+    }
+
     #[tokio::test]
     async fn test_prefilter_limits_definitions() {
         let expected = ClassifyOutput::new("Summary".to_string(), vec![]);
@@ -1,6 +1,8 @@
 //! Rendering use case - transforms classification output into platform-specific content
 
-use crate::model::{ClassifyOutput, RenderedPost, SourcePost, XPublishMode};
+use crate::model::{
+    ClassifyOutput, RenderedArticle, RenderedPost, SourcePost, TagLabel, XPublishMode,
+};
 
 /// Configuration for the renderer
 #[derive(Debug, Clone)]
@@ -9,12 +11,20 @@ pub struct RenderConfig {
     pub x_max_chars: usize,
     /// X publishing mode
     pub x_publish_mode: XPublishMode,
+    /// Maximum characters for Mastodon statuses
+    pub mastodon_max_chars: usize,
+    /// Maximum characters for Bluesky posts
+    pub bluesky_max_chars: usize,
     /// Whether to include confidence scores
     pub include_confidence: bool,
     /// Whether to include rationale
     pub include_rationale: bool,
     /// Minimum confidence to include a tag
     pub min_confidence: f64,
+    /// Render Nostr analyses as long-form articles with a short linking note
+    pub nostr_long_form: bool,
+    /// Base URL of the tag definitions, linked from long-form articles as `<base>/<id>.md`
+    pub tag_definitions_url: Option<String>,
 }
 
 impl Default for RenderConfig {
@@ -22,9 +32,13 @@ impl Default for RenderConfig {
         Self {
             x_max_chars: 280,
             x_publish_mode: XPublishMode::Reply,
+            mastodon_max_chars: 500,
+            bluesky_max_chars: 300,
             include_confidence: true,
             include_rationale: true,
             min_confidence: 0.5,
+            nostr_long_form: false,
+            tag_definitions_url: None,
         }
     }
 }
@@ -64,11 +78,7 @@ impl Renderer {
             }
         };
 
-        RenderedPost {
-            text: content,
-            source_post_id: post.id.clone(),
-            source_post_url: post.url.clone(),
-        }
+        self.rendered(post, classification, content)
     }
 
     /// Render classification output for Nostr
@@ -77,18 +87,181 @@ impl Renderer {
         post: &SourcePost,
         classification: &ClassifyOutput,
     ) -> RenderedPost {
-        let tags_line = self.format_tags_line(classification);
-        let rationale = self.format_full_rationale(classification);
+        if self.config.nostr_long_form {
+            // The note only points at the article, which carries the breakdown
+            let content = format!(
+                "Narrative analysis of {}\n\n{}\n\nOriginal: {}",
+                post.author,
+                self.format_tags_line(classification),
+                post.url
+            );
+            return RenderedPost {
+                article: Some(self.render_article(post, classification)),
+                ..self.rendered(post, classification, content)
+            };
+        }
+
+        let content = self.full_text(post, classification);
+        self.rendered(post, classification, content)
+    }
+
+    /// Render classification output for webhooks (full text, no length limit)
+    pub fn render_for_webhook(
+        &self,
+        post: &SourcePost,
+        classification: &ClassifyOutput,
+    ) -> RenderedPost {
+        let content = self.full_text(post, classification);
+        self.rendered(post, classification, content)
+    }
 
-        let content = format!(
+    /// Author, tags, full rationale and link to the original
+    fn full_text(&self, post: &SourcePost, classification: &ClassifyOutput) -> String {
+        format!(
             "Narrative analysis of {}\n\n{}\n\n{}\n\nOriginal: {}",
-            post.author, tags_line, rationale, post.url
+            post.author,
+            self.format_tags_line(classification),
+            self.format_full_rationale(classification),
+            post.url
+        )
+    }
+
+    /// Markdown article with the summary, and rationale, evidence and definition per tag
+    fn render_article(
+        &self,
+        post: &SourcePost,
+        classification: &ClassifyOutput,
+    ) -> RenderedArticle {
+        let title = format!("Narrative analysis of {}", post.author);
+        let mut content = format!(
+            "Analysis of [this post by {}]({}).\n\n## Summary\n\n{}\n",
+            post.author, post.url, classification.summary
         );
 
+        let matched: Vec<_> = classification
+            .tags
+            .iter()
+            .filter(|t| t.confidence >= self.config.min_confidence)
+            .collect();
+
+        if matched.is_empty() {
+            content.push_str("\n## Tags\n\nNo significant narrative patterns detected.\n");
+        } else {
+            content.push_str("\n## Tags\n");
+        }
+
+        for tag in matched {
+            let heading = match &self.config.tag_definitions_url {
+                Some(base) => format!("[{}]({}/{}.md)", tag.id, base.trim_end_matches('/'), tag.id),
+                None => format!("`{}`", tag.id),
+            };
+            content.push_str(&format!("\n### {}", heading));
+            if self.config.include_confidence {
+                content.push_str(&format!(" ({:.2})", tag.confidence));
+            }
+            content.push_str(&format!("\n\n{}\n", tag.rationale));
+
+            for quote in &tag.evidence {
+                let quoted = quote
+                    .lines()
+                    .map(|line| format!("> {}", line))
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                content.push_str(&format!("\n{}\n", quoted));
+            }
+        }
+
+        RenderedArticle {
+            identifier: RenderedArticle::identifier_for(&post.url),
+            title,
+            summary: classification.summary.clone(),
+            content,
+        }
+    }
+
+    /// Render classification output for Mastodon (threaded or quoted, so no URL)
+    pub fn render_for_mastodon(
+        &self,
+        post: &SourcePost,
+        classification: &ClassifyOutput,
+    ) -> RenderedPost {
+        self.render_attached(post, classification, self.config.mastodon_max_chars)
+    }
+
+    /// Render classification output for Bluesky (threaded or quoted, so no URL)
+    pub fn render_for_bluesky(
+        &self,
+        post: &SourcePost,
+        classification: &ClassifyOutput,
+    ) -> RenderedPost {
+        self.render_attached(post, classification, self.config.bluesky_max_chars)
+    }
+
+    /// Tags and full rationale for a post attached to its source by the platform
+    fn render_attached(
+        &self,
+        post: &SourcePost,
+        classification: &ClassifyOutput,
+        max_chars: usize,
+    ) -> RenderedPost {
+        let tags_line = self.format_tags_line(classification);
+        let content = if self.config.include_rationale {
+            format!(
+                "{}\n\n{}",
+                tags_line,
+                self.format_full_rationale(classification)
+            )
+        } else {
+            tags_line
+        };
+
+        self.rendered(
+            post,
+            classification,
+            self.truncate_to_length(&content, max_chars),
+        )
+    }
+
+    /// Render for a publisher by its platform name
+    pub fn render_for_platform(
+        &self,
+        platform: &str,
+        post: &SourcePost,
+        classification: &ClassifyOutput,
+    ) -> RenderedPost {
+        match platform {
+            "x" => self.render_for_x(post, classification),
+            "mastodon" => self.render_for_mastodon(post, classification),
+            "bluesky" => self.render_for_bluesky(post, classification),
+            "webhook" => self.render_for_webhook(post, classification),
+            _ => self.render_for_nostr(post, classification),
+        }
+    }
+
+    fn rendered(
+        &self,
+        post: &SourcePost,
+        classification: &ClassifyOutput,
+        text: String,
+    ) -> RenderedPost {
+        let labels = classification
+            .tags
+            .iter()
+            .filter(|t| t.confidence >= self.config.min_confidence)
+            .map(|t| TagLabel {
+                id: t.id.clone(),
+                confidence: t.confidence,
+            })
+            .collect();
+
         RenderedPost {
-            text: content,
+            text,
             source_post_id: post.id.clone(),
             source_post_url: post.url.clone(),
+            source_author: post.author.clone(),
+            labels,
+            article: None,
+            analysis: None,
         }
     }
 
@@ -196,6 +369,7 @@ mod tests {
             is_repost: false,
             is_reply: false,
             reply_to_id: None,
+            context: vec![],
         }
     }
 
@@ -234,6 +408,39 @@ mod tests {
         assert!(!result.text.contains("https://")); // Reply mode doesn't include URL
     }
 
+    #[test]
+    fn test_render_for_nostr_long_form_article() {
+        let renderer = Renderer::new(RenderConfig {
+            nostr_long_form: true,
+            tag_definitions_url: Some("https://example.com/definitions/".to_string()),
+            ..Default::default()
+        });
+
+        let result = renderer.render_for_nostr(&sample_post(), &sample_classification());
+        let article = result.article.expect("article");
+
+        assert!(!result.text.contains("First rationale"));
+        assert!(
+            result
+                .text
+                .contains("Original: https://x.com/testuser/status/123")
+        );
+        assert_eq!(article.summary, "Summary of the post");
+        assert!(
+            article
+                .content
+                .contains("## Summary\n\nSummary of the post")
+        );
+        assert!(article.content.contains(
+            "### [tag_one](https://example.com/definitions/tag_one.md) (0.85)\n\nFirst rationale explaining the match"
+        ));
+        assert!(article.content.contains("> evidence 2"));
+
+        let again = renderer.render_for_nostr(&sample_post(), &sample_classification());
+        assert_eq!(again.article.unwrap().identifier, article.identifier);
+        assert!(article.identifier.starts_with("news-tagger-"));
+    }
+
     #[test]
     fn test_render_for_x_new_post_mode() {
         let renderer = Renderer::new(RenderConfig {
@@ -295,4 +502,18 @@ mod tests {
         assert!(result.text.contains("Original:"));
         // Nostr has no strict length limit
     }
+
+    #[test]
+    fn test_render_for_mastodon_fits_limit() {
+        let renderer = Renderer::new(RenderConfig {
+            mastodon_max_chars: 60,
+            ..Default::default()
+        });
+
+        let result = renderer.render_for_mastodon(&sample_post(), &sample_classification());
+
+        assert!(result.text.starts_with("Tags: tag_one (0.85)"));
+        assert!(result.text.chars().count() <= 60);
+        assert!(!result.text.contains("https://"));
+    }
 }
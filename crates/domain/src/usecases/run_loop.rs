@@ -1,11 +1,20 @@
 //! Run loop use case - orchestrates watching, classifying, and publishing
 
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
+use time::OffsetDateTime;
 use uuid::Uuid;
 
 use crate::{
-    model::{AccountState, ProcessResult, PublishedRecord, SourcePost, Taxonomy},
-    ports::{Classifier, ClassifyError, Clock, DefinitionsRepo, PostSource, Publisher, StateStore},
+    model::{
+        AccountSchedule, AccountState, ClassificationRecord, PostAnalysis, PostRetry,
+        ProcessResult, PublishRetry, PublishedRecord, PublishedTarget, RateLimitDeferral,
+        RenderedPost, RetryStatus, SourcePost, Taxonomy,
+    },
+    ports::{
+        Classifier, ClassifyError, Clock, DefinitionsRepo, NoopMetrics, PostSource,
+        PostSourceError, PublishError, Publisher, RunMetrics, StateError, StateStore,
+    },
     usecases::{
         classify::{ClassifyConfig, ClassifyUseCase},
         render::{RenderConfig, Renderer},
@@ -14,8 +23,19 @@ use crate::{
 use futures::future::BoxFuture;
 use futures::stream::{FuturesUnordered, StreamExt};
 use regex::Regex;
-use tokio::sync::Mutex;
+use tokio::sync::{Mutex, MutexGuard, watch};
 use tokio::time::{Duration, Instant, sleep};
+use tracing::{Instrument, field};
+
+/// Deferral scope for the classifier provider
+const CLASSIFIER_SCOPE: &str = "classifier";
+
+/// Longest wait between retries
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// How early an account may be polled and still count as on schedule, so
+/// ticks landing a moment before its interval elapses do not skip it
+const SCHEDULE_SLACK: Duration = Duration::from_secs(1);
 
 /// Configuration for the run loop
 #[derive(Debug, Clone)]
@@ -28,6 +48,10 @@ pub struct RunLoopConfig {
     pub include_reposts: bool,
     /// Regex patterns for posts to ignore
     pub ignore_patterns: Vec<String>,
+    /// Time between polls of an account (None = every poll cycle)
+    pub poll_interval: Option<Duration>,
+    /// Per-account overrides, keyed by account
+    pub schedules: HashMap<String, AccountSchedule>,
     /// Dry run mode (don't actually publish)
     pub dry_run: bool,
     /// Maximum concurrent post processing tasks
@@ -36,6 +60,22 @@ pub struct RunLoopConfig {
     pub rate_limit_per_minute: Option<u32>,
     /// Max posts processed per hour (None = unlimited)
     pub rate_limit_per_hour: Option<u32>,
+    /// Deferral applied when a rate-limited call does not report when to retry
+    pub rate_limit_backoff: Duration,
+    /// Attempts per failed post, or per platform for a failed publish,
+    /// before its retry is marked failed
+    pub retry_max_attempts: u32,
+    /// Delay before the first retry, doubled after each failure
+    pub retry_base_delay: Duration,
+    /// Identifies this process in post claims and account leases
+    pub worker_id: String,
+    /// How long a claimed post stays reserved for this worker
+    pub claim_lease: Duration,
+    /// How long this worker stays the only one polling an account; renewed
+    /// every poll, so it must outlast the poll interval
+    pub account_lease: Duration,
+    /// How long posts already being processed may run after shutdown is requested
+    pub drain_timeout: Duration,
     /// Classification config
     pub classify_config: ClassifyConfig,
     /// Render config
@@ -49,10 +89,19 @@ impl Default for RunLoopConfig {
             include_replies: false,
             include_reposts: false,
             ignore_patterns: vec![],
+            poll_interval: None,
+            schedules: HashMap::new(),
             dry_run: true,
             max_concurrent: 4,
             rate_limit_per_minute: None,
             rate_limit_per_hour: None,
+            rate_limit_backoff: Duration::from_secs(15 * 60),
+            retry_max_attempts: 5,
+            retry_base_delay: Duration::from_secs(5 * 60),
+            worker_id: "local".to_string(),
+            claim_lease: Duration::from_secs(10 * 60),
+            account_lease: Duration::from_secs(10 * 60),
+            drain_timeout: Duration::from_secs(30),
             classify_config: ClassifyConfig::default(),
             render_config: RenderConfig::default(),
         }
@@ -61,50 +110,63 @@ impl Default for RunLoopConfig {
 
 /// Run loop orchestrator
 #[derive(Clone)]
-pub struct RunLoop<S, D, C, X, N, St, Cl>
+pub struct RunLoop<S, D, C, St, Cl>
 where
     S: PostSource + ?Sized,
     D: DefinitionsRepo + ?Sized,
     C: Classifier + ?Sized,
-    X: Publisher + ?Sized,
-    N: Publisher + ?Sized,
     St: StateStore + ?Sized,
     Cl: Clock + ?Sized,
 {
     post_source: Arc<S>,
     definitions_repo: Arc<D>,
     classifier: Arc<C>,
-    x_publisher: Arc<X>,
-    nostr_publisher: Arc<N>,
+    publishers: Vec<Arc<dyn Publisher>>,
     state_store: Arc<St>,
     clock: Arc<Cl>,
     config: RunLoopConfig,
     ignore_patterns: Vec<Regex>,
+    account_ignore_patterns: HashMap<String, Vec<Regex>>,
+    last_polled: Arc<Mutex<HashMap<String, OffsetDateTime>>>,
     rate_limiter: Arc<RateLimiter>,
+    deferrals: Arc<Mutex<Deferrals>>,
+    metrics: Arc<dyn RunMetrics>,
+    shutdown: Arc<watch::Sender<bool>>,
 }
 
-impl<S, D, C, X, N, St, Cl> RunLoop<S, D, C, X, N, St, Cl>
+impl<S, D, C, St, Cl> RunLoop<S, D, C, St, Cl>
 where
     S: PostSource + ?Sized,
     D: DefinitionsRepo + ?Sized,
     C: Classifier + ?Sized,
-    X: Publisher + ?Sized,
-    N: Publisher + ?Sized,
     St: StateStore + ?Sized,
     Cl: Clock + ?Sized,
 {
-    #[allow(clippy::too_many_arguments)]
+    /// Create a run loop publishing to every enabled publisher in `publishers`
+    ///
+    /// Each publisher's content is rendered for its platform, and each
+    /// platform is tracked separately so a failure on one is retried without
+    /// repeating the others.
     pub fn new(
         post_source: Arc<S>,
         definitions_repo: Arc<D>,
         classifier: Arc<C>,
-        x_publisher: Arc<X>,
-        nostr_publisher: Arc<N>,
+        publishers: Vec<Arc<dyn Publisher>>,
         state_store: Arc<St>,
         clock: Arc<Cl>,
         config: RunLoopConfig,
     ) -> Self {
         let ignore_patterns = compile_ignore_patterns(&config.ignore_patterns);
+        let account_ignore_patterns = config
+            .schedules
+            .iter()
+            .map(|(account, schedule)| {
+                (
+                    account.clone(),
+                    compile_ignore_patterns(&schedule.ignore_patterns),
+                )
+            })
+            .collect();
         let rate_limiter = Arc::new(RateLimiter::new(
             config.rate_limit_per_minute,
             config.rate_limit_per_hour,
@@ -113,17 +175,52 @@ where
             post_source,
             definitions_repo,
             classifier,
-            x_publisher,
-            nostr_publisher,
+            publishers,
             state_store,
             clock,
             config,
             ignore_patterns,
+            account_ignore_patterns,
+            last_polled: Arc::new(Mutex::new(HashMap::new())),
             rate_limiter,
+            deferrals: Arc::new(Mutex::new(Deferrals::default())),
+            metrics: Arc::new(NoopMetrics),
+            shutdown: Arc::new(watch::Sender::new(false)),
         }
     }
 
-    /// Run a single poll cycle for all accounts
+    /// Report fetch, classification and publish activity to `metrics`
+    pub fn with_metrics(mut self, metrics: Arc<dyn RunMetrics>) -> Self {
+        self.metrics = metrics;
+        self
+    }
+
+    /// Stop taking on accounts, posts and retries
+    ///
+    /// Posts already being processed are finished, for up to
+    /// `drain_timeout`, so the cycle in progress returns soon after.
+    pub fn shutdown(&self) {
+        self.shutdown.send_replace(true);
+    }
+
+    /// Whether [`shutdown`](Self::shutdown) was requested
+    pub fn is_shutting_down(&self) -> bool {
+        *self.shutdown.borrow()
+    }
+
+    /// Resolves `drain_timeout` after shutdown is requested
+    async fn drain_deadline(&self) {
+        let mut requested = self.shutdown.subscribe();
+        // The sender lives as long as self, so this only returns once requested
+        let _ = requested.wait_for(|stopping| *stopping).await;
+        sleep(self.config.drain_timeout).await;
+    }
+
+    /// Run a single poll cycle for the accounts that are due
+    ///
+    /// An account is due in its active hours once its poll interval has
+    /// elapsed since this run loop last polled it.
+    #[tracing::instrument(name = "poll_cycle", skip_all)]
     pub async fn poll_once(&self) -> Result<Vec<(String, ProcessResult)>, RunLoopError> {
         // Load definitions
         let definitions = self
@@ -139,10 +236,38 @@ where
             definition_count = taxonomy.definitions.len(),
             "Loaded taxonomy"
         );
+        self.metrics.taxonomy(&taxonomy.hash);
+
+        // Queued retries only need their publisher, so they run even while
+        // new posts are blocked
+        if !self.config.dry_run {
+            self.process_retries().await;
+            self.report_retry_queue().await;
+        }
+
+        if let Some(reason) = self.blocking_deferral().await {
+            tracing::info!(reason = %reason, "Skipping poll cycle");
+            return Ok(vec![]);
+        }
+
+        if let Err(e) = self
+            .post_source
+            .prepare_accounts(&self.config.accounts)
+            .await
+        {
+            tracing::warn!(error = %e, "Failed to prepare accounts, continuing");
+        }
 
         let mut results = Vec::new();
+        let cycle_started = self.clock.now();
 
         for account in &self.config.accounts {
+            if self.is_shutting_down() {
+                break;
+            }
+            if !self.take_due(account, cycle_started).await {
+                continue;
+            }
             match self.poll_account(account, Arc::clone(&taxonomy)).await {
                 Ok(account_results) => results.extend(account_results),
                 Err(e) => {
@@ -156,11 +281,35 @@ where
     }
 
     /// Poll a single account
+    #[tracing::instrument(skip_all, fields(account = %account))]
     async fn poll_account(
         &self,
         account: &str,
         taxonomy: Arc<Taxonomy>,
     ) -> Result<Vec<(String, ProcessResult)>, RunLoopError> {
+        if let Some(until) = self.deferred_until(&account_scope(account)).await {
+            tracing::debug!(account = %account, until = %until, "Account deferred by rate limit");
+            return Ok(vec![]);
+        }
+
+        // Dry runs neither take nor respect leases, so they never hold up a real worker
+        if !self.config.dry_run {
+            match self
+                .state_store
+                .claim_account(account, &self.config.worker_id, self.config.account_lease)
+                .await
+            {
+                Ok(true) => {}
+                Ok(false) => {
+                    tracing::debug!(account = %account, "Account polled by another worker");
+                    return Ok(vec![]);
+                }
+                Err(e) => {
+                    tracing::warn!(account = %account, error = %e, "Failed to claim account, continuing");
+                }
+            }
+        }
+
         // Get last processed ID
         let account_state = self
             .state_store
@@ -176,67 +325,170 @@ where
             "Fetching posts"
         );
 
-        // Fetch new posts
-        let posts = self
+        // Fetch new posts; each post's span links back to this fetch
+        let fetch_span = tracing::info_span!(
+            "fetch_posts",
+            account = %account,
+            since_id = ?since_id,
+            count = field::Empty
+        );
+        let posts = match self
             .post_source
             .fetch_posts(account, since_id)
+            .instrument(fetch_span.clone())
             .await
-            .map_err(|e| RunLoopError::PostSource(e.to_string()))?;
+        {
+            Ok(posts) => posts,
+            Err(PostSourceError::RateLimited(wait)) => {
+                let until = self.defer(&account_scope(account), wait).await;
+                tracing::warn!(account = %account, until = %until, "Rate limited, deferring account");
+                return Ok(vec![]);
+            }
+            Err(e) => return Err(RunLoopError::PostSource(e.to_string())),
+        };
 
         if posts.is_empty() {
             tracing::debug!(account = %account, "No new posts");
             return Ok(vec![]);
         }
 
+        fetch_span.record("count", posts.len());
         tracing::info!(account = %account, count = posts.len(), "Fetched posts");
+        self.metrics.posts_fetched(account, posts.len());
 
         // Filter posts
-        let filtered_posts = self.filter_posts(posts);
+        let fetched = posts.len();
+        let filtered_posts = self.filter_posts(account, posts);
+        self.metrics
+            .posts_filtered(account, fetched - filtered_posts.len());
 
         if filtered_posts.is_empty() {
             return Ok(vec![]);
         }
 
+        // Classify against the account's tag subset, keeping the full
+        // taxonomy's hash so processed state is shared with other accounts
+        let schedule = self.config.schedules.get(account);
+        let taxonomy = match schedule.and_then(|s| s.tags.as_ref()) {
+            Some(tags) => Arc::new(Taxonomy {
+                definitions: taxonomy
+                    .definitions
+                    .iter()
+                    .filter(|d| tags.contains(&d.id))
+                    .cloned()
+                    .collect(),
+                hash: taxonomy.hash.clone(),
+            }),
+            None => taxonomy,
+        };
+
+        // Posts that failed before are only processed again once due
+        let mut retries: HashMap<String, PostRetry> = match self
+            .state_store
+            .list_post_retries(account)
+            .await
+        {
+            Ok(retries) => retries
+                .into_iter()
+                .map(|r| (r.source_post_id.clone(), r))
+                .collect(),
+            Err(e) => {
+                tracing::warn!(account = %account, error = %e, "Failed to load post retries, continuing");
+                HashMap::new()
+            }
+        };
+        let now = self.clock.now();
+
         // Process each post with bounded concurrency and rate limiting
         let mut results = Vec::new();
-        let mut last_id = since_id.map(String::from);
+        let mut dispatched = Vec::new();
+        let mut deferred = false;
         let max_concurrent = self.config.max_concurrent.max(1);
         let mut tasks: FuturesUnordered<BoxFuture<'_, (String, ProcessResult)>> =
             FuturesUnordered::new();
         let mut posts_iter = filtered_posts.into_iter();
+        let drain_deadline = self.drain_deadline();
+        tokio::pin!(drain_deadline);
 
-        while tasks.len() < max_concurrent {
-            let Some(post) = posts_iter.next() else {
-                break;
-            };
-            last_id = Some(post.id.clone());
-            let rate_limiter = Arc::clone(&self.rate_limiter);
-            let taxonomy = Arc::clone(&taxonomy);
-            tasks.push(Box::pin(async move {
-                rate_limiter.acquire().await;
-                let result = self.process_post(&post, taxonomy.as_ref()).await;
-                (post.id, result)
-            }));
-        }
-
-        while let Some(result) = tasks.next().await {
-            results.push(result);
-            while tasks.len() < max_concurrent {
+        loop {
+            while !deferred && !self.is_shutting_down() && tasks.len() < max_concurrent {
                 let Some(post) = posts_iter.next() else {
                     break;
                 };
-                last_id = Some(post.id.clone());
+                dispatched.push(post.id.clone());
+                if let Some(result) = retries.get(&post.id).and_then(|r| waiting_retry(r, now)) {
+                    self.metrics.post_processed(account, &result);
+                    results.push((post.id, result));
+                    continue;
+                }
                 let rate_limiter = Arc::clone(&self.rate_limiter);
                 let taxonomy = Arc::clone(&taxonomy);
-                tasks.push(Box::pin(async move {
-                    rate_limiter.acquire().await;
-                    let result = self.process_post(&post, taxonomy.as_ref()).await;
-                    (post.id, result)
-                }));
+                let span = tracing::info_span!(
+                    "process_post",
+                    post_id = %post.id,
+                    account = %account,
+                    provider = self.classifier.provider(),
+                    tag_ids = field::Empty,
+                    outcome = field::Empty
+                );
+                span.follows_from(&fetch_span);
+                tasks.push(Box::pin(
+                    async move {
+                        let waited = rate_limiter.acquire().await;
+                        self.metrics.rate_limit_wait(waited);
+                        let result = self.process_post(&post, taxonomy.as_ref()).await;
+                        tracing::Span::current().record("outcome", result.outcome());
+                        (post.id, result)
+                    }
+                    .instrument(span),
+                ));
             }
+
+            let next = tokio::select! {
+                result = tasks.next() => result,
+                _ = &mut drain_deadline => {
+                    tracing::warn!(
+                        account = %account,
+                        abandoned = tasks.len(),
+                        "Shutdown deadline passed, abandoning in-flight posts"
+                    );
+                    None
+                }
+            };
+            let Some(result) = next else {
+                break;
+            };
+            let (post_id, mut result) = result;
+            deferred |= matches!(result, ProcessResult::Deferred { .. });
+            self.track_retry(account, &post_id, &mut result, &mut retries)
+                .await;
+            self.metrics.post_processed(account, &result);
+            results.push((post_id, result));
         }
 
-        // Update since_id
+        // Advance since_id only over posts that succeeded or were given up,
+        // up to the first one failed, left for a later cycle or abandoned at
+        // shutdown
+        let given_up: HashSet<&str> = retries
+            .values()
+            .filter(|r| r.status == RetryStatus::Failed)
+            .map(|r| r.source_post_id.as_str())
+            .collect();
+        let first_unfinished = dispatched.iter().position(|id| {
+            !results.iter().any(|(rid, r)| {
+                rid == id
+                    && match r {
+                        ProcessResult::Published { .. } | ProcessResult::Skipped { .. } => true,
+                        ProcessResult::Failed { .. } => given_up.contains(id.as_str()),
+                        ProcessResult::Deferred { .. } => false,
+                    }
+            })
+        });
+        let last_id = match first_unfinished {
+            Some(index) => index.checked_sub(1).map(|i| dispatched[i].clone()),
+            None => dispatched.last().cloned(),
+        };
+
         if let Some(last_id) = last_id {
             let new_state = AccountState {
                 account: account.to_string(),
@@ -252,20 +504,92 @@ where
         Ok(results)
     }
 
-    /// Filter posts based on config
-    fn filter_posts(&self, posts: Vec<SourcePost>) -> Vec<SourcePost> {
+    /// Count a failed post against its retry, or clear the retry of a post
+    /// that went through
+    async fn track_retry(
+        &self,
+        account: &str,
+        post_id: &str,
+        result: &mut ProcessResult,
+        retries: &mut HashMap<String, PostRetry>,
+    ) {
+        match result {
+            ProcessResult::Failed { error, retry } => {
+                let now = self.clock.now();
+                let attempts = retries.get(post_id).map_or(0, |r| r.attempts) + 1;
+                let failed = PostRetry {
+                    source_post_id: post_id.to_string(),
+                    account: account.to_string(),
+                    attempts,
+                    status: if attempts >= self.config.retry_max_attempts {
+                        RetryStatus::Failed
+                    } else {
+                        RetryStatus::Pending
+                    },
+                    last_error: error.clone(),
+                    next_attempt_at: now + retry_backoff(self.config.retry_base_delay, attempts),
+                    updated_at: now,
+                };
+                if failed.status == RetryStatus::Failed {
+                    tracing::error!(
+                        post_id,
+                        attempts,
+                        error = %failed.last_error,
+                        "Giving up on post"
+                    );
+                } else {
+                    tracing::warn!(
+                        post_id,
+                        attempts,
+                        next_attempt_at = %failed.next_attempt_at,
+                        error = %failed.last_error,
+                        "Failed to process post, will retry"
+                    );
+                }
+                if let Err(e) = self.state_store.set_post_retry(&failed).await {
+                    tracing::error!(post_id, error = %e, "Failed to record post retry");
+                }
+                *retry = Some(failed.clone());
+                retries.insert(post_id.to_string(), failed);
+            }
+            ProcessResult::Published { .. } | ProcessResult::Skipped { .. } => {
+                if retries.remove(post_id).is_some() {
+                    if let Err(e) = self.state_store.delete_post_retry(post_id).await {
+                        tracing::warn!(post_id, error = %e, "Failed to remove post retry");
+                    }
+                }
+            }
+            ProcessResult::Deferred { .. } => {}
+        }
+    }
+
+    /// Filter posts based on config and the account's overrides
+    fn filter_posts(&self, account: &str, posts: Vec<SourcePost>) -> Vec<SourcePost> {
+        let schedule = self.config.schedules.get(account);
+        let include_replies = schedule
+            .and_then(|s| s.include_replies)
+            .unwrap_or(self.config.include_replies);
+        let include_reposts = schedule
+            .and_then(|s| s.include_reposts)
+            .unwrap_or(self.config.include_reposts);
+        let account_patterns = self
+            .account_ignore_patterns
+            .get(account)
+            .map_or(&[][..], Vec::as_slice);
+
         posts
             .into_iter()
             .filter(|p| {
-                if !self.config.include_replies && p.is_reply {
+                if !include_replies && p.is_reply {
                     return false;
                 }
-                if !self.config.include_reposts && p.is_repost {
+                if !include_reposts && p.is_repost {
                     return false;
                 }
                 if self
                     .ignore_patterns
                     .iter()
+                    .chain(account_patterns)
                     .any(|pattern| pattern.is_match(&p.text))
                 {
                     return false;
@@ -275,49 +599,189 @@ where
             .collect()
     }
 
+    /// Whether `account` is due in the cycle started at `now`; a due
+    /// account is recorded as polled
+    async fn take_due(&self, account: &str, now: OffsetDateTime) -> bool {
+        let schedule = self.config.schedules.get(account);
+        if !schedule.is_none_or(|s| s.is_active(now)) {
+            tracing::debug!(account = %account, "Outside active hours");
+            return false;
+        }
+
+        let interval = schedule
+            .and_then(|s| s.poll_interval)
+            .or(self.config.poll_interval);
+        let mut last_polled = self.last_polled.lock().await;
+        if let (Some(interval), Some(polled_at)) = (interval, last_polled.get(account)) {
+            if now + SCHEDULE_SLACK < *polled_at + interval {
+                tracing::debug!(account = %account, "Not due yet");
+                return false;
+            }
+        }
+        last_polled.insert(account.to_string(), now);
+        true
+    }
+
     /// Process a single post
+    ///
+    /// Outside dry runs the post is claimed first, so workers sharing the
+    /// state store never classify or publish it twice. A post claimed by
+    /// another worker is deferred until that claim is released or expires.
     async fn process_post(&self, post: &SourcePost, taxonomy: &Taxonomy) -> ProcessResult {
-        // Check idempotency
+        let (record, pending) = self.publish_state(post, taxonomy).await;
+        if record.is_some() && pending.is_empty() {
+            return ProcessResult::Skipped {
+                reason: "Already processed with this taxonomy".to_string(),
+            };
+        }
+
+        if let Some(reason) = self.blocking_deferral().await {
+            return ProcessResult::Deferred { reason };
+        }
+
+        if self.config.dry_run {
+            return self
+                .classify_and_publish(post, taxonomy, record, pending)
+                .await;
+        }
+
+        let worker_id = &self.config.worker_id;
         match self
             .state_store
-            .is_processed(&post.id, &taxonomy.hash)
+            .claim_post(&post.id, &taxonomy.hash, worker_id, self.config.claim_lease)
             .await
         {
-            Ok(true) => {
-                return ProcessResult::Skipped {
-                    reason: "Already processed with this taxonomy".to_string(),
+            Ok(true) => {}
+            Ok(false) => {
+                return ProcessResult::Deferred {
+                    reason: "Claimed by another worker".to_string(),
                 };
             }
+            Err(e) => {
+                tracing::warn!(post_id = %post.id, error = %e, "Failed to claim post, continuing");
+            }
+        }
+
+        // Another worker may have finished the post before the claim
+        let (record, pending) = self.publish_state(post, taxonomy).await;
+        let result = if record.is_some() && pending.is_empty() {
+            ProcessResult::Skipped {
+                reason: "Already processed with this taxonomy".to_string(),
+            }
+        } else {
+            self.classify_and_publish(post, taxonomy, record, pending)
+                .await
+        };
+
+        if let Err(e) = self
+            .state_store
+            .release_post(&post.id, &taxonomy.hash, worker_id)
+            .await
+        {
+            tracing::warn!(post_id = %post.id, error = %e, "Failed to release post claim");
+        }
+
+        result
+    }
+
+    /// The post's published record, if any, and the enabled publishers it
+    /// has not gone out to yet
+    ///
+    /// Platforms with a queued retry are left to [`Self::process_retries`],
+    /// which keeps to their backoff and attempt limit.
+    async fn publish_state(
+        &self,
+        post: &SourcePost,
+        taxonomy: &Taxonomy,
+    ) -> (Option<PublishedRecord>, Vec<&Arc<dyn Publisher>>) {
+        // Check idempotency, per platform
+        let record = match self
+            .state_store
+            .get_published(&post.id, &taxonomy.hash)
+            .await
+        {
+            Ok(record) => record,
             Err(e) => {
                 tracing::warn!(error = %e, "Failed to check processed state, continuing");
+                None
+            }
+        };
+
+        let mut done_platforms = Vec::new();
+        if let Some(record) = &record {
+            match self.state_store.list_targets(record.id).await {
+                Ok(targets) => done_platforms.extend(targets.into_iter().map(|t| t.platform)),
+                Err(e) => {
+                    tracing::warn!(error = %e, "Failed to load published targets, continuing");
+                }
+            }
+            match self.state_store.list_retries_for_record(record.id).await {
+                Ok(retries) => done_platforms.extend(retries.into_iter().map(|r| r.platform)),
+                Err(e) => {
+                    tracing::warn!(error = %e, "Failed to load publish retries, continuing");
+                }
             }
-            Ok(false) => {}
         }
 
+        let pending: Vec<&Arc<dyn Publisher>> = self
+            .publishers
+            .iter()
+            .filter(|p| p.is_enabled() && !done_platforms.iter().any(|d| d == p.platform()))
+            .collect();
+
+        (record, pending)
+    }
+
+    /// Classify a post and publish it to the `pending` publishers
+    async fn classify_and_publish(
+        &self,
+        post: &SourcePost,
+        taxonomy: &Taxonomy,
+        record: Option<PublishedRecord>,
+        pending: Vec<&Arc<dyn Publisher>>,
+    ) -> ProcessResult {
         // Classify
         let classify_usecase = ClassifyUseCase::new(
             self.classifier.as_ref(),
             self.config.classify_config.clone(),
         );
 
-        let classification = match classify_usecase.classify(post, &taxonomy.definitions).await {
+        let started = Instant::now();
+        let classified = classify_usecase.classify(post, &taxonomy.definitions).await;
+        self.metrics.classification(
+            self.classifier.provider(),
+            started.elapsed(),
+            classified.as_ref().ok().and_then(|c| c.usage),
+        );
+
+        let classification = match classified {
             Ok(c) => c,
+            Err(ClassifyError::RateLimited(wait)) => {
+                let until = self.defer(CLASSIFIER_SCOPE, wait).await;
+                return ProcessResult::Deferred {
+                    reason: format!("Classifier rate limited until {}", until),
+                };
+            }
             Err(e) => {
                 return ProcessResult::Failed {
                     error: format!("Classification failed: {}", e),
+                    retry: None,
                 };
             }
         };
 
-        tracing::info!(
-            post_id = %post.id,
-            tags = ?classification.tags.iter().map(|t| &t.id).collect::<Vec<_>>(),
-            "Classified post"
-        );
+        let tag_ids = classification
+            .tags
+            .iter()
+            .map(|t| t.id.as_str())
+            .collect::<Vec<_>>();
+        tracing::Span::current().record("tag_ids", field::debug(&tag_ids));
+        tracing::info!(post_id = %post.id, tags = ?tag_ids, "Classified post");
 
         if self.config.dry_run {
             let renderer = Renderer::new(self.config.render_config.clone());
-            let rendered = renderer.render_for_x(post, &classification);
+            let rendered = tracing::info_span!("render", platform = "x")
+                .in_scope(|| renderer.render_for_x(post, &classification));
             tracing::info!(
                 post_id = %post.id,
                 rendered_text = %rendered.text,
@@ -326,65 +790,367 @@ where
             return ProcessResult::Published {
                 source_post: Box::new(post.clone()),
                 classification,
-                x_post_id: None,
-                nostr_event_id: None,
+                targets: vec![],
             };
         }
 
-        // Publish
-        let renderer = Renderer::new(self.config.render_config.clone());
-        let mut x_post_id = None;
-        let mut nostr_event_id = None;
-
-        // Publish to X
-        if self.x_publisher.is_enabled() {
-            let rendered = renderer.render_for_x(post, &classification);
-            match self.x_publisher.publish(&rendered).await {
-                Ok(result) => {
-                    x_post_id = Some(result.id);
-                }
+        // The record comes first so each platform can be tracked against it
+        let record = match record {
+            Some(record) => record,
+            None => match self.insert_record(post, taxonomy).await {
+                Ok(record) => record,
                 Err(e) => {
-                    tracing::error!(error = %e, "Failed to publish to X");
+                    tracing::error!(post_id = %post.id, error = %e, "Failed to record published state");
+                    return ProcessResult::Failed {
+                        error: format!("Failed to record published state: {}", e),
+                        retry: None,
+                    };
                 }
-            }
+            },
+        };
+
+        let stored = ClassificationRecord {
+            record_id: record.id,
+            source_post: post.clone(),
+            classification: classification.clone(),
+            taxonomy_hash: taxonomy.hash.clone(),
+            classified_at: self.clock.now(),
+        };
+        if let Err(e) = self.state_store.record_classification(&stored).await {
+            tracing::warn!(error = %e, "Failed to store classification");
         }
 
-        // Publish to Nostr
-        if self.nostr_publisher.is_enabled() {
-            let rendered = renderer.render_for_nostr(post, &classification);
-            match self.nostr_publisher.publish(&rendered).await {
-                Ok(result) => {
-                    nostr_event_id = Some(result.id);
-                }
-                Err(e) => {
-                    tracing::error!(error = %e, "Failed to publish to Nostr");
+        // Publish
+        let analysis = Arc::new(PostAnalysis {
+            source_post: post.clone(),
+            classification: classification.clone(),
+            taxonomy_hash: taxonomy.hash.clone(),
+        });
+        let mut targets = Vec::new();
+
+        for publisher in pending {
+            let platform = publisher.platform();
+            // Fresh: platforms with a queued retry are never pending
+            let retry = PublishRetry {
+                record_id: record.id,
+                platform: platform.to_string(),
+                source_post: post.clone(),
+                classification: classification.clone(),
+                taxonomy_hash: taxonomy.hash.clone(),
+                attempts: 0,
+                status: RetryStatus::Pending,
+                last_error: String::new(),
+                next_attempt_at: self.clock.now(),
+                updated_at: self.clock.now(),
+            };
+
+            // A rate-limited platform waits in the retry queue without
+            // holding back the others
+            if let Some(until) = self.deferred_until(&publisher_scope(platform)).await {
+                let retry = PublishRetry {
+                    last_error: format!("Rate limited until {}", until),
+                    next_attempt_at: until,
+                    ..retry
+                };
+                tracing::info!(post_id = %post.id, platform, until = %until, "Publisher rate limited, queued for retry");
+                if let Err(e) = self.state_store.set_retry(&retry).await {
+                    tracing::error!(platform, error = %e, "Failed to queue publish retry");
                 }
+                continue;
             }
+
+            match self
+                .publish_to(publisher.as_ref(), record.id, &analysis)
+                .await
+            {
+                Ok(target) => targets.push(target),
+                Err(e) => self.failed_attempt(retry, e).await,
+            }
+        }
+
+        ProcessResult::Published {
+            source_post: Box::new(post.clone()),
+            classification,
+            targets,
         }
+    }
 
-        // Record published state
+    /// Insert the post's published record and return the stored one
+    ///
+    /// The insert keeps an existing record for the post and taxonomy, such
+    /// as one another worker inserted first, so the stored ID is read back.
+    async fn insert_record(
+        &self,
+        post: &SourcePost,
+        taxonomy: &Taxonomy,
+    ) -> Result<PublishedRecord, StateError> {
         let record = PublishedRecord {
             id: Uuid::new_v4(),
             source_post_id: post.id.clone(),
             taxonomy_hash: taxonomy.hash.clone(),
-            x_post_id: x_post_id.clone(),
-            nostr_event_id: nostr_event_id.clone(),
             published_at: self.clock.now(),
         };
+        self.state_store.record_published(&record).await?;
+        self.state_store
+            .get_published(&post.id, &taxonomy.hash)
+            .await?
+            .ok_or_else(|| {
+                StateError::Database("Published record missing after insert".to_string())
+            })
+    }
+
+    /// Render the analysed post for `publisher`, publish it and record the target
+    async fn publish_to(
+        &self,
+        publisher: &dyn Publisher,
+        record_id: Uuid,
+        analysis: &Arc<PostAnalysis>,
+    ) -> Result<PublishedTarget, PublishError> {
+        let platform = publisher.platform();
+        let renderer = Renderer::new(self.config.render_config.clone());
+        let rendered = RenderedPost {
+            analysis: Some(analysis.clone()),
+            ..tracing::info_span!("render", platform).in_scope(|| {
+                renderer.render_for_platform(
+                    platform,
+                    &analysis.source_post,
+                    &analysis.classification,
+                )
+            })
+        };
+
+        let span = tracing::info_span!("publish", platform, target_id = field::Empty);
+        let published = publisher.publish(&rendered).instrument(span.clone()).await;
+        if let Ok(result) = &published {
+            span.record("target_id", result.id.as_str());
+        }
+        self.metrics.publish(platform, published.is_ok());
+        let result = published?;
+        tracing::info!(platform, id = %result.id, url = ?result.url, "Published");
+        let target = PublishedTarget {
+            record_id,
+            platform: platform.to_string(),
+            target_id: result.id,
+            url: result.url,
+            published_at: self.clock.now(),
+        };
+        if let Err(e) = self.state_store.record_target(&target).await {
+            tracing::error!(platform, error = %e, "Failed to record published target");
+        }
+        Ok(target)
+    }
+
+    /// Attempt every queued publish whose backoff has elapsed
+    async fn process_retries(&self) {
+        let retries = match self.state_store.list_retries().await {
+            Ok(retries) => retries,
+            Err(e) => {
+                tracing::warn!(error = %e, "Failed to load publish retry queue");
+                return;
+            }
+        };
 
-        if let Err(e) = self.state_store.record_published(&record).await {
-            tracing::error!(error = %e, "Failed to record published state");
+        let now = self.clock.now();
+        for retry in retries {
+            if self.is_shutting_down() {
+                break;
+            }
+            if retry.status != RetryStatus::Pending || retry.next_attempt_at > now {
+                continue;
+            }
+            let Some(publisher) = self
+                .publishers
+                .iter()
+                .find(|p| p.is_enabled() && p.platform() == retry.platform)
+            else {
+                continue;
+            };
+            if self
+                .deferred_until(&publisher_scope(&retry.platform))
+                .await
+                .is_some()
+            {
+                continue;
+            }
+
+            let analysis = Arc::new(PostAnalysis {
+                source_post: retry.source_post.clone(),
+                classification: retry.classification.clone(),
+                taxonomy_hash: retry.taxonomy_hash.clone(),
+            });
+            match self
+                .publish_to(publisher.as_ref(), retry.record_id, &analysis)
+                .await
+            {
+                Ok(_) => self.clear_retry(retry.record_id, &retry.platform).await,
+                Err(e) => self.failed_attempt(retry, e).await,
+            }
         }
+    }
 
-        ProcessResult::Published {
-            source_post: Box::new(post.clone()),
-            classification,
-            x_post_id,
-            nostr_event_id,
+    /// Report the size of the retry queue to the metrics
+    async fn report_retry_queue(&self) {
+        match self.state_store.list_retries().await {
+            Ok(retries) => {
+                let pending = retries
+                    .iter()
+                    .filter(|r| r.status == RetryStatus::Pending)
+                    .count();
+                self.metrics.retry_queue(pending, retries.len() - pending);
+            }
+            Err(e) => tracing::warn!(error = %e, "Failed to count publish retries"),
+        }
+    }
+
+    /// Count a failed publish against `retry` and schedule the next attempt,
+    /// or give up once the configured number of attempts is reached
+    async fn failed_attempt(&self, mut retry: PublishRetry, error: PublishError) {
+        let now = self.clock.now();
+        retry.attempts += 1;
+        retry.last_error = error.to_string();
+        retry.updated_at = now;
+        retry.next_attempt_at = now + retry_backoff(self.config.retry_base_delay, retry.attempts);
+
+        if let PublishError::RateLimited(wait) = error {
+            let until = self.defer(&publisher_scope(&retry.platform), wait).await;
+            retry.next_attempt_at = retry.next_attempt_at.max(until);
+        }
+
+        if retry.attempts >= self.config.retry_max_attempts {
+            retry.status = RetryStatus::Failed;
+            tracing::error!(
+                post_id = %retry.source_post.id,
+                platform = %retry.platform,
+                attempts = retry.attempts,
+                error = %retry.last_error,
+                "Giving up on publish"
+            );
+        } else {
+            tracing::warn!(
+                post_id = %retry.source_post.id,
+                platform = %retry.platform,
+                attempts = retry.attempts,
+                next_attempt_at = %retry.next_attempt_at,
+                error = %retry.last_error,
+                "Failed to publish, queued for retry"
+            );
+        }
+
+        if let Err(e) = self.state_store.set_retry(&retry).await {
+            tracing::error!(platform = %retry.platform, error = %e, "Failed to queue publish retry");
+        }
+    }
+
+    async fn clear_retry(&self, record_id: Uuid, platform: &str) {
+        if let Err(e) = self.state_store.delete_retry(record_id, platform).await {
+            tracing::warn!(platform, error = %e, "Failed to remove publish retry");
+        }
+    }
+
+    /// Deferral state, loaded from the state store on first use
+    async fn deferrals(&self) -> MutexGuard<'_, Deferrals> {
+        let mut deferrals = self.deferrals.lock().await;
+        if !deferrals.loaded {
+            match self.state_store.list_deferrals().await {
+                Ok(stored) => {
+                    for deferral in stored {
+                        deferrals.until.insert(deferral.scope, deferral.until);
+                    }
+                }
+                Err(e) => {
+                    tracing::warn!(error = %e, "Failed to load rate-limit deferrals");
+                }
+            }
+            deferrals.loaded = true;
+        }
+        deferrals
+    }
+
+    /// When `scope` may be called again, if it is currently deferred
+    async fn deferred_until(&self, scope: &str) -> Option<OffsetDateTime> {
+        let now = self.clock.now();
+        self.deferrals()
+            .await
+            .until
+            .get(scope)
+            .copied()
+            .filter(|until| *until > now)
+    }
+
+    /// Defer `scope` for `wait` (or the configured backoff) and persist it
+    async fn defer(&self, scope: &str, wait: Option<Duration>) -> OffsetDateTime {
+        let requested = self.clock.now() + wait.unwrap_or(self.config.rate_limit_backoff);
+        let until = {
+            let mut deferrals = self.deferrals().await;
+            let until = deferrals
+                .until
+                .get(scope)
+                .map_or(requested, |existing| (*existing).max(requested));
+            deferrals.until.insert(scope.to_string(), until);
+            until
+        };
+
+        let deferral = RateLimitDeferral {
+            scope: scope.to_string(),
+            until,
+        };
+        if let Err(e) = self.state_store.set_deferral(&deferral).await {
+            tracing::warn!(scope = %scope, error = %e, "Failed to persist rate-limit deferral");
         }
+
+        until
+    }
+
+    /// Reason posts cannot be processed right now: the classifier is
+    /// waiting out a rate limit
+    ///
+    /// A rate-limited publisher only holds back its own platform, whose
+    /// publishes are queued for retry.
+    async fn blocking_deferral(&self) -> Option<String> {
+        self.deferred_until(CLASSIFIER_SCOPE)
+            .await
+            .map(|until| format!("Classifier rate limited until {}", until))
+    }
+}
+
+/// The result of a previously failed post that is not due for another
+/// attempt, if any
+fn waiting_retry(retry: &PostRetry, now: OffsetDateTime) -> Option<ProcessResult> {
+    match retry.status {
+        RetryStatus::Failed => Some(ProcessResult::Skipped {
+            reason: format!("Gave up after {} failed attempts", retry.attempts),
+        }),
+        RetryStatus::Pending if retry.next_attempt_at > now => Some(ProcessResult::Deferred {
+            reason: format!("Retrying after {}", retry.next_attempt_at),
+        }),
+        RetryStatus::Pending => None,
     }
 }
 
+/// Delay before the attempt following `attempts` failures: the base delay
+/// doubled per failure, capped at [`MAX_RETRY_DELAY`]
+fn retry_backoff(base: Duration, attempts: u32) -> Duration {
+    let factor = 2u32.saturating_pow(attempts.saturating_sub(1));
+    base.saturating_mul(factor).min(MAX_RETRY_DELAY)
+}
+
+/// Deferral scope for a watched account
+fn account_scope(account: &str) -> String {
+    format!("account:{}", account.to_ascii_lowercase())
+}
+
+/// Deferral scope for a publishing platform
+fn publisher_scope(platform: &str) -> String {
+    format!("publisher:{}", platform)
+}
+
+/// Rate-limit deferrals keyed by scope, mirrored from the state store
+#[derive(Debug, Default)]
+struct Deferrals {
+    loaded: bool,
+    until: HashMap<String, OffsetDateTime>,
+}
+
 /// Errors from the run loop
 #[derive(Debug, thiserror::Error)]
 pub enum RunLoopError {
@@ -426,11 +1192,13 @@ impl RateLimiter {
         }
     }
 
-    async fn acquire(&self) {
+    /// Wait until a post may be processed; returns how long that took
+    async fn acquire(&self) -> Duration {
         if self.per_minute.is_none() && self.per_hour.is_none() {
-            return;
+            return Duration::ZERO;
         }
 
+        let started = Instant::now();
         loop {
             let mut state = self.state.lock().await;
             let now = Instant::now();
@@ -467,7 +1235,7 @@ impl RateLimiter {
                 if self.per_hour.is_some() {
                     state.hour_count = state.hour_count.saturating_add(1);
                 }
-                return;
+                return started.elapsed();
             }
 
             drop(state);
@@ -500,18 +1268,24 @@ impl<C: Classifier + ?Sized> Classifier for &C {
     ) -> Result<crate::model::ClassifyOutput, ClassifyError> {
         (*self).classify(input).await
     }
+
+    fn provider(&self) -> &str {
+        (*self).provider()
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::model::{ClassifyInput, ClassifyOutput, RenderedPost, TagDefinition, TagMatch};
+    use crate::model::{
+        ActiveHours, ClassifyInput, ClassifyOutput, FeedCursor, RelayStatus, RenderedPost,
+        ResolvedAccount, TagDefinition, TagMatch, TokenUsage,
+    };
     use crate::ports::{
         DefinitionsError, PostSourceError, PublishError, PublishResult, StateError,
     };
-    use std::collections::HashMap;
     use std::sync::Mutex;
-    use time::OffsetDateTime;
+    use std::sync::atomic::{AtomicUsize, Ordering};
 
     // Fake implementations for testing
     struct FakePostSource {
@@ -584,27 +1358,115 @@ mod tests {
         }
     }
 
-    struct FakeStateStore {
-        accounts: Mutex<HashMap<String, AccountState>>,
-        processed: Mutex<HashMap<String, bool>>,
+    struct FlakyPublisher {
+        platform: &'static str,
+        failures_left: AtomicUsize,
+        calls: AtomicUsize,
     }
 
-    impl FakeStateStore {
-        fn new() -> Self {
+    impl FlakyPublisher {
+        fn new(platform: &'static str, failures: usize) -> Self {
             Self {
-                accounts: Mutex::new(HashMap::new()),
-                processed: Mutex::new(HashMap::new()),
+                platform,
+                failures_left: AtomicUsize::new(failures),
+                calls: AtomicUsize::new(0),
             }
         }
     }
 
     #[async_trait]
-    impl StateStore for FakeStateStore {
-        async fn get_account_state(
-            &self,
-            account: &str,
-        ) -> Result<Option<AccountState>, StateError> {
-            Ok(self.accounts.lock().unwrap().get(account).cloned())
+    impl Publisher for FlakyPublisher {
+        async fn publish(&self, _post: &RenderedPost) -> Result<PublishResult, PublishError> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            let failing = self
+                .failures_left
+                .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1))
+                .is_ok();
+            if failing {
+                return Err(PublishError::Api("relay down".to_string()));
+            }
+            Ok(PublishResult {
+                id: format!("{}_id", self.platform),
+                url: None,
+            })
+        }
+
+        fn is_enabled(&self) -> bool {
+            true
+        }
+
+        fn platform(&self) -> &'static str {
+            self.platform
+        }
+    }
+
+    struct RateLimitedPostSource {
+        calls: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl PostSource for RateLimitedPostSource {
+        async fn fetch_posts(
+            &self,
+            _account: &str,
+            _since_id: Option<&str>,
+        ) -> Result<Vec<SourcePost>, PostSourceError> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            Err(PostSourceError::RateLimited(Some(Duration::from_secs(600))))
+        }
+    }
+
+    struct RateLimitedClassifier {
+        calls: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl Classifier for RateLimitedClassifier {
+        async fn classify(&self, _input: ClassifyInput) -> Result<ClassifyOutput, ClassifyError> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            Err(ClassifyError::RateLimited(None))
+        }
+    }
+
+    struct FakeStateStore {
+        accounts: Mutex<HashMap<String, AccountState>>,
+        processed: Mutex<HashMap<String, PublishedRecord>>,
+        targets: Mutex<Vec<PublishedTarget>>,
+        retries: Mutex<Vec<PublishRetry>>,
+        post_retries: Mutex<Vec<PostRetry>>,
+        classifications: Mutex<Vec<ClassificationRecord>>,
+        deferrals: Mutex<HashMap<String, RateLimitDeferral>>,
+        /// Holder of each post claim and account lease; leases never expire
+        claims: Mutex<HashMap<String, String>>,
+    }
+
+    impl FakeStateStore {
+        fn new() -> Self {
+            Self {
+                accounts: Mutex::new(HashMap::new()),
+                processed: Mutex::new(HashMap::new()),
+                targets: Mutex::new(Vec::new()),
+                retries: Mutex::new(Vec::new()),
+                post_retries: Mutex::new(Vec::new()),
+                classifications: Mutex::new(Vec::new()),
+                deferrals: Mutex::new(HashMap::new()),
+                claims: Mutex::new(HashMap::new()),
+            }
+        }
+
+        fn claim(&self, key: String, worker_id: &str) -> bool {
+            let mut claims = self.claims.lock().unwrap();
+            claims.entry(key).or_insert_with(|| worker_id.to_string()) == worker_id
+        }
+    }
+
+    #[async_trait]
+    impl StateStore for FakeStateStore {
+        async fn get_account_state(
+            &self,
+            account: &str,
+        ) -> Result<Option<AccountState>, StateError> {
+            Ok(self.accounts.lock().unwrap().get(account).cloned())
         }
 
         async fn set_account_state(&self, state: &AccountState) -> Result<(), StateError> {
@@ -615,28 +1477,211 @@ mod tests {
             Ok(())
         }
 
+        async fn list_account_states(&self) -> Result<Vec<AccountState>, StateError> {
+            Ok(self.accounts.lock().unwrap().values().cloned().collect())
+        }
+
         async fn is_processed(
             &self,
             source_post_id: &str,
             taxonomy_hash: &str,
         ) -> Result<bool, StateError> {
             let key = format!("{}:{}", source_post_id, taxonomy_hash);
-            Ok(*self.processed.lock().unwrap().get(&key).unwrap_or(&false))
+            Ok(self.processed.lock().unwrap().contains_key(&key))
         }
 
         async fn record_published(&self, record: &PublishedRecord) -> Result<(), StateError> {
             let key = format!("{}:{}", record.source_post_id, record.taxonomy_hash);
-            self.processed.lock().unwrap().insert(key, true);
+            self.processed
+                .lock()
+                .unwrap()
+                .entry(key)
+                .or_insert_with(|| record.clone());
             Ok(())
         }
 
         async fn get_published(
             &self,
-            _source_post_id: &str,
-            _taxonomy_hash: &str,
+            source_post_id: &str,
+            taxonomy_hash: &str,
         ) -> Result<Option<PublishedRecord>, StateError> {
+            let key = format!("{}:{}", source_post_id, taxonomy_hash);
+            Ok(self.processed.lock().unwrap().get(&key).cloned())
+        }
+
+        async fn list_published(&self) -> Result<Vec<PublishedRecord>, StateError> {
+            Ok(self.processed.lock().unwrap().values().cloned().collect())
+        }
+
+        async fn record_target(&self, target: &PublishedTarget) -> Result<(), StateError> {
+            self.targets.lock().unwrap().push(target.clone());
+            Ok(())
+        }
+
+        async fn list_targets(&self, record_id: Uuid) -> Result<Vec<PublishedTarget>, StateError> {
+            Ok(self
+                .targets
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|t| t.record_id == record_id)
+                .cloned()
+                .collect())
+        }
+
+        async fn record_classification(
+            &self,
+            record: &ClassificationRecord,
+        ) -> Result<(), StateError> {
+            self.classifications.lock().unwrap().push(record.clone());
+            Ok(())
+        }
+
+        async fn list_classifications(&self) -> Result<Vec<ClassificationRecord>, StateError> {
+            Ok(self.classifications.lock().unwrap().clone())
+        }
+
+        async fn set_retry(&self, retry: &PublishRetry) -> Result<(), StateError> {
+            let mut retries = self.retries.lock().unwrap();
+            retries.retain(|r| !(r.record_id == retry.record_id && r.platform == retry.platform));
+            retries.push(retry.clone());
+            Ok(())
+        }
+
+        async fn list_retries(&self) -> Result<Vec<PublishRetry>, StateError> {
+            Ok(self.retries.lock().unwrap().clone())
+        }
+
+        async fn list_retries_for_record(
+            &self,
+            record_id: Uuid,
+        ) -> Result<Vec<PublishRetry>, StateError> {
+            Ok(self
+                .retries
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|r| r.record_id == record_id)
+                .cloned()
+                .collect())
+        }
+
+        async fn delete_retry(&self, record_id: Uuid, platform: &str) -> Result<(), StateError> {
+            self.retries
+                .lock()
+                .unwrap()
+                .retain(|r| !(r.record_id == record_id && r.platform == platform));
+            Ok(())
+        }
+
+        async fn set_post_retry(&self, retry: &PostRetry) -> Result<(), StateError> {
+            let mut retries = self.post_retries.lock().unwrap();
+            retries.retain(|r| r.source_post_id != retry.source_post_id);
+            retries.push(retry.clone());
+            Ok(())
+        }
+
+        async fn list_post_retries(&self, account: &str) -> Result<Vec<PostRetry>, StateError> {
+            Ok(self
+                .post_retries
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|r| r.account == account)
+                .cloned()
+                .collect())
+        }
+
+        async fn delete_post_retry(&self, source_post_id: &str) -> Result<(), StateError> {
+            self.post_retries
+                .lock()
+                .unwrap()
+                .retain(|r| r.source_post_id != source_post_id);
+            Ok(())
+        }
+
+        async fn get_resolved_account(
+            &self,
+            _account: &str,
+        ) -> Result<Option<ResolvedAccount>, StateError> {
+            Ok(None)
+        }
+
+        async fn set_resolved_account(
+            &self,
+            _resolved: &ResolvedAccount,
+        ) -> Result<(), StateError> {
+            Ok(())
+        }
+
+        async fn list_resolved_accounts(&self) -> Result<Vec<ResolvedAccount>, StateError> {
+            Ok(vec![])
+        }
+
+        async fn list_deferrals(&self) -> Result<Vec<RateLimitDeferral>, StateError> {
+            Ok(self.deferrals.lock().unwrap().values().cloned().collect())
+        }
+
+        async fn set_deferral(&self, deferral: &RateLimitDeferral) -> Result<(), StateError> {
+            self.deferrals
+                .lock()
+                .unwrap()
+                .insert(deferral.scope.clone(), deferral.clone());
+            Ok(())
+        }
+
+        async fn get_feed_cursor(&self, _feed: &str) -> Result<Option<FeedCursor>, StateError> {
+            Ok(None)
+        }
+
+        async fn set_feed_cursor(&self, _cursor: &FeedCursor) -> Result<(), StateError> {
+            Ok(())
+        }
+
+        async fn get_relay_status(&self, _relay: &str) -> Result<Option<RelayStatus>, StateError> {
             Ok(None)
         }
+
+        async fn set_relay_status(&self, _status: &RelayStatus) -> Result<(), StateError> {
+            Ok(())
+        }
+
+        async fn list_relay_statuses(&self) -> Result<Vec<RelayStatus>, StateError> {
+            Ok(vec![])
+        }
+
+        async fn claim_post(
+            &self,
+            source_post_id: &str,
+            taxonomy_hash: &str,
+            worker_id: &str,
+            _lease: Duration,
+        ) -> Result<bool, StateError> {
+            Ok(self.claim(format!("post:{source_post_id}:{taxonomy_hash}"), worker_id))
+        }
+
+        async fn release_post(
+            &self,
+            source_post_id: &str,
+            taxonomy_hash: &str,
+            worker_id: &str,
+        ) -> Result<(), StateError> {
+            let key = format!("post:{source_post_id}:{taxonomy_hash}");
+            let mut claims = self.claims.lock().unwrap();
+            if claims.get(&key).is_some_and(|holder| holder == worker_id) {
+                claims.remove(&key);
+            }
+            Ok(())
+        }
+
+        async fn claim_account(
+            &self,
+            account: &str,
+            worker_id: &str,
+            _lease: Duration,
+        ) -> Result<bool, StateError> {
+            Ok(self.claim(format!("account:{account}"), worker_id))
+        }
     }
 
     struct FakeClock {
@@ -661,6 +1706,7 @@ mod tests {
                 is_repost: false,
                 is_reply: false,
                 reply_to_id: None,
+                context: vec![],
             }],
         });
 
@@ -676,11 +1722,11 @@ mod tests {
         });
 
         let classifier = Arc::new(FakeClassifier);
-        let x_publisher = Arc::new(FakePublisher {
+        let x_publisher: Arc<dyn Publisher> = Arc::new(FakePublisher {
             enabled: false,
             platform: "x",
         });
-        let nostr_publisher = Arc::new(FakePublisher {
+        let nostr_publisher: Arc<dyn Publisher> = Arc::new(FakePublisher {
             enabled: false,
             platform: "nostr",
         });
@@ -699,8 +1745,7 @@ mod tests {
             post_source,
             definitions_repo,
             classifier,
-            x_publisher,
-            nostr_publisher,
+            vec![x_publisher, nostr_publisher],
             state_store,
             clock,
             config,
@@ -724,6 +1769,7 @@ mod tests {
                 is_repost: false,
                 is_reply: true,
                 reply_to_id: Some("original".to_string()),
+                context: vec![],
             }],
         });
 
@@ -731,11 +1777,11 @@ mod tests {
             definitions: vec![],
         });
         let classifier = Arc::new(FakeClassifier);
-        let x_publisher = Arc::new(FakePublisher {
+        let x_publisher: Arc<dyn Publisher> = Arc::new(FakePublisher {
             enabled: false,
             platform: "x",
         });
-        let nostr_publisher = Arc::new(FakePublisher {
+        let nostr_publisher: Arc<dyn Publisher> = Arc::new(FakePublisher {
             enabled: false,
             platform: "nostr",
         });
@@ -755,8 +1801,7 @@ mod tests {
             post_source,
             definitions_repo,
             classifier,
-            x_publisher,
-            nostr_publisher,
+            vec![x_publisher, nostr_publisher],
             state_store,
             clock,
             config,
@@ -780,6 +1825,7 @@ mod tests {
                 is_repost: false,
                 is_reply: false,
                 reply_to_id: None,
+                context: vec![],
             }],
         });
 
@@ -787,11 +1833,11 @@ mod tests {
             definitions: vec![],
         });
         let classifier = Arc::new(FakeClassifier);
-        let x_publisher = Arc::new(FakePublisher {
+        let x_publisher: Arc<dyn Publisher> = Arc::new(FakePublisher {
             enabled: false,
             platform: "x",
         });
-        let nostr_publisher = Arc::new(FakePublisher {
+        let nostr_publisher: Arc<dyn Publisher> = Arc::new(FakePublisher {
             enabled: false,
             platform: "nostr",
         });
@@ -811,8 +1857,7 @@ mod tests {
             post_source,
             definitions_repo,
             classifier,
-            x_publisher,
-            nostr_publisher,
+            vec![x_publisher, nostr_publisher],
             state_store,
             clock,
             config,
@@ -823,4 +1868,1057 @@ mod tests {
         // Post should be filtered out by ignore pattern
         assert_eq!(results.len(), 0);
     }
+
+    #[tokio::test]
+    async fn test_post_claimed_by_another_worker_is_deferred() {
+        let post_source = Arc::new(FakePostSource {
+            posts: vec![sample_post("1"), sample_post("2")],
+        });
+        let definitions_repo = Arc::new(FakeDefinitionsRepo {
+            definitions: vec![],
+        });
+        let taxonomy_hash = Taxonomy::new(vec![]).hash;
+        let x = Arc::new(FlakyPublisher::new("x", 0));
+        let state_store = Arc::new(FakeStateStore::new());
+        state_store
+            .claim_post("1", &taxonomy_hash, "other", Duration::from_secs(60))
+            .await
+            .unwrap();
+
+        let run_loop = RunLoop::new(
+            post_source,
+            definitions_repo,
+            Arc::new(FakeClassifier),
+            vec![x.clone()],
+            Arc::clone(&state_store),
+            Arc::new(FakeClock {
+                time: OffsetDateTime::now_utc(),
+            }),
+            RunLoopConfig {
+                accounts: vec!["testuser".to_string()],
+                dry_run: false,
+                max_concurrent: 1,
+                worker_id: "me".to_string(),
+                ..Default::default()
+            },
+        );
+
+        let results = run_loop.poll_once().await.unwrap();
+        assert_eq!(results.len(), 1);
+        assert!(matches!(results[0].1, ProcessResult::Deferred { .. }));
+        assert_eq!(x.calls.load(Ordering::SeqCst), 0);
+        assert!(
+            state_store
+                .get_account_state("testuser")
+                .await
+                .unwrap()
+                .is_none()
+        );
+
+        // Once the other worker lets go, the post is processed and released
+        state_store
+            .release_post("1", &taxonomy_hash, "other")
+            .await
+            .unwrap();
+        let results = run_loop.poll_once().await.unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(x.calls.load(Ordering::SeqCst), 2);
+        let claims = state_store.claims.lock().unwrap();
+        assert_eq!(claims.len(), 1);
+        assert_eq!(claims["account:testuser"], "me");
+    }
+
+    #[tokio::test]
+    async fn test_account_leased_by_another_worker_is_not_polled() {
+        let state_store = Arc::new(FakeStateStore::new());
+        state_store
+            .claim_account("testuser", "other", Duration::from_secs(60))
+            .await
+            .unwrap();
+
+        let run_loop = RunLoop::new(
+            Arc::new(FakePostSource {
+                posts: vec![sample_post("1")],
+            }),
+            Arc::new(FakeDefinitionsRepo {
+                definitions: vec![],
+            }),
+            Arc::new(FakeClassifier),
+            disabled_publishers(),
+            Arc::clone(&state_store),
+            Arc::new(FakeClock {
+                time: OffsetDateTime::now_utc(),
+            }),
+            RunLoopConfig {
+                accounts: vec!["testuser".to_string(), "otheruser".to_string()],
+                dry_run: false,
+                worker_id: "me".to_string(),
+                ..Default::default()
+            },
+        );
+
+        let results = run_loop.poll_once().await.unwrap();
+        assert_eq!(results.len(), 1);
+        assert!(
+            state_store
+                .get_account_state("testuser")
+                .await
+                .unwrap()
+                .is_none()
+        );
+        assert!(
+            state_store
+                .get_account_state("otheruser")
+                .await
+                .unwrap()
+                .is_some()
+        );
+    }
+
+    fn sample_post(id: &str) -> SourcePost {
+        SourcePost {
+            id: id.to_string(),
+            text: "Test post".to_string(),
+            author: "testuser".to_string(),
+            url: format!("https://x.com/testuser/status/{}", id),
+            created_at: OffsetDateTime::now_utc(),
+            is_repost: false,
+            is_reply: false,
+            reply_to_id: None,
+            context: vec![],
+        }
+    }
+
+    fn disabled_publishers() -> Vec<Arc<dyn Publisher>> {
+        ["x", "nostr"]
+            .into_iter()
+            .map(|platform| {
+                Arc::new(FakePublisher {
+                    enabled: false,
+                    platform,
+                }) as Arc<dyn Publisher>
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_rate_limited_account_is_deferred_across_restarts() {
+        let post_source = Arc::new(RateLimitedPostSource {
+            calls: AtomicUsize::new(0),
+        });
+        let definitions_repo = Arc::new(FakeDefinitionsRepo {
+            definitions: vec![],
+        });
+        let state_store = Arc::new(FakeStateStore::new());
+        let now = OffsetDateTime::now_utc();
+        let config = RunLoopConfig {
+            accounts: vec!["TestUser".to_string()],
+            dry_run: true,
+            ..Default::default()
+        };
+
+        let run_loop = RunLoop::new(
+            Arc::clone(&post_source),
+            Arc::clone(&definitions_repo),
+            Arc::new(FakeClassifier),
+            disabled_publishers(),
+            Arc::clone(&state_store),
+            Arc::new(FakeClock { time: now }),
+            config.clone(),
+        );
+
+        run_loop.poll_once().await.unwrap();
+        run_loop.poll_once().await.unwrap();
+        assert_eq!(post_source.calls.load(Ordering::SeqCst), 1);
+
+        let deferrals = state_store.list_deferrals().await.unwrap();
+        assert_eq!(deferrals.len(), 1);
+        assert_eq!(deferrals[0].scope, "account:testuser");
+        assert_eq!(deferrals[0].until, now + Duration::from_secs(600));
+
+        // A fresh run loop over the same state still honours the deferral
+        let restarted = RunLoop::new(
+            Arc::clone(&post_source),
+            Arc::clone(&definitions_repo),
+            Arc::new(FakeClassifier),
+            disabled_publishers(),
+            Arc::clone(&state_store),
+            Arc::new(FakeClock {
+                time: now + Duration::from_secs(60),
+            }),
+            config.clone(),
+        );
+        restarted.poll_once().await.unwrap();
+        assert_eq!(post_source.calls.load(Ordering::SeqCst), 1);
+
+        // After the reset time the account is polled again
+        let after_reset = RunLoop::new(
+            Arc::clone(&post_source),
+            definitions_repo,
+            Arc::new(FakeClassifier),
+            disabled_publishers(),
+            state_store,
+            Arc::new(FakeClock {
+                time: now + Duration::from_secs(601),
+            }),
+            config,
+        );
+        after_reset.poll_once().await.unwrap();
+        assert_eq!(post_source.calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_classifier_rate_limit_defers_posts_without_advancing_cursor() {
+        let post_source = Arc::new(FakePostSource {
+            posts: vec![sample_post("1"), sample_post("2"), sample_post("3")],
+        });
+        let definitions_repo = Arc::new(FakeDefinitionsRepo {
+            definitions: vec![],
+        });
+        let classifier = Arc::new(RateLimitedClassifier {
+            calls: AtomicUsize::new(0),
+        });
+        let state_store = Arc::new(FakeStateStore::new());
+        let now = OffsetDateTime::now_utc();
+
+        let run_loop = RunLoop::new(
+            post_source,
+            definitions_repo,
+            Arc::clone(&classifier),
+            disabled_publishers(),
+            Arc::clone(&state_store),
+            Arc::new(FakeClock { time: now }),
+            RunLoopConfig {
+                accounts: vec!["testuser".to_string()],
+                dry_run: true,
+                max_concurrent: 1,
+                rate_limit_backoff: Duration::from_secs(120),
+                ..Default::default()
+            },
+        );
+
+        let results = run_loop.poll_once().await.unwrap();
+        assert_eq!(results.len(), 1);
+        assert!(matches!(results[0].1, ProcessResult::Deferred { .. }));
+        assert!(
+            state_store
+                .get_account_state("testuser")
+                .await
+                .unwrap()
+                .is_none()
+        );
+
+        let deferrals = state_store.list_deferrals().await.unwrap();
+        assert_eq!(deferrals[0].scope, CLASSIFIER_SCOPE);
+        assert_eq!(deferrals[0].until, now + Duration::from_secs(120));
+
+        // The whole cycle is skipped while the classifier is deferred
+        assert!(run_loop.poll_once().await.unwrap().is_empty());
+        assert_eq!(classifier.calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_failed_platform_is_retried_without_repeating_others() {
+        let post_source = Arc::new(FakePostSource {
+            posts: vec![sample_post("1")],
+        });
+        let definitions_repo = Arc::new(FakeDefinitionsRepo {
+            definitions: vec![],
+        });
+        let x = Arc::new(FlakyPublisher::new("x", 0));
+        let nostr = Arc::new(FlakyPublisher::new("nostr", 1));
+        let classifier = Arc::new(FailingClassifier::new(&[]));
+        let state_store = Arc::new(FakeStateStore::new());
+        let clock = Arc::new(SteppingClock {
+            time: Mutex::new(at("2026-01-15T12:00:00Z")),
+        });
+
+        let run_loop = RunLoop::new(
+            post_source,
+            definitions_repo,
+            classifier.clone(),
+            vec![x.clone(), nostr.clone()],
+            Arc::clone(&state_store),
+            clock.clone(),
+            RunLoopConfig {
+                accounts: vec!["testuser".to_string()],
+                dry_run: false,
+                retry_base_delay: Duration::from_secs(60),
+                ..Default::default()
+            },
+        );
+
+        let results = run_loop.poll_once().await.unwrap();
+        match &results[0].1 {
+            ProcessResult::Published { targets, .. } => {
+                assert_eq!(targets.len(), 1);
+                assert_eq!(targets[0].platform, "x");
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+        let retries = state_store.list_retries().await.unwrap();
+        assert_eq!(retries.len(), 1);
+        assert_eq!(retries[0].platform, "nostr");
+        assert_eq!(retries[0].attempts, 1);
+
+        // Fetched again before the retry is due: nothing is classified or
+        // published, and the retry keeps its attempts and backoff
+        let results = run_loop.poll_once().await.unwrap();
+        assert!(matches!(results[0].1, ProcessResult::Skipped { .. }));
+        assert_eq!(nostr.calls.load(Ordering::SeqCst), 1);
+        assert_eq!(classifier.calls.lock().unwrap().len(), 1);
+        let retries = state_store.list_retries().await.unwrap();
+        assert_eq!(retries[0].attempts, 1);
+        assert_eq!(retries[0].next_attempt_at, at("2026-01-15T12:01:00Z"));
+
+        // Once due, the retry queue publishes the stored classification
+        clock.set("2026-01-15T12:01:00Z");
+        run_loop.poll_once().await.unwrap();
+        assert_eq!(x.calls.load(Ordering::SeqCst), 1);
+        assert_eq!(nostr.calls.load(Ordering::SeqCst), 2);
+        assert_eq!(classifier.calls.lock().unwrap().len(), 1);
+        assert!(state_store.list_retries().await.unwrap().is_empty());
+        assert_eq!(
+            state_store.classifications.lock().unwrap()[0]
+                .source_post
+                .id,
+            "1"
+        );
+        assert_eq!(state_store.targets.lock().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_rate_limited_publisher_does_not_block_other_platforms() {
+        let x = Arc::new(FlakyPublisher::new("x", 0));
+        let nostr = Arc::new(FlakyPublisher::new("nostr", 0));
+        let state_store = Arc::new(FakeStateStore::new());
+        let now = OffsetDateTime::now_utc();
+        let until = now + Duration::from_secs(600);
+        state_store
+            .set_deferral(&RateLimitDeferral {
+                scope: publisher_scope("nostr"),
+                until,
+            })
+            .await
+            .unwrap();
+
+        let run_loop = RunLoop::new(
+            Arc::new(FakePostSource {
+                posts: vec![sample_post("1")],
+            }),
+            Arc::new(FakeDefinitionsRepo {
+                definitions: vec![],
+            }),
+            Arc::new(FakeClassifier),
+            vec![x.clone(), nostr.clone()],
+            Arc::clone(&state_store),
+            Arc::new(FakeClock { time: now }),
+            RunLoopConfig {
+                accounts: vec!["testuser".to_string()],
+                dry_run: false,
+                ..Default::default()
+            },
+        );
+
+        let results = run_loop.poll_once().await.unwrap();
+        match &results[0].1 {
+            ProcessResult::Published { targets, .. } => {
+                assert_eq!(targets.len(), 1);
+                assert_eq!(targets[0].platform, "x");
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+        assert_eq!(x.calls.load(Ordering::SeqCst), 1);
+        assert_eq!(nostr.calls.load(Ordering::SeqCst), 0);
+
+        // Nostr waits in the retry queue until the deferral ends
+        let retries = state_store.list_retries().await.unwrap();
+        assert_eq!(retries.len(), 1);
+        assert_eq!(retries[0].platform, "nostr");
+        assert_eq!(retries[0].attempts, 0);
+        assert_eq!(retries[0].next_attempt_at, until);
+    }
+
+    /// Inserts the post's record while classifying, like a worker that
+    /// got there first
+    struct RacingClassifier {
+        state_store: Arc<FakeStateStore>,
+        record_id: Uuid,
+    }
+
+    #[async_trait]
+    impl Classifier for RacingClassifier {
+        async fn classify(&self, input: ClassifyInput) -> Result<ClassifyOutput, ClassifyError> {
+            self.state_store
+                .record_published(&PublishedRecord {
+                    id: self.record_id,
+                    source_post_id: input.post.id,
+                    taxonomy_hash: Taxonomy::new(vec![]).hash,
+                    published_at: OffsetDateTime::now_utc(),
+                })
+                .await
+                .unwrap();
+            Ok(ClassifyOutput::new("Test summary".to_string(), vec![]))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_existing_record_is_used_when_insert_conflicts() {
+        let state_store = Arc::new(FakeStateStore::new());
+        let record_id = Uuid::new_v4();
+        let x = Arc::new(FlakyPublisher::new("x", 0));
+        let nostr = Arc::new(FlakyPublisher::new("nostr", 1));
+
+        let run_loop = RunLoop::new(
+            Arc::new(FakePostSource {
+                posts: vec![sample_post("1")],
+            }),
+            Arc::new(FakeDefinitionsRepo {
+                definitions: vec![],
+            }),
+            Arc::new(RacingClassifier {
+                state_store: Arc::clone(&state_store),
+                record_id,
+            }),
+            vec![x, nostr],
+            Arc::clone(&state_store),
+            Arc::new(FakeClock {
+                time: OffsetDateTime::now_utc(),
+            }),
+            RunLoopConfig {
+                accounts: vec!["testuser".to_string()],
+                dry_run: false,
+                ..Default::default()
+            },
+        );
+
+        run_loop.poll_once().await.unwrap();
+
+        // Everything hangs off the stored record, not the one that lost the insert
+        let targets = state_store.targets.lock().unwrap().clone();
+        assert_eq!(targets.len(), 1);
+        assert_eq!(targets[0].record_id, record_id);
+        assert_eq!(
+            state_store.classifications.lock().unwrap()[0].record_id,
+            record_id
+        );
+        assert_eq!(
+            state_store.list_retries().await.unwrap()[0].record_id,
+            record_id
+        );
+    }
+
+    fn queued_retry(
+        platform: &str,
+        attempts: u32,
+        next_attempt_at: OffsetDateTime,
+    ) -> PublishRetry {
+        PublishRetry {
+            record_id: Uuid::new_v4(),
+            platform: platform.to_string(),
+            source_post: sample_post("1"),
+            classification: ClassifyOutput::new("Test summary".to_string(), vec![]),
+            taxonomy_hash: "hash".to_string(),
+            attempts,
+            status: RetryStatus::Pending,
+            last_error: "relay down".to_string(),
+            next_attempt_at,
+            updated_at: next_attempt_at,
+        }
+    }
+
+    fn retry_loop(
+        publisher: Arc<FlakyPublisher>,
+        state_store: Arc<FakeStateStore>,
+        now: OffsetDateTime,
+    ) -> RunLoop<FakePostSource, FakeDefinitionsRepo, FakeClassifier, FakeStateStore, FakeClock>
+    {
+        RunLoop::new(
+            Arc::new(FakePostSource { posts: vec![] }),
+            Arc::new(FakeDefinitionsRepo {
+                definitions: vec![],
+            }),
+            Arc::new(FakeClassifier),
+            vec![publisher],
+            state_store,
+            Arc::new(FakeClock { time: now }),
+            RunLoopConfig {
+                accounts: vec!["testuser".to_string()],
+                dry_run: false,
+                retry_max_attempts: 3,
+                retry_base_delay: Duration::from_secs(60),
+                ..Default::default()
+            },
+        )
+    }
+
+    #[tokio::test]
+    async fn test_due_retry_is_published_and_dequeued() {
+        let now = OffsetDateTime::now_utc();
+        let state_store = Arc::new(FakeStateStore::new());
+        let retry = queued_retry("nostr", 1, now);
+        state_store.set_retry(&retry).await.unwrap();
+        let nostr = Arc::new(FlakyPublisher::new("nostr", 0));
+
+        retry_loop(nostr.clone(), Arc::clone(&state_store), now)
+            .poll_once()
+            .await
+            .unwrap();
+
+        assert_eq!(nostr.calls.load(Ordering::SeqCst), 1);
+        assert!(state_store.list_retries().await.unwrap().is_empty());
+        let targets = state_store.list_targets(retry.record_id).await.unwrap();
+        assert_eq!(targets[0].target_id, "nostr_id");
+    }
+
+    #[tokio::test]
+    async fn test_retry_backs_off_then_fails_after_max_attempts() {
+        let now = OffsetDateTime::now_utc();
+        let state_store = Arc::new(FakeStateStore::new());
+        state_store
+            .set_retry(&queued_retry("nostr", 1, now))
+            .await
+            .unwrap();
+        let nostr = Arc::new(FlakyPublisher::new("nostr", 5));
+
+        retry_loop(nostr.clone(), Arc::clone(&state_store), now)
+            .poll_once()
+            .await
+            .unwrap();
+        let retry = state_store.list_retries().await.unwrap().remove(0);
+        assert_eq!(retry.attempts, 2);
+        assert_eq!(retry.status, RetryStatus::Pending);
+        assert_eq!(retry.next_attempt_at, now + Duration::from_secs(120));
+
+        // Not due yet
+        let early = now + Duration::from_secs(60);
+        retry_loop(nostr.clone(), Arc::clone(&state_store), early)
+            .poll_once()
+            .await
+            .unwrap();
+        assert_eq!(nostr.calls.load(Ordering::SeqCst), 1);
+
+        let later = now + Duration::from_secs(120);
+        retry_loop(nostr.clone(), Arc::clone(&state_store), later)
+            .poll_once()
+            .await
+            .unwrap();
+        let retry = state_store.list_retries().await.unwrap().remove(0);
+        assert_eq!(retry.attempts, 3);
+        assert_eq!(retry.status, RetryStatus::Failed);
+
+        // Failed entries are left for the operator
+        let much_later = now + Duration::from_secs(3600);
+        retry_loop(nostr.clone(), Arc::clone(&state_store), much_later)
+            .poll_once()
+            .await
+            .unwrap();
+        assert_eq!(nostr.calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[derive(Default)]
+    struct RecordingMetrics {
+        events: Mutex<Vec<String>>,
+    }
+
+    impl RunMetrics for RecordingMetrics {
+        fn posts_fetched(&self, account: &str, count: usize) {
+            self.record(format!("fetched {account} {count}"));
+        }
+
+        fn posts_filtered(&self, account: &str, count: usize) {
+            self.record(format!("filtered {account} {count}"));
+        }
+
+        fn post_processed(&self, account: &str, result: &ProcessResult) {
+            self.record(format!("processed {account} {}", result.outcome()));
+        }
+
+        fn classification(&self, provider: &str, _elapsed: Duration, _usage: Option<TokenUsage>) {
+            self.record(format!("classified {provider}"));
+        }
+
+        fn publish(&self, platform: &str, success: bool) {
+            self.record(format!("publish {platform} {success}"));
+        }
+
+        fn retry_queue(&self, pending: usize, failed: usize) {
+            self.record(format!("retries {pending} {failed}"));
+        }
+    }
+
+    impl RecordingMetrics {
+        fn record(&self, event: String) {
+            self.events.lock().unwrap().push(event);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_metrics_follow_posts_through_the_cycle() {
+        let reply = SourcePost {
+            is_reply: true,
+            ..sample_post("2")
+        };
+        let post_source = Arc::new(FakePostSource {
+            posts: vec![sample_post("1"), reply],
+        });
+        let metrics = Arc::new(RecordingMetrics::default());
+        let state_store = Arc::new(FakeStateStore::new());
+
+        let run_loop = RunLoop::new(
+            post_source,
+            Arc::new(FakeDefinitionsRepo {
+                definitions: vec![],
+            }),
+            Arc::new(FakeClassifier),
+            vec![
+                Arc::new(FlakyPublisher::new("x", 0)),
+                Arc::new(FlakyPublisher::new("nostr", 1)),
+            ],
+            Arc::clone(&state_store),
+            Arc::new(FakeClock {
+                time: OffsetDateTime::now_utc(),
+            }),
+            RunLoopConfig {
+                accounts: vec!["testuser".to_string()],
+                dry_run: false,
+                ..Default::default()
+            },
+        )
+        .with_metrics(metrics.clone());
+
+        run_loop.poll_once().await.unwrap();
+        assert_eq!(
+            *metrics.events.lock().unwrap(),
+            [
+                "retries 0 0",
+                "fetched testuser 2",
+                "filtered testuser 1",
+                "classified unknown",
+                "publish x true",
+                "publish nostr false",
+                "processed testuser published",
+            ]
+        );
+
+        metrics.events.lock().unwrap().clear();
+        run_loop.poll_once().await.unwrap();
+        assert_eq!(metrics.events.lock().unwrap()[0], "retries 1 0");
+    }
+
+    #[test]
+    fn test_retry_backoff_doubles_and_caps() {
+        let base = Duration::from_secs(60);
+        assert_eq!(retry_backoff(base, 1), Duration::from_secs(60));
+        assert_eq!(retry_backoff(base, 3), Duration::from_secs(240));
+        assert_eq!(retry_backoff(base, 40), MAX_RETRY_DELAY);
+    }
+
+    struct AccountPostSource {
+        posts: Vec<SourcePost>,
+        polled: Mutex<Vec<String>>,
+    }
+
+    #[async_trait]
+    impl PostSource for AccountPostSource {
+        async fn fetch_posts(
+            &self,
+            account: &str,
+            _since_id: Option<&str>,
+        ) -> Result<Vec<SourcePost>, PostSourceError> {
+            self.polled.lock().unwrap().push(account.to_string());
+            Ok(self.posts.clone())
+        }
+    }
+
+    struct SteppingClock {
+        time: Mutex<OffsetDateTime>,
+    }
+
+    impl SteppingClock {
+        fn set(&self, time: &str) {
+            *self.time.lock().unwrap() = at(time);
+        }
+    }
+
+    impl Clock for SteppingClock {
+        fn now(&self) -> OffsetDateTime {
+            *self.time.lock().unwrap()
+        }
+    }
+
+    fn at(time: &str) -> OffsetDateTime {
+        OffsetDateTime::parse(time, &time::format_description::well_known::Rfc3339).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_accounts_are_polled_on_their_own_schedule() {
+        let post_source = Arc::new(AccountPostSource {
+            posts: vec![],
+            polled: Mutex::new(vec![]),
+        });
+        let clock = Arc::new(SteppingClock {
+            time: Mutex::new(at("2026-01-15T12:00:00Z")),
+        });
+        let schedules = HashMap::from([
+            (
+                "dormant".to_string(),
+                AccountSchedule {
+                    poll_interval: Some(Duration::from_secs(3600)),
+                    ..Default::default()
+                },
+            ),
+            (
+                "night".to_string(),
+                AccountSchedule {
+                    active_hours: vec![ActiveHours {
+                        start: time::Time::from_hms(22, 0, 0).unwrap(),
+                        end: time::Time::from_hms(6, 0, 0).unwrap(),
+                    }],
+                    time_zone: time_tz::timezones::get_by_name("Europe/Berlin"),
+                    ..Default::default()
+                },
+            ),
+        ]);
+        let run_loop = RunLoop::new(
+            post_source.clone(),
+            Arc::new(FakeDefinitionsRepo {
+                definitions: vec![],
+            }),
+            Arc::new(FakeClassifier),
+            disabled_publishers(),
+            Arc::new(FakeStateStore::new()),
+            clock.clone(),
+            RunLoopConfig {
+                accounts: vec![
+                    "busy".to_string(),
+                    "dormant".to_string(),
+                    "night".to_string(),
+                ],
+                poll_interval: Some(Duration::from_secs(60)),
+                schedules,
+                ..Default::default()
+            },
+        );
+        let poll = async |time: &str| {
+            clock.set(time);
+            run_loop.poll_once().await.unwrap();
+            std::mem::take(&mut *post_source.polled.lock().unwrap())
+        };
+
+        // 13:00 in Berlin, outside the night account's hours
+        assert_eq!(poll("2026-01-15T12:00:00Z").await, ["busy", "dormant"]);
+        // A tick landing just before the interval elapses still counts
+        assert_eq!(poll("2026-01-15T12:00:59.5Z").await, ["busy"]);
+        assert_eq!(poll("2026-01-15T12:01:30Z").await, Vec::<String>::new());
+        // 23:30 in Berlin
+        assert_eq!(
+            poll("2026-01-15T22:30:00Z").await,
+            ["busy", "dormant", "night"]
+        );
+        // 06:00 in Berlin, the end of the window
+        assert_eq!(poll("2026-01-16T05:00:00Z").await, ["busy", "dormant"]);
+    }
+
+    struct DefinitionsClassifier {
+        seen: Mutex<Vec<Vec<String>>>,
+    }
+
+    #[async_trait]
+    impl Classifier for DefinitionsClassifier {
+        async fn classify(&self, input: ClassifyInput) -> Result<ClassifyOutput, ClassifyError> {
+            self.seen
+                .lock()
+                .unwrap()
+                .push(input.definitions.into_iter().map(|d| d.id).collect());
+            Ok(ClassifyOutput::new("Test summary".to_string(), vec![]))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_account_overrides_filters_and_tags() {
+        let definition = |id: &str| TagDefinition {
+            id: id.to_string(),
+            title: id.to_string(),
+            aliases: vec![],
+            short: None,
+            content: "Test definition".to_string(),
+            file_path: format!("{id}.md"),
+        };
+        let post_source = Arc::new(AccountPostSource {
+            posts: vec![
+                SourcePost {
+                    is_reply: true,
+                    ..sample_post("reply")
+                },
+                SourcePost {
+                    text: "AD: buy now".to_string(),
+                    ..sample_post("ad")
+                },
+            ],
+            polled: Mutex::new(vec![]),
+        });
+        let classifier = Arc::new(DefinitionsClassifier {
+            seen: Mutex::new(vec![]),
+        });
+        let schedules = HashMap::from([(
+            "alice".to_string(),
+            AccountSchedule {
+                include_replies: Some(true),
+                ignore_patterns: vec!["^AD:".to_string()],
+                tags: Some(vec!["tag_a".to_string()]),
+                ..Default::default()
+            },
+        )]);
+        let run_loop = RunLoop::new(
+            post_source,
+            Arc::new(FakeDefinitionsRepo {
+                definitions: vec![definition("tag_a"), definition("tag_b")],
+            }),
+            classifier.clone(),
+            disabled_publishers(),
+            Arc::new(FakeStateStore::new()),
+            Arc::new(FakeClock {
+                time: OffsetDateTime::now_utc(),
+            }),
+            RunLoopConfig {
+                accounts: vec!["alice".to_string(), "bob".to_string()],
+                schedules,
+                ..Default::default()
+            },
+        );
+
+        let results = run_loop.poll_once().await.unwrap();
+
+        // Alice's reply is kept and her ad ignored; Bob gets the global filters
+        let ids: Vec<_> = results.iter().map(|(id, _)| id.as_str()).collect();
+        assert_eq!(ids, ["reply", "ad"]);
+        assert_eq!(
+            *classifier.seen.lock().unwrap(),
+            [vec!["tag_a"], vec!["tag_a", "tag_b"]]
+        );
+    }
+
+    /// Holds every classification until the test lets it through
+    struct GatedClassifier {
+        started: AtomicUsize,
+        gate: tokio::sync::Semaphore,
+    }
+
+    #[async_trait]
+    impl Classifier for GatedClassifier {
+        async fn classify(&self, _input: ClassifyInput) -> Result<ClassifyOutput, ClassifyError> {
+            self.started.fetch_add(1, Ordering::SeqCst);
+            self.gate.acquire().await.unwrap().forget();
+            Ok(ClassifyOutput::new("Test summary".to_string(), vec![]))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_drains_in_flight_posts_and_stops_dispatching() {
+        let classifier = Arc::new(GatedClassifier {
+            started: AtomicUsize::new(0),
+            gate: tokio::sync::Semaphore::new(0),
+        });
+        let state_store = Arc::new(FakeStateStore::new());
+        let run_loop = RunLoop::new(
+            Arc::new(FakePostSource {
+                posts: vec![sample_post("1"), sample_post("2"), sample_post("3")],
+            }),
+            Arc::new(FakeDefinitionsRepo {
+                definitions: vec![],
+            }),
+            classifier.clone(),
+            disabled_publishers(),
+            Arc::clone(&state_store),
+            Arc::new(FakeClock {
+                time: OffsetDateTime::now_utc(),
+            }),
+            RunLoopConfig {
+                accounts: vec!["testuser".to_string()],
+                max_concurrent: 2,
+                drain_timeout: Duration::from_millis(50),
+                ..Default::default()
+            },
+        );
+
+        let (results, ()) = tokio::join!(run_loop.poll_once(), async {
+            while classifier.started.load(Ordering::SeqCst) < 2 {
+                tokio::task::yield_now().await;
+            }
+            run_loop.shutdown();
+            // Only the first post finishes before the drain deadline
+            classifier.gate.add_permits(1);
+        });
+
+        let results = results.unwrap();
+        let ids: Vec<_> = results.iter().map(|(id, _)| id.as_str()).collect();
+        assert_eq!(ids, ["1"]);
+        assert_eq!(classifier.started.load(Ordering::SeqCst), 2);
+        let state = state_store.get_account_state("testuser").await.unwrap();
+        assert_eq!(state.unwrap().since_id.as_deref(), Some("1"));
+
+        assert!(run_loop.is_shutting_down());
+        assert!(run_loop.poll_once().await.unwrap().is_empty());
+    }
+
+    /// Fails to classify the posts listed in `failing`
+    struct FailingClassifier {
+        failing: Mutex<Vec<String>>,
+        calls: Mutex<Vec<String>>,
+    }
+
+    impl FailingClassifier {
+        fn new(failing: &[&str]) -> Self {
+            Self {
+                failing: Mutex::new(failing.iter().map(|id| id.to_string()).collect()),
+                calls: Mutex::new(vec![]),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl Classifier for FailingClassifier {
+        async fn classify(&self, input: ClassifyInput) -> Result<ClassifyOutput, ClassifyError> {
+            self.calls.lock().unwrap().push(input.post.id.clone());
+            if self.failing.lock().unwrap().contains(&input.post.id) {
+                return Err(ClassifyError::Api("model overloaded".to_string()));
+            }
+            Ok(ClassifyOutput::new("Test summary".to_string(), vec![]))
+        }
+    }
+
+    fn failed_post_run_loop(
+        classifier: Arc<FailingClassifier>,
+        state_store: Arc<FakeStateStore>,
+        clock: Arc<SteppingClock>,
+        retry_max_attempts: u32,
+    ) -> RunLoop<
+        FakePostSource,
+        FakeDefinitionsRepo,
+        FailingClassifier,
+        FakeStateStore,
+        SteppingClock,
+    > {
+        RunLoop::new(
+            Arc::new(FakePostSource {
+                posts: vec![sample_post("1"), sample_post("2"), sample_post("3")],
+            }),
+            Arc::new(FakeDefinitionsRepo {
+                definitions: vec![],
+            }),
+            classifier,
+            disabled_publishers(),
+            state_store,
+            clock,
+            RunLoopConfig {
+                accounts: vec!["testuser".to_string()],
+                max_concurrent: 1,
+                retry_max_attempts,
+                retry_base_delay: Duration::from_secs(60),
+                ..Default::default()
+            },
+        )
+    }
+
+    #[tokio::test]
+    async fn test_failed_post_holds_cursor_until_retried() {
+        let classifier = Arc::new(FailingClassifier::new(&["2"]));
+        let state_store = Arc::new(FakeStateStore::new());
+        let clock = Arc::new(SteppingClock {
+            time: Mutex::new(at("2026-01-15T12:00:00Z")),
+        });
+        let run_loop = failed_post_run_loop(
+            classifier.clone(),
+            Arc::clone(&state_store),
+            clock.clone(),
+            5,
+        );
+        let since_id = async || {
+            state_store
+                .get_account_state("testuser")
+                .await
+                .unwrap()
+                .and_then(|s| s.since_id)
+        };
+
+        let results = run_loop.poll_once().await.unwrap();
+        let ProcessResult::Failed {
+            retry: Some(retry), ..
+        } = &results[1].1
+        else {
+            panic!("expected a failed post with a retry: {:?}", results[1]);
+        };
+        assert_eq!(retry.attempts, 1);
+        assert_eq!(retry.status, RetryStatus::Pending);
+        assert_eq!(retry.next_attempt_at, at("2026-01-15T12:01:00Z"));
+        assert!(matches!(results[2].1, ProcessResult::Published { .. }));
+        // Post 3 went through, but the cursor stays before post 2
+        assert_eq!(since_id().await.as_deref(), Some("1"));
+
+        // Not due yet: post 2 waits without being classified again
+        clock.set("2026-01-15T12:00:30Z");
+        classifier.calls.lock().unwrap().clear();
+        let results = run_loop.poll_once().await.unwrap();
+        assert!(matches!(results[1].1, ProcessResult::Deferred { .. }));
+        assert_eq!(*classifier.calls.lock().unwrap(), ["1", "3"]);
+        assert_eq!(since_id().await.as_deref(), Some("1"));
+
+        // Due and recovered: the retry is cleared and the cursor catches up
+        clock.set("2026-01-15T12:01:00Z");
+        classifier.failing.lock().unwrap().clear();
+        let results = run_loop.poll_once().await.unwrap();
+        assert!(matches!(results[1].1, ProcessResult::Published { .. }));
+        assert!(
+            state_store
+                .list_post_retries("testuser")
+                .await
+                .unwrap()
+                .is_empty()
+        );
+        assert_eq!(since_id().await.as_deref(), Some("3"));
+    }
+
+    #[tokio::test]
+    async fn test_failed_post_is_given_up_after_max_attempts() {
+        let classifier = Arc::new(FailingClassifier::new(&["2"]));
+        let state_store = Arc::new(FakeStateStore::new());
+        let clock = Arc::new(SteppingClock {
+            time: Mutex::new(at("2026-01-15T12:00:00Z")),
+        });
+        let run_loop = failed_post_run_loop(
+            classifier.clone(),
+            Arc::clone(&state_store),
+            clock.clone(),
+            2,
+        );
+
+        run_loop.poll_once().await.unwrap();
+        clock.set("2026-01-15T12:01:00Z");
+        let results = run_loop.poll_once().await.unwrap();
+        let ProcessResult::Failed {
+            retry: Some(retry), ..
+        } = &results[1].1
+        else {
+            panic!("expected a failed post with a retry: {:?}", results[1]);
+        };
+        assert_eq!(retry.attempts, 2);
+        assert_eq!(retry.status, RetryStatus::Failed);
+
+        // Given up, so the cursor moves past it and it is not tried again
+        let state = state_store.get_account_state("testuser").await.unwrap();
+        assert_eq!(state.unwrap().since_id.as_deref(), Some("3"));
+        classifier.calls.lock().unwrap().clear();
+        let results = run_loop.poll_once().await.unwrap();
+        assert!(matches!(results[1].1, ProcessResult::Skipped { .. }));
+        assert_eq!(*classifier.calls.lock().unwrap(), ["1", "3"]);
+        assert_eq!(
+            state_store
+                .list_post_retries("testuser")
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+    }
 }